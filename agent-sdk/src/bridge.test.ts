@@ -7,9 +7,11 @@ import {
   CACHE_SPLIT_POLICY,
   buildToolResultFields,
   buildUsageUpdateFromResult,
+  compactSummaryFromMessage,
   createToolCall,
   extractSessionHistoryUpdatesFromJsonl,
   agentSdkVersionCompatibilityError,
+  findForkPointInJsonl,
   looksLikeAuthRequired,
   normalizeToolResultText,
   normalizeToolKind,
@@ -17,6 +19,9 @@ import {
   permissionOptionsFromSuggestions,
   permissionResultFromOutcome,
   previewKilobyteLabel,
+  promptImageBlocksFromChunks,
+  promptTextFromChunks,
+  recordAssistantTurnUsage,
   resolveInstalledAgentSdkVersion,
   unwrapToolUseResult,
 } from "./bridge.js";
@@ -53,6 +58,30 @@ test("parseCommandEnvelope validates load_session command without cwd", () => {
   assert.equal(parsed.command.session_id, "session-123");
 });
 
+test("parseCommandEnvelope keeps replace_session_id for tabbed new_session", () => {
+  const parsed = parseCommandEnvelope(
+    JSON.stringify({
+      request_id: "tab:2",
+      command: "new_session",
+      cwd: "/work",
+      yolo: false,
+      replace_session_id: "session-7",
+    }),
+  );
+  assert.equal(parsed.requestId, "tab:2");
+  if (parsed.command.command !== "new_session") {
+    throw new Error("unexpected command variant");
+  }
+  assert.equal(parsed.command.replace_session_id, "session-7");
+});
+
+test("parseCommandEnvelope validates close_session command", () => {
+  const parsed = parseCommandEnvelope(
+    JSON.stringify({ command: "close_session", session_id: "session-9" }),
+  );
+  assert.deepEqual(parsed.command, { command: "close_session", session_id: "session-9" });
+});
+
 test("parseCommandEnvelope rejects missing required fields", () => {
   assert.throws(
     () => parseCommandEnvelope(JSON.stringify({ command: "set_model", session_id: "s1" })),
@@ -60,6 +89,56 @@ test("parseCommandEnvelope rejects missing required fields", () => {
   );
 });
 
+test("promptTextFromChunks appends resource chunks as context blocks", () => {
+  assert.equal(promptTextFromChunks([{ kind: "text", value: "plain" }]), "plain");
+  const text = promptTextFromChunks([
+    { kind: "text", value: "explain @App::render" },
+    {
+      kind: "resource",
+      value: {
+        uri: "file:///p/src/app.rs#L4-6",
+        mention: "@App::render",
+        text: "fn render() {\n}",
+        truncated: false,
+      },
+    },
+    { kind: "resource", value: { uri: "file:///p/missing" } },
+  ]);
+  assert.equal(
+    text,
+    'explain @App::render\n\n<context mention="@App::render" uri="file:///p/src/app.rs#L4-6">\nfn render() {\n}\n</context>',
+  );
+});
+
+test("promptTextFromChunks picks a context tag that the body cannot close", () => {
+  const text = promptTextFromChunks([
+    { kind: "text", value: "see @prompt.ts" },
+    {
+      kind: "resource",
+      value: {
+        uri: "file:///p/prompt.ts#L1-2",
+        mention: "@prompt.ts#L1-2",
+        text: 'const wrap = (s) => `<context>${s}</context>`;\nconst alt = "</context-1>";',
+      },
+    },
+  ]);
+  assert.equal(
+    text,
+    'see @prompt.ts\n\n<context-2 mention="@prompt.ts#L1-2" uri="file:///p/prompt.ts#L1-2">\nconst wrap = (s) => `<context>${s}</context>`;\nconst alt = "</context-1>";\n</context-2>',
+  );
+});
+
+test("promptImageBlocksFromChunks keeps supported base64 images", () => {
+  const blocks = promptImageBlocksFromChunks([
+    { kind: "text", value: "look at @shot.png" },
+    { kind: "image", value: { mime_type: "image/png", data: "iVBORw0KGgo=" } },
+    { kind: "image", value: { mime_type: "image/tiff", data: "AAAA" } },
+  ]);
+  assert.deepEqual(blocks, [
+    { type: "image", source: { type: "base64", media_type: "image/png", data: "iVBORw0KGgo=" } },
+  ]);
+});
+
 test("normalizeToolKind maps known tool names", () => {
   assert.equal(normalizeToolKind("Bash"), "execute");
   assert.equal(normalizeToolKind("Delete"), "delete");
@@ -86,6 +165,13 @@ test("createToolCall builds edit diff content", () => {
   assert.deepEqual(toolCall.meta, { claudeCode: { toolName: "Edit" } });
 });
 
+test("createToolCall records the parent Task for subagent tool calls", () => {
+  const toolCall = createToolCall("tc-2", "Read", { file_path: "src/lib.rs" }, "task-1");
+  assert.deepEqual(toolCall.meta, {
+    claudeCode: { toolName: "Read", parentToolUseId: "task-1" },
+  });
+});
+
 test("createToolCall builds write preview diff content", () => {
   const toolCall = createToolCall("tc-w", "Write", {
     file_path: "src/new-file.ts",
@@ -120,6 +206,17 @@ test("buildToolResultFields extracts plain-text output", () => {
   ]);
 });
 
+test("buildToolResultFields forwards base64 image blocks", () => {
+  const fields = buildToolResultFields(false, [
+    { type: "image", source: { type: "base64", media_type: "image/png", data: "iVBORw0KGgo=" } },
+  ]);
+  assert.equal(fields.status, "completed");
+  assert.equal(fields.raw_output, "[1 image]");
+  assert.deepEqual(fields.content, [
+    { type: "content", content: { type: "image", mime_type: "image/png", data: "iVBORw0KGgo=" } },
+  ]);
+});
+
 test("normalizeToolResultText collapses persisted-output payload to first meaningful line", () => {
   const normalized = normalizeToolResultText(`
 <persisted-output>
@@ -427,6 +524,27 @@ test("buildUsageUpdateFromResult includes cost and context window fields", () =>
   });
 });
 
+test("recordAssistantTurnUsage sums distinct responses and ignores repeated blocks", () => {
+  const tracker = new Map();
+  const response = (id: string, output: number) => ({
+    type: "assistant",
+    message: {
+      id,
+      usage: { input_tokens: 10, output_tokens: output, cache_read_input_tokens: 100 },
+    },
+  });
+
+  recordAssistantTurnUsage(tracker, response("msg-1", 5));
+  recordAssistantTurnUsage(tracker, response("msg-1", 7));
+  const update = recordAssistantTurnUsage(tracker, response("msg-2", 3));
+
+  assert.deepEqual(update, {
+    type: "turn_usage",
+    usage: { input_tokens: 20, output_tokens: 10, cache_read_tokens: 200, cache_write_tokens: 0 },
+  });
+  assert.equal(recordAssistantTurnUsage(tracker, { type: "assistant", message: {} }), null);
+});
+
 test("looksLikeAuthRequired detects login hints", () => {
   assert.equal(looksLikeAuthRequired("Please run /login to continue"), true);
   assert.equal(looksLikeAuthRequired("normal tool output"), false);
@@ -547,6 +665,138 @@ test("extractSessionHistoryUpdatesFromJsonl parses nested progress message recor
   });
 });
 
+test("extractSessionHistoryUpdatesFromJsonl maps compaction boundary and summary", () => {
+  withTempJsonl(
+    [
+      { type: "system", subtype: "compact_boundary", compactMetadata: { trigger: "manual", preTokens: 91000 } },
+      {
+        type: "user",
+        isCompactSummary: true,
+        message: { role: "user", content: "This session is being continued.\n\nSummary: auth refactor" },
+      },
+      { type: "user", message: { role: "user", content: [{ type: "text", text: "next prompt" }] } },
+    ],
+    (filePath) => {
+      const updates = extractSessionHistoryUpdatesFromJsonl(filePath);
+      assert.deepEqual(updates, [
+        { type: "compaction_boundary", trigger: "manual", pre_tokens: 91000 },
+        { type: "compaction_summary", summary: "This session is being continued.\n\nSummary: auth refactor" },
+        { type: "user_message_chunk", content: { type: "text", text: "next prompt" } },
+      ]);
+    },
+  );
+});
+
+test("compactSummaryFromMessage only takes flagged top-level summaries", () => {
+  const text = (value: string) => ({ role: "user", content: [{ type: "text", text: value }] });
+  assert.equal(
+    compactSummaryFromMessage({ type: "user", isCompactSummary: true, message: text("Summary: auth") }),
+    "Summary: auth",
+  );
+  assert.equal(compactSummaryFromMessage({ type: "user", message: text("next prompt") }), undefined);
+  assert.equal(
+    compactSummaryFromMessage({
+      type: "user",
+      isCompactSummary: true,
+      parent_tool_use_id: "task-1",
+      message: text("subagent prompt"),
+    }),
+    undefined,
+  );
+});
+
+test("findForkPointInJsonl resumes at the assistant reply before the edited prompt", () => {
+  const prompt = (uuid: string, text: string) => ({
+    type: "user",
+    uuid,
+    message: { role: "user", content: [{ type: "text", text }] },
+  });
+  const reply = (uuid: string, text: string) => ({
+    type: "assistant",
+    uuid,
+    message: { role: "assistant", content: [{ type: "text", text }] },
+  });
+  withTempJsonl(
+    [
+      prompt("u1", "fix the build"),
+      reply("a1", "done"),
+      {
+        type: "user",
+        uuid: "t1",
+        message: { role: "user", content: [{ type: "tool_result", tool_use_id: "x", content: "ok" }] },
+      },
+      prompt("u2", "fix  the build"),
+      reply("a2", "done again"),
+    ],
+    (filePath) => {
+      assert.deepEqual(findForkPointInJsonl(filePath, "fix the build", 0), {
+        promptUuid: "u2",
+        resumeAt: "a1",
+      });
+      assert.deepEqual(findForkPointInJsonl(filePath, "fix the build", 1), { promptUuid: "u1" });
+      assert.equal(findForkPointInJsonl(filePath, "fix the build", 2), null);
+      assert.equal(findForkPointInJsonl(filePath, "unknown", 0), null);
+
+      const updates = extractSessionHistoryUpdatesFromJsonl(filePath, "u2");
+      assert.deepEqual(updates, [
+        { type: "user_message_chunk", content: { type: "text", text: "fix the build" } },
+        { type: "agent_message_chunk", content: { type: "text", text: "done" } },
+      ]);
+    },
+  );
+});
+
+test("findForkPointInJsonl ignores prompts that only share a prefix", () => {
+  const prompt = (uuid: string, text: string) => ({
+    type: "user",
+    uuid,
+    message: { role: "user", content: [{ type: "text", text }] },
+  });
+  const reply = (uuid: string) => ({
+    type: "assistant",
+    uuid,
+    message: { role: "assistant", content: [{ type: "text", text: "ok" }] },
+  });
+  withTempJsonl(
+    [
+      prompt("u1", "fix the build"),
+      reply("a1"),
+      prompt("u2", "fix the build on windows too"),
+      reply("a2"),
+    ],
+    (filePath) => {
+      assert.deepEqual(findForkPointInJsonl(filePath, "fix the build", 0), { promptUuid: "u1" });
+      assert.equal(findForkPointInJsonl(filePath, "fix the build", 1), null);
+      assert.deepEqual(findForkPointInJsonl(filePath, "fix the build on windows too", 0), {
+        promptUuid: "u2",
+        resumeAt: "a1",
+      });
+    },
+  );
+});
+
+test("parseCommandEnvelope validates fork_session", () => {
+  const parsed = parseCommandEnvelope(
+    JSON.stringify({
+      command: "fork_session",
+      session_id: "s1",
+      prompt_text: "hello",
+      occurrence_from_end: 1,
+    }),
+  );
+  assert.deepEqual(parsed.command, {
+    command: "fork_session",
+    session_id: "s1",
+    prompt_text: "hello",
+    occurrence_from_end: 1,
+  });
+  assert.throws(() =>
+    parseCommandEnvelope(
+      JSON.stringify({ command: "fork_session", session_id: "s1", prompt_text: "x", occurrence_from_end: -1 }),
+    ),
+  );
+});
+
 test("extractSessionHistoryUpdatesFromJsonl ignores invalid records", () => {
   withTempJsonl(
     [
@@ -24,11 +24,13 @@ import type {
   PermissionOption,
   PermissionRequest,
   PlanEntry,
+  PromptChunk,
   SessionUpdate,
   ToolCall,
   ToolCallUpdate,
   ToolCallUpdateFields,
   TurnErrorKind,
+  TurnUsage,
 } from "./types.js";
 import { parseCommandEnvelope, toPermissionMode, buildModeState } from "./bridge/commands.js";
 import { asRecordOrNull } from "./bridge/shared.js";
@@ -43,14 +45,21 @@ import {
   unwrapToolUseResult,
 } from "./bridge/tooling.js";
 import { CACHE_SPLIT_POLICY, previewKilobyteLabel } from "./bridge/cache_policy.js";
-import { buildUsageUpdateFromResult, buildUsageUpdateFromResultForSession } from "./bridge/usage.js";
+import {
+  buildUsageUpdateFromResult,
+  buildUsageUpdateFromResultForSession,
+  recordAssistantTurnUsage,
+  type TurnUsageTracker,
+} from "./bridge/usage.js";
 import {
   formatPermissionUpdates,
   permissionOptionsFromSuggestions,
   permissionResultFromOutcome,
 } from "./bridge/permissions.js";
 import {
+  compactSummaryFromMessage,
   extractSessionHistoryUpdatesFromJsonl,
+  findForkPointInJsonl,
   listRecentPersistedSessions,
   resolvePersistedSessionEntry,
 } from "./bridge/history.js";
@@ -59,8 +68,10 @@ export {
   CACHE_SPLIT_POLICY,
   buildToolResultFields,
   buildUsageUpdateFromResult,
+  compactSummaryFromMessage,
   createToolCall,
   extractSessionHistoryUpdatesFromJsonl,
+  findForkPointInJsonl,
   looksLikeAuthRequired,
   normalizeToolKind,
   normalizeToolResultText,
@@ -68,6 +79,9 @@ export {
   permissionOptionsFromSuggestions,
   permissionResultFromOutcome,
   previewKilobyteLabel,
+  promptImageBlocksFromChunks,
+  promptTextFromChunks,
+  recordAssistantTurnUsage,
   unwrapToolUseResult,
 };
 
@@ -98,6 +112,7 @@ type SessionState = {
   authHintSent: boolean;
   lastAssistantError?: string;
   lastTotalCostUsd?: number;
+  turnUsage: TurnUsageTracker;
   sessionsToCloseAfterConnect?: SessionState[];
   resumeUpdates?: SessionUpdate[];
 };
@@ -251,17 +266,60 @@ function emitConnectEvent(session: SessionState): void {
   })();
 }
 
-function textFromPrompt(command: Extract<BridgeCommand, { command: "prompt" }>): string {
-  const chunks = command.chunks ?? [];
-  return chunks
-    .map((chunk) => {
-      if (chunk.kind !== "text") {
-        return "";
-      }
-      return typeof chunk.value === "string" ? chunk.value : "";
-    })
+function escapeContextAttribute(value: string): string {
+  return value.replace(/&/g, "&amp;").replace(/"/g, "&quot;").replace(/</g, "&lt;");
+}
+
+// Tag name for a context block whose closing tag cannot occur in `body`, so
+// quoted source containing `</context>` cannot end the block early.
+function contextTagFor(body: string): string {
+  let tag = "context";
+  for (let n = 1; body.includes(`</${tag}`); n += 1) {
+    tag = `context-${n}`;
+  }
+  return tag;
+}
+
+// Text chunks are joined as typed; `resource` chunks (line-range and symbol
+// mentions) follow as tagged context blocks.
+function promptTextFromChunks(chunks: PromptChunk[]): string {
+  const typed = chunks
+    .map((chunk) => (chunk.kind === "text" && typeof chunk.value === "string" ? chunk.value : ""))
     .filter((part) => part.length > 0)
     .join("");
+  const contexts = chunks.flatMap((chunk) => {
+    const resource = chunk.kind === "resource" ? asRecordOrNull(chunk.value) : null;
+    const text = resource?.text;
+    const uri = resource?.uri;
+    if (typeof text !== "string" || typeof uri !== "string") {
+      return [];
+    }
+    const label = typeof resource?.mention === "string" ? resource.mention : uri;
+    const truncated = resource?.truncated === true ? ' truncated="true"' : "";
+    const body = text.endsWith("\n") ? text : `${text}\n`;
+    const tag = contextTagFor(body);
+    return [
+      `<${tag} mention="${escapeContextAttribute(label)}" uri="${escapeContextAttribute(uri)}"${truncated}>\n${body}</${tag}>`,
+    ];
+  });
+  return contexts.length === 0 ? typed : [typed, ...contexts].join("\n\n");
+}
+
+const PROMPT_IMAGE_TYPES = new Set(["image/png", "image/jpeg", "image/gif", "image/webp"]);
+
+// `image` chunks carry base64 data for attached image files.
+function promptImageBlocksFromChunks(
+  chunks: PromptChunk[],
+): Array<{ type: "image"; source: { type: "base64"; media_type: string; data: string } }> {
+  return chunks.flatMap((chunk) => {
+    const image = chunk.kind === "image" ? asRecordOrNull(chunk.value) : null;
+    const mediaType = image?.mime_type;
+    const data = image?.data;
+    if (typeof mediaType !== "string" || !PROMPT_IMAGE_TYPES.has(mediaType) || typeof data !== "string") {
+      return [];
+    }
+    return [{ type: "image" as const, source: { type: "base64" as const, media_type: mediaType, data } }];
+  });
 }
 
 function sessionById(sessionId: string): SessionState | null {
@@ -277,8 +335,14 @@ function updateSessionId(session: SessionState, newSessionId: string): void {
   sessions.set(newSessionId, session);
 }
 
-function emitToolCall(session: SessionState, toolUseId: string, name: string, input: Record<string, unknown>): void {
-  const toolCall = createToolCall(toolUseId, name, input);
+function emitToolCall(
+  session: SessionState,
+  toolUseId: string,
+  name: string,
+  input: Record<string, unknown>,
+  parentToolUseId?: string,
+): void {
+  const toolCall = createToolCall(toolUseId, name, input, parentToolUseId);
   const status: ToolCall["status"] = "in_progress";
   toolCall.status = status;
 
@@ -554,7 +618,11 @@ function handleTaskSystemMessage(
   }
 }
 
-function handleContentBlock(session: SessionState, block: Record<string, unknown>): void {
+function handleContentBlock(
+  session: SessionState,
+  block: Record<string, unknown>,
+  parentToolUseId?: string,
+): void {
   const blockType = typeof block.type === "string" ? block.type : "";
 
   if (blockType === "text") {
@@ -582,7 +650,7 @@ function handleContentBlock(session: SessionState, block: Record<string, unknown
       return;
     }
     emitPlanIfTodoWrite(session, name, input);
-    emitToolCall(session, toolUseId, name, input);
+    emitToolCall(session, toolUseId, name, input, parentToolUseId);
     return;
   }
 
@@ -596,12 +664,16 @@ function handleContentBlock(session: SessionState, block: Record<string, unknown
   }
 }
 
-function handleStreamEvent(session: SessionState, event: Record<string, unknown>): void {
+function handleStreamEvent(
+  session: SessionState,
+  event: Record<string, unknown>,
+  parentToolUseId?: string,
+): void {
   const eventType = typeof event.type === "string" ? event.type : "";
 
   if (eventType === "content_block_start") {
     if (event.content_block && typeof event.content_block === "object") {
-      handleContentBlock(session, event.content_block as Record<string, unknown>);
+      handleContentBlock(session, event.content_block as Record<string, unknown>, parentToolUseId);
     }
     return;
   }
@@ -627,11 +699,18 @@ function handleStreamEvent(session: SessionState, event: Record<string, unknown>
 }
 
 function handleAssistantMessage(session: SessionState, message: Record<string, unknown>): void {
+  const parentToolUseId =
+    typeof message.parent_tool_use_id === "string" ? message.parent_tool_use_id : undefined;
   const assistantError = typeof message.error === "string" ? message.error : "";
   if (assistantError.length > 0) {
     session.lastAssistantError = assistantError;
   }
 
+  const turnUsage = recordAssistantTurnUsage(session.turnUsage, message);
+  if (turnUsage) {
+    emitSessionUpdate(session.sessionId, turnUsage);
+  }
+
   const messageObject =
     message.message && typeof message.message === "object"
       ? (message.message as Record<string, unknown>)
@@ -652,7 +731,7 @@ function handleAssistantMessage(session: SessionState, message: Record<string, u
       blockType === "mcp_tool_use" ||
       TOOL_RESULT_TYPES.has(blockType)
     ) {
-      handleContentBlock(session, blockRecord);
+      handleContentBlock(session, blockRecord, parentToolUseId);
     }
   }
 }
@@ -750,6 +829,7 @@ function numberField(record: Record<string, unknown>, ...keys: string[]): number
 }
 
 function handleResultMessage(session: SessionState, message: Record<string, unknown>): void {
+  session.turnUsage.clear();
   const usageUpdate = buildUsageUpdateFromResultForSession(session, message);
   if (usageUpdate) {
     emitSessionUpdate(session.sessionId, usageUpdate);
@@ -898,7 +978,9 @@ function handleSdkMessage(session: SessionState, message: SDKMessage): void {
 
   if (type === "stream_event") {
     if (msg.event && typeof msg.event === "object") {
-      handleStreamEvent(session, msg.event as Record<string, unknown>);
+      const parentToolUseId =
+        typeof msg.parent_tool_use_id === "string" ? msg.parent_tool_use_id : undefined;
+      handleStreamEvent(session, msg.event as Record<string, unknown>, parentToolUseId);
     }
     return;
   }
@@ -926,6 +1008,12 @@ function handleSdkMessage(session: SessionState, message: SDKMessage): void {
   }
 
   if (type === "user") {
+    // The SDK follows a compact boundary with a synthetic user message carrying the summary.
+    const summary = compactSummaryFromMessage(msg);
+    if (summary) {
+      emitSessionUpdate(session.sessionId, { type: "compaction_summary", summary });
+      return;
+    }
     handleUserToolResultBlocks(session, msg);
 
     const toolUseId = typeof msg.parent_tool_use_id === "string" ? msg.parent_tool_use_id : "";
@@ -1138,6 +1226,23 @@ async function closeSession(session: SessionState): Promise<void> {
   session.pendingPermissions.clear();
 }
 
+async function closeSessionById(sessionId: string): Promise<void> {
+  const session = sessions.get(sessionId);
+  if (!session) {
+    return;
+  }
+  sessions.delete(sessionId);
+  await closeSession(session);
+}
+
+function sessionsToReplace(replaceSessionId: string | undefined): SessionState[] {
+  if (!replaceSessionId) {
+    return Array.from(sessions.values());
+  }
+  const session = sessions.get(replaceSessionId);
+  return session ? [session] : [];
+}
+
 async function closeAllSessions(): Promise<void> {
   const active = Array.from(sessions.values());
   sessions.clear();
@@ -1149,6 +1254,10 @@ async function createSession(params: {
   yolo: boolean;
   model?: string;
   resume?: string;
+  // Copy `resume` into a new session that keeps history up to and including
+  // the assistant message `resumeSessionAt`.
+  forkSession?: boolean;
+  resumeSessionAt?: string;
   connectEvent: ConnectEventKind;
   requestId?: string;
   sessionsToCloseAfterConnect?: SessionState[];
@@ -1156,7 +1265,10 @@ async function createSession(params: {
 }): Promise<void> {
   const input = new AsyncQueue<SDKUserMessage>();
   const startMode: PermissionMode = params.yolo ? "bypassPermissions" : "default";
-  const provisionalSessionId = params.resume ?? randomUUID();
+  // Forks get their own id up front so the first prompt does not trigger a
+  // second session_replaced when the SDK reports it.
+  const keepsResumeId = params.resume !== undefined && !params.forkSession;
+  const provisionalSessionId = keepsResumeId ? (params.resume as string) : randomUUID();
 
   let session!: SessionState;
   const canUseTool: CanUseTool = async (toolName, inputData, options) => {
@@ -1212,7 +1324,9 @@ async function createSession(params: {
         cwd: params.cwd,
         includePartialMessages: true,
         executable: "node",
-        ...(params.resume ? {} : { sessionId: provisionalSessionId }),
+        ...(keepsResumeId ? {} : { sessionId: provisionalSessionId }),
+        ...(params.forkSession ? { forkSession: true } : {}),
+        ...(params.resumeSessionAt ? { resumeSessionAt: params.resumeSessionAt } : {}),
         ...(claudeCodeExecutable
           ? { pathToClaudeCodeExecutable: claudeCodeExecutable }
           : {}),
@@ -1283,6 +1397,7 @@ async function createSession(params: {
     connectRequestId: params.requestId,
     toolCalls: new Map<string, ToolCall>(),
     taskToolUseIds: new Map<string, string>(),
+    turnUsage: new Map<string, TurnUsage>(),
     pendingPermissions: new Map<string, PendingPermission>(),
     authHintSent: false,
     ...(params.resumeUpdates && params.resumeUpdates.length > 0
@@ -1432,7 +1547,7 @@ async function handleCommand(command: BridgeCommand, requestId?: string): Promis
               },
             ],
             capabilities: {
-              prompt_image: false,
+              prompt_image: true,
               prompt_embedded_context: true,
               load_session: true,
               supports_list_sessions: true,
@@ -1471,7 +1586,7 @@ async function handleCommand(command: BridgeCommand, requestId?: string): Promis
         return;
       }
       const resumeUpdates = extractSessionHistoryUpdatesFromJsonl(persisted.file_path);
-      const staleSessions = Array.from(sessions.values());
+      const staleSessions = sessionsToReplace(command.replace_session_id);
       const hadActiveSession = staleSessions.length > 0;
       try {
         await createSession({
@@ -1490,8 +1605,57 @@ async function handleCommand(command: BridgeCommand, requestId?: string): Promis
       return;
     }
 
+    case "fork_session": {
+      const persisted = resolvePersistedSessionEntry(command.session_id);
+      if (!persisted) {
+        slashError(command.session_id, `unknown session: ${command.session_id}`, requestId);
+        return;
+      }
+      const forkPoint = findForkPointInJsonl(
+        persisted.file_path,
+        command.prompt_text,
+        command.occurrence_from_end,
+      );
+      if (!forkPoint) {
+        slashError(
+          command.session_id,
+          "cannot edit message: it was not found in the saved transcript",
+          requestId,
+        );
+        return;
+      }
+      const live = sessionById(command.session_id);
+      const resumeUpdates = extractSessionHistoryUpdatesFromJsonl(
+        persisted.file_path,
+        forkPoint.promptUuid,
+      );
+      try {
+        // Forking at the very first prompt has nothing to keep: start fresh.
+        await createSession({
+          cwd: persisted.cwd,
+          yolo: live?.yolo ?? false,
+          ...(live && live.model !== "default" ? { model: live.model } : {}),
+          ...(forkPoint.resumeAt
+            ? { resume: command.session_id, forkSession: true, resumeSessionAt: forkPoint.resumeAt }
+            : {}),
+          ...(resumeUpdates.length > 0 ? { resumeUpdates } : {}),
+          connectEvent: "session_replaced",
+          requestId,
+          sessionsToCloseAfterConnect: live ? [live] : [],
+        });
+      } catch (error) {
+        const message = error instanceof Error ? error.message : String(error);
+        slashError(command.session_id, `failed to fork session: ${message}`, requestId);
+      }
+      return;
+    }
+
     case "new_session":
-      await closeAllSessions();
+      if (command.replace_session_id) {
+        await closeSessionById(command.replace_session_id);
+      } else {
+        await closeAllSessions();
+      }
       await createSession({
         cwd: command.cwd,
         yolo: command.yolo,
@@ -1501,23 +1665,29 @@ async function handleCommand(command: BridgeCommand, requestId?: string): Promis
       });
       return;
 
+    case "close_session":
+      await closeSessionById(command.session_id);
+      return;
+
     case "prompt": {
       const session = sessionById(command.session_id);
       if (!session) {
         slashError(command.session_id, `unknown session: ${command.session_id}`, requestId);
         return;
       }
-      const text = textFromPrompt(command);
-      if (!text.trim()) {
+      const text = promptTextFromChunks(command.chunks ?? []);
+      const images = promptImageBlocksFromChunks(command.chunks ?? []);
+      if (!text.trim() && images.length === 0) {
         return;
       }
+      session.turnUsage.clear();
       session.input.enqueue({
         type: "user",
         session_id: session.sessionId,
         parent_tool_use_id: null,
         message: {
           role: "user",
-          content: [{ type: "text", text }],
+          content: [...(text.trim() ? [{ type: "text", text }] : []), ...images],
         },
       } as SDKUserMessage);
       return;
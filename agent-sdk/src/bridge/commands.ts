@@ -47,6 +47,14 @@ function expectBoolean(record: Record<string, unknown>, key: string, context: st
   return value;
 }
 
+function expectCount(record: Record<string, unknown>, key: string, context: string): number {
+  const value = record[key];
+  if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+    throw new Error(`${context}.${key} must be a non-negative integer`);
+  }
+  return value;
+}
+
 function optionalString(
   record: Record<string, unknown>,
   key: string,
@@ -111,14 +119,28 @@ export function parseCommandEnvelope(line: string): { requestId?: string; comman
         return {
           command: "load_session",
           session_id: expectString(raw, "session_id", "load_session"),
+          replace_session_id: optionalString(raw, "replace_session_id", "load_session"),
           metadata: optionalMetadata(raw, "metadata"),
         };
+      case "fork_session":
+        return {
+          command: "fork_session",
+          session_id: expectString(raw, "session_id", "fork_session"),
+          prompt_text: expectString(raw, "prompt_text", "fork_session"),
+          occurrence_from_end: expectCount(raw, "occurrence_from_end", "fork_session"),
+        };
       case "new_session":
         return {
           command: "new_session",
           cwd: expectString(raw, "cwd", "new_session"),
           yolo: expectBoolean(raw, "yolo", "new_session"),
           model: optionalString(raw, "model", "new_session"),
+          replace_session_id: optionalString(raw, "replace_session_id", "new_session"),
+        };
+      case "close_session":
+        return {
+          command: "close_session",
+          session_id: expectString(raw, "session_id", "close_session"),
         };
       case "prompt":
         return {
@@ -308,6 +308,55 @@ function pushResumeToolResult(
   }
 }
 
+// Text of a compaction summary user message (live SDK message or persisted record).
+export function compactSummaryText(record: Record<string, unknown>): string | undefined {
+  const message = asRecordOrNull(record.message);
+  if (!message) {
+    return undefined;
+  }
+  let text = "";
+  if (typeof message.content === "string") {
+    text = message.content;
+  } else if (Array.isArray(message.content)) {
+    text = message.content
+      .map((item) => asRecordOrNull(item))
+      .filter((block) => block?.type === "text" && typeof block.text === "string")
+      .map((block) => block?.text as string)
+      .join("\n");
+  }
+  const trimmed = text.trim();
+  return trimmed.length > 0 ? trimmed : undefined;
+}
+
+// Summary carried by a live SDK user message. Only messages flagged as the compaction
+// summary qualify; subagent messages (with a parent tool use) never do.
+export function compactSummaryFromMessage(message: Record<string, unknown>): string | undefined {
+  if (message.isCompactSummary !== true || typeof message.parent_tool_use_id === "string") {
+    return undefined;
+  }
+  return compactSummaryText(message);
+}
+
+function pushResumeCompaction(updates: SessionUpdate[], record: Record<string, unknown>): boolean {
+  if (record.type === "system" && record.subtype === "compact_boundary") {
+    const metadata = asRecordOrNull(record.compactMetadata) ?? asRecordOrNull(record.compact_metadata);
+    const trigger = metadata?.trigger;
+    const preTokens = metadata?.preTokens ?? metadata?.pre_tokens;
+    if ((trigger === "manual" || trigger === "auto") && typeof preTokens === "number") {
+      updates.push({ type: "compaction_boundary", trigger, pre_tokens: preTokens });
+    }
+    return true;
+  }
+  if (record.isCompactSummary === true) {
+    const summary = compactSummaryText(record);
+    if (summary) {
+      updates.push({ type: "compaction_summary", summary });
+    }
+    return true;
+  }
+  return false;
+}
+
 function pushResumeUsageUpdate(
   updates: SessionUpdate[],
   message: Record<string, unknown>,
@@ -329,7 +378,102 @@ function pushResumeUsageUpdate(
   }
 }
 
-export function extractSessionHistoryUpdatesFromJsonl(filePath: string): SessionUpdate[] {
+// Where a forked session branches off: the last assistant message it keeps
+// (absent when forking at the first prompt) and the prompt being replaced.
+export type ForkPoint = {
+  resumeAt?: string;
+  promptUuid: string;
+};
+
+function promptRecordText(record: Record<string, unknown>): string | undefined {
+  if (record.type !== "user" || record.isMeta === true || record.isCompactSummary === true) {
+    return undefined;
+  }
+  const message = asRecordOrNull(record.message);
+  if (!message || message.role !== "user") {
+    return undefined;
+  }
+  if (typeof message.content === "string") {
+    return message.content;
+  }
+  if (!Array.isArray(message.content)) {
+    return undefined;
+  }
+  const blocks = message.content.map((item) => asRecordOrNull(item));
+  if (blocks.some((block) => TOOL_RESULT_TYPES.has(typeof block?.type === "string" ? block.type : ""))) {
+    return undefined;
+  }
+  return blocks
+    .filter((block) => block?.type === "text" && typeof block.text === "string")
+    .map((block) => block?.text as string)
+    .join("");
+}
+
+function collapseWhitespace(text: string): string {
+  return text.replace(/\s+/g, " ").trim();
+}
+
+// Locate the user prompt whose text equals `promptText` up to whitespace, counting
+// `occurrenceFromEnd` matches back from the newest one. The TUI counts
+// occurrences the same way, so prompts that merely share a prefix never match.
+export function findForkPointInJsonl(
+  filePath: string,
+  promptText: string,
+  occurrenceFromEnd: number,
+): ForkPoint | null {
+  let text: string;
+  try {
+    text = fs.readFileSync(filePath, "utf8");
+  } catch {
+    return null;
+  }
+  const target = collapseWhitespace(promptText);
+  if (!target) {
+    return null;
+  }
+
+  const matches: ForkPoint[] = [];
+  let lastAssistantUuid: string | undefined;
+  for (const rawLine of text.split(/\r?\n/)) {
+    const line = rawLine.trim();
+    if (line.length === 0) {
+      continue;
+    }
+    let parsed: unknown;
+    try {
+      parsed = JSON.parse(line);
+    } catch {
+      continue;
+    }
+    const record = asRecordOrNull(parsed);
+    const uuid = typeof record?.uuid === "string" ? record.uuid : undefined;
+    if (!record || !uuid) {
+      continue;
+    }
+    if (record.type === "assistant") {
+      lastAssistantUuid = uuid;
+      continue;
+    }
+    const prompt = promptRecordText(record);
+    if (prompt === undefined) {
+      continue;
+    }
+    if (collapseWhitespace(prompt) === target) {
+      matches.push({
+        promptUuid: uuid,
+        ...(lastAssistantUuid ? { resumeAt: lastAssistantUuid } : {}),
+      });
+    }
+  }
+  return matches[matches.length - 1 - occurrenceFromEnd] ?? null;
+}
+
+// Replay a persisted transcript as session updates. With `stopBeforeUuid` the
+// replay ends just before that record, which is how forks show their history.
+export function extractSessionHistoryUpdatesFromJsonl(
+  filePath: string,
+  stopBeforeUuid?: string,
+): SessionUpdate[] {
   let text: string;
   try {
     text = fs.readFileSync(filePath, "utf8");
@@ -356,6 +500,12 @@ export function extractSessionHistoryUpdatesFromJsonl(filePath: string): Session
     if (!record) {
       continue;
     }
+    if (stopBeforeUuid && record.uuid === stopBeforeUuid) {
+      break;
+    }
+    if (pushResumeCompaction(updates, record)) {
+      continue;
+    }
     for (const message of persistedMessageCandidates(record)) {
       const role = message.role;
       if (role !== "user" && role !== "assistant") {
@@ -108,7 +108,12 @@ function editDiffContent(name: string, input: Record<string, unknown>): ToolCall
   return [];
 }
 
-export function createToolCall(toolUseId: string, name: string, input: Record<string, unknown>): ToolCall {
+export function createToolCall(
+  toolUseId: string,
+  name: string,
+  input: Record<string, unknown>,
+  parentToolUseId?: string,
+): ToolCall {
   return {
     tool_call_id: toolUseId,
     title: toolTitle(name, input),
@@ -120,6 +125,7 @@ export function createToolCall(toolUseId: string, name: string, input: Record<st
     meta: {
       claudeCode: {
         toolName: name,
+        ...(parentToolUseId ? { parentToolUseId } : {}),
       },
     },
   };
@@ -266,6 +272,24 @@ function writeDiffFromResult(rawContent: unknown): ToolCall["content"] {
   return [];
 }
 
+function imageContentFromResult(rawContent: unknown): ToolCall["content"] {
+  const candidates = Array.isArray(rawContent) ? rawContent : [rawContent];
+  const images: ToolCall["content"] = [];
+  for (const candidate of candidates) {
+    const record = asRecordOrNull(candidate);
+    if (!record || record.type !== "image") {
+      continue;
+    }
+    const source = asRecordOrNull(record.source);
+    if (!source || source.type !== "base64" || typeof source.data !== "string") {
+      continue;
+    }
+    const mimeType = typeof source.media_type === "string" ? source.media_type : "image/png";
+    images.push({ type: "content", content: { type: "image", mime_type: mimeType, data: source.data } });
+  }
+  return images;
+}
+
 export function buildToolResultFields(
   isError: boolean,
   rawContent: unknown,
@@ -302,8 +326,12 @@ export function buildToolResultFields(
     }
   }
 
+  const images = isError ? [] : imageContentFromResult(rawContent);
   if (rawOutput) {
-    fields.content = [{ type: "content", content: { type: "text", text: rawOutput } }];
+    fields.content = [{ type: "content", content: { type: "text", text: rawOutput } }, ...images];
+  } else if (images.length > 0) {
+    fields.content = images;
+    fields.raw_output = `[${images.length} image${images.length === 1 ? "" : "s"}]`;
   }
   return fields;
 }
@@ -1,4 +1,4 @@
-import type { SessionUpdate } from "../types.js";
+import type { SessionUpdate, TurnUsage } from "../types.js";
 import { asRecordOrNull } from "./shared.js";
 
 export type UsageSessionContext = {
@@ -6,6 +6,11 @@ export type UsageSessionContext = {
   lastTotalCostUsd?: number;
 };
 
+// Per-API-call usage seen so far in the running turn, keyed by assistant message id.
+// The SDK repeats the same usage on every content-block message of one response,
+// so entries are replaced rather than summed.
+export type TurnUsageTracker = Map<string, TurnUsage>;
+
 function numberField(record: Record<string, unknown>, ...keys: string[]): number | undefined {
   for (const key of keys) {
     const value = record[key];
@@ -127,3 +132,42 @@ export function buildUsageUpdateFromResult(message: Record<string, unknown>): Se
   return buildUsageUpdateFromResultForSession(undefined, message);
 }
 
+
+// Record the usage carried by one SDK assistant message and return the cumulative
+// turn usage, so the client can enforce budgets before the turn's result arrives.
+export function recordAssistantTurnUsage(
+  tracker: TurnUsageTracker,
+  message: Record<string, unknown>,
+): SessionUpdate | null {
+  const messageObject = asRecordOrNull(message.message);
+  const usage = messageObject ? asRecordOrNull(messageObject.usage) : null;
+  if (!messageObject || !usage) {
+    return null;
+  }
+  const messageId =
+    typeof messageObject.id === "string" && messageObject.id.length > 0
+      ? messageObject.id
+      : `anonymous-${tracker.size}`;
+  tracker.set(messageId, {
+    input_tokens: numberField(usage, "input_tokens", "inputTokens") ?? 0,
+    output_tokens: numberField(usage, "output_tokens", "outputTokens") ?? 0,
+    cache_read_tokens:
+      numberField(usage, "cache_read_input_tokens", "cacheReadInputTokens") ?? 0,
+    cache_write_tokens:
+      numberField(usage, "cache_creation_input_tokens", "cacheCreationInputTokens") ?? 0,
+  });
+
+  const total: TurnUsage = {
+    input_tokens: 0,
+    output_tokens: 0,
+    cache_read_tokens: 0,
+    cache_write_tokens: 0,
+  };
+  for (const entry of tracker.values()) {
+    total.input_tokens += entry.input_tokens;
+    total.output_tokens += entry.output_tokens;
+    total.cache_read_tokens += entry.cache_read_tokens;
+    total.cache_write_tokens += entry.cache_write_tokens;
+  }
+  return { type: "turn_usage", usage: total };
+}
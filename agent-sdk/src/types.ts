@@ -34,6 +34,13 @@ export interface UsageUpdate {
   max_output_tokens?: number;
 }
 
+export interface TurnUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+}
+
 export type ContentBlock =
   | { type: "text"; text: string }
   | { type: "image"; mime_type?: string; uri?: string; data?: string };
@@ -92,8 +99,10 @@ export type SessionUpdate =
   | { type: "current_mode_update"; current_mode_id: string }
   | { type: "config_option_update"; option_id: string; value: Json }
   | { type: "usage_update"; usage: UsageUpdate }
+  | { type: "turn_usage"; usage: TurnUsage }
   | { type: "session_status_update"; status: "compacting" | "idle" }
-  | { type: "compaction_boundary"; trigger: "manual" | "auto"; pre_tokens: number };
+  | { type: "compaction_boundary"; trigger: "manual" | "auto"; pre_tokens: number }
+  | { type: "compaction_summary"; summary: string };
 
 export interface PermissionOption {
   option_id: string;
@@ -134,8 +143,15 @@ export type BridgeCommand =
   | {
       command: "load_session";
       session_id: string;
+      replace_session_id?: string;
       metadata?: Record<string, Json>;
     }
+  | {
+      command: "fork_session";
+      session_id: string;
+      prompt_text: string;
+      occurrence_from_end: number;
+    }
   | {
       command: "prompt";
       session_id: string;
@@ -160,6 +176,11 @@ export type BridgeCommand =
       cwd: string;
       yolo: boolean;
       model?: string;
+      replace_session_id?: string;
+    }
+  | {
+      command: "close_session";
+      session_id: string;
     }
   | {
       command: "permission_response";
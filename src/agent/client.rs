@@ -105,6 +105,11 @@ impl BridgeClient {
 #[derive(Clone)]
 pub struct AgentConnection {
     command_tx: mpsc::UnboundedSender<CommandEnvelope>,
+    /// Whether the bridge accepts `resource` prompt chunks, from its
+    /// initialize capabilities.
+    embedded_context: std::cell::Cell<bool>,
+    /// Whether the bridge accepts `image` prompt chunks.
+    images: std::cell::Cell<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -115,19 +120,46 @@ pub struct PromptResponse {
 impl AgentConnection {
     #[must_use]
     pub fn new(command_tx: mpsc::UnboundedSender<CommandEnvelope>) -> Self {
-        Self { command_tx }
+        Self {
+            command_tx,
+            embedded_context: std::cell::Cell::new(false),
+            images: std::cell::Cell::new(false),
+        }
+    }
+
+    pub fn set_capabilities(&self, capabilities: &crate::agent::types::AgentCapabilities) {
+        self.embedded_context.set(capabilities.prompt_embedded_context);
+        self.images.set(capabilities.prompt_image);
+    }
+
+    #[must_use]
+    pub fn supports_embedded_context(&self) -> bool {
+        self.embedded_context.get()
+    }
+
+    #[must_use]
+    pub fn supports_images(&self) -> bool {
+        self.images.get()
     }
 
     pub fn prompt_text(&self, session_id: String, text: String) -> anyhow::Result<PromptResponse> {
+        self.prompt(
+            session_id,
+            vec![crate::agent::types::PromptChunk {
+                kind: "text".to_owned(),
+                value: serde_json::Value::String(text),
+            }],
+        )
+    }
+
+    pub fn prompt(
+        &self,
+        session_id: String,
+        chunks: Vec<crate::agent::types::PromptChunk>,
+    ) -> anyhow::Result<PromptResponse> {
         self.send(CommandEnvelope {
             request_id: None,
-            command: BridgeCommand::Prompt {
-                session_id,
-                chunks: vec![crate::agent::types::PromptChunk {
-                    kind: "text".to_owned(),
-                    value: serde_json::Value::String(text),
-                }],
-            },
+            command: BridgeCommand::Prompt { session_id, chunks },
         })?;
         Ok(PromptResponse { stop_reason: "end_turn".to_owned() })
     }
@@ -153,28 +185,77 @@ impl AgentConnection {
         })
     }
 
+    /// Open an additional session next to the existing ones. `request_id` is
+    /// echoed on the resulting connect event so the caller can route it.
+    pub fn create_session(
+        &self,
+        cwd: String,
+        yolo: bool,
+        model: Option<String>,
+        request_id: String,
+    ) -> anyhow::Result<()> {
+        self.send(CommandEnvelope {
+            request_id: Some(request_id),
+            command: BridgeCommand::CreateSession {
+                cwd,
+                yolo,
+                model,
+                resume: None,
+                metadata: std::collections::BTreeMap::new(),
+            },
+        })
+    }
+
     pub fn new_session(
         &self,
         cwd: String,
         yolo: bool,
         model: Option<String>,
+        replace_session_id: Option<String>,
+        request_id: Option<String>,
     ) -> anyhow::Result<()> {
         self.send(CommandEnvelope {
-            request_id: None,
-            command: BridgeCommand::NewSession { cwd, yolo, model },
+            request_id,
+            command: BridgeCommand::NewSession { cwd, yolo, model, replace_session_id },
         })
     }
 
-    pub fn load_session(&self, session_id: String) -> anyhow::Result<()> {
+    pub fn load_session(
+        &self,
+        session_id: String,
+        replace_session_id: Option<String>,
+        request_id: Option<String>,
+    ) -> anyhow::Result<()> {
         self.send(CommandEnvelope {
-            request_id: None,
+            request_id,
             command: BridgeCommand::LoadSession {
                 session_id,
+                replace_session_id,
                 metadata: std::collections::BTreeMap::new(),
             },
         })
     }
 
+    pub fn fork_session(
+        &self,
+        session_id: String,
+        prompt_text: String,
+        occurrence_from_end: usize,
+        request_id: Option<String>,
+    ) -> anyhow::Result<()> {
+        self.send(CommandEnvelope {
+            request_id,
+            command: BridgeCommand::ForkSession { session_id, prompt_text, occurrence_from_end },
+        })
+    }
+
+    pub fn close_session(&self, session_id: String) -> anyhow::Result<()> {
+        self.send(CommandEnvelope {
+            request_id: None,
+            command: BridgeCommand::CloseSession { session_id },
+        })
+    }
+
     fn send(&self, envelope: CommandEnvelope) -> anyhow::Result<()> {
         self.command_tx.send(envelope).map_err(|_| anyhow::anyhow!("bridge command channel closed"))
     }
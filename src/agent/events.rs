@@ -68,8 +68,22 @@ pub enum ClientEvent {
     },
     /// Startup update check found a newer published version.
     UpdateAvailable { latest_version: String, current_version: String },
+    /// A `!command` shell escape exited (`None` when killed by a signal).
+    ShellCommandFinished { tool_call_id: String, exit_code: Option<i32> },
+    /// The background file index published a new snapshot.
+    FileIndexUpdated,
     /// Fatal app error that should terminate and map to an exit code.
     FatalError(AppError),
+    /// Session-scoped event addressed to the tab that owns the session.
+    Routed { target: EventTarget, event: Box<ClientEvent> },
+}
+
+/// Owner of a routed event: a bridge session id, or a tab id echoed back
+/// through the request id of the command that created its session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventTarget {
+    Session(String),
+    Tab(u64),
 }
 
 /// Shared handle to all spawned terminal processes.
@@ -17,6 +17,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -92,15 +93,36 @@ impl TextContent {
 pub struct ImageContent {
     pub data: String,
     pub mime_type: String,
+    /// Facts derived from `data` on first use, so rendering does not redo them.
+    #[serde(skip)]
+    pub derived: ImageDerived,
 }
 
 impl ImageContent {
     #[must_use]
     pub fn new(data: impl Into<String>, mime_type: impl Into<String>) -> Self {
-        Self { data: data.into(), mime_type: mime_type.into() }
+        Self { data: data.into(), mime_type: mime_type.into(), derived: ImageDerived::default() }
     }
 }
 
+/// Lazily computed image facts. Clones keep what was already computed;
+/// equality ignores them since they follow from the payload.
+#[derive(Debug, Clone, Default)]
+pub struct ImageDerived {
+    /// Pixel `(width, height)`, `None` when the payload cannot be decoded.
+    pub dimensions: OnceLock<Option<(u32, u32)>>,
+    /// Hash of the full payload and mime type.
+    pub key: OnceLock<u64>,
+}
+
+impl PartialEq for ImageDerived {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ImageDerived {}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ContentBlock {
     Text(TextContent),
@@ -487,6 +509,16 @@ pub struct UsageUpdate {
     pub max_output_tokens: Option<u64>,
 }
 
+/// Cumulative token usage of the running turn; superseded by the turn's `UsageUpdate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[allow(clippy::struct_field_names)]
+pub struct TurnUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionStatus {
@@ -519,8 +551,11 @@ pub enum SessionUpdate {
     CurrentModeUpdate(CurrentModeUpdate),
     ConfigOptionUpdate(ConfigOptionUpdate),
     UsageUpdate(UsageUpdate),
+    TurnUsage(TurnUsage),
     SessionStatusUpdate(SessionStatus),
     CompactionBoundary(CompactionBoundary),
+    /// Summary the agent continues from after the last `CompactionBoundary`.
+    CompactionSummary(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -50,6 +50,16 @@ pub struct UsageUpdate {
     pub max_output_tokens: Option<u64>,
 }
 
+/// Cumulative token usage of the running turn, reported per API response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_field_names)]
+pub struct TurnUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionStatus {
@@ -136,8 +146,10 @@ pub enum SessionUpdate {
     CurrentModeUpdate { current_mode_id: String },
     ConfigOptionUpdate { option_id: String, value: serde_json::Value },
     UsageUpdate { usage: UsageUpdate },
+    TurnUsage { usage: TurnUsage },
     SessionStatusUpdate { status: SessionStatus },
     CompactionBoundary { trigger: CompactionTrigger, pre_tokens: u64 },
+    CompactionSummary { summary: String },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
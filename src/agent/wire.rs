@@ -44,9 +44,21 @@ pub enum BridgeCommand {
     },
     LoadSession {
         session_id: String,
+        /// Session to close once the loaded one connects. Without it every
+        /// open session is replaced.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        replace_session_id: Option<String>,
         #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
         metadata: BTreeMap<String, serde_json::Value>,
     },
+    /// Branch `session_id` just before the user prompt `prompt_text`, counting
+    /// `occurrence_from_end` identical prompts back from the newest. The fork
+    /// replaces the session; the original stays on disk for `LoadSession`.
+    ForkSession {
+        session_id: String,
+        prompt_text: String,
+        occurrence_from_end: usize,
+    },
     Prompt {
         session_id: String,
         chunks: Vec<types::PromptChunk>,
@@ -66,6 +78,13 @@ pub enum BridgeCommand {
         cwd: String,
         yolo: bool,
         model: Option<String>,
+        /// Session to close before creating the new one. Without it every
+        /// open session is closed.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        replace_session_id: Option<String>,
+    },
+    CloseSession {
+        session_id: String,
     },
     PermissionResponse {
         session_id: String,
@@ -157,6 +176,46 @@ mod tests {
         assert_eq!(decoded, env);
     }
 
+    #[test]
+    fn new_session_omits_absent_replace_session_id() {
+        let env = CommandEnvelope {
+            request_id: None,
+            command: BridgeCommand::NewSession {
+                cwd: "/work".to_owned(),
+                yolo: false,
+                model: None,
+                replace_session_id: None,
+            },
+        };
+        let json = serde_json::to_string(&env).expect("serialize");
+        assert!(!json.contains("replace_session_id"));
+        let decoded: CommandEnvelope = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(decoded, env);
+    }
+
+    #[test]
+    fn fork_session_uses_bridge_field_names() {
+        let env = CommandEnvelope {
+            request_id: Some("tab-1".to_owned()),
+            command: BridgeCommand::ForkSession {
+                session_id: "s1".to_owned(),
+                prompt_text: "fix the build".to_owned(),
+                occurrence_from_end: 1,
+            },
+        };
+        let json = serde_json::to_value(&env).expect("serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "request_id": "tab-1",
+                "command": "fork_session",
+                "session_id": "s1",
+                "prompt_text": "fix the build",
+                "occurrence_from_end": 1,
+            })
+        );
+    }
+
     #[test]
     fn event_envelope_roundtrip_json() {
         let env = EventEnvelope {
@@ -0,0 +1,593 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Attachments tray above the input.
+//!
+//! Lists what the draft will send besides its text: `@` mentions, collapsed
+//! paste blocks and images, each with a size and token estimate. With focus
+//! (Alt+P) an item can be previewed, removed from the draft, or switched
+//! between inline and attached. Inline means part of the prompt text (a
+//! mention as a reference the agent resolves itself); attached means its
+//! content travels as a separate prompt chunk. The choices are kept here
+//! until the prompt that uses them is sent.
+
+use super::mention_context::{self, PromptSupport, Target};
+use super::overlay::{self, OverlayKind};
+use super::{App, FocusOwner, FocusTarget};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Lines shown by a preview.
+const PREVIEW_LINES: usize = 400;
+/// Bytes read from a file for its preview; the rest is never loaded.
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Long edge images are scaled to before they are tokenized.
+const IMAGE_MAX_EDGE: f64 = 1568.0;
+/// Pixels per image token.
+const IMAGE_PIXELS_PER_TOKEN: f64 = 750.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachMode {
+    Inline,
+    Attached,
+}
+
+impl AttachMode {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Attached => "attached",
+        }
+    }
+
+    #[must_use]
+    const fn toggled(self) -> Self {
+        match self {
+            Self::Inline => Self::Attached,
+            Self::Attached => Self::Inline,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    File,
+    Directory,
+    /// Line range or symbol.
+    Span,
+    Image,
+    Paste,
+    /// A range or symbol that matched nothing.
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    /// Mention text without the `@` and trailing punctuation.
+    Mention(String),
+    /// Index into `InputState::paste_blocks`.
+    Paste(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    pub label: String,
+    pub bytes: Option<u64>,
+    pub tokens: Option<u64>,
+    pub mode: AttachMode,
+    source: Source,
+    /// Input row and byte range of the mention or placeholder.
+    row: usize,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct AttachmentTray {
+    /// Modes picked in the tray, by mention, until the prompt is sent.
+    mention_modes: HashMap<String, AttachMode>,
+    /// Paste blocks switched to attached, by content.
+    attached_pastes: Vec<String>,
+    /// Highlighted item while the tray has focus.
+    pub selected: usize,
+    entries: Vec<Attachment>,
+    /// Input version, index versions and agent support `entries` were
+    /// built for.
+    built_for: Option<(u64, IndexVersions, PromptSupport)>,
+    /// Resolved mentions of the current draft, so symbol lookups run once
+    /// per index version.
+    targets: HashMap<String, Option<Target>>,
+    targets_version: IndexVersions,
+}
+
+/// File index and tag index versions; resolved mentions go stale with either.
+type IndexVersions = (u64, u64);
+
+impl AttachmentTray {
+    #[must_use]
+    pub fn entries(&self) -> &[Attachment] {
+        &self.entries
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The tray's mode for `mention`, forgotten once read by the send path.
+    pub(super) fn take_mention_mode(&mut self, mention: &str) -> Option<AttachMode> {
+        self.mention_modes.remove(mention)
+    }
+
+    /// Attached paste blocks found verbatim in `text`, forgotten once taken.
+    pub(super) fn take_attached_pastes(&mut self, text: &str) -> Vec<String> {
+        let (taken, kept) =
+            std::mem::take(&mut self.attached_pastes).into_iter().partition(|c| text.contains(c));
+        self.attached_pastes = kept;
+        taken
+    }
+
+    fn invalidate(&mut self) {
+        self.built_for = None;
+    }
+}
+
+fn text_tokens(bytes: u64) -> u64 {
+    bytes.div_ceil(4)
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn image_tokens(path: &std::path::Path) -> Option<u64> {
+    let (width, height) = image::image_dimensions(path).ok()?;
+    let (width, height) = (f64::from(width), f64::from(height));
+    let scale = (IMAGE_MAX_EDGE / width.max(height)).min(1.0);
+    Some(((width * scale) * (height * scale) / IMAGE_PIXELS_PER_TOKEN).ceil() as u64)
+}
+
+/// Byte offset of `col` (chars) in `line`.
+fn byte_at(line: &str, col: usize) -> usize {
+    line.char_indices().nth(col).map_or(line.len(), |(i, _)| i)
+}
+
+fn mention_entry(
+    target: &Target,
+    mention: &str,
+    mode: Option<AttachMode>,
+    support: PromptSupport,
+) -> (AttachmentKind, Option<u64>, Option<u64>, AttachMode) {
+    let mode = mode.unwrap_or_else(|| target.default_mode(support));
+    match target {
+        Target::File { bytes, .. } => {
+            (AttachmentKind::File, Some(*bytes), Some(text_tokens(*bytes)), mode)
+        }
+        Target::Directory { .. } => (AttachmentKind::Directory, None, None, AttachMode::Inline),
+        Target::Image { abs_path, bytes, .. } => {
+            (AttachmentKind::Image, Some(*bytes), image_tokens(abs_path), mode)
+        }
+        Target::Spans(spans) => {
+            let bytes = spans.iter().map(|span| span.text.len() as u64).sum();
+            (AttachmentKind::Span, Some(bytes), Some(text_tokens(bytes)), mode)
+        }
+        Target::Missing => {
+            tracing::debug!("attachment @{mention} resolved to nothing");
+            (AttachmentKind::Missing, None, None, mode)
+        }
+    }
+}
+
+/// Rebuild the tray when the draft or the project's files changed. Runs from
+/// the event loop after input handling, never from rendering.
+pub fn refresh(app: &mut App) {
+    let support = PromptSupport::of(app.conn.as_deref());
+    let indexes = (app.file_index.version(), app.file_index.tags.version());
+    let key = (app.input.version, indexes, support);
+    if app.attachments.built_for == Some(key) {
+        return;
+    }
+    app.attachments.built_for = Some(key);
+    app.needs_redraw = true;
+    if app.input.is_empty() || app.attachments.targets_version != indexes {
+        app.attachments.targets.clear();
+        app.attachments.targets_version = indexes;
+    }
+
+    let mut entries = Vec::new();
+    let lines = app.input.lines.clone();
+    let (cursor_row, cursor_col) = (app.input.cursor_row, app.input.cursor_col);
+    for (row, line) in lines.iter().enumerate() {
+        for (start, end, token) in super::mention::find_mention_spans(line) {
+            // Skip the mention being typed; it is not settled yet.
+            let cursor = byte_at(line, cursor_col);
+            if row == cursor_row && start < cursor && cursor <= end {
+                continue;
+            }
+            let target = if let Some(target) = app.attachments.targets.get(&token) {
+                target.clone()
+            } else {
+                let target = mention_context::resolve_mention(app, &token);
+                app.attachments.targets.insert(token.clone(), target.clone());
+                target
+            };
+            let Some(target) = target else {
+                continue;
+            };
+            let mention = token.trim_end_matches(mention_context::TRAILING_PUNCTUATION).to_owned();
+            let mode = app.attachments.mention_modes.get(&mention).copied();
+            let (kind, bytes, tokens, mode) = mention_entry(&target, &mention, mode, support);
+            entries.push(Attachment {
+                kind,
+                label: format!("@{mention}"),
+                bytes,
+                tokens,
+                mode,
+                source: Source::Mention(mention),
+                row,
+                start,
+                end,
+            });
+        }
+    }
+    for (row, start, end, index) in app.input.paste_placeholders() {
+        let content = &app.input.paste_blocks[index];
+        let bytes = content.len() as u64;
+        let mode = if app.attachments.attached_pastes.contains(content) {
+            AttachMode::Attached
+        } else {
+            AttachMode::Inline
+        };
+        entries.push(Attachment {
+            kind: AttachmentKind::Paste,
+            label: format!("Pasted Text {}", index + 1),
+            bytes: Some(bytes),
+            tokens: Some(text_tokens(bytes)),
+            mode,
+            source: Source::Paste(index),
+            row,
+            start,
+            end,
+        });
+    }
+    app.attachments.selected = app.attachments.selected.min(entries.len().saturating_sub(1));
+    app.attachments.entries = entries;
+    if app.attachments.entries.is_empty() {
+        app.release_focus_target(FocusTarget::Attachments);
+    }
+}
+
+/// Toggle keyboard focus on the tray (Alt+P).
+pub(super) fn toggle_focus(app: &mut App) -> bool {
+    refresh(app);
+    if app.attachments.is_empty() {
+        return false;
+    }
+    if app.focus_owner() == FocusOwner::Attachments {
+        app.release_focus_target(FocusTarget::Attachments);
+    } else {
+        app.claim_focus_target(FocusTarget::Attachments);
+    }
+    app.needs_redraw = true;
+    true
+}
+
+/// Keys while the tray has focus. Returns `true` when consumed; other keys
+/// fall through to the input.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    refresh(app);
+    let len = app.attachments.entries.len();
+    if len == 0 {
+        return false;
+    }
+    if key.modifiers != KeyModifiers::NONE {
+        return false;
+    }
+    let selected = app.attachments.selected.min(len - 1);
+    match key.code {
+        KeyCode::Esc => app.release_focus_target(FocusTarget::Attachments),
+        KeyCode::Left => app.attachments.selected = selected.saturating_sub(1),
+        KeyCode::Right => {
+            app.attachments.selected = (selected + 1).min(len - 1);
+        }
+        KeyCode::Enter | KeyCode::Char('p') => preview(app, selected),
+        KeyCode::Tab | KeyCode::Char('c') => convert(app, selected),
+        KeyCode::Delete | KeyCode::Backspace | KeyCode::Char('d') => remove(app, selected),
+        _ => return false,
+    }
+    app.needs_redraw = true;
+    true
+}
+
+fn convert(app: &mut App, index: usize) {
+    let Some(entry) = app.attachments.entries.get(index).cloned() else {
+        return;
+    };
+    let support = PromptSupport::of(app.conn.as_deref());
+    let next = entry.mode.toggled();
+    let refusal = match entry.kind {
+        AttachmentKind::Directory => Some("Directories are always sent as a reference"),
+        AttachmentKind::Missing => Some("Nothing to attach: the reference matched nothing"),
+        AttachmentKind::Image if next == AttachMode::Attached && !support.images => {
+            Some("The agent does not accept attached images")
+        }
+        _ if next == AttachMode::Attached && !support.embedded_context => {
+            Some("The agent does not accept attached context")
+        }
+        _ => None,
+    };
+    if let Some(refusal) = refusal {
+        app.show_toast(refusal, true);
+        return;
+    }
+    match entry.source {
+        Source::Mention(mention) => {
+            app.attachments.mention_modes.insert(mention, next);
+        }
+        Source::Paste(index) => {
+            let Some(content) = app.input.paste_blocks.get(index).cloned() else {
+                return;
+            };
+            app.attachments.attached_pastes.retain(|known| *known != content);
+            if next == AttachMode::Attached {
+                app.attachments.attached_pastes.push(content);
+            }
+        }
+    }
+    app.attachments.invalidate();
+}
+
+fn remove(app: &mut App, index: usize) {
+    let Some(entry) = app.attachments.entries.get(index).cloned() else {
+        return;
+    };
+    match &entry.source {
+        Source::Mention(mention) => {
+            app.attachments.mention_modes.remove(mention);
+        }
+        Source::Paste(index) => {
+            if let Some(content) = app.input.paste_blocks.get(*index) {
+                app.attachments.attached_pastes.retain(|known| known != content);
+            }
+        }
+    }
+    app.input.remove_range(entry.row, entry.start, entry.end);
+    refresh(app);
+}
+
+fn preview_lines(app: &mut App, entry: &Attachment) -> Vec<String> {
+    let mut lines = Vec::new();
+    match &entry.source {
+        Source::Paste(index) => {
+            let content = app.input.paste_blocks.get(*index).cloned().unwrap_or_default();
+            lines.extend(content.lines().map(str::to_owned));
+        }
+        Source::Mention(mention) => match mention_context::resolve_mention(app, mention) {
+            Some(Target::File { abs_path, .. }) => {
+                lines.extend(file_preview(&abs_path));
+            }
+            Some(Target::Directory { abs_path }) => {
+                let prefix = mention.trim_end_matches('/');
+                let prefix = format!("{prefix}/");
+                let files = app.file_index.cache.clone().unwrap_or_default();
+                lines.extend(
+                    files
+                        .iter()
+                        .filter(|file| file.rel_path.starts_with(&prefix))
+                        .map(|file| file.rel_path.clone()),
+                );
+                if lines.is_empty() {
+                    lines.push(format!("{}", abs_path.display()));
+                }
+            }
+            Some(Target::Image { abs_path, mime_type, .. }) => {
+                lines.push(format!("{mime_type} {}", abs_path.display()));
+                if let Ok((width, height)) = image::image_dimensions(&abs_path) {
+                    lines.push(format!("{width} \u{00D7} {height} px"));
+                }
+            }
+            Some(Target::Spans(spans)) => {
+                for span in spans {
+                    if !lines.is_empty() {
+                        lines.push(String::new());
+                    }
+                    lines.push(format!(
+                        "{}:{}-{}",
+                        span.display_path, span.start_line, span.end_line
+                    ));
+                    lines.extend(span.text.lines().map(str::to_owned));
+                }
+            }
+            Some(Target::Missing) | None => {
+                lines.push("Nothing matches this reference.".to_owned());
+            }
+        },
+    }
+    if lines.len() > PREVIEW_LINES {
+        let hidden = lines.len() - PREVIEW_LINES;
+        lines.truncate(PREVIEW_LINES);
+        lines.push(format!("\u{2026} {hidden} more lines"));
+    }
+    lines
+}
+
+/// Leading lines of `path`, reading at most [`PREVIEW_BYTES`].
+fn file_preview(path: &std::path::Path) -> Vec<String> {
+    use std::io::Read as _;
+    let mut bytes = Vec::new();
+    let read = std::fs::File::open(path).and_then(|file| {
+        let size = file.metadata()?.len();
+        file.take(PREVIEW_BYTES).read_to_end(&mut bytes)?;
+        Ok(size)
+    });
+    let size = match read {
+        Ok(size) => size,
+        Err(err) => return vec![format!("Cannot show {}: {err}", path.display())],
+    };
+    if bytes.contains(&0) {
+        return vec![format!("Binary file, {size} bytes")];
+    }
+    let mut lines: Vec<String> =
+        String::from_utf8_lossy(&bytes).lines().map(str::to_owned).collect();
+    if size > PREVIEW_BYTES {
+        // The cut may split the last line.
+        lines.pop();
+        lines.push(format!("\u{2026} first {} KB of {size} bytes", PREVIEW_BYTES / 1024));
+    }
+    lines
+}
+
+fn preview(app: &mut App, index: usize) {
+    let Some(entry) = app.attachments.entries.get(index).cloned() else {
+        return;
+    };
+    let lines = preview_lines(app, &entry);
+    overlay::open(app, OverlayKind::Preview { title: entry.label, lines });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::client::AgentConnection;
+    use crate::agent::types::AgentCapabilities;
+
+    fn app_in(dir: &std::path::Path) -> App {
+        let mut app = App::test_default();
+        app.cwd_raw = dir.to_string_lossy().into_owned();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let conn = AgentConnection::new(tx);
+        conn.set_capabilities(&AgentCapabilities {
+            prompt_image: true,
+            prompt_embedded_context: true,
+            load_session: true,
+            supports_list_sessions: true,
+            supports_resume: true,
+        });
+        app.conn = Some(std::rc::Rc::new(conn));
+        app
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn tray_lists_mentions_and_pastes_with_estimates() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}\n".repeat(10)).expect("write");
+        let mut app = app_in(dir.path());
+        app.input.set_text("see @src/main.rs and @src/ and @nobody ");
+        app.input.insert_paste_block(&"x".repeat(1200));
+        refresh(&mut app);
+
+        let entries = app.attachments.entries();
+        let labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, vec!["@src/main.rs", "@src/", "Pasted Text 1"]);
+        assert_eq!(entries[0].kind, AttachmentKind::File);
+        assert_eq!(entries[0].bytes, Some(130));
+        assert_eq!(entries[0].tokens, Some(33));
+        assert_eq!(entries[0].mode, AttachMode::Inline);
+        assert_eq!(entries[1].kind, AttachmentKind::Directory);
+        assert_eq!(entries[2].tokens, Some(300));
+    }
+
+    #[test]
+    fn resolved_mentions_are_dropped_when_the_file_index_changes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("notes.md"), "short\n").expect("write");
+        let mut app = app_in(dir.path());
+        app.input.set_text("read @notes.md ");
+        refresh(&mut app);
+        assert_eq!(app.attachments.entries()[0].bytes, Some(6));
+
+        std::fs::write(dir.path().join("notes.md"), "much longer now\n").expect("write");
+        refresh(&mut app);
+        assert_eq!(app.attachments.entries()[0].bytes, Some(6));
+
+        crate::app::file_index::refresh(&mut app);
+        refresh(&mut app);
+        assert_eq!(app.attachments.entries()[0].bytes, Some(16));
+    }
+
+    #[test]
+    fn converted_items_are_sent_as_separate_chunks() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("notes.md"), "# Notes\nbody\n").expect("write");
+        let mut app = app_in(dir.path());
+        let paste = "p".repeat(1200);
+        app.input.set_text("read @notes.md then ");
+        app.input.insert_paste_block(&paste);
+        assert!(toggle_focus(&mut app));
+        assert_eq!(app.focus_owner(), FocusOwner::Attachments);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('c'))));
+        assert!(handle_key(&mut app, key(KeyCode::Right)));
+        assert!(handle_key(&mut app, key(KeyCode::Char('c'))));
+        refresh(&mut app);
+        assert!(app.attachments.entries().iter().all(|e| e.mode == AttachMode::Attached));
+
+        let text = app.input.text();
+        let support = PromptSupport::of(app.conn.as_deref());
+        let chunks = mention_context::prompt_chunks(&mut app, text, support);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].value, "read @notes.md then [Pasted Text 1]");
+        assert_eq!(chunks[1].value["text"], paste);
+        assert_eq!(chunks[2].value["text"], "# Notes\nbody\n");
+        assert!(app.attachments.mention_modes.is_empty());
+        assert!(app.attachments.attached_pastes.is_empty());
+    }
+
+    #[test]
+    fn remove_deletes_the_mention_and_preview_opens_an_overlay() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.txt"), "alpha\n").expect("write");
+        std::fs::write(dir.path().join("b.txt"), "beta\n").expect("write");
+        let mut app = app_in(dir.path());
+        app.input.set_text("compare @a.txt with @b.txt please");
+        assert!(toggle_focus(&mut app));
+
+        assert!(handle_key(&mut app, key(KeyCode::Enter)));
+        assert!(matches!(
+            app.overlay.as_ref().map(|o| &o.kind),
+            Some(OverlayKind::Preview { lines, .. }) if lines == &["alpha".to_owned()]
+        ));
+        overlay::close(&mut app);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('d'))));
+        assert_eq!(app.input.text(), "compare with @b.txt please");
+        assert_eq!(app.attachments.entries().len(), 1);
+        assert!(handle_key(&mut app, key(KeyCode::Delete)));
+        assert_eq!(app.input.text(), "compare with please");
+        assert!(app.attachments.is_empty());
+        assert_ne!(app.focus_owner(), FocusOwner::Attachments);
+    }
+
+    #[test]
+    fn file_preview_reads_a_bounded_prefix() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let big = dir.path().join("big.log");
+        let content = format!("{}\n", "x".repeat(99)).repeat(2_000);
+        std::fs::write(&big, content).expect("write");
+        let lines = file_preview(&big);
+        assert!(lines.len() < 700, "{}", lines.len());
+        assert!(lines.last().is_some_and(|l| l.starts_with("\u{2026} first 64 KB")));
+
+        let binary = dir.path().join("blob.bin");
+        std::fs::write(&binary, [0_u8, 1, 2]).expect("write");
+        assert_eq!(file_preview(&binary), vec!["Binary file, 3 bytes".to_owned()]);
+    }
+}
@@ -0,0 +1,385 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Editing an earlier prompt, and the session branches that creates.
+//!
+//! Resubmitting an edited prompt asks the bridge to fork the session just
+//! before it. The original session stays on disk and is listed as a branch so
+//! `/branch` can switch back to it.
+
+use super::{App, AppStatus, ChatMessage, MessageBlock, MessageRole};
+use std::fmt::Write as _;
+
+/// Characters of the edited prompt kept as the branch label.
+const LABEL_CHARS: usize = 40;
+
+/// Earlier user message loaded into the input, and the draft it displaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageEdit {
+    pub message_index: usize,
+    pub stashed_draft: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionBranch {
+    pub session_id: String,
+    pub label: String,
+}
+
+/// Session swap requested by this module, resolved on `SessionReplaced`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Transition {
+    /// Fork in flight; `prompt` is sent once the forked session is live.
+    Fork {
+        prompt: String,
+    },
+    Switch,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BranchState {
+    /// Empty until the first fork; then the original session plus each fork.
+    pub items: Vec<SessionBranch>,
+    pub active: usize,
+    pub editing: Option<MessageEdit>,
+    transition: Option<Transition>,
+}
+
+impl BranchState {
+    /// `(active, total)` for the header, 1-based, once there is anything to switch to.
+    #[must_use]
+    pub fn indicator(&self) -> Option<(usize, usize)> {
+        (self.items.len() > 1).then(|| (self.active + 1, self.items.len()))
+    }
+
+    #[must_use]
+    pub fn is_editing(&self, message_index: usize) -> bool {
+        self.editing.as_ref().is_some_and(|edit| edit.message_index == message_index)
+    }
+}
+
+/// Text of a user prompt that can be edited. Local slash commands never reach
+/// the transcript, so they are skipped.
+fn editable_prompt(msg: &ChatMessage) -> Option<String> {
+    if !matches!(msg.role, MessageRole::User) {
+        return None;
+    }
+    let text: String = msg
+        .blocks
+        .iter()
+        .filter_map(|block| match block {
+            MessageBlock::Text(text, ..) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    let trimmed = text.trim();
+    (!trimmed.is_empty() && !trimmed.starts_with('/')).then(|| trimmed.to_owned())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn branch_label(prompt: &str) -> String {
+    let collapsed = collapse_whitespace(prompt);
+    if collapsed.chars().count() <= LABEL_CHARS {
+        return collapsed;
+    }
+    let mut label: String = collapsed.chars().take(LABEL_CHARS).collect();
+    label.push('\u{2026}');
+    label
+}
+
+fn set_edit_target(app: &mut App, index: usize) {
+    if let Some(previous) = app.branches.editing.as_ref().map(|edit| edit.message_index) {
+        app.mark_message_layout_dirty(previous);
+    }
+    app.mark_message_layout_dirty(index);
+    app.viewport.scroll_to_message(index);
+    app.needs_redraw = true;
+}
+
+/// Load the previous user prompt into the input (Alt+E). Pressing it again
+/// steps further back. Only available between turns.
+pub(super) fn step_edit(app: &mut App) -> bool {
+    if app.status != AppStatus::Ready
+        || app.session_id.is_none()
+        || app.submission_queue.editing.is_some()
+        || app.branches.transition.is_some()
+    {
+        return false;
+    }
+    let before = app.branches.editing.as_ref().map_or(app.messages.len(), |e| e.message_index);
+    let Some((index, text)) = app.messages[..before]
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, msg)| editable_prompt(msg).map(|text| (i, text)))
+    else {
+        return app.branches.editing.is_some();
+    };
+    set_edit_target(app, index);
+    let stashed_draft = match app.branches.editing.take() {
+        Some(edit) => edit.stashed_draft,
+        None => app.input.text(),
+    };
+    app.input.set_text(&text);
+    app.branches.editing = Some(MessageEdit { message_index: index, stashed_draft });
+    true
+}
+
+/// Abandon an in-progress message edit and put the stashed draft back.
+pub(super) fn cancel_edit(app: &mut App) -> bool {
+    let Some(edit) = app.branches.editing.take() else {
+        return false;
+    };
+    app.input.set_text(&edit.stashed_draft);
+    app.mark_message_layout_dirty(edit.message_index);
+    app.viewport.engage_auto_scroll();
+    app.needs_redraw = true;
+    true
+}
+
+/// Resubmit an edited prompt by forking the session just before the original.
+/// Returns `false` when no edit is in progress.
+pub(super) fn submit_edit(app: &mut App, text: &str) -> bool {
+    let Some(edit) = app.branches.editing.take() else {
+        return false;
+    };
+    app.input.set_text(&edit.stashed_draft);
+    app.mark_message_layout_dirty(edit.message_index);
+    app.viewport.engage_auto_scroll();
+    app.needs_redraw = true;
+    if text.trim().is_empty() {
+        return true;
+    }
+    let Some(original) = app.messages.get(edit.message_index).and_then(editable_prompt) else {
+        return true;
+    };
+    let target = collapse_whitespace(&original);
+    let occurrence_from_end = app.messages[edit.message_index + 1..]
+        .iter()
+        .filter_map(editable_prompt)
+        .filter(|later| collapse_whitespace(later) == target)
+        .count();
+
+    let (Some(conn), Some(session_id)) = (app.conn.clone(), app.session_id.clone()) else {
+        return true;
+    };
+    let request_id = Some(super::tabs::tab_request_id(super::tabs::active_tab_id(app)));
+    if let Err(e) =
+        conn.fork_session(session_id.to_string(), original, occurrence_from_end, request_id)
+    {
+        tracing::error!("Failed to send fork_session: {e}");
+        app.input.set_text(text);
+        return true;
+    }
+    app.branches.transition = Some(Transition::Fork { prompt: text.to_owned() });
+    app.status = AppStatus::Resuming;
+    app.resuming_session_id = Some(session_id.to_string());
+    true
+}
+
+/// Resume branch `index` in place of the current session.
+pub(super) fn switch_to(app: &mut App, index: usize) -> Result<(), String> {
+    let Some(branch) = app.branches.items.get(index) else {
+        return Err(format!("No branch {}. Use /branch to list them.", index + 1));
+    };
+    if index == app.branches.active {
+        return Err(format!("Already on branch {}.", index + 1));
+    }
+    if app.status != AppStatus::Ready {
+        return Err("Cannot switch branches while a turn is running.".to_owned());
+    }
+    let Some(conn) = app.conn.clone() else {
+        return Err("Cannot switch branches: not connected yet.".to_owned());
+    };
+    let session_id = branch.session_id.clone();
+    let replace = app.session_id.as_ref().map(ToString::to_string);
+    let request_id = Some(super::tabs::tab_request_id(super::tabs::active_tab_id(app)));
+    conn.load_session(session_id.clone(), replace, request_id).map_err(|e| e.to_string())?;
+    app.branches.transition = Some(Transition::Switch);
+    app.status = AppStatus::Resuming;
+    app.resuming_session_id = Some(session_id);
+    Ok(())
+}
+
+/// Branch list for `/branch`, newest last, with the active one marked.
+pub(super) fn list_text(app: &App) -> String {
+    if app.branches.items.is_empty() {
+        return "No branches yet. Press Alt+E to edit an earlier prompt and fork from it."
+            .to_owned();
+    }
+    let mut text = String::from("Branches:");
+    for (i, branch) in app.branches.items.iter().enumerate() {
+        let marker = if i == app.branches.active { '*' } else { ' ' };
+        let short_id: String = branch.session_id.chars().take(8).collect();
+        let _ = write!(text, "\n{marker} {}. {} ({short_id})", i + 1, branch.label);
+    }
+    text.push_str("\nSwitch with /branch <number>.");
+    text
+}
+
+/// Resolve a pending fork or switch once the bridge has swapped sessions.
+/// Any other replacement starts an unrelated session, which drops the list.
+pub(super) fn on_session_replaced(app: &mut App, previous_session_id: Option<String>) {
+    app.branches.editing = None;
+    let new_id = app.session_id.as_ref().map(ToString::to_string).unwrap_or_default();
+    match app.branches.transition.take() {
+        Some(Transition::Fork { prompt }) => {
+            if app.branches.items.is_empty()
+                && let Some(original) = previous_session_id
+            {
+                app.branches
+                    .items
+                    .push(SessionBranch { session_id: original, label: "original".to_owned() });
+            }
+            app.branches
+                .items
+                .push(SessionBranch { session_id: new_id, label: branch_label(&prompt) });
+            app.branches.active = app.branches.items.len() - 1;
+            app.cached_header_line = None;
+            super::input_submit::enqueue_submission(app, prompt);
+        }
+        Some(Transition::Switch) | None => {
+            if let Some(index) = app.branches.items.iter().position(|b| b.session_id == new_id) {
+                app.branches.active = index;
+            } else {
+                app.branches = BranchState::default();
+            }
+            app.cached_header_line = None;
+        }
+    }
+}
+
+/// The bridge rejected a fork or switch: return the edited prompt to the input.
+pub(super) fn on_transition_failed(app: &mut App) {
+    if let Some(Transition::Fork { prompt }) = app.branches.transition.take()
+        && app.input.is_empty()
+    {
+        app.input.set_text(&prompt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::model;
+    use crate::agent::wire::{BridgeCommand, CommandEnvelope};
+    use crate::app::{BlockCache, IncrementalMarkdown};
+    use std::rc::Rc;
+    use tokio::sync::mpsc;
+
+    fn text_message(role: MessageRole, text: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            blocks: vec![MessageBlock::Text(
+                text.to_owned(),
+                BlockCache::default(),
+                IncrementalMarkdown::from_complete(text),
+            )],
+            usage: None,
+        }
+    }
+
+    fn connected_app() -> (App, mpsc::UnboundedReceiver<CommandEnvelope>) {
+        let mut app = App::test_default();
+        let (tx, rx) = mpsc::unbounded_channel();
+        app.conn = Some(Rc::new(crate::agent::client::AgentConnection::new(tx)));
+        app.session_id = Some(model::SessionId::new("s-original"));
+        app.status = AppStatus::Ready;
+        app.messages = vec![
+            text_message(MessageRole::User, "fix the build"),
+            text_message(MessageRole::Assistant, "done"),
+            text_message(MessageRole::User, "/usage"),
+            text_message(MessageRole::User, "fix the  build"),
+            text_message(MessageRole::Assistant, "done again"),
+        ];
+        (app, rx)
+    }
+
+    #[test]
+    fn step_edit_walks_back_over_prompts_and_cancel_restores_draft() {
+        let (mut app, _rx) = connected_app();
+        app.input.set_text("draft");
+
+        assert!(step_edit(&mut app));
+        assert_eq!(app.input.text(), "fix the  build");
+        assert!(app.branches.is_editing(3));
+
+        assert!(step_edit(&mut app), "slash commands are skipped");
+        assert!(app.branches.is_editing(0));
+        assert!(step_edit(&mut app), "stays on the oldest prompt");
+        assert!(app.branches.is_editing(0));
+
+        assert!(cancel_edit(&mut app));
+        assert_eq!(app.input.text(), "draft");
+        assert!(app.branches.editing.is_none());
+    }
+
+    #[test]
+    fn submitting_edit_forks_then_sends_prompt_on_new_branch() {
+        let (mut app, mut rx) = connected_app();
+        step_edit(&mut app);
+        step_edit(&mut app);
+
+        assert!(submit_edit(&mut app, "fix the build without touching CI"));
+        assert_eq!(app.status, AppStatus::Resuming);
+        let sent = rx.try_recv().expect("fork command");
+        assert_eq!(
+            sent.command,
+            BridgeCommand::ForkSession {
+                session_id: "s-original".to_owned(),
+                prompt_text: "fix the build".to_owned(),
+                occurrence_from_end: 1,
+            }
+        );
+
+        app.session_id = Some(model::SessionId::new("s-fork"));
+        app.messages.clear();
+        app.status = AppStatus::Ready;
+        on_session_replaced(&mut app, Some("s-original".to_owned()));
+
+        assert_eq!(app.branches.indicator(), Some((2, 2)));
+        assert_eq!(app.branches.items[0].session_id, "s-original");
+        assert_eq!(app.branches.items[1].label, "fix the build without touching CI");
+        assert!(matches!(rx.try_recv().map(|e| e.command), Ok(BridgeCommand::Prompt { .. })));
+        assert!(list_text(&app).contains("* 2. fix the build without touching CI"));
+    }
+
+    #[test]
+    fn switching_back_keeps_branches_and_unrelated_session_drops_them() {
+        let (mut app, mut rx) = connected_app();
+        app.branches.items = vec![
+            SessionBranch { session_id: "s-original".into(), label: "original".into() },
+            SessionBranch { session_id: "s-fork".into(), label: "retry".into() },
+        ];
+        app.branches.active = 1;
+        app.session_id = Some(model::SessionId::new("s-fork"));
+
+        assert!(switch_to(&mut app, 1).is_err());
+        switch_to(&mut app, 0).expect("switch");
+        assert!(matches!(rx.try_recv().map(|e| e.command), Ok(BridgeCommand::LoadSession { .. })));
+
+        app.session_id = Some(model::SessionId::new("s-original"));
+        on_session_replaced(&mut app, Some("s-fork".to_owned()));
+        assert_eq!(app.branches.indicator(), Some((1, 2)));
+
+        app.session_id = Some(model::SessionId::new("s-unrelated"));
+        on_session_replaced(&mut app, Some("s-original".to_owned()));
+        assert!(app.branches.items.is_empty());
+    }
+}
@@ -0,0 +1,559 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Cost and token budgets.
+//!
+//! Enforcement runs on the usage events themselves (not in the renderer or key
+//! handling), so a session left running unattended is cancelled the same way as
+//! one being watched.
+
+use super::{App, AppStatus, CancelOrigin, ChatMessage, MessageBlock, MessageRole};
+use super::{BlockCache, IncrementalMarkdown, SessionUsageState};
+use crate::Cli;
+use crate::agent::model;
+
+/// Fraction of a budget at which the footer starts warning.
+pub const WARN_FRACTION: f64 = 0.8;
+
+const SESSION_COST_ENV: &str = "CLAUDE_RS_MAX_SESSION_COST";
+const TURN_COST_ENV: &str = "CLAUDE_RS_MAX_TURN_COST";
+const SESSION_TOKENS_ENV: &str = "CLAUDE_RS_MAX_SESSION_TOKENS";
+const TURN_TOKENS_ENV: &str = "CLAUDE_RS_MAX_TURN_TOKENS";
+
+/// Relative prices of the token classes, as multiples of a fresh input token.
+/// Used to price in-flight turn tokens before the SDK reports the turn's cost.
+const OUTPUT_TOKEN_WEIGHT: f64 = 5.0;
+const CACHE_READ_TOKEN_WEIGHT: f64 = 0.1;
+const CACHE_WRITE_TOKEN_WEIGHT: f64 = 1.25;
+
+/// List price of a fresh input token in USD per million, by model id with any
+/// date suffix removed. Output and cache tokens follow from the weights above.
+/// Models not listed are unpriced rather than guessed from their family.
+const INPUT_PRICE_PER_MTOK: &[(&str, f64)] = &[
+    ("opus-4-5", 5.0),
+    ("opus-4-1", 15.0),
+    ("opus-4", 15.0),
+    ("3-opus", 15.0),
+    ("sonnet-4-5", 3.0),
+    ("sonnet-4", 3.0),
+    ("3-7-sonnet", 3.0),
+    ("3-5-sonnet", 3.0),
+    ("haiku-4-5", 1.0),
+    ("3-5-haiku", 0.8),
+    ("3-haiku", 0.25),
+];
+
+/// Configured limits. Tokens count input + output, matching the per-turn usage
+/// shown on assistant messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BudgetLimits {
+    pub session_cost_usd: Option<f64>,
+    pub turn_cost_usd: Option<f64>,
+    pub session_tokens: Option<u64>,
+    pub turn_tokens: Option<u64>,
+}
+
+impl BudgetLimits {
+    /// CLI flags win; each limit falls back to its `CLAUDE_RS_MAX_*` env var.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self::resolve(cli, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(cli: &Cli, env: impl Fn(&str) -> Option<String>) -> Self {
+        let cost = |flag: Option<f64>, name: &str| {
+            flag.or_else(|| env(name)?.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v > 0.0)
+        };
+        let tokens = |flag: Option<u64>, name: &str| {
+            flag.or_else(|| env(name)?.trim().replace('_', "").parse::<u64>().ok())
+                .filter(|v| *v > 0)
+        };
+        Self {
+            session_cost_usd: cost(cli.max_session_cost, SESSION_COST_ENV),
+            turn_cost_usd: cost(cli.max_turn_cost, TURN_COST_ENV),
+            session_tokens: tokens(cli.max_session_tokens, SESSION_TOKENS_ENV),
+            turn_tokens: tokens(cli.max_turn_tokens, TURN_TOKENS_ENV),
+        }
+    }
+
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Per-session budget bookkeeping (parked with the session when tabs switch).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BudgetState {
+    /// Cumulative usage of the running turn. Cleared once the turn's final
+    /// `UsageUpdate` folds it into the session totals.
+    pub turn_progress: Option<model::TurnUsage>,
+    /// The running turn was already cancelled for exceeding a budget.
+    pub cancelled_this_turn: bool,
+    /// The "session budget reached" notice was already posted.
+    pub session_exhausted_notified: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    Session,
+    Turn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetAmount {
+    Usd { spent: f64, limit: f64 },
+    Tokens { spent: u64, limit: u64 },
+}
+
+/// Spend against one configured limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetReading {
+    pub scope: BudgetScope,
+    pub amount: BudgetAmount,
+    /// Cost includes an in-flight estimate (the SDK prices a turn only when it ends).
+    pub estimated: bool,
+    /// Cost leaves out the in-flight turn: its model has no known price yet.
+    pub unpriced: bool,
+}
+
+impl BudgetReading {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> f64 {
+        match self.amount {
+            BudgetAmount::Usd { spent, limit } => spent / limit,
+            BudgetAmount::Tokens { spent, limit } => spent as f64 / limit as f64,
+        }
+    }
+
+    #[must_use]
+    pub fn is_exceeded(&self) -> bool {
+        match self.amount {
+            BudgetAmount::Usd { spent, limit } => spent >= limit,
+            BudgetAmount::Tokens { spent, limit } => spent >= limit,
+        }
+    }
+
+    /// Compact form for the footer, e.g. `turn ~$0.42/$0.50`, or `session $0.42+?/$1.00`
+    /// while an unpriced turn is running.
+    #[must_use]
+    pub fn short_label(&self) -> String {
+        let scope = match self.scope {
+            BudgetScope::Session => "session",
+            BudgetScope::Turn => "turn",
+        };
+        let approx = if self.estimated { "~" } else { "" };
+        let unpriced = if self.unpriced { "+?" } else { "" };
+        match self.amount {
+            BudgetAmount::Usd { spent, limit } => {
+                format!("{scope} {approx}${spent:.2}{unpriced}/${limit:.2}")
+            }
+            BudgetAmount::Tokens { spent, limit } => format!("{scope} {spent}/{limit} tok"),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let scope = match self.scope {
+            BudgetScope::Session => "session",
+            BudgetScope::Turn => "per-turn",
+        };
+        let approx = if self.estimated { "about " } else { "" };
+        let unpriced = if self.unpriced { ", not counting the unpriced running turn" } else { "" };
+        match self.amount {
+            BudgetAmount::Usd { spent, limit } => format!(
+                "{scope} cost budget of ${limit:.2} reached ({approx}${spent:.2} spent{unpriced})"
+            ),
+            BudgetAmount::Tokens { spent, limit } => {
+                format!("{scope} token budget of {limit} reached ({spent} tokens used)")
+            }
+        }
+    }
+}
+
+fn progress_tokens(progress: Option<model::TurnUsage>) -> u64 {
+    progress.map_or(0, |p| p.input_tokens.saturating_add(p.output_tokens))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn weighted_tokens(input: u64, output: u64, cache_read: u64, cache_write: u64) -> f64 {
+    input as f64
+        + output as f64 * OUTPUT_TOKEN_WEIGHT
+        + cache_read as f64 * CACHE_READ_TOKEN_WEIGHT
+        + cache_write as f64 * CACHE_WRITE_TOKEN_WEIGHT
+}
+
+/// USD per weighted token for `model`, from the list prices above.
+fn model_token_price(model: &str) -> Option<f64> {
+    let model = model.to_ascii_lowercase().replace('.', "-");
+    let model = match model.rsplit_once('-') {
+        Some((base, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => &model,
+    };
+    INPUT_PRICE_PER_MTOK
+        .iter()
+        .find(|(id, _)| model == *id || model.ends_with(&format!("-{id}")))
+        .map(|(_, price)| price / 1_000_000.0)
+}
+
+/// USD per weighted token this session has actually paid. Unusable after a
+/// resume, where the cost covers fewer turns than the token totals.
+fn observed_token_price(usage: &SessionUsageState) -> Option<f64> {
+    let spent = usage.total_cost_usd.filter(|c| *c > 0.0)?;
+    if usage.cost_is_since_resume {
+        return None;
+    }
+    let priced = weighted_tokens(
+        usage.total_input_tokens,
+        usage.total_output_tokens,
+        usage.total_cache_read_tokens,
+        usage.total_cache_write_tokens,
+    );
+    (priced > 0.0).then(|| spent / priced)
+}
+
+/// Estimate the cost of in-flight turn tokens from the model's list price,
+/// falling back to the price this session has paid for unknown models.
+fn estimate_progress_cost(
+    model: &str,
+    usage: &SessionUsageState,
+    progress: model::TurnUsage,
+) -> Option<f64> {
+    let price = model_token_price(model).or_else(|| observed_token_price(usage))?;
+    let pending = weighted_tokens(
+        progress.input_tokens,
+        progress.output_tokens,
+        progress.cache_read_tokens,
+        progress.cache_write_tokens,
+    );
+    Some(price * pending)
+}
+
+/// Readings for every configured limit.
+#[must_use]
+pub fn readings(
+    limits: &BudgetLimits,
+    model: &str,
+    usage: &SessionUsageState,
+    state: &BudgetState,
+) -> Vec<BudgetReading> {
+    let mut out = Vec::new();
+    let turn_tokens = progress_tokens(state.turn_progress);
+    let turn_cost = state.turn_progress.and_then(|p| estimate_progress_cost(model, usage, p));
+    let unpriced = state.turn_progress.is_some() && turn_cost.is_none();
+
+    if let Some(limit) = limits.session_cost_usd {
+        let spent = usage.total_cost_usd.unwrap_or(0.0) + turn_cost.unwrap_or(0.0);
+        out.push(BudgetReading {
+            scope: BudgetScope::Session,
+            amount: BudgetAmount::Usd { spent, limit },
+            estimated: turn_cost.is_some(),
+            unpriced,
+        });
+    }
+    if let Some(limit) = limits.session_tokens {
+        out.push(BudgetReading {
+            scope: BudgetScope::Session,
+            amount: BudgetAmount::Tokens {
+                spent: usage.total_tokens().saturating_add(turn_tokens),
+                limit,
+            },
+            estimated: false,
+            unpriced: false,
+        });
+    }
+    if state.turn_progress.is_some() {
+        if let Some(limit) = limits.turn_cost_usd {
+            out.push(BudgetReading {
+                scope: BudgetScope::Turn,
+                amount: BudgetAmount::Usd { spent: turn_cost.unwrap_or(0.0), limit },
+                estimated: turn_cost.is_some(),
+                unpriced,
+            });
+        }
+        if let Some(limit) = limits.turn_tokens {
+            out.push(BudgetReading {
+                scope: BudgetScope::Turn,
+                amount: BudgetAmount::Tokens { spent: turn_tokens, limit },
+                estimated: false,
+                unpriced: false,
+            });
+        }
+    }
+    out
+}
+
+/// The reading closest to (or furthest past) its limit.
+#[must_use]
+pub fn most_pressing(app: &App) -> Option<BudgetReading> {
+    readings(&app.budget_limits, &app.model_name, &app.session_usage, &app.budget)
+        .into_iter()
+        .max_by(|a, b| a.fraction().total_cmp(&b.fraction()))
+}
+
+/// Session-scope limit already spent; new prompts are refused until a new session.
+#[must_use]
+pub fn session_exhausted(app: &App) -> Option<BudgetReading> {
+    readings(&app.budget_limits, &app.model_name, &app.session_usage, &app.budget)
+        .into_iter()
+        .find(|r| r.scope == BudgetScope::Session && r.is_exceeded())
+}
+
+/// Reset per-turn tracking when a prompt is dispatched.
+pub(super) fn begin_turn(app: &mut App) {
+    app.budget.turn_progress = Some(model::TurnUsage::default());
+    app.budget.cancelled_this_turn = false;
+}
+
+pub(super) fn record_turn_progress(app: &mut App, usage: model::TurnUsage) {
+    app.budget.turn_progress = Some(usage);
+    enforce(app);
+}
+
+/// The turn's final usage is now part of the session totals.
+pub(super) fn record_turn_usage(app: &mut App) {
+    app.budget.turn_progress = None;
+    enforce(app);
+}
+
+/// Cancel the running turn once any limit is hit, and post a single notice when
+/// the session budget runs out between turns.
+fn enforce(app: &mut App) {
+    if app.budget_limits.is_unlimited() {
+        return;
+    }
+    let exceeded = readings(&app.budget_limits, &app.model_name, &app.session_usage, &app.budget)
+        .into_iter()
+        .filter(BudgetReading::is_exceeded)
+        .max_by(|a, b| a.fraction().total_cmp(&b.fraction()));
+    let Some(reading) = exceeded else {
+        return;
+    };
+
+    let turn_active = matches!(app.status, AppStatus::Thinking | AppStatus::Running);
+    if turn_active && !app.budget.cancelled_this_turn {
+        app.budget.cancelled_this_turn = true;
+        if reading.scope == BudgetScope::Session {
+            app.budget.session_exhausted_notified = true;
+        }
+        tracing::warn!("Cancelling turn: {}", reading.describe());
+        if let Err(message) = super::input_submit::request_cancel(app, CancelOrigin::Budget) {
+            tracing::error!("Failed to cancel turn over budget: {message}");
+        }
+        push_notice(app, &format!("Turn cancelled: {}.", reading.describe()));
+        return;
+    }
+    if !turn_active
+        && reading.scope == BudgetScope::Session
+        && !app.budget.session_exhausted_notified
+    {
+        app.budget.session_exhausted_notified = true;
+        push_notice(app, &exhausted_message(&reading));
+    }
+}
+
+/// Explain why a prompt was not sent.
+pub(super) fn push_blocked_notice(app: &mut App, reading: &BudgetReading) {
+    push_notice(app, &exhausted_message(reading));
+}
+
+fn exhausted_message(reading: &BudgetReading) -> String {
+    let mut text = reading.describe();
+    if let Some(first) = text.get(..1) {
+        text = first.to_uppercase() + &text[1..];
+    }
+    format!("{text}. New prompts are blocked; start a new session with /new-session to continue.")
+}
+
+fn push_notice(app: &mut App, text: &str) {
+    app.messages.push(ChatMessage {
+        role: MessageRole::System,
+        blocks: vec![MessageBlock::Text(
+            text.to_owned(),
+            BlockCache::default(),
+            IncrementalMarkdown::from_complete(text),
+        )],
+        usage: None,
+    });
+    app.enforce_history_retention();
+    app.viewport.engage_auto_scroll();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn limits(args: &[&str]) -> BudgetLimits {
+        let mut command_line = vec!["claude-rs"];
+        command_line.extend_from_slice(args);
+        BudgetLimits::resolve(&Cli::parse_from(command_line), |_| None)
+    }
+
+    fn progress(input: u64, output: u64) -> model::TurnUsage {
+        model::TurnUsage { input_tokens: input, output_tokens: output, ..Default::default() }
+    }
+
+    #[test]
+    fn limits_prefer_flags_and_fall_back_to_env() {
+        let cli = Cli::parse_from(["claude-rs", "--max-turn-cost", "0.5"]);
+        let resolved = BudgetLimits::resolve(&cli, |name| match name {
+            TURN_COST_ENV => Some("2".to_owned()),
+            SESSION_TOKENS_ENV => Some("200_000".to_owned()),
+            SESSION_COST_ENV => Some("nope".to_owned()),
+            _ => None,
+        });
+        assert_eq!(resolved.turn_cost_usd, Some(0.5));
+        assert_eq!(resolved.session_tokens, Some(200_000));
+        assert_eq!(resolved.session_cost_usd, None);
+        assert!(limits(&[]).is_unlimited());
+    }
+
+    #[test]
+    fn turn_token_budget_counts_only_in_flight_turn() {
+        let limits = limits(&["--max-turn-tokens", "1000", "--max-session-tokens", "5000"]);
+        let usage = SessionUsageState {
+            total_input_tokens: 3000,
+            total_output_tokens: 500,
+            ..SessionUsageState::default()
+        };
+        let state = BudgetState { turn_progress: Some(progress(900, 200)), ..Default::default() };
+
+        let all = readings(&limits, "test-model", &usage, &state);
+        let turn = all.iter().find(|r| r.scope == BudgetScope::Turn).expect("turn reading");
+        assert!(turn.is_exceeded());
+        let session = all.iter().find(|r| r.scope == BudgetScope::Session).expect("session");
+        assert_eq!(session.amount, BudgetAmount::Tokens { spent: 4600, limit: 5000 });
+        assert!(!session.is_exceeded());
+        assert!(session.fraction() > WARN_FRACTION);
+    }
+
+    #[test]
+    fn in_flight_cost_is_estimated_from_session_price() {
+        let usage = SessionUsageState {
+            total_input_tokens: 1000,
+            total_cost_usd: Some(1.0),
+            ..SessionUsageState::default()
+        };
+        let estimate =
+            estimate_progress_cost("test-model", &usage, progress(500, 0)).expect("estimate");
+        assert!((estimate - 0.5).abs() < f64::EPSILON);
+        let unknown = SessionUsageState::default();
+        assert_eq!(estimate_progress_cost("test-model", &unknown, progress(500, 0)), None);
+    }
+
+    #[test]
+    fn first_turn_cost_is_estimated_from_list_price() {
+        let limits = limits(&["--max-turn-cost", "0.5"]);
+        let state =
+            BudgetState { turn_progress: Some(progress(20_000, 30_000)), ..Default::default() };
+        let all = readings(&limits, "claude-sonnet-4-5", &SessionUsageState::default(), &state);
+
+        let turn = all.iter().find(|r| r.scope == BudgetScope::Turn).expect("turn reading");
+        // 20k input at $3/MTok plus 30k output at $15/MTok.
+        assert!((turn.fraction() - 0.51 / 0.5).abs() < 1e-9);
+        assert!(turn.is_exceeded() && turn.estimated);
+    }
+
+    #[test]
+    fn list_prices_match_whole_model_ids() {
+        let per_mtok = |model: &str| model_token_price(model).map(|p| p * 1_000_000.0);
+        assert_eq!(per_mtok("claude-opus-4-5-20251101"), Some(5.0));
+        assert_eq!(per_mtok("claude-opus-4-20250514"), Some(15.0));
+        assert_eq!(per_mtok("claude-3-5-haiku-20241022"), Some(0.8));
+        assert_eq!(per_mtok("claude-3-haiku-20240307"), Some(0.25));
+        assert_eq!(per_mtok("claude-opus-4-7"), None);
+        assert_eq!(per_mtok("opus"), None);
+    }
+
+    #[test]
+    fn unknown_model_turn_is_unpriced_not_guessed() {
+        let limits = limits(&["--max-session-cost", "1", "--max-turn-cost", "0.5"]);
+        let usage = SessionUsageState { total_cost_usd: Some(0.25), ..Default::default() };
+        let state =
+            BudgetState { turn_progress: Some(progress(20_000, 30_000)), ..Default::default() };
+        let all = readings(&limits, "claude-opus-4-7", &usage, &state);
+
+        let turn = all.iter().find(|r| r.scope == BudgetScope::Turn).expect("turn reading");
+        assert!(turn.unpriced && !turn.estimated && !turn.is_exceeded());
+        assert_eq!(turn.short_label(), "turn $0.00+?/$0.50");
+        let session = all.iter().find(|r| r.scope == BudgetScope::Session).expect("session");
+        assert_eq!(session.short_label(), "session $0.25+?/$1.00");
+        assert!(
+            session.describe().ends_with("($0.25 spent, not counting the unpriced running turn)")
+        );
+    }
+
+    #[test]
+    fn resumed_session_cost_cap_fires_mid_turn() {
+        let mut app = App::test_default();
+        app.model_name = "claude-opus-4-5-20251101".to_owned();
+        app.budget_limits = limits(&["--max-session-cost", "1"]);
+        app.session_usage = SessionUsageState {
+            total_input_tokens: 5_000_000,
+            total_cost_usd: Some(0.9),
+            cost_is_since_resume: true,
+            ..SessionUsageState::default()
+        };
+        app.status = AppStatus::Running;
+        begin_turn(&mut app);
+
+        record_turn_progress(&mut app, progress(10_000, 1_000));
+        assert!(!app.budget.cancelled_this_turn);
+        record_turn_progress(&mut app, progress(10_000, 4_000));
+        assert!(app.budget.cancelled_this_turn);
+        assert!(app.budget.session_exhausted_notified);
+    }
+
+    #[test]
+    fn exceeding_turn_budget_cancels_once_with_notice() {
+        let mut app = App::test_default();
+        app.budget_limits = limits(&["--max-turn-tokens", "100"]);
+        app.status = AppStatus::Running;
+        begin_turn(&mut app);
+
+        record_turn_progress(&mut app, progress(80, 30));
+        record_turn_progress(&mut app, progress(120, 30));
+
+        assert!(app.budget.cancelled_this_turn);
+        let notices = app
+            .messages
+            .iter()
+            .filter(|m| matches!(m.role, MessageRole::System))
+            .filter(|m| {
+                matches!(m.blocks.first(), Some(MessageBlock::Text(t, ..))
+                    if t.starts_with("Turn cancelled: per-turn token budget"))
+            })
+            .count();
+        assert_eq!(notices, 1);
+    }
+
+    #[test]
+    fn exhausted_session_budget_notifies_once_between_turns() {
+        let mut app = App::test_default();
+        app.budget_limits = limits(&["--max-session-cost", "1"]);
+        app.session_usage.total_cost_usd = Some(1.2);
+
+        record_turn_usage(&mut app);
+        record_turn_usage(&mut app);
+
+        assert!(session_exhausted(&app).is_some());
+        let system_count =
+            app.messages.iter().filter(|m| matches!(m.role, MessageRole::System)).count();
+        assert_eq!(system_count, 1);
+    }
+}
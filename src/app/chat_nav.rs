@@ -0,0 +1,468 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Keyboard navigation over chat blocks (Alt+J).
+//!
+//! A cursor selects one text, tool call or compaction block. From there the
+//! block can be expanded, copied, opened in `$EDITOR` or paged as a diff
+//! without the mouse. Block rows depend on wrapping, so the chat renderer
+//! highlights the selection and performs the scroll for `pending_jump`.
+
+use super::{App, FocusTarget, MessageBlock, MessageRole, ToolCallInfo, external};
+use crate::agent::model;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use similar::TextDiff;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatNavState {
+    pub message: usize,
+    pub block: usize,
+    /// Scroll the selected block into view on the next frame.
+    pub pending_jump: bool,
+    /// Result of the last action, shown in the navigation bar.
+    pub notice: Option<String>,
+}
+
+fn is_target(block: &MessageBlock) -> bool {
+    match block {
+        MessageBlock::Text(..) | MessageBlock::Compaction(_) => true,
+        MessageBlock::ToolCall(tc) => tc.is_rendered_inline(),
+        MessageBlock::Welcome(_) => false,
+    }
+}
+
+/// Selectable `(message, block)` pairs in conversation order.
+fn targets(app: &App) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    for (mi, msg) in app.messages.iter().enumerate() {
+        if matches!(msg.role, MessageRole::Welcome) {
+            continue;
+        }
+        for (bi, block) in msg.blocks.iter().enumerate() {
+            if is_target(block) {
+                out.push((mi, bi));
+            }
+        }
+    }
+    out
+}
+
+fn selected_block(app: &App) -> Option<&MessageBlock> {
+    let nav = app.chat_nav.as_ref()?;
+    app.messages.get(nav.message)?.blocks.get(nav.block)
+}
+
+fn selected_tool_call(app: &App) -> Option<&ToolCallInfo> {
+    match selected_block(app)? {
+        MessageBlock::ToolCall(tc) => Some(tc),
+        _ => None,
+    }
+}
+
+/// Enter navigation on the newest block (Alt+J).
+pub(super) fn open(app: &mut App) -> bool {
+    let Some(&(message, block)) = targets(app).last() else {
+        return false;
+    };
+    app.chat_nav =
+        Some(ChatNavState { message, block, pending_jump: true, ..ChatNavState::default() });
+    app.claim_focus_target(FocusTarget::ChatNav);
+    app.needs_redraw = true;
+    true
+}
+
+/// Leave navigation and drop the selection highlight.
+pub(super) fn close(app: &mut App) {
+    if app.chat_nav.take().is_some() {
+        app.release_focus_target(FocusTarget::ChatNav);
+        app.needs_redraw = true;
+    }
+}
+
+fn select(app: &mut App, (message, block): (usize, usize)) {
+    if let Some(nav) = app.chat_nav.as_mut() {
+        nav.message = message;
+        nav.block = block;
+        nav.pending_jump = true;
+        nav.notice = None;
+    }
+    app.needs_redraw = true;
+}
+
+/// Move to the next or previous block, stopping at either end.
+fn step_block(app: &mut App, forward: bool) {
+    let Some(nav) = app.chat_nav.as_ref() else {
+        return;
+    };
+    let current = (nav.message, nav.block);
+    let all = targets(app);
+    let next = if forward {
+        all.iter().find(|t| **t > current)
+    } else {
+        all.iter().rev().find(|t| **t < current)
+    };
+    if let Some(&target) = next {
+        select(app, target);
+    }
+}
+
+/// Move to the first block of the next or previous message.
+fn step_message(app: &mut App, forward: bool) {
+    let Some(message) = app.chat_nav.as_ref().map(|nav| nav.message) else {
+        return;
+    };
+    let all = targets(app);
+    let next = if forward {
+        all.iter().find(|t| t.0 > message)
+    } else {
+        all.iter().filter(|t| t.0 < message).min_by_key(|t| (std::cmp::Reverse(t.0), t.1))
+    };
+    if let Some(&target) = next {
+        select(app, target);
+    }
+}
+
+fn select_edge(app: &mut App, last: bool) {
+    let all = targets(app);
+    let edge = if last { all.last() } else { all.first() };
+    if let Some(&target) = edge {
+        select(app, target);
+    }
+}
+
+fn set_notice(app: &mut App, notice: impl Into<String>) {
+    if let Some(nav) = app.chat_nav.as_mut() {
+        nav.notice = Some(notice.into());
+    }
+    app.needs_redraw = true;
+}
+
+/// Expand or collapse the selected tool call or compaction block.
+fn toggle_collapsed(app: &mut App) {
+    let Some(nav) = app.chat_nav.as_ref() else {
+        return;
+    };
+    let message = nav.message;
+    let block = app.messages.get_mut(message).and_then(|m| m.blocks.get_mut(nav.block));
+    match block {
+        Some(MessageBlock::ToolCall(tc)) => {
+            let tc = tc.as_mut();
+            tc.collapsed = !tc.collapsed;
+            tc.mark_tool_call_layout_dirty();
+        }
+        Some(MessageBlock::Compaction(block)) => {
+            block.collapsed = !block.collapsed;
+            block.cache.invalidate();
+        }
+        _ => return,
+    }
+    app.mark_message_layout_dirty(message);
+    if let Some(nav) = app.chat_nav.as_mut() {
+        nav.pending_jump = true;
+    }
+    app.needs_redraw = true;
+}
+
+/// Body of the first fenced code block in markdown `text`.
+#[must_use]
+pub fn first_code_block(text: &str) -> Option<String> {
+    let mut lines = text.lines();
+    let fence = lines.find_map(|line| {
+        let trimmed = line.trim_start();
+        ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f))
+    })?;
+    let body: Vec<&str> = lines.take_while(|line| !line.trim_start().starts_with(fence)).collect();
+    Some(body.join("\n"))
+}
+
+fn tool_call_text(tc: &ToolCallInfo) -> String {
+    if let Some(command) = tc.terminal_command.as_deref() {
+        return command.to_owned();
+    }
+    let parts: Vec<&str> = tc
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            model::ToolCallContent::Content(model::Content {
+                content: model::ContentBlock::Text(text),
+            }) => Some(text.text.as_str()),
+            model::ToolCallContent::Diff(diff) => Some(diff.new_text.as_str()),
+            _ => None,
+        })
+        .collect();
+    if parts.is_empty() { tc.title.clone() } else { parts.join("\n") }
+}
+
+/// Text copied by `y`: the block's source, or with `code_only` the first
+/// fenced code block of a text block.
+fn yank_text(block: &MessageBlock, code_only: bool) -> Option<String> {
+    match block {
+        MessageBlock::Text(text, ..) if code_only => first_code_block(text),
+        MessageBlock::Text(text, ..) => Some(text.clone()),
+        MessageBlock::ToolCall(tc) => Some(tool_call_text(tc)),
+        MessageBlock::Compaction(block) => block.summary.clone(),
+        MessageBlock::Welcome(_) => None,
+    }
+}
+
+fn yank(app: &mut App, code_only: bool) {
+    let Some(text) = selected_block(app).and_then(|b| yank_text(b, code_only)) else {
+        set_notice(app, if code_only { "No code block here" } else { "Nothing to copy" });
+        return;
+    };
+    match super::clipboard::copy(&text, app.clipboard_mode) {
+        Ok(method) => set_notice(app, method.confirmation()),
+        Err(e) => set_notice(app, format!("Copy failed: {e}")),
+    }
+}
+
+/// File and line the selected tool call points at.
+fn tool_location(tc: &ToolCallInfo) -> Option<(PathBuf, Option<u32>)> {
+    if let Some(location) = tc.locations.first() {
+        return Some((location.path.clone(), location.line));
+    }
+    tc.content.iter().find_map(|content| match content {
+        model::ToolCallContent::Diff(diff) => Some((diff.path.clone(), None)),
+        _ => None,
+    })
+}
+
+fn open_location(app: &mut App) {
+    let Some((path, line)) = selected_tool_call(app).and_then(tool_location) else {
+        set_notice(app, "No file location for this block");
+        return;
+    };
+    external::open_in_editor(app, &path, line, set_notice);
+}
+
+/// Open the location of the tool call at `(message, block)`, e.g. after a
+/// click on its title. Returns false when it has no location.
+pub(super) fn open_tool_call(app: &mut App, message: usize, block: usize) -> bool {
+    let location = match app.messages.get(message).and_then(|msg| msg.blocks.get(block)) {
+        Some(MessageBlock::ToolCall(tc)) => tool_location(tc),
+        _ => None,
+    };
+    let Some((path, line)) = location else {
+        return false;
+    };
+    open_with_toast(app, &path, line);
+    true
+}
+
+/// Alt+O: open the selected tool call's location, else the newest one.
+pub(super) fn open_latest_location(app: &mut App) {
+    let location = selected_tool_call(app).and_then(tool_location).or_else(|| {
+        app.messages.iter().rev().flat_map(|msg| msg.blocks.iter().rev()).find_map(|block| {
+            match block {
+                MessageBlock::ToolCall(tc) if tc.is_rendered_inline() => tool_location(tc),
+                _ => None,
+            }
+        })
+    });
+    let Some((path, line)) = location else {
+        app.show_toast("No tool call with a file location", true);
+        return;
+    };
+    open_with_toast(app, &path, line);
+}
+
+fn open_with_toast(app: &mut App, path: &std::path::Path, line: Option<u32>) {
+    let shown = path.display().to_string();
+    external::open_in_editor(app, path, line, move |app, err| {
+        app.show_toast(format!("Could not open {shown}: {err}"), true);
+    });
+}
+
+/// Unified diff of every edit in the tool call.
+fn unified_diff(tc: &ToolCallInfo) -> Option<String> {
+    let mut out = String::new();
+    for content in &tc.content {
+        if let model::ToolCallContent::Diff(diff) = content {
+            let path = diff.path.to_string_lossy();
+            let old = diff.old_text.as_deref().unwrap_or("");
+            let text_diff = TextDiff::from_lines(old, diff.new_text.as_str());
+            let mut unified = text_diff.unified_diff();
+            out.push_str(&unified.header(&format!("a/{path}"), &format!("b/{path}")).to_string());
+        }
+    }
+    if out.is_empty() { None } else { Some(out) }
+}
+
+fn open_diff(app: &mut App) {
+    let Some(diff) = selected_tool_call(app).and_then(unified_diff) else {
+        set_notice(app, "No diff in this block");
+        return;
+    };
+    external::page_text(app, diff, set_notice);
+}
+
+/// Keys while chat navigation has focus. Returns `true` when consumed; other
+/// keys leave navigation and fall through to the input.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    if app.chat_nav.is_none() {
+        return false;
+    }
+    let plain = key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT;
+    match (key.code, plain) {
+        (KeyCode::Esc | KeyCode::Char('q'), true) => close(app),
+        (KeyCode::Char('j'), true) | (KeyCode::Down, _) => step_block(app, true),
+        (KeyCode::Char('k'), true) | (KeyCode::Up, _) => step_block(app, false),
+        (KeyCode::Char('J'), true) => step_message(app, true),
+        (KeyCode::Char('K'), true) => step_message(app, false),
+        (KeyCode::Char('g'), true) | (KeyCode::Home, _) => select_edge(app, false),
+        (KeyCode::Char('G'), true) | (KeyCode::End, _) => select_edge(app, true),
+        (KeyCode::Enter, true) => toggle_collapsed(app),
+        (KeyCode::Char('y'), true) => yank(app, false),
+        (KeyCode::Char('Y'), true) => yank(app, true),
+        (KeyCode::Char('o'), true) => open_location(app),
+        (KeyCode::Char('d'), true) => open_diff(app),
+        _ => {
+            close(app);
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{BlockCache, ChatMessage, IncrementalMarkdown};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn text_block(text: &str) -> MessageBlock {
+        MessageBlock::Text(
+            text.to_owned(),
+            BlockCache::default(),
+            IncrementalMarkdown::from_complete(text),
+        )
+    }
+
+    fn tool_call(id: &str, hidden: bool) -> MessageBlock {
+        MessageBlock::ToolCall(Box::new(ToolCallInfo {
+            id: id.to_owned(),
+            title: "Edit src/lib.rs".to_owned(),
+            sdk_tool_name: "Edit".to_owned(),
+            raw_input: None,
+            status: model::ToolCallStatus::Completed,
+            content: vec![model::ToolCallContent::Diff(
+                model::Diff::new("src/lib.rs", "a\nc\n").old_text(Some("a\nb\n")),
+            )],
+            collapsed: true,
+            hidden,
+            terminal_id: None,
+            terminal_command: None,
+            terminal_output: None,
+            terminal_output_len: 0,
+            terminal_bytes_seen: 0,
+            terminal_snapshot_mode: crate::app::TerminalSnapshotMode::AppendOnly,
+            render_epoch: 0,
+            layout_epoch: 0,
+            last_measured_width: 0,
+            last_measured_height: 0,
+            last_measured_layout_epoch: 0,
+            last_measured_layout_generation: 0,
+            cache: BlockCache::default(),
+            pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: vec![model::ToolCallLocation::new("src/lib.rs").line(7)],
+        }))
+    }
+
+    fn app_with_conversation() -> App {
+        let mut app = App::test_default();
+        app.messages.push(ChatMessage {
+            role: MessageRole::User,
+            blocks: vec![text_block("fix it")],
+            usage: None,
+        });
+        app.messages.push(ChatMessage {
+            role: MessageRole::Assistant,
+            blocks: vec![
+                text_block("Sure:\n```rust\nfn main() {}\n```\n"),
+                tool_call("tc-hidden", true),
+                tool_call("tc-1", false),
+            ],
+            usage: None,
+        });
+        app
+    }
+
+    fn cursor(app: &App) -> (usize, usize) {
+        let nav = app.chat_nav.as_ref().expect("navigation active");
+        (nav.message, nav.block)
+    }
+
+    #[test]
+    fn j_and_k_walk_blocks_and_skip_hidden_tool_calls() {
+        let mut app = app_with_conversation();
+        assert!(open(&mut app));
+        assert_eq!(app.focus_owner(), crate::app::FocusOwner::ChatNav);
+        assert_eq!(cursor(&app), (1, 2));
+
+        handle_key(&mut app, key(KeyCode::Char('k')));
+        assert_eq!(cursor(&app), (1, 0));
+        handle_key(&mut app, key(KeyCode::Char('k')));
+        assert_eq!(cursor(&app), (0, 0));
+        handle_key(&mut app, key(KeyCode::Char('k')));
+        assert_eq!(cursor(&app), (0, 0));
+        handle_key(&mut app, KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT));
+        assert_eq!(cursor(&app), (1, 0));
+        handle_key(&mut app, key(KeyCode::Char('j')));
+        assert_eq!(cursor(&app), (1, 2));
+
+        assert!(!handle_key(&mut app, key(KeyCode::Char('x'))));
+        assert!(app.chat_nav.is_none());
+        assert_eq!(app.focus_owner(), crate::app::FocusOwner::Input);
+    }
+
+    #[test]
+    fn enter_toggles_the_selected_tool_call() {
+        let mut app = app_with_conversation();
+        open(&mut app);
+        handle_key(&mut app, key(KeyCode::Enter));
+        let Some(MessageBlock::ToolCall(tc)) = app.messages[1].blocks.get(2) else {
+            panic!("expected tool call");
+        };
+        assert!(!tc.collapsed);
+        handle_key(&mut app, key(KeyCode::Enter));
+        let Some(MessageBlock::ToolCall(tc)) = app.messages[1].blocks.get(2) else {
+            panic!("expected tool call");
+        };
+        assert!(tc.collapsed);
+    }
+
+    #[test]
+    fn block_text_location_and_diff_are_extracted() {
+        let app = app_with_conversation();
+        let text = &app.messages[1].blocks[0];
+        assert_eq!(yank_text(text, true).as_deref(), Some("fn main() {}"));
+        assert_eq!(first_code_block("no code"), None);
+
+        let MessageBlock::ToolCall(tc) = &app.messages[1].blocks[2] else {
+            panic!("expected tool call");
+        };
+        assert_eq!(tool_location(tc), Some((PathBuf::from("src/lib.rs"), Some(7))));
+        let diff = unified_diff(tc).expect("diff");
+        assert!(diff.starts_with("--- a/src/lib.rs\n+++ b/src/lib.rs\n"));
+        assert!(diff.contains("-b\n+c\n"));
+    }
+}
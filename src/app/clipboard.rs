@@ -0,0 +1,143 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Clipboard writes. The native clipboard (arboard) needs X11, Wayland, macOS
+//! or Windows; over SSH, in containers or under a bare tmux it is missing, so
+//! copies fall back to OSC 52, which asks the terminal to set its clipboard.
+
+use crate::Cli;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+const MODE_ENV: &str = "CLAUDE_RS_CLIPBOARD";
+
+/// Largest OSC 52 payload (base64 bytes). Terminals silently drop bigger
+/// ones; xterm and tmux cap around 100 KB.
+const OSC52_MAX_ENCODED_BYTES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardMode {
+    /// Native clipboard, OSC 52 when it is unavailable.
+    #[default]
+    Auto,
+    Native,
+    Osc52,
+}
+
+impl ClipboardMode {
+    /// `--clipboard` wins over `CLAUDE_RS_CLIPBOARD`.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self::resolve(cli, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(cli: &Cli, env: impl Fn(&str) -> Option<String>) -> Self {
+        let Some(value) = cli.clipboard.clone().or_else(|| env(MODE_ENV)) else {
+            return Self::default();
+        };
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" | "" => Self::Auto,
+            "native" | "system" => Self::Native,
+            "osc52" | "osc" => Self::Osc52,
+            other => {
+                tracing::warn!("unknown clipboard mode: {other}");
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    Native,
+    Osc52,
+}
+
+impl CopyMethod {
+    /// Confirmation shown after a successful copy.
+    #[must_use]
+    pub fn confirmation(self) -> &'static str {
+        match self {
+            Self::Native => "Copied to clipboard",
+            Self::Osc52 => "Copied via OSC 52",
+        }
+    }
+}
+
+fn osc52_sequence(text: &str) -> Result<String, String> {
+    let encoded = BASE64.encode(text);
+    if encoded.len() > OSC52_MAX_ENCODED_BYTES {
+        return Err(format!("{} KB is too large for OSC 52", text.len() / 1024));
+    }
+    Ok(format!("\x1b]52;c;{encoded}\x07"))
+}
+
+fn copy_osc52(text: &str) -> Result<CopyMethod, String> {
+    let sequence = osc52_sequence(text)?;
+    super::osc::write(&sequence).map_err(|e| format!("OSC 52 write failed: {e}"))?;
+    Ok(CopyMethod::Osc52)
+}
+
+fn copy_native(text: &str) -> Result<CopyMethod, String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text.to_owned()))
+        .map(|()| CopyMethod::Native)
+        .map_err(|e| e.to_string())
+}
+
+/// Copy `text`, returning the mechanism that took it or why none did.
+pub fn copy(text: &str, mode: ClipboardMode) -> Result<CopyMethod, String> {
+    match mode {
+        ClipboardMode::Native => copy_native(text),
+        ClipboardMode::Osc52 => copy_osc52(text),
+        ClipboardMode::Auto => copy_native(text).or_else(|native_err| {
+            tracing::debug!("native clipboard unavailable, using OSC 52: {native_err}");
+            copy_osc52(text).map_err(|osc_err| format!("{native_err}; {osc_err}"))
+        }),
+    }
+}
+
+/// Copy `text` and confirm in a footer toast.
+pub(super) fn copy_with_toast(app: &mut super::App, text: &str) {
+    match copy(text, app.clipboard_mode) {
+        Ok(method) => app.show_toast(method.confirmation(), false),
+        Err(e) => app.show_toast(format!("Copy failed: {e}"), true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser as _;
+
+    #[test]
+    fn mode_prefers_flag_over_env() {
+        let resolve = |args: &[&str], env: Option<&str>| {
+            let cli = Cli::parse_from(std::iter::once("claude-rs").chain(args.iter().copied()));
+            ClipboardMode::resolve(&cli, |_| env.map(str::to_owned))
+        };
+        assert_eq!(resolve(&[], None), ClipboardMode::Auto);
+        assert_eq!(resolve(&[], Some("OSC52")), ClipboardMode::Osc52);
+        assert_eq!(resolve(&["--clipboard", "native"], Some("osc52")), ClipboardMode::Native);
+        assert_eq!(resolve(&[], Some("pbcopy")), ClipboardMode::Auto);
+    }
+
+    #[test]
+    fn osc52_sequence_encodes_and_caps_payload() {
+        assert_eq!(osc52_sequence("hi").as_deref(), Ok("\x1b]52;c;aGk=\x07"));
+        assert!(osc52_sequence(&"x".repeat(OSC52_MAX_ENCODED_BYTES)).is_err());
+    }
+}
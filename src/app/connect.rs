@@ -22,7 +22,7 @@ use super::{
 use crate::Cli;
 use crate::agent::client::{AgentConnection, BridgeClient};
 use crate::agent::error_handling::parse_turn_error_class;
-use crate::agent::events::{ClientEvent, TerminalMap};
+use crate::agent::events::{ClientEvent, EventTarget, TerminalMap};
 use crate::agent::model;
 use crate::agent::types;
 use crate::agent::wire::{BridgeCommand, BridgeEvent, CommandEnvelope, EventEnvelope};
@@ -56,6 +56,7 @@ fn extract_app_error(err: &anyhow::Error) -> Option<AppError> {
 }
 
 /// Create the `App` struct in `Connecting` state. No I/O - returns immediately.
+#[allow(clippy::too_many_lines)]
 pub fn create_app(cli: &Cli) -> App {
     let cwd = resolve_startup_cwd(cli);
 
@@ -89,7 +90,12 @@ pub fn create_app(cli: &Cli) -> App {
         help_view: HelpView::Keys,
         pending_permission_ids: Vec::new(),
         cancelled_turn_pending_hint: false,
-        queued_submission: None,
+        submission_queue: super::queue::SubmissionQueue::default(),
+        branches: super::branch::BranchState::default(),
+        search: None,
+        chat_nav: None,
+        last_shell_output: None,
+        vim: super::vim::VimState::from_cli(cli),
         pending_cancel_origin: None,
         event_tx,
         event_rx,
@@ -109,6 +115,10 @@ pub fn create_app(cli: &Cli) -> App {
         todo_selected: 0,
         focus: FocusManager::default(),
         available_commands: Vec::new(),
+        custom_commands: super::custom_commands::load_from(&cwd, dirs::home_dir().as_deref()),
+        terminal_focused: true,
+        notify: super::notify::NotifyConfig::from_cli(cli),
+        terminal_title: super::title::TerminalTitle::from_cli(cli),
         recent_sessions: Vec::new(),
         cached_frame_area: ratatui::layout::Rect::new(0, 0, 0, 0),
         selection: Option::<SelectionState>::None,
@@ -127,15 +137,30 @@ pub fn create_app(cli: &Cli) -> App {
         active_paste_session: None,
         next_paste_session_id: 1,
         paste_burst_start: None,
-        file_cache: None,
+        file_index: super::file_index::FileIndex::live(),
+        mention_frecency: super::frecency::Frecency::load(super::frecency::default_path()),
+        attachments: super::attachments::AttachmentTray::default(),
+        drafts: super::draft::DraftStore::load(super::draft::default_path()),
         cached_todo_compact: None,
         git_branch: None,
         cached_header_line: None,
         cached_footer_line: None,
         update_check_hint: None,
+        toast: None,
+        clipboard_mode: super::clipboard::ClipboardMode::from_cli(cli),
+        open_command: super::external::open_command_from_cli(cli),
+        suspended: None,
         session_usage: super::SessionUsageState::default(),
+        budget_limits: super::budget::BudgetLimits::from_cli(cli),
+        budget: super::budget::BudgetState::default(),
+        usage_ledger_path: super::ledger::default_path(),
+        overlay: None,
         is_compacting: false,
         terminal_tool_calls: Vec::new(),
+        inline_images: crate::ui::graphics::InlineImageState::default(),
+        hyperlinks: crate::ui::hyperlink::HyperlinkState::from_cli(cli),
+        tabs: super::tabs::TabStrip::default(),
+        subagent_view: None,
         needs_redraw: true,
         perf: cli
             .perf_log
@@ -202,8 +227,8 @@ pub fn start_connection(app: &App, cli: &Cli) {
 
         let mut connected_once = false;
         let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel::<CommandEnvelope>();
-        *conn_slot_writer.borrow_mut() =
-            Some(ConnectionSlot { conn: Rc::new(AgentConnection::new(cmd_tx.clone())) });
+        let conn = Rc::new(AgentConnection::new(cmd_tx.clone()));
+        *conn_slot_writer.borrow_mut() = Some(ConnectionSlot { conn: Rc::clone(&conn) });
 
         let init_cmd = CommandEnvelope {
             request_id: None,
@@ -222,7 +247,7 @@ pub fn start_connection(app: &App, cli: &Cli) {
             return;
         }
         tracing::debug!("sent initialize command to bridge");
-        if let Err(app_error) = wait_for_bridge_initialized(
+        match wait_for_bridge_initialized(
             &mut bridge,
             &event_tx,
             &cmd_tx,
@@ -231,12 +256,15 @@ pub fn start_connection(app: &App, cli: &Cli) {
         )
         .await
         {
-            emit_connection_failed(
-                &event_tx,
-                "Bridge did not complete initialization".to_owned(),
-                app_error,
-            );
-            return;
+            Ok(capabilities) => conn.set_capabilities(&capabilities),
+            Err(app_error) => {
+                emit_connection_failed(
+                    &event_tx,
+                    "Bridge did not complete initialization".to_owned(),
+                    app_error,
+                );
+                return;
+            }
         }
 
         let create_cmd = if let Some(resume) = resume_id {
@@ -244,6 +272,7 @@ pub fn start_connection(app: &App, cli: &Cli) {
                 request_id: None,
                 command: BridgeCommand::LoadSession {
                     session_id: resume,
+                    replace_session_id: None,
                     metadata: std::collections::BTreeMap::new(),
                 },
             }
@@ -342,7 +371,7 @@ async fn wait_for_bridge_initialized(
     cmd_tx: &mpsc::UnboundedSender<CommandEnvelope>,
     connected_once: &mut bool,
     resume_requested: bool,
-) -> Result<(), AppError> {
+) -> Result<crate::agent::types::AgentCapabilities, AppError> {
     let timeout = Duration::from_secs(10);
     let started = tokio::time::Instant::now();
     loop {
@@ -355,8 +384,8 @@ async fn wait_for_bridge_initialized(
         let event = tokio::time::timeout(remaining, bridge.recv()).await;
         match event {
             Ok(Ok(Some(envelope))) => {
-                if matches!(envelope.event, BridgeEvent::Initialized { .. }) {
-                    return Ok(());
+                if let BridgeEvent::Initialized { result } = envelope.event {
+                    return Ok(result.capabilities);
                 }
                 if matches!(envelope.event, BridgeEvent::ConnectionFailed { .. }) {
                     handle_bridge_event(
@@ -382,11 +411,14 @@ fn handle_bridge_event(
     resume_requested: bool,
     envelope: EventEnvelope,
 ) {
+    let target = event_target(&envelope);
+    let target = target.as_ref();
     match envelope.event {
         BridgeEvent::Connected { session_id, cwd, model_name, mode, history_updates } => {
             handle_connected_event(
                 event_tx,
                 connected_once,
+                target,
                 session_id,
                 cwd,
                 model_name,
@@ -404,25 +436,30 @@ fn handle_bridge_event(
         }
         BridgeEvent::ConnectionFailed { message } => {
             tracing::error!("bridge connection_failed: {message}");
-            emit_connection_failed(event_tx, message, AppError::ConnectionFailed);
+            if let Some(target) = target {
+                // A failed extra tab session must not take the whole app down.
+                send_routed(event_tx, Some(target), ClientEvent::ConnectionFailed(message));
+            } else {
+                emit_connection_failed(event_tx, message, AppError::ConnectionFailed);
+            }
         }
         BridgeEvent::SessionUpdate { update, .. } => {
             if let Some(update) = map_session_update(update) {
-                let _ = event_tx.send(ClientEvent::SessionUpdate(update));
+                send_routed(event_tx, target, ClientEvent::SessionUpdate(update));
             }
         }
         BridgeEvent::PermissionRequest { session_id, request } => {
-            handle_permission_request_event(event_tx, cmd_tx, session_id, request);
+            handle_permission_request_event(event_tx, cmd_tx, target, session_id, request);
         }
         BridgeEvent::TurnComplete { .. } => {
-            let _ = event_tx.send(ClientEvent::TurnComplete);
+            send_routed(event_tx, target, ClientEvent::TurnComplete);
         }
         BridgeEvent::TurnError { message, error_kind, .. } => {
             tracing::warn!("bridge turn_error: {message}");
             if let Some(class) = error_kind.as_deref().and_then(parse_turn_error_class) {
-                let _ = event_tx.send(ClientEvent::TurnErrorClassified { message, class });
+                send_routed(event_tx, target, ClientEvent::TurnErrorClassified { message, class });
             } else {
-                let _ = event_tx.send(ClientEvent::TurnError(message));
+                send_routed(event_tx, target, ClientEvent::TurnError(message));
             }
         }
         BridgeEvent::SlashError { message, .. } => {
@@ -434,7 +471,7 @@ fn handle_bridge_event(
                 let _ = event_tx.send(ClientEvent::FatalError(AppError::SessionNotFound));
                 return;
             }
-            let _ = event_tx.send(ClientEvent::SlashCommandError(message));
+            send_routed(event_tx, target, ClientEvent::SlashCommandError(message));
         }
         BridgeEvent::SessionReplaced { session_id, cwd, model_name, mode, history_updates } => {
             let history_updates = history_updates
@@ -442,13 +479,17 @@ fn handle_bridge_event(
                 .into_iter()
                 .filter_map(map_session_update)
                 .collect();
-            let _ = event_tx.send(ClientEvent::SessionReplaced {
-                session_id: model::SessionId::new(session_id),
-                cwd,
-                model_name,
-                mode: mode.map(convert_mode_state),
-                history_updates,
-            });
+            send_routed(
+                event_tx,
+                target,
+                ClientEvent::SessionReplaced {
+                    session_id: model::SessionId::new(session_id),
+                    cwd,
+                    model_name,
+                    mode: mode.map(convert_mode_state),
+                    history_updates,
+                },
+            );
         }
         BridgeEvent::SessionsListed { sessions, next_cursor } => {
             let _ = event_tx.send(ClientEvent::SessionsListed { sessions, next_cursor });
@@ -457,9 +498,42 @@ fn handle_bridge_event(
     }
 }
 
+/// Which tab a bridge event belongs to. Tab-tagged request ids win over the
+/// session id so connect events for a not-yet-known session still find their tab.
+fn event_target(envelope: &EventEnvelope) -> Option<EventTarget> {
+    if let Some(tab_id) = envelope.request_id.as_deref().and_then(super::tabs::parse_tab_request_id)
+    {
+        return Some(EventTarget::Tab(tab_id));
+    }
+    match &envelope.event {
+        BridgeEvent::SessionUpdate { session_id, .. }
+        | BridgeEvent::PermissionRequest { session_id, .. }
+        | BridgeEvent::TurnComplete { session_id }
+        | BridgeEvent::TurnError { session_id, .. }
+        | BridgeEvent::SlashError { session_id, .. } => {
+            Some(EventTarget::Session(session_id.clone()))
+        }
+        _ => None,
+    }
+}
+
+fn send_routed(
+    event_tx: &mpsc::UnboundedSender<ClientEvent>,
+    target: Option<&EventTarget>,
+    event: ClientEvent,
+) -> bool {
+    let event = match target {
+        Some(target) => ClientEvent::Routed { target: target.clone(), event: Box::new(event) },
+        None => event,
+    };
+    event_tx.send(event).is_ok()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_connected_event(
     event_tx: &mpsc::UnboundedSender<ClientEvent>,
     connected_once: &mut bool,
+    target: Option<&EventTarget>,
     session_id: String,
     cwd: String,
     model_name: String,
@@ -471,13 +545,17 @@ fn handle_connected_event(
     let history_updates =
         history_updates.unwrap_or_default().into_iter().filter_map(map_session_update).collect();
     if *connected_once {
-        let _ = event_tx.send(ClientEvent::SessionReplaced {
-            session_id: model::SessionId::new(session_id),
-            cwd,
-            model_name,
-            mode,
-            history_updates,
-        });
+        send_routed(
+            event_tx,
+            target,
+            ClientEvent::SessionReplaced {
+                session_id: model::SessionId::new(session_id),
+                cwd,
+                model_name,
+                mode,
+                history_updates,
+            },
+        );
     } else {
         *connected_once = true;
         let _ = event_tx.send(ClientEvent::Connected {
@@ -493,6 +571,7 @@ fn handle_connected_event(
 fn handle_permission_request_event(
     event_tx: &mpsc::UnboundedSender<ClientEvent>,
     cmd_tx: &mpsc::UnboundedSender<CommandEnvelope>,
+    target: Option<&EventTarget>,
     session_id: String,
     request: types::PermissionRequest,
 ) {
@@ -504,7 +583,7 @@ fn handle_permission_request_event(
     );
     let (request, tool_call_id) = map_permission_request(&session_id, request);
     let (response_tx, response_rx) = tokio::sync::oneshot::channel();
-    if event_tx.send(ClientEvent::PermissionRequest { request, response_tx }).is_ok() {
+    if send_routed(event_tx, target, ClientEvent::PermissionRequest { request, response_tx }) {
         spawn_permission_response_forwarder(cmd_tx.clone(), response_rx, session_id, tool_call_id);
     }
 }
@@ -603,6 +682,14 @@ fn map_session_update(update: types::SessionUpdate) -> Option<model::SessionUpda
                 max_output_tokens: usage.max_output_tokens,
             }))
         }
+        types::SessionUpdate::TurnUsage { usage } => {
+            Some(model::SessionUpdate::TurnUsage(model::TurnUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_read_tokens: usage.cache_read_tokens,
+                cache_write_tokens: usage.cache_write_tokens,
+            }))
+        }
         types::SessionUpdate::SessionStatusUpdate { status } => {
             Some(model::SessionUpdate::SessionStatusUpdate(match status {
                 types::SessionStatus::Compacting => model::SessionStatus::Compacting,
@@ -618,6 +705,9 @@ fn map_session_update(update: types::SessionUpdate) -> Option<model::SessionUpda
                 pre_tokens,
             }))
         }
+        types::SessionUpdate::CompactionSummary { summary } => {
+            Some(model::SessionUpdate::CompactionSummary(summary))
+        }
     }
 }
 
@@ -672,8 +762,14 @@ fn convert_content_block(content: types::ContentBlock) -> Option<model::ContentB
         types::ContentBlock::Text { text } => {
             Some(model::ContentBlock::Text(model::TextContent::new(text)))
         }
-        // Deferred for parity follow-up per scope.
-        types::ContentBlock::Image { .. } => None,
+        types::ContentBlock::Image { mime_type, data: Some(data), .. } => {
+            Some(model::ContentBlock::Image(model::ImageContent::new(
+                data,
+                mime_type.unwrap_or_else(|| "image/png".to_owned()),
+            )))
+        }
+        // URI-only images would need a fetch; nothing to render inline.
+        types::ContentBlock::Image { data: None, .. } => None,
     }
 }
 
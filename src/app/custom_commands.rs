@@ -0,0 +1,280 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! User-defined slash commands: markdown prompt templates loaded from
+//! `.claude-rs/commands/*.md` in the project and `~/.claude-rs/commands/*.md`.
+//!
+//! The file stem is the command name. Optional frontmatter sets
+//! `description` and `argument-hint`. On submit the body is expanded locally
+//! (`$ARGUMENTS`, `$1`, `$2`, ...) and sent as an ordinary prompt.
+
+use std::path::{Path, PathBuf};
+
+/// Command directory, relative to the project root or the home directory.
+const COMMANDS_DIR: &str = ".claude-rs/commands";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    Project,
+    User,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomCommand {
+    /// Slash name including the leading `/`.
+    pub name: String,
+    pub description: String,
+    pub argument_hint: Option<String>,
+    pub body: String,
+    pub scope: CommandScope,
+}
+
+impl CustomCommand {
+    /// Parse a command file's contents; `name` is the file stem.
+    #[must_use]
+    pub fn parse(name: &str, contents: &str, scope: CommandScope) -> Self {
+        let (frontmatter, body) = split_frontmatter(contents);
+        let mut description = String::new();
+        let mut argument_hint = None;
+        for line in frontmatter.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = unquote(value.trim());
+            match key.trim() {
+                "description" => value.clone_into(&mut description),
+                "argument-hint" | "argument_hint" if !value.is_empty() => {
+                    argument_hint = Some(value.to_owned());
+                }
+                _ => {}
+            }
+        }
+        if description.is_empty() {
+            // Fall back to the first non-empty body line, like a commit subject.
+            body.lines()
+                .map(|l| l.trim().trim_start_matches('#').trim())
+                .find(|l| !l.is_empty())
+                .unwrap_or_default()
+                .clone_into(&mut description);
+        }
+        Self {
+            name: format!("/{name}"),
+            description,
+            argument_hint,
+            body: body.trim().to_owned(),
+            scope,
+        }
+    }
+
+    /// Prompt text for this command invoked with `args` (everything after
+    /// the command name). Arguments are appended when the template has no
+    /// placeholders, so they are never dropped silently.
+    #[must_use]
+    pub fn expand(&self, args: &str) -> String {
+        let args = args.trim();
+        let positional: Vec<&str> = args.split_whitespace().collect();
+        let mut out = String::with_capacity(self.body.len() + args.len());
+        let mut used_placeholder = false;
+        let mut rest = self.body.as_str();
+        while let Some(pos) = rest.find('$') {
+            out.push_str(&rest[..pos]);
+            let after = &rest[pos + 1..];
+            if let Some(tail) = after.strip_prefix("ARGUMENTS") {
+                out.push_str(args);
+                used_placeholder = true;
+                rest = tail;
+                continue;
+            }
+            let digits = after.bytes().take_while(u8::is_ascii_digit).count();
+            match after[..digits].parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    out.push_str(positional.get(n - 1).copied().unwrap_or_default());
+                    used_placeholder = true;
+                    rest = &after[digits..];
+                }
+                _ => {
+                    out.push('$');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        if !used_placeholder && !args.is_empty() {
+            out.push_str("\n\n");
+            out.push_str(args);
+        }
+        out
+    }
+
+    /// One-line summary for autocomplete and help.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let scope = match self.scope {
+            CommandScope::Project => "project",
+            CommandScope::User => "user",
+        };
+        let description =
+            if self.description.is_empty() { "Custom command" } else { &self.description };
+        match &self.argument_hint {
+            Some(hint) => format!("{description} {hint} ({scope})"),
+            None => format!("{description} ({scope})"),
+        }
+    }
+}
+
+/// Split leading `---` frontmatter from the body.
+fn split_frontmatter(contents: &str) -> (&str, &str) {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let Some(after_open) =
+        contents.strip_prefix("---\n").or_else(|| contents.strip_prefix("---\r\n"))
+    else {
+        return ("", contents);
+    };
+    let mut offset = 0;
+    for line in after_open.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (&after_open[..offset], &after_open[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    ("", contents)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+fn load_dir(dir: &Path, scope: CommandScope, out: &mut Vec<CustomCommand>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md") && path.is_file())
+        .collect();
+    paths.sort();
+    for path in paths {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if stem.is_empty() || stem.contains(char::is_whitespace) {
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => out.push(CustomCommand::parse(stem, &contents, scope)),
+            Err(e) => tracing::warn!("skipping custom command {}: {e}", path.display()),
+        }
+    }
+}
+
+/// Commands from `project_root` and `home`; project commands shadow user
+/// commands of the same name. Sorted by name.
+#[must_use]
+pub fn load_from(project_root: &Path, home: Option<&Path>) -> Vec<CustomCommand> {
+    let mut project = Vec::new();
+    load_dir(&project_root.join(COMMANDS_DIR), CommandScope::Project, &mut project);
+    let mut user = Vec::new();
+    if let Some(home) = home {
+        let user_dir = home.join(COMMANDS_DIR);
+        if user_dir != project_root.join(COMMANDS_DIR) {
+            load_dir(&user_dir, CommandScope::User, &mut user);
+        }
+    }
+    user.retain(|u| !project.iter().any(|p| p.name == u.name));
+    project.extend(user);
+    project.sort_by(|a, b| a.name.cmp(&b.name));
+    project
+}
+
+/// Rescan the command directories for the session cwd. Cheap enough to run
+/// whenever slash autocomplete opens or a command is submitted, so edits to
+/// command files apply without restarting.
+pub(super) fn refresh(app: &mut super::App) {
+    let home = dirs::home_dir();
+    app.custom_commands = load_from(Path::new(&app.cwd_raw), home.as_deref());
+}
+
+#[must_use]
+pub fn find<'a>(commands: &'a [CustomCommand], name: &str) -> Option<&'a CustomCommand> {
+    commands.iter().find(|cmd| cmd.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(body: &str) -> CustomCommand {
+        CustomCommand::parse("review", body, CommandScope::Project)
+    }
+
+    #[test]
+    fn parse_reads_frontmatter_description_and_hint() {
+        let cmd = command(
+            "---\ndescription: \"Review a file\"\nargument-hint: <path> [focus]\n---\n\nReview $1.\n",
+        );
+        assert_eq!(cmd.name, "/review");
+        assert_eq!(cmd.description, "Review a file");
+        assert_eq!(cmd.argument_hint.as_deref(), Some("<path> [focus]"));
+        assert_eq!(cmd.body, "Review $1.");
+        assert_eq!(cmd.summary(), "Review a file <path> [focus] (project)");
+
+        let plain = command("# Explain the diff\n\nWalk through it.");
+        assert_eq!(plain.description, "Explain the diff");
+        assert_eq!(plain.argument_hint, None);
+    }
+
+    #[test]
+    fn expand_substitutes_arguments_and_positionals() {
+        let cmd = command("Review $1 focusing on $2 (all: $ARGUMENTS), cost $5.00 $9");
+        assert_eq!(
+            cmd.expand("  src/app.rs  errors "),
+            "Review src/app.rs focusing on errors (all: src/app.rs  errors), cost .00 "
+        );
+        assert_eq!(command("Price is $ and $0").expand(""), "Price is $ and $0");
+        assert_eq!(
+            command("Summarize the branch.").expand("briefly"),
+            "Summarize the branch.\n\nbriefly"
+        );
+    }
+
+    #[test]
+    fn load_from_prefers_project_commands() {
+        let project = tempfile::tempdir().expect("tempdir");
+        let home = tempfile::tempdir().expect("tempdir");
+        let project_dir = project.path().join(COMMANDS_DIR);
+        let user_dir = home.path().join(COMMANDS_DIR);
+        std::fs::create_dir_all(&project_dir).expect("mkdir");
+        std::fs::create_dir_all(&user_dir).expect("mkdir");
+        std::fs::write(project_dir.join("review.md"), "Project review").expect("write");
+        std::fs::write(project_dir.join("notes.txt"), "ignored").expect("write");
+        std::fs::write(user_dir.join("review.md"), "User review").expect("write");
+        std::fs::write(user_dir.join("standup.md"), "Standup").expect("write");
+
+        let commands = load_from(project.path(), Some(home.path()));
+        let names: Vec<_> = commands.iter().map(|c| (c.name.as_str(), c.scope)).collect();
+        assert_eq!(
+            names,
+            vec![("/review", CommandScope::Project), ("/standup", CommandScope::User)]
+        );
+        assert_eq!(find(&commands, "/review").map(|c| c.body.as_str()), Some("Project review"));
+    }
+}
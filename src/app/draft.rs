@@ -0,0 +1,528 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Crash-safe input drafts.
+//!
+//! The unsent input (lines, cursor and collapsed paste blocks) is written per
+//! project and session shortly after the last edit, and removed once the
+//! input is empty again. A session that connects with an empty input gets
+//! its draft back; the first fresh session after launch also adopts the
+//! newest draft left in the project, so a crash or quit mid-prompt loses
+//! nothing. Like the frecency stats, the file is re-read before every write
+//! so parallel instances do not drop each other's drafts.
+//!
+//! Every draft records the process that wrote it, which keeps confirming it
+//! is alive with a heartbeat and gives its drafts up on a clean exit. Drafts
+//! of a live instance are never taken over: the session that would have
+//! adopted one only says that a draft is available elsewhere.
+
+use super::App;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DRAFT_DIR_NAME: &str = "claude-code-rust";
+const DRAFT_FILE: &str = "drafts.json";
+
+/// Quiet time after the last edit before the draft is written.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Drafts kept per project; the oldest are dropped beyond this.
+const MAX_DRAFTS_PER_PROJECT: usize = 20;
+
+/// Drafts untouched for longer than this are dropped.
+const MAX_DRAFT_AGE_SECS: u64 = 30 * 24 * 3_600;
+
+/// How often a running instance confirms that it still owns its drafts.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Drafts whose owner has not confirmed for this long belong to nobody.
+const OWNER_TIMEOUT_SECS: u64 = 90;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Draft {
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub paste_blocks: Vec<String>,
+    #[serde(default)]
+    pub cursor_row: usize,
+    #[serde(default)]
+    pub cursor_col: usize,
+    /// Unix seconds of the last write.
+    pub saved_at: u64,
+    /// Process that wrote the draft.
+    #[serde(default)]
+    pub owner_pid: u32,
+    /// Unix seconds the owner last confirmed it is running; 0 once it quit.
+    #[serde(default)]
+    pub heartbeat: u64,
+}
+
+impl Draft {
+    /// Another running instance still holds this draft.
+    fn held_elsewhere(&self, now: u64) -> bool {
+        self.owner_pid != std::process::id()
+            && self.heartbeat > 0
+            && now.saturating_sub(self.heartbeat) <= OWNER_TIMEOUT_SECS
+    }
+}
+
+/// Project root -> session id -> draft.
+type Drafts = BTreeMap<String, BTreeMap<String, Draft>>;
+
+/// A draft put back into the input, shown until it is edited, sent or
+/// discarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoredDraft {
+    pub session_id: String,
+    pub saved_at: u64,
+    lines: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct DraftStore {
+    /// Drafts file; `None` keeps nothing on disk (tests, no data dir).
+    path: Option<PathBuf>,
+    /// In-memory copy when there is no file.
+    drafts: Drafts,
+    /// Session and input version last seen by [`autosave`].
+    seen: Option<(String, u64)>,
+    /// When the input last changed without being written yet.
+    dirty_since: Option<Instant>,
+    /// The newest project draft may be adopted once, by the first session.
+    adopted_on_launch: bool,
+    /// When this instance last confirmed ownership of its drafts.
+    last_heartbeat: Option<Instant>,
+    pub restored: Option<RestoredDraft>,
+}
+
+/// Default drafts location under the platform data dir.
+#[must_use]
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join(DRAFT_DIR_NAME).join(DRAFT_FILE))
+}
+
+fn unix_now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn read_drafts(path: &Path) -> Drafts {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Drafts often hold pasted secrets: create the file readable by the owner
+/// only, replacing any leftover from a crashed run with the same pid.
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write as _;
+    let _ = std::fs::remove_file(path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(bytes)
+}
+
+impl DraftStore {
+    #[must_use]
+    pub fn load(path: Option<PathBuf>) -> Self {
+        Self { path, ..Self::default() }
+    }
+
+    /// Apply `change` to the current drafts and persist. Failures are logged,
+    /// never surfaced.
+    fn update(&mut self, change: impl FnOnce(&mut Drafts)) {
+        // Pick up other instances' drafts before merging ours.
+        if let Some(path) = self.path.as_deref() {
+            self.drafts = read_drafts(path);
+        }
+        change(&mut self.drafts);
+        let now = unix_now_secs();
+        self.drafts.retain(|_, sessions| {
+            sessions.retain(|_, draft| now.saturating_sub(draft.saved_at) <= MAX_DRAFT_AGE_SECS);
+            if sessions.len() > MAX_DRAFTS_PER_PROJECT {
+                let mut by_age: Vec<(u64, String)> =
+                    sessions.iter().map(|(sid, draft)| (draft.saved_at, sid.clone())).collect();
+                by_age.sort_unstable_by(|a, b| b.cmp(a));
+                for (_, sid) in by_age.into_iter().skip(MAX_DRAFTS_PER_PROJECT) {
+                    sessions.remove(&sid);
+                }
+            }
+            !sessions.is_empty()
+        });
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string(&self.drafts).map_err(std::io::Error::other)?;
+            // Write then rename so a crash never leaves a torn file behind.
+            let tmp = path.with_extension(format!("json.{}", std::process::id()));
+            write_private(&tmp, json.as_bytes())?;
+            std::fs::rename(&tmp, path)
+        })();
+        if let Err(err) = result {
+            tracing::warn!("input draft save failed ({}): {err}", path.display());
+        }
+    }
+
+    fn current(&mut self) -> &Drafts {
+        if let Some(path) = self.path.as_deref() {
+            self.drafts = read_drafts(path);
+        }
+        &self.drafts
+    }
+
+    /// Set the heartbeat of every draft this process owns. Writes only when
+    /// it owns any.
+    fn stamp_owned(&mut self, heartbeat: u64) {
+        let pid = std::process::id();
+        let owned = |drafts: &Drafts| {
+            drafts.values().flat_map(BTreeMap::values).any(|draft| draft.owner_pid == pid)
+        };
+        if !owned(self.current()) {
+            return;
+        }
+        self.update(|drafts| {
+            for draft in drafts.values_mut().flat_map(BTreeMap::values_mut) {
+                if draft.owner_pid == pid {
+                    draft.heartbeat = heartbeat;
+                }
+            }
+        });
+    }
+
+    fn heartbeat(&mut self, now: Instant) {
+        if self.last_heartbeat.is_some_and(|at| now.duration_since(at) < HEARTBEAT_INTERVAL) {
+            return;
+        }
+        self.last_heartbeat = Some(now);
+        self.stamp_owned(unix_now_secs());
+    }
+}
+
+fn session_key(app: &App) -> Option<String> {
+    app.session_id.as_ref().map(ToString::to_string)
+}
+
+/// Write the current input under its project and session, or drop the entry
+/// when the input is empty.
+fn write(app: &mut App) {
+    app.drafts.dirty_since = None;
+    let Some(session_id) = session_key(app) else {
+        return;
+    };
+    let project = app.cwd_raw.clone();
+    if app.input.is_empty() {
+        let known = app.drafts.current().get(&project).is_some_and(|s| s.contains_key(&session_id));
+        if known {
+            app.drafts.update(|drafts| {
+                if let Some(sessions) = drafts.get_mut(&project) {
+                    sessions.remove(&session_id);
+                }
+            });
+        }
+        return;
+    }
+    let now = unix_now_secs();
+    let draft = Draft {
+        lines: app.input.lines.clone(),
+        paste_blocks: app.input.paste_blocks.clone(),
+        cursor_row: app.input.cursor_row,
+        cursor_col: app.input.cursor_col,
+        saved_at: now,
+        owner_pid: std::process::id(),
+        heartbeat: now,
+    };
+    app.drafts.update(|drafts| {
+        drafts.entry(project).or_default().insert(session_id, draft);
+    });
+}
+
+/// Track input changes and write the draft once edits have settled. Called
+/// once per event loop iteration.
+pub(super) fn autosave(app: &mut App, now: Instant) {
+    app.drafts.heartbeat(now);
+    let Some(session_id) = session_key(app) else {
+        return;
+    };
+    let seen = (session_id, app.input.version);
+    if app.drafts.seen.as_ref() != Some(&seen) {
+        let first = app.drafts.seen.is_none();
+        app.drafts.seen = Some(seen);
+        let edited = app.drafts.restored.as_ref().is_some_and(|restored| {
+            Some(&restored.session_id) == app.drafts.seen.as_ref().map(|(sid, _)| sid)
+                && restored.lines != app.input.lines
+        });
+        if edited {
+            app.drafts.restored = None;
+            app.needs_redraw = true;
+        }
+        if first && app.input.is_empty() {
+            return;
+        }
+        // An emptied input (sent or cleared) is written right away so a crash
+        // cannot bring back a prompt that already went out.
+        if app.input.is_empty() {
+            write(app);
+            return;
+        }
+        app.drafts.dirty_since = Some(now);
+    }
+    if app.drafts.dirty_since.is_some_and(|since| now.duration_since(since) >= SAVE_DEBOUNCE) {
+        write(app);
+    }
+}
+
+/// Write a pending draft now (before quitting or switching tabs).
+pub(super) fn flush(app: &mut App) {
+    if app.drafts.dirty_since.is_some() {
+        write(app);
+    }
+}
+
+/// Write a pending draft and give up ownership of all drafts, so the next
+/// launch can adopt them right away.
+pub(super) fn close(app: &mut App) {
+    flush(app);
+    app.drafts.stamp_owned(0);
+}
+
+/// Put the saved draft of the just-connected session back into an empty
+/// input. `fresh` sessions (not resumed) on launch adopt the project's newest
+/// draft instead, moving it to the new session. Drafts held by another
+/// running instance stay where they are; a toast points them out.
+pub(super) fn restore(app: &mut App, fresh: bool) {
+    let adopt = fresh && !std::mem::replace(&mut app.drafts.adopted_on_launch, true);
+    let Some(session_id) = session_key(app) else {
+        return;
+    };
+    if !app.input.is_empty() {
+        return;
+    }
+    let project = app.cwd_raw.clone();
+    let now = unix_now_secs();
+    let mut sessions = app.drafts.current().get(&project).cloned().unwrap_or_default();
+    if !adopt {
+        sessions.retain(|sid, _| *sid == session_id);
+    }
+    let open = open_session_ids(app);
+    sessions.retain(|sid, _| *sid == session_id || !open.contains(sid));
+    let held_elsewhere = sessions.values().any(|draft| draft.held_elsewhere(now));
+    sessions.retain(|_, draft| !draft.held_elsewhere(now));
+    let found = sessions
+        .remove_entry(&session_id)
+        .or_else(|| sessions.into_iter().max_by_key(|(_, draft)| draft.saved_at));
+    let Some((from, draft)) = found else {
+        if held_elsewhere {
+            app.show_toast("Draft available: it is still open in another instance", false);
+        }
+        return;
+    };
+    // Claim the draft so no other instance adopts it while it is on screen.
+    let claimed = Draft { owner_pid: std::process::id(), heartbeat: now, ..draft.clone() };
+    let target = session_id.clone();
+    app.drafts.update(|drafts| {
+        let sessions = drafts.entry(project).or_default();
+        sessions.remove(&from);
+        sessions.insert(target, claimed);
+    });
+
+    app.input.load_draft(
+        draft.lines.clone(),
+        draft.paste_blocks,
+        draft.cursor_row,
+        draft.cursor_col,
+    );
+    app.drafts.seen = Some((session_id.clone(), app.input.version));
+    app.drafts.dirty_since = None;
+    app.drafts.restored = Some(RestoredDraft {
+        session_id,
+        saved_at: draft.saved_at,
+        lines: app.input.lines.clone(),
+    });
+    app.needs_redraw = true;
+}
+
+fn open_session_ids(app: &App) -> Vec<String> {
+    let mut open: Vec<String> = super::tabs::session_ids(app);
+    open.extend(session_key(app));
+    open
+}
+
+/// The restored draft of the session on screen, if still untouched.
+#[must_use]
+pub fn visible_restored(app: &App) -> Option<&RestoredDraft> {
+    let session_id = session_key(app)?;
+    app.drafts.restored.as_ref().filter(|restored| restored.session_id == session_id)
+}
+
+/// Throw the restored draft away (Alt+D). Returns `false` when there is none.
+pub(super) fn discard(app: &mut App) -> bool {
+    if visible_restored(app).is_none() {
+        return false;
+    }
+    app.drafts.restored = None;
+    app.input.clear();
+    write(app);
+    app.drafts.seen = session_key(app).map(|sid| (sid, app.input.version));
+    app.show_toast("Draft discarded", false);
+    app.needs_redraw = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::model;
+
+    fn app_with_store(path: &Path, session: &str) -> App {
+        let mut app = App::test_default();
+        app.cwd_raw = "/project".to_owned();
+        app.drafts = DraftStore::load(Some(path.to_path_buf()));
+        app.session_id = Some(model::SessionId::new(session));
+        app
+    }
+
+    #[test]
+    fn drafts_are_written_after_the_debounce_and_dropped_once_sent() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(DRAFT_FILE);
+        let mut app = app_with_store(&path, "s1");
+        let start = Instant::now();
+        autosave(&mut app, start);
+
+        app.input.set_text("half a thought\n\nsecond paragraph ");
+        app.input.insert_paste_block(&"x".repeat(1500));
+        autosave(&mut app, start);
+        assert!(!path.exists());
+        autosave(&mut app, start + SAVE_DEBOUNCE);
+        let saved = read_drafts(&path);
+        let draft = &saved["/project"]["s1"];
+        assert_eq!(draft.lines, app.input.lines);
+        assert_eq!(draft.paste_blocks, vec!["x".repeat(1500)]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = std::fs::metadata(&path).expect("metadata").permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        app.input.clear();
+        autosave(&mut app, start + SAVE_DEBOUNCE);
+        assert!(read_drafts(&path).is_empty());
+    }
+
+    #[test]
+    fn launch_adopts_the_newest_draft_and_discard_removes_it() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(DRAFT_FILE);
+        let mut crashed = app_with_store(&path, "old");
+        crashed.input.set_text("unsent");
+        crashed.input.insert_paste_block(&"y".repeat(1200));
+        write(&mut crashed);
+
+        let mut app = app_with_store(&path, "new");
+        restore(&mut app, true);
+        assert_eq!(app.input.text(), format!("unsent{}", "y".repeat(1200)));
+        assert_eq!(app.input.paste_blocks.len(), 1);
+        assert!(visible_restored(&app).is_some());
+        let saved = read_drafts(&path);
+        assert!(saved["/project"].contains_key("new"));
+        assert!(!saved["/project"].contains_key("old"));
+
+        // Only the first session after launch adopts someone else's draft.
+        let mut other = app_with_store(&path, "tab");
+        other.drafts.adopted_on_launch = true;
+        restore(&mut other, true);
+        assert!(other.input.is_empty());
+
+        assert!(discard(&mut app));
+        assert!(app.input.is_empty());
+        assert!(visible_restored(&app).is_none());
+        assert!(read_drafts(&path).is_empty());
+        assert!(!discard(&mut app));
+    }
+
+    #[test]
+    fn editing_a_restored_draft_hides_the_indicator() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(DRAFT_FILE);
+        let mut app = app_with_store(&path, "s1");
+        app.input.set_text("resume me");
+        write(&mut app);
+        app.input.clear();
+
+        restore(&mut app, false);
+        assert_eq!(app.input.text(), "resume me");
+        let now = Instant::now();
+        app.input.move_left();
+        autosave(&mut app, now);
+        assert!(visible_restored(&app).is_some());
+        app.input.insert_char('!');
+        autosave(&mut app, now);
+        assert!(visible_restored(&app).is_none());
+    }
+
+    #[test]
+    fn drafts_of_a_running_instance_stay_put() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(DRAFT_FILE);
+        let mut store = DraftStore::load(Some(path.clone()));
+        let elsewhere = |heartbeat| Draft {
+            lines: vec!["still typing".to_owned()],
+            paste_blocks: Vec::new(),
+            cursor_row: 0,
+            cursor_col: 0,
+            saved_at: unix_now_secs(),
+            owner_pid: std::process::id().wrapping_add(1),
+            heartbeat,
+        };
+        let live = elsewhere(unix_now_secs());
+        store.update(|drafts| {
+            drafts.entry("/project".to_owned()).or_default().insert("other".to_owned(), live);
+        });
+
+        let mut app = app_with_store(&path, "new");
+        restore(&mut app, true);
+        assert!(app.input.is_empty());
+        assert!(app.toast.as_ref().is_some_and(|toast| toast.text.starts_with("Draft available")));
+        assert!(read_drafts(&path)["/project"].contains_key("other"));
+
+        let stale = elsewhere(unix_now_secs() - OWNER_TIMEOUT_SECS - 1);
+        store.update(|drafts| {
+            drafts.entry("/project".to_owned()).or_default().insert("other".to_owned(), stale);
+        });
+        let mut app = app_with_store(&path, "new");
+        restore(&mut app, true);
+        assert_eq!(app.input.text(), "still typing");
+        let saved = read_drafts(&path);
+        assert_eq!(saved["/project"]["new"].owner_pid, std::process::id());
+
+        close(&mut app);
+        assert_eq!(read_drafts(&path)["/project"]["new"].heartbeat, 0);
+    }
+}
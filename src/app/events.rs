@@ -15,12 +15,14 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use super::connect::take_connection_slot;
+use super::notify::{self, NotifyEvent};
 use super::selection::clear_selection;
 use super::state::{RecentSessionInfo, ScrollbarDragState};
 use super::{
-    App, AppStatus, BlockCache, CancelOrigin, ChatMessage, FocusTarget, IncrementalMarkdown,
-    InlinePermission, LoginHint, MessageBlock, MessageRole, MessageUsage, SelectionKind,
-    SelectionPoint, ToolCallInfo, ToolCallScope, default_cache_split_policy, find_text_split_index,
+    App, AppStatus, BlockCache, CancelOrigin, ChatMessage, CompactionBlock, FocusTarget,
+    IncrementalMarkdown, InlinePermission, LoginHint, MessageBlock, MessageRole, MessageUsage,
+    SelectionKind, SelectionPoint, ToolCallInfo, ToolCallScope, default_cache_split_policy,
+    find_text_split_index,
 };
 use crate::agent::error_handling::{
     TurnErrorClass, classify_turn_error, looks_like_internal_error, summarize_internal_error,
@@ -46,6 +48,7 @@ const PLAN_LIMIT_NEXT_STEPS_HINT: &str = "Next steps:\n\
 const AUTH_REQUIRED_NEXT_STEPS_HINT: &str =
     "Authentication required. Run `claude /login` in a terminal, then restart and retry.";
 
+#[allow(clippy::collapsible_match, clippy::match_wildcard_for_single_variants)]
 pub fn handle_terminal_event(app: &mut App, event: Event) {
     app.needs_redraw = true;
     match event {
@@ -85,8 +88,12 @@ pub fn handle_terminal_event(app: &mut App, event: Event) {
             }
         }
         Event::FocusGained => {
+            app.terminal_focused = true;
             app.refresh_git_branch();
         }
+        Event::FocusLost => {
+            app.terminal_focused = false;
+        }
         Event::Resize(_, _) => {
             // Force a full terminal clear on resize. Without this, terminal
             // emulators (especially on Windows) corrupt their scrollback buffer
@@ -139,6 +146,7 @@ fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
             if let Some(sel) = &mut app.selection {
                 sel.dragging = false;
             }
+            open_clicked_tool_call(app, mouse);
         }
         _ => {}
     }
@@ -159,6 +167,22 @@ fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
     }
 }
 
+/// A click (no drag) on a tool call title opens its location in the editor.
+fn open_clicked_tool_call(app: &mut App, mouse: MouseEvent) {
+    let is_click = app
+        .selection
+        .is_some_and(|sel| sel.kind == super::SelectionKind::Chat && sel.start == sel.end);
+    if !is_click {
+        return;
+    }
+    if let Some((message, block)) =
+        crate::ui::chat::tool_call_title_at(app, mouse.column, mouse.row)
+        && super::chat_nav::open_tool_call(app, message, block)
+    {
+        clear_selection(app);
+    }
+}
+
 #[derive(Clone, Copy)]
 struct ScrollbarMetrics {
     viewport_height: usize,
@@ -342,6 +366,10 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
     app.needs_redraw = true;
     match event {
         ClientEvent::SessionUpdate(update) => {
+            // Only live updates reach the ledger; resume history replays bypass this arm.
+            if let model::SessionUpdate::UsageUpdate(usage) = &update {
+                super::ledger::record(app, usage);
+            }
             let needs_history_retention = matches!(
                 &update,
                 model::SessionUpdate::AgentMessageChunk(_)
@@ -373,11 +401,13 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
                 }
 
                 let mut layout_dirty = false;
+                let mut notify_body = None;
                 if let Some(MessageBlock::ToolCall(tc)) =
                     app.messages.get_mut(mi).and_then(|m| m.blocks.get_mut(bi))
                 {
                     let tc = tc.as_mut();
                     let is_first = app.pending_permission_ids.is_empty();
+                    notify_body = Some(format!("Permission requested: {}", tc.title));
                     tc.pending_permission = Some(InlinePermission {
                         options: request.options,
                         response_tx,
@@ -386,7 +416,7 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
                     });
                     tc.mark_tool_call_layout_dirty();
                     layout_dirty = true;
-                    app.pending_permission_ids.push(tool_id);
+                    app.pending_permission_ids.push(tool_id.clone());
                     app.claim_focus_target(FocusTarget::Permission);
                     app.viewport.engage_auto_scroll();
                 } else {
@@ -403,6 +433,10 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
                 }
                 if layout_dirty {
                     app.mark_message_layout_dirty(mi);
+                    super::subagents::sync_child(app, &tool_id);
+                }
+                if let Some(body) = notify_body {
+                    notify::notify(app, NotifyEvent::PermissionRequest, &body);
                 }
             } else {
                 tracing::warn!(
@@ -453,10 +487,13 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
                 push_interrupted_hint(app);
             }
             if should_compact_clear {
-                super::slash::clear_conversation_history(app);
+                clear_history_after_compact(app);
             } else if turn_was_active || cancelled_requested {
                 mark_turn_exit_assistant_layout_dirty(app, tail_assistant_idx);
             }
+            if turn_was_active && !cancelled_requested {
+                notify::notify(app, NotifyEvent::TurnComplete, "Turn complete");
+            }
             super::input_submit::drain_queued_submission(app);
         }
         ClientEvent::TurnError(msg) => handle_turn_error_event(app, &msg, None),
@@ -476,17 +513,23 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
             app.pending_compact_clear = false;
             app.is_compacting = false;
             app.session_usage = super::SessionUsageState::default();
+            app.budget = super::budget::BudgetState::default();
             app.history_retention_stats = super::state::HistoryRetentionStats::default();
             app.cancelled_turn_pending_hint = false;
             app.pending_cancel_origin = None;
-            app.queued_submission = None;
+            super::queue::clear(app);
+            super::search::close(app);
+            super::chat_nav::close(app);
+            app.branches = super::branch::BranchState::default();
             app.cached_header_line = None;
             app.cached_footer_line = None;
             app.update_welcome_model_if_pristine();
             app.sync_welcome_recent_sessions();
-            if !history_updates.is_empty() {
+            let resumed = !history_updates.is_empty();
+            if resumed {
                 load_resume_history(app, &history_updates);
             }
+            super::draft::restore(app, !resumed);
             app.status = AppStatus::Ready;
             app.resuming_session_id = None;
         }
@@ -512,14 +555,14 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
             app.is_compacting = false;
             app.cancelled_turn_pending_hint = false;
             app.pending_cancel_origin = None;
-            app.queued_submission = None;
+            super::queue::clear(app);
         }
         ClientEvent::ConnectionFailed(msg) => {
             app.pending_compact_clear = false;
             app.is_compacting = false;
             app.cancelled_turn_pending_hint = false;
             app.pending_cancel_origin = None;
-            app.queued_submission = None;
+            super::queue::clear(app);
             app.resuming_session_id = None;
             app.input.clear();
             app.pending_submit = false;
@@ -540,19 +583,25 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
             app.viewport.engage_auto_scroll();
             app.status = AppStatus::Ready;
             app.resuming_session_id = None;
+            super::branch::on_transition_failed(app);
         }
         ClientEvent::SessionReplaced { session_id, cwd, model_name, mode, history_updates } => {
             app.pending_compact_clear = false;
             app.is_compacting = false;
             app.pending_cancel_origin = None;
-            app.queued_submission = None;
+            super::queue::clear(app);
+            super::search::close(app);
+            super::chat_nav::close(app);
+            let previous_session_id = app.session_id.as_ref().map(ToString::to_string);
             apply_session_cwd(app, cwd);
             reset_for_new_session(app, session_id, model_name, mode);
             if !history_updates.is_empty() {
                 load_resume_history(app, &history_updates);
             }
+            super::draft::restore(app, false);
             app.status = AppStatus::Ready;
             app.resuming_session_id = None;
+            super::branch::on_session_replaced(app, previous_session_id);
         }
         ClientEvent::UpdateAvailable { latest_version, current_version } => {
             app.update_check_hint = Some(format!(
@@ -565,6 +614,17 @@ pub fn handle_client_event(app: &mut App, event: ClientEvent) {
             app.status = AppStatus::Error;
             app.pending_submit = false;
         }
+        ClientEvent::ShellCommandFinished { tool_call_id, exit_code } => {
+            super::shell::finish(app, &tool_call_id, exit_code);
+        }
+        ClientEvent::FileIndexUpdated => {
+            if super::file_index::pull(app) && app.mention.is_some() {
+                super::mention::update_query(app);
+            }
+        }
+        ClientEvent::Routed { target, event: routed } => {
+            super::tabs::handle_routed_event(app, &target, *routed);
+        }
     }
 }
 
@@ -610,7 +670,7 @@ fn handle_turn_error_event(app: &mut App, msg: &str, classified: Option<TurnErro
             "Turn error suppressed after cancellation request"
         );
         if should_compact_clear {
-            super::slash::clear_conversation_history(app);
+            clear_history_after_compact(app);
         } else {
             mark_turn_exit_assistant_layout_dirty(app, tail_assistant_idx);
         }
@@ -631,6 +691,7 @@ fn handle_turn_error_event(app: &mut App, msg: &str, classified: Option<TurnErro
     let error_class = classified.unwrap_or_else(|| classify_turn_error(msg));
     tracing::error!("Turn error: {msg}");
     let summary = summarize_internal_error(msg);
+    notify::notify(app, NotifyEvent::TurnError, &format!("Turn failed: {summary}"));
     match error_class {
         TurnErrorClass::PlanLimit => {
             tracing::warn!(
@@ -655,7 +716,7 @@ fn handle_turn_error_event(app: &mut App, msg: &str, classified: Option<TurnErro
         TurnErrorClass::Other => {}
     }
     if should_compact_clear {
-        super::slash::clear_conversation_history(app);
+        clear_history_after_compact(app);
     }
     let _ = app.finalize_in_progress_tool_calls(model::ToolCallStatus::Failed);
     app.input.clear();
@@ -738,7 +799,8 @@ fn sync_welcome_cwd(app: &mut App) {
 fn apply_session_cwd(app: &mut App, cwd_raw: String) {
     app.cwd_raw = cwd_raw;
     app.cwd = shorten_cwd_display(&app.cwd_raw);
-    app.file_cache = None;
+    app.file_index.cache = None;
+    super::file_index::start(app);
     app.cached_header_line = None;
     app.cached_footer_line = None;
     app.refresh_git_branch();
@@ -794,6 +856,9 @@ fn update_session_usage(app: &mut App, usage: &model::UsageUpdate) -> MessageUsa
     if let Some(v) = usage.max_output_tokens {
         app.session_usage.max_output_tokens = Some(v);
     }
+    if has_turn_usage_snapshot {
+        app.session_usage.record_context_turn();
+    }
 
     MessageUsage {
         input_tokens: usage.input_tokens,
@@ -888,11 +953,12 @@ fn reset_for_new_session(
     app.pending_compact_clear = false;
     app.is_compacting = false;
     app.session_usage = super::SessionUsageState::default();
+    app.budget = super::budget::BudgetState::default();
     app.should_quit = false;
     app.files_accessed = 0;
     app.cancelled_turn_pending_hint = false;
     app.pending_cancel_origin = None;
-    app.queued_submission = None;
+    super::queue::clear(app);
 
     app.messages.clear();
     app.history_retention_stats = super::state::HistoryRetentionStats::default();
@@ -921,6 +987,7 @@ fn reset_for_new_session(
     app.todo_selected = 0;
     app.focus = super::FocusManager::default();
     app.available_commands.clear();
+    app.subagent_view = None;
 
     app.selection = None;
     app.scrollbar_drag = None;
@@ -930,7 +997,7 @@ fn reset_for_new_session(
     app.rendered_input_area = ratatui::layout::Rect::default();
     app.mention = None;
     app.slash = None;
-    app.file_cache = None;
+    app.file_index.cache = None;
 
     app.cached_todo_compact = None;
     app.cached_header_line = None;
@@ -989,9 +1056,10 @@ fn handle_tool_call(app: &mut App, tc: model::ToolCall) {
     };
     app.register_tool_call_scope(id_str.clone(), scope);
 
-    // Subagent children are never hidden -- they need to be visible so
-    // permission prompts render and the user can interact with them.
-    let hidden = false;
+    // Subagent children render inside their Task's tree instead of the chat flow.
+    // `is_rendered_inline` still surfaces them while a permission prompt is pending.
+    let parent_task_id = super::subagents::resolve_parent_task(app, tc.meta.as_ref(), scope);
+    let hidden = parent_task_id.is_some();
 
     // Extract todos from TodoWrite tool calls
     if sdk_tool_name == "TodoWrite" {
@@ -1040,7 +1108,7 @@ fn handle_tool_call(app: &mut App, tc: model::ToolCall) {
     };
 
     let mut tool_info = ToolCallInfo {
-        id: id_str,
+        id: id_str.clone(),
         title: shorten_tool_title(&tc.title, &app.cwd_raw),
         sdk_tool_name,
         raw_input: tc.raw_input,
@@ -1062,6 +1130,9 @@ fn handle_tool_call(app: &mut App, tc: model::ToolCall) {
         last_measured_layout_generation: 0,
         cache: BlockCache::default(),
         pending_permission: None,
+        parent_task_id,
+        subagent: crate::app::SubagentTree::default(),
+        locations: tc.locations,
     };
     if let Some(output) = initial_execute_output {
         tool_info.terminal_output_len = output.len();
@@ -1104,6 +1175,9 @@ fn handle_tool_call(app: &mut App, tc: model::ToolCall) {
                     existing.raw_input.clone_from(&tool_info.raw_input);
                     changed = true;
                 }
+                if !tool_info.locations.is_empty() {
+                    existing.locations.clone_from(&tool_info.locations);
+                }
                 if changed {
                     existing.mark_tool_call_layout_dirty();
                     layout_dirty = true;
@@ -1132,6 +1206,9 @@ fn handle_tool_call(app: &mut App, tc: model::ToolCall) {
         app.index_tool_call(tc_id, new_idx, 0);
     }
 
+    if hidden {
+        super::subagents::sync_child(app, &id_str);
+    }
     app.status = AppStatus::Running;
     if !hidden {
         app.files_accessed += 1;
@@ -1229,6 +1306,7 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
 
             let mut pending_todos: Option<Vec<super::TodoItem>> = None;
             let mut layout_dirty_idx: Option<usize> = None;
+            let mut completed_at: Option<(usize, usize)> = None;
             if let Some((mi, bi)) = app.lookup_tool_call(&id_str) {
                 if let Some(MessageBlock::ToolCall(tc)) =
                     app.messages.get_mut(mi).and_then(|m| m.blocks.get_mut(bi))
@@ -1240,6 +1318,9 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
                     {
                         tc.status = status;
                         changed = true;
+                        if status == model::ToolCallStatus::Completed {
+                            completed_at = Some((mi, bi));
+                        }
                     }
                     if let Some(title) = &tcu.fields.title {
                         let shortened = shorten_tool_title(title, &app.cwd_raw);
@@ -1278,6 +1359,9 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
                             changed = true;
                         }
                     }
+                    if let Some(locations) = tcu.fields.locations {
+                        tc.locations = locations;
+                    }
                     if let Some(raw_input) = tcu.fields.raw_input.as_ref()
                         && tc.raw_input.as_ref() != Some(raw_input)
                     {
@@ -1346,6 +1430,10 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
             }
             if let Some(mi) = layout_dirty_idx {
                 app.mark_message_layout_dirty(mi);
+                super::subagents::sync_child(app, &id_str);
+            }
+            if let Some((mi, bi)) = completed_at {
+                super::frecency::record_edits(app, mi, bi);
             }
             if let Some(todos) = pending_todos {
                 set_todos(app, todos);
@@ -1395,6 +1483,7 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
             let message_usage = update_session_usage(app, &usage);
             attach_usage_to_latest_assistant_message(app, message_usage);
             app.cached_footer_line = None;
+            super::budget::record_turn_usage(app);
             tracing::debug!(
                 "UsageUpdate: in={:?} out={:?} cache_read={:?} cache_write={:?} total_cost={:?} turn_cost={:?} ctx_window={:?}",
                 usage.input_tokens,
@@ -1406,6 +1495,9 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
                 usage.context_window
             );
         }
+        model::SessionUpdate::TurnUsage(usage) => {
+            super::budget::record_turn_progress(app, usage);
+        }
         model::SessionUpdate::SessionStatusUpdate(status) => {
             // TODO(runtime-verification): confirm in real SDK sessions that compaction
             // status updates are emitted consistently; if not, add a fallback indicator.
@@ -1423,20 +1515,66 @@ fn handle_session_update(app: &mut App, update: model::SessionUpdate) {
                 boundary.trigger,
                 boundary.pre_tokens
             );
-            if matches!(boundary.trigger, model::CompactionTrigger::Auto) {
-                let text = "Auto-compacting context...";
-                app.messages.push(ChatMessage {
-                    role: MessageRole::System,
-                    blocks: vec![MessageBlock::Text(
-                        text.to_owned(),
-                        BlockCache::default(),
-                        IncrementalMarkdown::from_complete(text),
-                    )],
-                    usage: None,
-                });
-                app.viewport.engage_auto_scroll();
-            }
+            push_compaction_block(app, boundary);
+        }
+        model::SessionUpdate::CompactionSummary(summary) => {
+            attach_compaction_summary(app, summary);
+        }
+    }
+}
+
+fn push_compaction_block(app: &mut App, boundary: model::CompactionBoundary) {
+    app.messages.push(ChatMessage {
+        role: MessageRole::System,
+        blocks: vec![MessageBlock::Compaction(CompactionBlock::new(boundary, app.tools_collapsed))],
+        usage: None,
+    });
+    app.viewport.engage_auto_scroll();
+}
+
+/// Fill the newest compaction marker still waiting for its summary. A summary
+/// without a preceding boundary gets a marker of its own.
+fn attach_compaction_summary(app: &mut App, summary: String) {
+    let pending = app.messages.iter().enumerate().rev().find_map(|(mi, msg)| {
+        msg.blocks
+            .iter()
+            .position(|block| matches!(block, MessageBlock::Compaction(c) if c.summary.is_none()))
+            .map(|bi| (mi, bi))
+    });
+    let Some((mi, bi)) = pending else {
+        let boundary = model::CompactionBoundary {
+            trigger: app
+                .session_usage
+                .last_compaction_trigger
+                .unwrap_or(model::CompactionTrigger::Auto),
+            pre_tokens: app.session_usage.last_compaction_pre_tokens.unwrap_or(0),
+        };
+        push_compaction_block(app, boundary);
+        let mi = app.messages.len() - 1;
+        if let Some(MessageBlock::Compaction(block)) = app.messages[mi].blocks.first_mut() {
+            block.summary = Some(summary);
         }
+        return;
+    };
+    if let Some(MessageBlock::Compaction(block)) = app.messages[mi].blocks.get_mut(bi) {
+        block.summary = Some(summary);
+        block.cache.invalidate();
+    }
+    app.mark_message_layout_dirty(mi);
+}
+
+/// Clear history after a manual `/compact`, keeping the newest compaction marker so
+/// the summary the agent continues from stays visible.
+fn clear_history_after_compact(app: &mut App) {
+    let marker = app
+        .messages
+        .iter()
+        .rposition(|msg| msg.blocks.iter().any(|b| matches!(b, MessageBlock::Compaction(_))))
+        .map(|idx| app.messages.remove(idx));
+    super::slash::clear_conversation_history(app);
+    if let Some(marker) = marker {
+        app.messages.push(marker);
+        app.enforce_history_retention();
     }
 }
 
@@ -1557,6 +1695,7 @@ fn append_agent_stream_text(blocks: &mut Vec<MessageBlock>, chunk: &str) {
     crate::perf::mark_with("text_block_frozen_count", "count", text_block_count.saturating_sub(1));
 }
 
+#[allow(clippy::while_let_loop)]
 fn split_tail_text_block(blocks: &mut Vec<MessageBlock>) -> usize {
     let mut split_count = 0usize;
     loop {
@@ -1608,8 +1747,10 @@ fn session_update_name(update: &model::SessionUpdate) -> &'static str {
         model::SessionUpdate::CurrentModeUpdate(_) => "CurrentModeUpdate",
         model::SessionUpdate::ConfigOptionUpdate(_) => "ConfigOptionUpdate",
         model::SessionUpdate::UsageUpdate(_) => "UsageUpdate",
+        model::SessionUpdate::TurnUsage(_) => "TurnUsage",
         model::SessionUpdate::SessionStatusUpdate(_) => "SessionStatusUpdate",
         model::SessionUpdate::CompactionBoundary(_) => "CompactionBoundary",
+        model::SessionUpdate::CompactionSummary(_) => "CompactionSummary",
     }
 }
 
@@ -1652,6 +1793,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         }
     }
 
@@ -2336,7 +2480,7 @@ mod tests {
     fn connected_updates_cwd_and_clears_resuming_marker() {
         let mut app = make_test_app();
         app.messages.push(ChatMessage::welcome("Connecting...", "/test"));
-        app.file_cache = Some(Vec::new());
+        app.file_index.cache = Some(std::sync::Arc::new(Vec::new()));
         app.resuming_session_id = Some("resume-123".into());
 
         handle_client_event(
@@ -2352,7 +2496,7 @@ mod tests {
 
         assert_eq!(app.cwd_raw, "/changed");
         assert_eq!(app.cwd, "/changed");
-        assert!(app.file_cache.is_none());
+        assert!(app.file_index.cache.is_none());
         assert!(app.resuming_session_id.is_none());
         let Some(first) = app.messages.first() else {
             panic!("missing welcome message");
@@ -2778,6 +2922,39 @@ mod tests {
         assert_eq!(app.session_usage.last_compaction_pre_tokens, Some(123_456));
     }
 
+    #[test]
+    fn compaction_summary_fills_marker_and_survives_compact_clear() {
+        let mut app = make_test_app();
+        app.pending_compact_clear = true;
+        app.messages.push(user_msg("/compact focus on the parser"));
+
+        handle_client_event(
+            &mut app,
+            ClientEvent::SessionUpdate(model::SessionUpdate::CompactionBoundary(
+                model::CompactionBoundary {
+                    trigger: model::CompactionTrigger::Manual,
+                    pre_tokens: 90_000,
+                },
+            )),
+        );
+        handle_client_event(
+            &mut app,
+            ClientEvent::SessionUpdate(model::SessionUpdate::CompactionSummary(
+                "Parser rewrite in progress".into(),
+            )),
+        );
+        handle_client_event(&mut app, ClientEvent::TurnComplete);
+
+        assert_eq!(app.messages.len(), 2);
+        assert!(matches!(app.messages[0].role, MessageRole::Welcome));
+        let Some(MessageBlock::Compaction(block)) = app.messages[1].blocks.first() else {
+            panic!("expected compaction block");
+        };
+        assert_eq!(block.trigger, model::CompactionTrigger::Manual);
+        assert_eq!(block.pre_tokens, 90_000);
+        assert_eq!(block.summary.as_deref(), Some("Parser rewrite in progress"));
+    }
+
     #[test]
     fn turn_error_after_cancel_shows_interrupted_hint_instead_of_error_block() {
         let mut app = make_test_app();
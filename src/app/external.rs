@@ -0,0 +1,381 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Handing the terminal to external programs ($EDITOR, $PAGER) and back.
+//!
+//! Programs that need the terminal are not started from key handlers: they
+//! are queued as a [`Suspended`] job that the event loop runs after dropping
+//! its crossterm event stream. While the stream is alive its reader thread
+//! keeps reading the tty and would steal the child's keystrokes.
+
+use super::App;
+use crate::Cli;
+use crossterm::event::{
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const OPEN_COMMAND_ENV: &str = "CLAUDE_RS_OPEN_COMMAND";
+
+/// Editors that accept `+LINE` before the file to open at a line.
+const PLUS_LINE_EDITORS: &[&str] =
+    &["vi", "vim", "nvim", "gvim", "nano", "emacs", "emacsclient", "hx", "kak", "micro", "helix"];
+
+/// Editors that open their own window. They are started in the background
+/// instead of suspending the TUI.
+const GUI_EDITORS: &[&str] = &[
+    "code",
+    "code-insiders",
+    "codium",
+    "cursor",
+    "windsurf",
+    "zed",
+    "subl",
+    "gvim",
+    "gedit",
+    "kate",
+    "idea",
+    "rustrover",
+    "clion",
+    "open",
+    "xdg-open",
+];
+
+/// Enable bracketed paste, mouse capture, focus events and the enhanced
+/// keyboard protocol. Errors are ignored on terminals without support.
+pub(crate) fn enable_input_modes() {
+    let _ = crossterm::execute!(
+        io::stdout(),
+        crossterm::event::EnableBracketedPaste,
+        crossterm::event::EnableMouseCapture,
+        crossterm::event::EnableFocusChange,
+        // Enable enhanced keyboard protocol for reliable modifier detection (e.g. Shift+Enter)
+        PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+        )
+    );
+}
+
+/// Undo [`enable_input_modes`].
+pub(crate) fn disable_input_modes() {
+    let _ = crossterm::execute!(
+        io::stdout(),
+        crossterm::event::DisableBracketedPaste,
+        crossterm::event::DisableMouseCapture,
+        crossterm::event::DisableFocusChange,
+        PopKeyboardEnhancementFlags
+    );
+}
+
+/// Work that needs the terminal to itself, run by the event loop with the
+/// TUI suspended.
+pub type Suspended = Box<dyn FnOnce(&mut App)>;
+
+/// Queue `job` to run with the terminal handed over. A job already queued
+/// in the same loop iteration wins.
+fn suspend(app: &mut App, job: impl FnOnce(&mut App) + 'static) {
+    if app.suspended.is_none() {
+        app.suspended = Some(Box::new(job));
+    }
+}
+
+/// Leave the alternate screen and raw mode, run `job`, then restore the TUI
+/// and schedule a full repaint. Called by the event loop once its event
+/// stream is dropped; the loop is blocked meanwhile.
+pub(crate) fn run_suspended(app: &mut App, job: Suspended) {
+    let _ = crate::ui::graphics::clear_all(&mut io::stdout());
+    disable_input_modes();
+    ratatui::restore();
+
+    job(app);
+
+    if let Err(err) = crossterm::terminal::enable_raw_mode()
+        .and_then(|()| crossterm::execute!(io::stdout(), crossterm::terminal::EnterAlternateScreen))
+    {
+        tracing::warn!("failed to restore the terminal after an external program: {err}");
+    }
+    enable_input_modes();
+    if let Some(title) = app.terminal_title.as_mut() {
+        title.invalidate();
+    }
+    app.force_redraw = true;
+    app.needs_redraw = true;
+}
+
+/// Split a command line from the environment into program and arguments,
+/// with shell quoting rules (`EDITOR="'/opt/My Editor/edit' --wait"`).
+pub(crate) fn split_command(command: &str) -> Option<Vec<String>> {
+    let parts = shell_words::split(command).ok()?;
+    if parts.is_empty() { None } else { Some(parts) }
+}
+
+fn env_command(names: &[&str]) -> Option<Vec<String>> {
+    names.iter().find_map(|name| std::env::var(name).ok().as_deref().and_then(split_command))
+}
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`.
+#[must_use]
+pub(crate) fn editor_command() -> Vec<String> {
+    env_command(&["VISUAL", "EDITOR"]).unwrap_or_else(|| vec!["vi".to_owned()])
+}
+
+/// `$PAGER`, then `less -R`.
+#[must_use]
+fn pager_command() -> Vec<String> {
+    env_command(&["PAGER"]).unwrap_or_else(|| vec!["less".to_owned(), "-R".to_owned()])
+}
+
+/// `--open-command`, then `CLAUDE_RS_OPEN_COMMAND`, split into a template.
+#[must_use]
+pub(crate) fn open_command_from_cli(cli: &Cli) -> Option<Vec<String>> {
+    cli.open_command
+        .clone()
+        .or_else(|| std::env::var(OPEN_COMMAND_ENV).ok())
+        .as_deref()
+        .and_then(split_command)
+}
+
+fn program_name(program: &str) -> &str {
+    Path::new(program).file_stem().and_then(|s| s.to_str()).unwrap_or(program)
+}
+
+/// Arguments opening `path` at `line` for the given editor program.
+fn editor_args(program: &str, path: &Path, line: Option<u32>) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(line) = line.filter(|_| PLUS_LINE_EDITORS.contains(&program_name(program))) {
+        args.push(format!("+{line}"));
+    }
+    args.push(path.to_string_lossy().into_owned());
+    args
+}
+
+/// Fill `{path}` and `{line}` in an open-command template. An unknown line
+/// becomes 1, and the path is appended when the template has no `{path}`.
+fn expand_open_command(template: &[String], path: &Path, line: Option<u32>) -> Vec<String> {
+    let path = path.to_string_lossy();
+    let line = line.unwrap_or(1).to_string();
+    let mut command: Vec<String> =
+        template.iter().map(|arg| arg.replace("{path}", &path).replace("{line}", &line)).collect();
+    if !template.iter().any(|arg| arg.contains("{path}")) {
+        command.push(path.into_owned());
+    }
+    command
+}
+
+fn check_status(program: &str, status: io::Result<ExitStatus>) -> Result<(), String> {
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{program} exited with {status}")),
+        Err(e) => Err(format!("{program}: {e}")),
+    }
+}
+
+/// Open `path` at `line` with `--open-command`, or else `$VISUAL`/`$EDITOR`.
+/// Terminal editors get the terminal until they exit; GUI editors are
+/// started in the background. Failures, now or once the editor exits, go
+/// to `on_error`.
+pub(crate) fn open_in_editor(
+    app: &mut App,
+    path: &Path,
+    line: Option<u32>,
+    on_error: impl FnOnce(&mut App, String) + 'static,
+) {
+    let command = if let Some(template) = &app.open_command {
+        expand_open_command(template, path, line)
+    } else {
+        let mut command = editor_command();
+        let args = editor_args(&command[0], path, line);
+        command.extend(args);
+        command
+    };
+    let Some((program, args)) = command.split_first() else {
+        on_error(app, "no editor configured".to_owned());
+        return;
+    };
+    let program = program.clone();
+    let mut cmd = Command::new(&program);
+    cmd.args(args).current_dir(&app.cwd_raw);
+    if GUI_EDITORS.contains(&program_name(&program)) {
+        cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        // tokio reaps the child once it exits.
+        if let Err(e) = tokio::process::Command::from(cmd).spawn() {
+            on_error(app, format!("{program}: {e}"));
+        }
+        return;
+    }
+    suspend(app, move |app| {
+        if let Err(message) = check_status(&program, cmd.status()) {
+            on_error(app, message);
+        }
+    });
+}
+
+/// Show `text` in the user's pager, suspending the TUI until it exits.
+pub(crate) fn page_text(
+    app: &mut App,
+    text: String,
+    on_error: impl FnOnce(&mut App, String) + 'static,
+) {
+    let command = pager_command();
+    let Some((program, args)) = command.split_first() else {
+        on_error(app, "no pager configured".to_owned());
+        return;
+    };
+    let program = program.clone();
+    let mut cmd = Command::new(&program);
+    cmd.args(args).stdin(Stdio::piped());
+    suspend(app, move |app| {
+        let result = cmd.spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may quit before reading everything.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()
+        });
+        // Pagers exit non-zero when quit early; only spawn failures count.
+        if let Err(e) = result {
+            on_error(app, format!("{program}: {e}"));
+        }
+    });
+}
+
+/// Create an empty, uniquely named temp file for an editor session.
+fn create_temp_file(text: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.subsec_nanos());
+    let name = format!("claude-rs-prompt-{}-{nanos}.md", std::process::id());
+    let path = std::env::temp_dir().join(name);
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(path)
+}
+
+/// Edit `text` with `command` through a temp file and return what was
+/// saved. The temp file is removed afterwards.
+fn edit_with(command: &[String], cwd: &str, text: &str) -> Result<String, String> {
+    let (program, args) = command.split_first().ok_or("no editor configured")?;
+    let path = create_temp_file(text).map_err(|e| format!("temp file: {e}"))?;
+    let status = Command::new(program).args(args).arg(&path).current_dir(cwd).status();
+    let result = check_status(program, status)
+        .and_then(|()| fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display())));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// Ctrl+G: compose the prompt in `$VISUAL`/`$EDITOR`. Paste placeholders
+/// are expanded for editing and collapsed again when the text comes back.
+pub(crate) fn compose_input(app: &mut App) {
+    suspend(app, |app| {
+        let original = app.input.text();
+        match edit_with(&editor_command(), &app.cwd_raw, &original) {
+            Ok(edited) => {
+                let edited = edited.replace("\r\n", "\n");
+                let edited = super::input::trim_trailing_line_breaks(&edited);
+                if edited != original {
+                    app.input.load_expanded_text(edited);
+                    super::mention::deactivate(app);
+                    super::slash::deactivate(app);
+                }
+            }
+            Err(message) => {
+                super::slash::push_system_message(app, format!("Could not open editor: {message}"));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_command_keeps_arguments() {
+        assert_eq!(
+            split_command("code  --wait"),
+            Some(vec!["code".to_owned(), "--wait".to_owned()])
+        );
+        assert_eq!(split_command("   "), None);
+        assert_eq!(
+            split_command("'/opt/My Editor/edit' --wait \"a b\""),
+            Some(vec!["/opt/My Editor/edit".to_owned(), "--wait".to_owned(), "a b".to_owned()])
+        );
+        assert_eq!(split_command("vim 'unterminated"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_round_trip_returns_the_saved_text() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cwd = dir.path().to_string_lossy();
+        // A scripted "editor" that appends to the file it is given.
+        let editor =
+            split_command(r#"sh -c 'printf " and more" >> "$1"' editor"#).expect("command");
+        assert_eq!(edit_with(&editor, &cwd, "draft"), Ok("draft and more".to_owned()));
+
+        let failing = split_command("sh -c 'exit 3' editor").expect("command");
+        assert!(edit_with(&failing, &cwd, "draft").is_err_and(|e| e.contains("exited with")));
+    }
+
+    #[test]
+    fn compose_input_waits_for_the_event_loop() {
+        let mut app = App::test_default();
+        app.input.set_text("draft");
+        compose_input(&mut app);
+        assert!(app.suspended.is_some());
+        assert_eq!(app.input.text(), "draft");
+    }
+
+    #[test]
+    fn temp_files_are_unique_and_hold_the_text() {
+        let a = create_temp_file("draft").expect("temp file");
+        let b = create_temp_file("").expect("temp file");
+        assert_ne!(a, b);
+        assert_eq!(fs::read_to_string(&a).expect("read"), "draft");
+        let _ = fs::remove_file(a);
+        let _ = fs::remove_file(b);
+    }
+
+    #[test]
+    fn editor_args_only_pass_line_to_known_editors() {
+        let path = Path::new("src/main.rs");
+        assert_eq!(editor_args("/usr/bin/nvim", path, Some(12)), vec!["+12", "src/main.rs"]);
+        assert_eq!(editor_args("code", path, Some(12)), vec!["src/main.rs"]);
+        assert_eq!(editor_args("vim", path, None), vec!["src/main.rs"]);
+    }
+
+    #[test]
+    fn open_command_fills_path_and_line() {
+        let template = |t: &str| split_command(t).expect("template");
+        let path = Path::new("src/app/mod.rs");
+        assert_eq!(
+            expand_open_command(&template("nvim +{line} {path}"), path, Some(42)),
+            vec!["nvim", "+42", "src/app/mod.rs"]
+        );
+        assert_eq!(
+            expand_open_command(&template("code -g {path}:{line}"), path, None),
+            vec!["code", "-g", "src/app/mod.rs:1"]
+        );
+        assert_eq!(
+            expand_open_command(&template("subl"), path, Some(3)),
+            vec!["subl", "src/app/mod.rs"]
+        );
+    }
+}
@@ -0,0 +1,589 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Live file index for @-mention completion.
+//!
+//! A worker thread scans the project once, then keeps the index current from
+//! filesystem events. Every indexed directory gets a non-recursive watch, so
+//! ignored trees (`target/`, `node_modules/`) cost nothing, and a changed
+//! directory is re-listed through the same `WalkBuilder` rules as the scan.
+//! The UI thread only swaps in finished snapshots, announced by
+//! `ClientEvent::FileIndexUpdated`.
+//!
+//! Cost is bounded: the index stops at [`MAX_ENTRIES`], and bursts touching
+//! many directories (checkouts, builds) fall back to one rescan. Watches come
+//! from the per-user inotify budget shared with editors and other tools, so at
+//! most [`MAX_WATCHED_DIRS`] are taken; larger projects (or a failed watch)
+//! are rescanned every [`RESCAN_INTERVAL`] instead.
+
+use super::App;
+use super::mention::FileCandidate;
+use crate::agent::events::ClientEvent;
+use ignore::WalkBuilder;
+use notify::{EventKind, RecursiveMode, Watcher as _};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Files and directories kept in the index.
+const MAX_ENTRIES: usize = 200_000;
+/// Directories watched for changes; the rest are refreshed by rescans.
+const MAX_WATCHED_DIRS: usize = 1_024;
+/// Rescan period while some indexed directories are not watched.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+/// Directories re-listed one by one per batch before a full rescan is cheaper.
+const MAX_INCREMENTAL_DIRS: usize = 64;
+/// Quiet period that ends a batch of filesystem events.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// Longest a continuous stream of events can delay an update.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    is_dir: bool,
+    modified: SystemTime,
+}
+
+/// Project-relative paths (forward slashes, no trailing `/`) to entries.
+#[derive(Debug, Default)]
+struct Tree {
+    entries: BTreeMap<String, Entry>,
+}
+
+fn walker(path: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(path);
+    builder
+        .hidden(false) // include dotfiles like .github/, .gitignore
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true);
+    builder
+}
+
+fn rel_string(root: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+    if rel.is_empty() { None } else { Some(rel) }
+}
+
+fn is_git_internal(rel: &str) -> bool {
+    rel == ".git" || rel.starts_with(".git/")
+}
+
+/// Direct children of `dir` (`""` is the root) have this prefix and no
+/// further `/`.
+fn child_prefix(dir: &str) -> String {
+    if dir.is_empty() { String::new() } else { format!("{dir}/") }
+}
+
+impl Tree {
+    /// Walk `start` (absolute) up to `max_depth`, adding entries until the
+    /// index is full.
+    fn walk(&mut self, root: &Path, start: &Path, max_depth: Option<usize>) {
+        for entry in walker(start).max_depth(max_depth).build().flatten() {
+            if self.entries.len() >= MAX_ENTRIES {
+                tracing::debug!("file index full at {MAX_ENTRIES} entries");
+                return;
+            }
+            let Some(file_type) = entry.file_type() else {
+                continue;
+            };
+            if !file_type.is_file() && !file_type.is_dir() {
+                continue;
+            }
+            let Some(rel) = rel_string(root, entry.path()) else {
+                continue; // the walk root itself
+            };
+            if is_git_internal(&rel) {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            self.entries.insert(rel, Entry { is_dir: file_type.is_dir(), modified });
+        }
+    }
+
+    fn scan(root: &Path) -> Self {
+        let mut tree = Self::default();
+        tree.walk(root, root, None);
+        tree
+    }
+
+    fn is_dir(&self, rel: &str) -> bool {
+        rel.is_empty() || self.entries.get(rel).is_some_and(|e| e.is_dir)
+    }
+
+    fn remove_subtree(&mut self, rel: &str) {
+        self.entries.remove(rel);
+        let prefix = format!("{rel}/");
+        let nested: Vec<String> = self
+            .entries
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in nested {
+            self.entries.remove(&key);
+        }
+    }
+
+    /// Re-read the direct children of the indexed directory `dir`. New
+    /// subdirectories are scanned whole; vanished ones drop their subtree.
+    fn relist(&mut self, root: &Path, dir: &str) {
+        if !self.is_dir(dir) {
+            return; // unknown, removed or ignored
+        }
+        let prefix = child_prefix(dir);
+        let old: Vec<(String, bool)> = self
+            .entries
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .filter(|(k, _)| !k[prefix.len()..].contains('/'))
+            .map(|(k, e)| (k.clone(), e.is_dir))
+            .collect();
+
+        let mut listed = Self::default();
+        listed.walk(root, &root.join(dir), Some(1));
+
+        for (rel, was_dir) in &old {
+            let now_dir = listed.entries.get(rel).map(|e| e.is_dir);
+            if now_dir != Some(*was_dir) {
+                self.remove_subtree(rel);
+            }
+        }
+        for (rel, entry) in listed.entries {
+            let known = self.entries.contains_key(&rel);
+            let is_dir = entry.is_dir;
+            self.entries.insert(rel.clone(), entry);
+            if is_dir && !known {
+                self.walk(root, &root.join(&rel), None);
+            }
+        }
+    }
+
+    /// Candidates sorted like the mention dropdown expects: depth ascending,
+    /// directories first, then most recently modified.
+    fn candidates(&self) -> Vec<FileCandidate> {
+        let mut candidates: Vec<FileCandidate> = self
+            .entries
+            .iter()
+            .map(|(rel, entry)| FileCandidate {
+                rel_path: if entry.is_dir { format!("{rel}/") } else { rel.clone() },
+                depth: rel.matches('/').count(),
+                modified: entry.modified,
+                is_dir: entry.is_dir,
+                matched: Vec::new(),
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.depth
+                .cmp(&b.depth)
+                .then_with(|| b.is_dir.cmp(&a.is_dir))
+                .then_with(|| b.modified.cmp(&a.modified))
+        });
+        candidates
+    }
+}
+
+/// Scan all files and directories under `cwd` on the calling thread.
+#[must_use]
+pub fn scan(cwd: &str) -> Vec<FileCandidate> {
+    Tree::scan(Path::new(cwd)).candidates()
+}
+
+enum Command {
+    Fs(notify::Result<notify::Event>),
+    Rescan,
+    Stop,
+}
+
+#[derive(Default)]
+struct Shared {
+    snapshot: Mutex<Option<Arc<Vec<FileCandidate>>>>,
+    version: AtomicU64,
+    /// Every indexed directory is watched, so the snapshot cannot go stale.
+    fully_watched: AtomicBool,
+}
+
+struct Worker {
+    root: String,
+    shared: Arc<Shared>,
+    commands: mpsc::Sender<Command>,
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Stop);
+    }
+}
+
+#[derive(Default)]
+pub struct FileIndex {
+    /// Keep the index current in the background. Off (the default, used by
+    /// tests) rescans synchronously whenever completion opens.
+    live: bool,
+    worker: Option<Worker>,
+    /// Latest file list from cwd (scanned on first `@` trigger).
+    pub cache: Option<Arc<Vec<FileCandidate>>>,
+    /// Snapshot version last copied into `cache`.
+    pulled: u64,
+    /// Symbol definitions for `@Type::method` mentions, parsed from `cache`.
+    pub tags: super::tags::TagIndex,
+}
+
+impl FileIndex {
+    #[must_use]
+    pub fn live() -> Self {
+        Self { live: true, tags: super::tags::TagIndex::live(), ..Self::default() }
+    }
+
+    /// Bumped whenever `cache` gets a new snapshot, so caches derived from
+    /// the project's files know to start over.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.pulled
+    }
+}
+
+/// Start (or restart, after a cwd change) the background index for the
+/// session cwd. The first scan runs on the worker thread.
+pub(super) fn start(app: &mut App) {
+    let index = &mut app.file_index;
+    if !index.live || index.worker.as_ref().is_some_and(|w| w.root == app.cwd_raw) {
+        return;
+    }
+    let shared = Arc::new(Shared::default());
+    let (commands, receiver) = mpsc::channel();
+    let root = PathBuf::from(&app.cwd_raw);
+    let thread_shared = Arc::clone(&shared);
+    let watch_commands = commands.clone();
+    let events = app.event_tx.clone();
+    let spawned = std::thread::Builder::new().name("file-index".to_owned()).spawn(move || {
+        run(&root, &thread_shared, watch_commands, &receiver, &events);
+    });
+    if let Err(err) = spawned {
+        tracing::warn!("file index thread failed to start: {err}");
+        return;
+    }
+    index.worker = Some(Worker { root: app.cwd_raw.clone(), shared, commands });
+    index.pulled = 0;
+}
+
+/// Copy a newer snapshot into `cache`. Returns true when it changed.
+pub(super) fn pull(app: &mut App) -> bool {
+    let Some(worker) = app.file_index.worker.as_ref() else {
+        return false;
+    };
+    let version = worker.shared.version.load(Ordering::Acquire);
+    if version == app.file_index.pulled && app.file_index.cache.is_some() {
+        return false;
+    }
+    let snapshot = worker.shared.snapshot.lock().ok().and_then(|s| s.clone());
+    app.file_index.pulled = version;
+    app.file_index.cache = snapshot;
+    true
+}
+
+/// Completion opened: make sure candidates are available and current.
+pub(super) fn refresh(app: &mut App) {
+    if !app.file_index.live {
+        app.file_index.cache = Some(Arc::new(scan(&app.cwd_raw)));
+        app.file_index.pulled += 1;
+        return;
+    }
+    start(app);
+    pull(app);
+    if let Some(worker) = app.file_index.worker.as_ref()
+        && worker.shared.version.load(Ordering::Acquire) > 0
+        && !worker.shared.fully_watched.load(Ordering::Acquire)
+    {
+        let _ = worker.commands.send(Command::Rescan);
+    }
+}
+
+/// Directories to re-list for one filesystem event.
+fn affected_dirs(root: &Path, event: &notify::Event, dirs: &mut HashSet<String>) {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    for path in &event.paths {
+        let Some(rel) = rel_string(root, path) else {
+            continue;
+        };
+        if is_git_internal(&rel) {
+            continue;
+        }
+        let parent = rel.rsplit_once('/').map_or("", |(parent, _)| parent);
+        dirs.insert(parent.to_owned());
+    }
+}
+
+fn publish(tree: &Tree, shared: &Shared, events: &UnboundedSender<ClientEvent>) {
+    let candidates = Arc::new(tree.candidates());
+    if let Ok(mut snapshot) = shared.snapshot.lock() {
+        *snapshot = Some(candidates);
+    }
+    shared.version.fetch_add(1, Ordering::AcqRel);
+    let _ = events.send(ClientEvent::FileIndexUpdated);
+}
+
+/// Watch every indexed directory (up to the cap, shallowest first so the
+/// top-level directories are always covered) and drop watches of directories
+/// that left the index or fell past the cap.
+fn sync_watches(
+    watcher: &mut notify::RecommendedWatcher,
+    root: &Path,
+    tree: &Tree,
+    watched_dirs: &mut HashSet<String>,
+) -> bool {
+    let mut wanted: Vec<&str> =
+        tree.entries.iter().filter(|(_, e)| e.is_dir).map(|(k, _)| k.as_str()).collect();
+    // Stable sort: paths stay in name order within one depth.
+    wanted.sort_by_key(|dir| dir.matches('/').count());
+    wanted.insert(0, "");
+    let complete = wanted.len() <= MAX_WATCHED_DIRS;
+    wanted.truncate(MAX_WATCHED_DIRS);
+    let keep: HashSet<&str> = wanted.iter().copied().collect();
+    watched_dirs.retain(|dir| {
+        let stays = keep.contains(dir.as_str());
+        if !stays {
+            let _ = watcher.unwatch(&root.join(dir));
+        }
+        stays
+    });
+    for dir in wanted {
+        if watched_dirs.contains(dir) {
+            continue;
+        }
+        match watcher.watch(&root.join(dir), RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                watched_dirs.insert(dir.to_owned());
+            }
+            Err(err) => {
+                tracing::debug!("file index cannot watch {dir:?}: {err}");
+                return false;
+            }
+        }
+    }
+    complete
+}
+
+/// Worker loop: scan, then apply debounced batches of filesystem events.
+fn run(
+    root: &Path,
+    shared: &Shared,
+    watch_commands: mpsc::Sender<Command>,
+    commands: &mpsc::Receiver<Command>,
+    events: &UnboundedSender<ClientEvent>,
+) {
+    let mut tree = Tree::scan(root);
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = watch_commands.send(Command::Fs(event));
+    })
+    .map_err(|err| tracing::warn!("file watching unavailable: {err}"))
+    .ok();
+    let mut watched_dirs = HashSet::new();
+    let watch = |watcher: &mut Option<notify::RecommendedWatcher>,
+                 tree: &Tree,
+                 watched_dirs: &mut HashSet<String>| {
+        let complete = watcher.as_mut().is_some_and(|w| sync_watches(w, root, tree, watched_dirs));
+        shared.fully_watched.store(complete, Ordering::Release);
+        complete
+    };
+    let mut complete = watch(&mut watcher, &tree, &mut watched_dirs);
+    publish(&tree, shared, events);
+
+    loop {
+        // Unwatched directories only change the index through rescans.
+        let first = if complete {
+            commands.recv().ok()
+        } else {
+            match commands.recv_timeout(RESCAN_INTERVAL) {
+                Ok(command) => Some(command),
+                Err(mpsc::RecvTimeoutError::Timeout) => Some(Command::Rescan),
+                Err(mpsc::RecvTimeoutError::Disconnected) => None,
+            }
+        };
+        let Some(first) = first else {
+            return;
+        };
+        let started = Instant::now();
+        let mut dirs = HashSet::new();
+        let mut rescan = false;
+        let mut next = Some(first);
+        while let Some(command) = next.take() {
+            match command {
+                Command::Stop => return,
+                Command::Rescan => rescan = true,
+                Command::Fs(Ok(event)) => affected_dirs(root, &event, &mut dirs),
+                Command::Fs(Err(err)) => {
+                    tracing::debug!("file watcher error: {err}");
+                    rescan = true;
+                }
+            }
+            let remaining = MAX_BATCH_DELAY.saturating_sub(started.elapsed());
+            if !remaining.is_zero() {
+                next = commands.recv_timeout(DEBOUNCE.min(remaining)).ok();
+            }
+        }
+        if rescan || dirs.len() > MAX_INCREMENTAL_DIRS {
+            let scanned = Tree::scan(root);
+            if scanned.entries == tree.entries {
+                continue;
+            }
+            tree = scanned;
+        } else if dirs.is_empty() {
+            continue;
+        } else {
+            for dir in &dirs {
+                tree.relist(root, dir);
+            }
+        }
+        complete = watch(&mut watcher, &tree, &mut watched_dirs);
+        publish(&tree, shared, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str) {
+        let path = root.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("mkdir");
+        }
+        std::fs::write(path, "").expect("write");
+    }
+
+    fn paths(tree: &Tree) -> Vec<&str> {
+        tree.entries.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn relist_tracks_new_and_removed_entries_and_respects_gitignore() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        std::fs::create_dir(root.join(".git")).expect("git dir");
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").expect("gitignore");
+        write(root, "src/main.rs");
+        let mut tree = Tree::scan(root);
+        assert_eq!(paths(&tree), vec![".gitignore", "src", "src/main.rs"]);
+
+        write(root, "src/app/state.rs");
+        write(root, "src/debug.log");
+        write(root, "target/debug/out.rs");
+        std::fs::remove_file(root.join("src/main.rs")).expect("remove");
+        tree.relist(root, "src");
+        tree.relist(root, "");
+        assert_eq!(paths(&tree), vec![".gitignore", "src", "src/app", "src/app/state.rs"]);
+
+        // Ignored or unknown directories are never re-listed.
+        tree.relist(root, "target");
+        assert!(!tree.entries.contains_key("target/debug/out.rs"));
+
+        std::fs::remove_dir_all(root.join("src/app")).expect("remove dir");
+        tree.relist(root, "src");
+        assert_eq!(paths(&tree), vec![".gitignore", "src"]);
+    }
+
+    #[test]
+    fn affected_dirs_are_parents_outside_git_internals() {
+        let root = Path::new("/repo");
+        let event = notify::Event::new(EventKind::Create(notify::event::CreateKind::File))
+            .add_path(PathBuf::from("/repo/src/new.rs"))
+            .add_path(PathBuf::from("/repo/top.rs"))
+            .add_path(PathBuf::from("/repo/.git/index"));
+        let mut dirs = HashSet::new();
+        affected_dirs(root, &event, &mut dirs);
+        let mut dirs: Vec<_> = dirs.into_iter().collect();
+        dirs.sort();
+        assert_eq!(dirs, vec!["", "src"]);
+    }
+
+    #[test]
+    fn watches_stop_at_the_cap() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        for i in 0..MAX_WATCHED_DIRS {
+            std::fs::create_dir(root.join(format!("d{i}"))).expect("mkdir");
+        }
+        let tree = Tree::scan(root);
+        let Ok(mut watcher) = notify::recommended_watcher(|_| {}) else {
+            return;
+        };
+        let mut watched_dirs = HashSet::new();
+        assert!(!sync_watches(&mut watcher, root, &tree, &mut watched_dirs), "root plus every dir");
+        assert!(watched_dirs.len() <= MAX_WATCHED_DIRS);
+    }
+
+    #[test]
+    fn watches_prefer_shallow_directories() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        for i in 0..MAX_WATCHED_DIRS {
+            std::fs::create_dir_all(root.join(format!("a/d{i}"))).expect("mkdir");
+        }
+        std::fs::create_dir(root.join("z")).expect("mkdir");
+        let tree = Tree::scan(root);
+        let Ok(mut watcher) = notify::recommended_watcher(|_| {}) else {
+            return;
+        };
+        let mut watched_dirs = HashSet::new();
+        assert!(!sync_watches(&mut watcher, root, &tree, &mut watched_dirs));
+        for top in ["", "a", "z"] {
+            assert!(watched_dirs.contains(top), "{top:?} is watched");
+        }
+        assert_eq!(watched_dirs.len(), MAX_WATCHED_DIRS);
+    }
+
+    #[test]
+    fn live_index_picks_up_created_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write(dir.path(), "src/main.rs");
+        let mut app = App::test_default();
+        app.cwd_raw = dir.path().to_string_lossy().into_owned();
+        app.file_index = FileIndex::live();
+
+        let wait_for = |app: &mut App, rel: &str| {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while Instant::now() < deadline {
+                if let Ok(ClientEvent::FileIndexUpdated) = app.event_rx.try_recv() {
+                    pull(app);
+                }
+                if app
+                    .file_index
+                    .cache
+                    .as_ref()
+                    .is_some_and(|c| c.iter().any(|f| f.rel_path == rel))
+                {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            false
+        };
+        start(&mut app);
+        assert!(wait_for(&mut app, "src/main.rs"), "initial scan");
+        write(dir.path(), "src/created.rs");
+        assert!(wait_for(&mut app, "src/created.rs"), "watch update");
+    }
+}
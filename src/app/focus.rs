@@ -18,6 +18,10 @@
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusTarget {
     TodoList,
+    Queue,
+    Attachments,
+    Search,
+    ChatNav,
     Mention,
     Permission,
     Help,
@@ -28,6 +32,10 @@ pub enum FocusTarget {
 pub enum FocusOwner {
     Input,
     TodoList,
+    Queue,
+    Attachments,
+    Search,
+    ChatNav,
     Mention,
     Permission,
     Help,
@@ -40,6 +48,10 @@ pub struct FocusContext {
     pub mention_active: bool,
     pub permission_active: bool,
     pub help_active: bool,
+    pub queue_focus_available: bool,
+    pub attachments_focus_available: bool,
+    pub search_active: bool,
+    pub chat_nav_active: bool,
 }
 
 impl FocusContext {
@@ -49,7 +61,16 @@ impl FocusContext {
         mention_active: bool,
         permission_active: bool,
     ) -> Self {
-        Self { todo_focus_available, mention_active, permission_active, help_active: false }
+        Self {
+            todo_focus_available,
+            mention_active,
+            permission_active,
+            help_active: false,
+            queue_focus_available: false,
+            attachments_focus_available: false,
+            search_active: false,
+            chat_nav_active: false,
+        }
     }
 
     #[must_use]
@@ -60,13 +81,50 @@ impl FocusContext {
         permission_active: bool,
         help_active: bool,
     ) -> Self {
-        Self { todo_focus_available, mention_active, permission_active, help_active }
+        Self {
+            todo_focus_available,
+            mention_active,
+            permission_active,
+            help_active,
+            queue_focus_available: false,
+            attachments_focus_available: false,
+            search_active: false,
+            chat_nav_active: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_queue(mut self, queue_focus_available: bool) -> Self {
+        self.queue_focus_available = queue_focus_available;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_attachments(mut self, attachments_focus_available: bool) -> Self {
+        self.attachments_focus_available = attachments_focus_available;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_search(mut self, search_active: bool) -> Self {
+        self.search_active = search_active;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_chat_nav(mut self, chat_nav_active: bool) -> Self {
+        self.chat_nav_active = chat_nav_active;
+        self
     }
 
     #[must_use]
     pub const fn supports(self, target: FocusTarget) -> bool {
         match target {
             FocusTarget::TodoList => self.todo_focus_available,
+            FocusTarget::Queue => self.queue_focus_available,
+            FocusTarget::Attachments => self.attachments_focus_available,
+            FocusTarget::Search => self.search_active,
+            FocusTarget::ChatNav => self.chat_nav_active,
             FocusTarget::Mention => self.mention_active,
             FocusTarget::Permission => self.permission_active,
             FocusTarget::Help => self.help_active,
@@ -78,6 +136,10 @@ impl From<FocusTarget> for FocusOwner {
     fn from(value: FocusTarget) -> Self {
         match value {
             FocusTarget::TodoList => Self::TodoList,
+            FocusTarget::Queue => Self::Queue,
+            FocusTarget::Attachments => Self::Attachments,
+            FocusTarget::Search => Self::Search,
+            FocusTarget::ChatNav => Self::ChatNav,
             FocusTarget::Mention => Self::Mention,
             FocusTarget::Permission => Self::Permission,
             FocusTarget::Help => Self::Help,
@@ -164,4 +226,14 @@ mod tests {
         mgr.claim(FocusTarget::Help, ctx);
         assert_eq!(mgr.owner(ctx), FocusOwner::Help);
     }
+
+    #[test]
+    fn chat_nav_claim_is_dropped_when_navigation_ends() {
+        let mut mgr = FocusManager::default();
+        let active = FocusContext::new(false, false, false).with_chat_nav(true);
+        mgr.claim(FocusTarget::ChatNav, active);
+        assert_eq!(mgr.owner(active), FocusOwner::ChatNav);
+        mgr.normalize(FocusContext::new(false, false, false));
+        assert_eq!(mgr.owner(active), FocusOwner::Input);
+    }
 }
@@ -0,0 +1,346 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-project file frecency for @-mention ranking.
+//!
+//! Files mentioned in a sent prompt or edited by the agent are counted with
+//! their last use. The stats are kept per project in one JSON file under the
+//! platform data dir. New uses collect in memory and are flushed a few seconds
+//! later (and on exit), merged into a fresh read of the file so parallel
+//! instances add up instead of overwriting each other.
+
+use super::{App, MessageBlock};
+use crate::agent::model;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const FRECENCY_DIR_NAME: &str = "claude-code-rust";
+const FRECENCY_FILE: &str = "mention-frecency.json";
+
+/// Files remembered per project; the lowest ranked are dropped beyond this.
+const MAX_FILES_PER_PROJECT: usize = 500;
+
+/// Projects remembered at all; the least recently used are dropped beyond this.
+const MAX_PROJECTS: usize = 200;
+
+/// Projects without any use for this long are forgotten.
+const PROJECT_TTL: u64 = 180 * DAY;
+
+/// Quiet time after a use before the stats file is rewritten.
+const FLUSH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Uses counted towards the boost, so one hot file cannot bury every match.
+const MAX_COUNTED_USES: u32 = 12;
+
+/// Upper bound of [`Frecency::boost`], below a good fuzzy match on its own.
+const MAX_BOOST: i32 = 96;
+
+const HOUR: u64 = 3_600;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileUse {
+    pub count: u32,
+    /// Unix seconds of the last mention or edit.
+    pub last_used: u64,
+}
+
+impl FileUse {
+    fn score(self, now: u64) -> i32 {
+        let age = now.saturating_sub(self.last_used);
+        let weight = match age {
+            a if a < HOUR => 8,
+            a if a < DAY => 4,
+            a if a < WEEK => 2,
+            _ => 1,
+        };
+        let uses = i32::try_from(self.count.min(MAX_COUNTED_USES)).unwrap_or(0);
+        (uses * weight).min(MAX_BOOST)
+    }
+}
+
+type ProjectUses = BTreeMap<String, FileUse>;
+
+#[derive(Debug, Default)]
+pub struct Frecency {
+    /// Stats file; `None` keeps everything in memory (tests, no data dir).
+    path: Option<PathBuf>,
+    /// Project root -> relative path (forward slashes) -> use.
+    projects: BTreeMap<String, ProjectUses>,
+    /// Uses recorded since the last flush, merged into the file on flush.
+    pending: BTreeMap<String, ProjectUses>,
+    /// When the oldest unflushed use was recorded.
+    dirty_since: Option<Instant>,
+}
+
+/// Default stats location under the platform data dir.
+#[must_use]
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join(FRECENCY_DIR_NAME).join(FRECENCY_FILE))
+}
+
+fn unix_now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn read_projects(path: &Path) -> BTreeMap<String, ProjectUses> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+impl Frecency {
+    #[must_use]
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let projects = path.as_deref().map(read_projects).unwrap_or_default();
+        Self { path, projects, pending: BTreeMap::new(), dirty_since: None }
+    }
+
+    /// Ranking boost for `rel_path` in `project`, 0 for unknown files.
+    #[must_use]
+    pub fn boost(&self, project: &str, rel_path: &str, now: u64) -> i32 {
+        self.projects
+            .get(project)
+            .and_then(|files| files.get(rel_path))
+            .map_or(0, |file_use| file_use.score(now))
+    }
+
+    /// Count one use of each path. The stats file is only written by
+    /// [`Frecency::flush`].
+    pub fn record<I>(&mut self, project: &str, rel_paths: I, now: u64)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut rel_paths = rel_paths.into_iter().peekable();
+        if rel_paths.peek().is_none() {
+            return;
+        }
+        let files = self.projects.entry(project.to_owned()).or_default();
+        let pending = self.pending.entry(project.to_owned()).or_default();
+        for rel_path in rel_paths {
+            for uses in [&mut *files, &mut *pending] {
+                let file_use =
+                    uses.entry(rel_path.clone()).or_insert(FileUse { count: 0, last_used: 0 });
+                file_use.count = file_use.count.saturating_add(1);
+                file_use.last_used = now;
+            }
+        }
+        self.dirty_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether unflushed uses have waited out the debounce.
+    fn flush_due(&self, now: Instant) -> bool {
+        self.dirty_since.is_some_and(|since| now.duration_since(since) >= FLUSH_DEBOUNCE)
+    }
+
+    /// Merge the uses recorded since the last flush into a fresh read of the
+    /// stats file, prune it and write it back. Failures are logged, never
+    /// surfaced.
+    pub fn flush(&mut self, now: u64) {
+        if self.pending.is_empty() {
+            return;
+        }
+        // Pick up other instances' updates before merging ours.
+        if let Some(path) = self.path.as_deref() {
+            self.projects = read_projects(path);
+            for (project, uses) in std::mem::take(&mut self.pending) {
+                let files = self.projects.entry(project).or_default();
+                for (rel_path, new) in uses {
+                    let file_use =
+                        files.entry(rel_path).or_insert(FileUse { count: 0, last_used: 0 });
+                    file_use.count = file_use.count.saturating_add(new.count);
+                    file_use.last_used = file_use.last_used.max(new.last_used);
+                }
+            }
+        }
+        self.pending.clear();
+        self.dirty_since = None;
+        prune(&mut self.projects, now);
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string(&self.projects).map_err(std::io::Error::other)?;
+            // Write then rename so a crash never leaves a torn file behind.
+            let tmp = path.with_extension(format!("json.{}", std::process::id()));
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(&tmp, path)
+        })();
+        if let Err(err) = result {
+            tracing::warn!("mention frecency save failed ({}): {err}", path.display());
+        }
+    }
+}
+
+/// Drop stale and surplus projects, then the lowest ranked files of each.
+fn prune(projects: &mut BTreeMap<String, ProjectUses>, now: u64) {
+    let last_use = |files: &ProjectUses| files.values().map(|u| u.last_used).max().unwrap_or(0);
+    projects.retain(|_, files| now.saturating_sub(last_use(files)) < PROJECT_TTL);
+    if projects.len() > MAX_PROJECTS {
+        let mut ranked: Vec<(u64, String)> =
+            projects.iter().map(|(project, files)| (last_use(files), project.clone())).collect();
+        ranked.sort_unstable_by_key(|(last_used, _)| std::cmp::Reverse(*last_used));
+        for (_, project) in ranked.into_iter().skip(MAX_PROJECTS) {
+            projects.remove(&project);
+        }
+    }
+    for files in projects.values_mut() {
+        if files.len() <= MAX_FILES_PER_PROJECT {
+            continue;
+        }
+        let mut ranked: Vec<(i32, u64, String)> = files
+            .iter()
+            .map(|(rel, file_use)| (file_use.score(now), file_use.last_used, rel.clone()))
+            .collect();
+        ranked.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        for (_, _, rel) in ranked.into_iter().skip(MAX_FILES_PER_PROJECT) {
+            files.remove(&rel);
+        }
+    }
+}
+
+/// Flush recorded uses once they have been quiet for [`FLUSH_DEBOUNCE`].
+pub(super) fn autosave(app: &mut App, now: Instant) {
+    if app.mention_frecency.flush_due(now) {
+        app.mention_frecency.flush(unix_now_secs());
+    }
+}
+
+/// Write out whatever is still pending before exit.
+pub(super) fn close(app: &mut App) {
+    app.mention_frecency.flush(unix_now_secs());
+}
+
+/// `path` relative to `cwd` with forward slashes and a trailing `/` for
+/// directories, or `None` when it is outside the project or missing.
+fn project_rel_path(cwd: &Path, path: &Path) -> Option<String> {
+    let full = if path.is_absolute() { path.to_path_buf() } else { cwd.join(path) };
+    let rel = full.strip_prefix(cwd).ok()?.to_string_lossy().replace('\\', "/");
+    let rel = rel.trim_end_matches('/');
+    if rel.is_empty() {
+        return None;
+    }
+    let metadata = std::fs::metadata(&full).ok()?;
+    Some(if metadata.is_dir() { format!("{rel}/") } else { rel.to_owned() })
+}
+
+/// Count the `@path` mentions of a sent prompt that name existing files.
+pub(super) fn record_mentions(app: &mut App, text: &str) {
+    let cwd = PathBuf::from(&app.cwd_raw);
+    let rel_paths: Vec<String> = super::mention::find_mention_spans(text)
+        .into_iter()
+        .filter_map(|(_, _, path)| project_rel_path(&cwd, Path::new(&path)))
+        .collect();
+    app.mention_frecency.record(&app.cwd_raw, rel_paths, unix_now_secs());
+}
+
+/// Count the files a completed tool call edited.
+pub(super) fn record_edits(app: &mut App, message: usize, block: usize) {
+    let Some(MessageBlock::ToolCall(tc)) =
+        app.messages.get(message).and_then(|msg| msg.blocks.get(block))
+    else {
+        return;
+    };
+    let cwd = PathBuf::from(&app.cwd_raw);
+    let mut rel_paths: Vec<String> = tc
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            model::ToolCallContent::Diff(diff) => project_rel_path(&cwd, &diff.path),
+            _ => None,
+        })
+        .collect();
+    rel_paths.dedup();
+    app.mention_frecency.record(&app.cwd_raw, rel_paths, unix_now_secs());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boost_grows_with_uses_and_fades_with_age() {
+        let mut frecency = Frecency::default();
+        let now = 10 * WEEK;
+        frecency.record("/repo", ["src/state.rs".to_owned()], now);
+        let once = frecency.boost("/repo", "src/state.rs", now);
+        frecency.record("/repo", ["src/state.rs".to_owned()], now);
+        let twice = frecency.boost("/repo", "src/state.rs", now);
+        assert!(twice > once && once > 0);
+        assert!(frecency.boost("/repo", "src/state.rs", now + 2 * WEEK) < twice);
+        assert_eq!(frecency.boost("/other", "src/state.rs", now), 0);
+        assert_eq!(frecency.boost("/repo", "src/lib.rs", now), 0);
+    }
+
+    #[test]
+    fn stats_persist_per_project_and_merge_with_other_writers() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("nested").join(FRECENCY_FILE);
+        let mut first = Frecency::load(Some(path.clone()));
+        let mut second = Frecency::load(Some(path.clone()));
+        first.record("/a", ["main.rs".to_owned()], 100);
+        second.record("/b", ["lib.rs".to_owned()], 100);
+        second.record("/a", ["main.rs".to_owned()], 100);
+        assert!(!path.exists(), "recording alone does not write");
+        first.flush(100);
+        second.flush(100);
+
+        let reloaded = Frecency::load(Some(path));
+        assert_eq!(reloaded.projects["/a"]["main.rs"].count, 2);
+        assert!(reloaded.boost("/b", "lib.rs", 100) > 0);
+    }
+
+    #[test]
+    fn flush_waits_for_the_debounce_and_forgets_stale_projects() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(FRECENCY_FILE);
+        let mut frecency = Frecency::load(Some(path.clone()));
+        frecency.record("/old", ["a.rs".to_owned()], 0);
+        let start = Instant::now();
+        assert!(!frecency.flush_due(start));
+        assert!(frecency.flush_due(start + FLUSH_DEBOUNCE + Duration::from_secs(1)));
+
+        let later = PROJECT_TTL + DAY;
+        frecency.record("/new", ["b.rs".to_owned()], later);
+        frecency.flush(later);
+        assert!(!frecency.flush_due(start + 2 * FLUSH_DEBOUNCE));
+        let reloaded = Frecency::load(Some(path));
+        assert_eq!(reloaded.projects.keys().collect::<Vec<_>>(), vec!["/new"]);
+    }
+
+    #[test]
+    fn project_paths_are_relative_and_mark_directories() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join("src/main.rs"), "").expect("write");
+        let cwd = dir.path();
+        assert_eq!(project_rel_path(cwd, &cwd.join("src/main.rs")).as_deref(), Some("src/main.rs"));
+        assert_eq!(project_rel_path(cwd, Path::new("src")).as_deref(), Some("src/"));
+        assert_eq!(project_rel_path(cwd, Path::new("missing.rs")), None);
+        assert_eq!(project_rel_path(cwd, Path::new("/etc/hosts")), None);
+    }
+}
@@ -92,6 +92,42 @@ impl InputState {
         self.rebuild_editor_from_snapshot();
     }
 
+    /// Replace the input with text edited outside the TUI (which saw every
+    /// placeholder expanded). Paste blocks whose content is still present
+    /// verbatim collapse back into placeholders; edited ones stay inline.
+    pub fn load_expanded_text(&mut self, text: &str) {
+        let mut collapsed = text.to_owned();
+        let mut kept = Vec::new();
+        for block in std::mem::take(&mut self.paste_blocks) {
+            if block.is_empty() {
+                continue;
+            }
+            if let Some(start) = collapsed.find(&block) {
+                let placeholder = paste_placeholder_label(kept.len(), count_text_chars(&block));
+                collapsed.replace_range(start..start + block.len(), &placeholder);
+                kept.push(block);
+            }
+        }
+        self.set_text(&collapsed);
+        self.paste_blocks = kept;
+    }
+
+    /// Replace the input with a saved draft, paste blocks included.
+    pub fn load_draft(
+        &mut self,
+        lines: Vec<String>,
+        paste_blocks: Vec<String>,
+        cursor_row: usize,
+        cursor_col: usize,
+    ) {
+        self.lines = if lines.is_empty() { vec![String::new()] } else { lines };
+        self.cursor_row = cursor_row.min(self.lines.len() - 1);
+        self.cursor_col = cursor_col.min(self.lines[self.cursor_row].chars().count());
+        self.paste_blocks = paste_blocks;
+        self.version += 1;
+        self.rebuild_editor_from_snapshot();
+    }
+
     pub fn insert_char(&mut self, c: char) {
         let line = &mut self.lines[self.cursor_row];
         let byte_idx = char_to_byte_index(line, self.cursor_col);
@@ -314,6 +350,45 @@ impl InputState {
         true
     }
 
+    /// Paste placeholders in the buffer as `(row, start_byte, end_byte, block_index)`.
+    #[must_use]
+    pub fn paste_placeholders(&self) -> Vec<(usize, usize, usize, usize)> {
+        let mut found = Vec::new();
+        for (row, line) in self.lines.iter().enumerate() {
+            let mut cursor = 0usize;
+            while let Some((start, end, idx)) = find_next_placeholder_with_suffix(line, cursor) {
+                if idx < self.paste_blocks.len() {
+                    found.push((row, start, end, idx));
+                }
+                cursor = end;
+            }
+        }
+        found
+    }
+
+    /// Delete a byte range of one line, plus one space left dangling by it.
+    pub fn remove_range(&mut self, row: usize, mut start: usize, mut end: usize) {
+        let Some(line) = self.lines.get_mut(row) else {
+            return;
+        };
+        if start > end || end > line.len() {
+            return;
+        }
+        if line[end..].starts_with(' ') {
+            end += 1;
+        } else if line[..start].ends_with(' ') {
+            start -= 1;
+        }
+        let removed_chars = line[start..end].chars().count();
+        let start_col = line[..start].chars().count();
+        line.replace_range(start..end, "");
+        if self.cursor_row == row && self.cursor_col > start_col {
+            self.cursor_col = self.cursor_col.saturating_sub(removed_chars).max(start_col);
+        }
+        self.version += 1;
+        self.rebuild_editor_from_snapshot();
+    }
+
     pub fn delete_char_before(&mut self) {
         if self.cursor_col > 0 {
             let line = &mut self.lines[self.cursor_row];
@@ -1564,6 +1639,24 @@ mod tests {
         assert_eq!(count_text_chars("a\nb"), 3);
     }
 
+    #[test]
+    fn load_expanded_text_recollapses_unchanged_paste_blocks() {
+        let first = "x".repeat(1200);
+        let second = "y\n".repeat(600);
+        let mut input = InputState::new();
+        input.insert_str("see ");
+        input.insert_paste_block(&first);
+        input.insert_str(" and ");
+        input.insert_paste_block(&second);
+
+        let edited = input.text().replacen("see", "compare", 1).replace(&second, "short");
+        input.load_expanded_text(&edited);
+
+        assert_eq!(input.paste_blocks, vec![first.clone()]);
+        assert_eq!(input.lines, vec!["compare [Pasted Text 1 - 1200 chars] and short"]);
+        assert_eq!(input.text(), format!("compare {first} and short"));
+    }
+
     #[test]
     fn trim_trailing_line_breaks_handles_crlf_and_lf() {
         assert_eq!(trim_trailing_line_breaks("a\r\n\r\n"), "a");
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use super::queue::QueueDelivery;
 use super::{
     App, AppStatus, BlockCache, CancelOrigin, ChatMessage, IncrementalMarkdown, MessageBlock,
     MessageRole,
@@ -38,6 +39,13 @@ pub(super) fn submit_input(app: &mut App) {
     }
 
     app.input.clear();
+    if super::queue::finish_edit(app, text.clone()) || super::branch::submit_edit(app, &text) {
+        return;
+    }
+    if let Some(command) = super::shell::parse(&text) {
+        super::shell::run(app, command);
+        return;
+    }
     enqueue_submission(app, text);
 }
 
@@ -58,10 +66,7 @@ pub(super) fn enqueue_submission(app: &mut App, text: String) {
     }
 
     if is_turn_busy(app) {
-        app.queued_submission = Some(text);
-        if let Err(message) = request_cancel(app, CancelOrigin::AutoQueue) {
-            tracing::error!("Failed to request queued auto-cancel: {message}");
-        }
+        super::queue::push(app, text, QueueDelivery::AfterTurn);
         return;
     }
 
@@ -101,7 +106,7 @@ pub(super) fn drain_queued_submission(app: &mut App) {
     if !matches!(app.status, AppStatus::Ready) || app.pending_cancel_origin.is_some() {
         return;
     }
-    let Some(text) = app.queued_submission.take() else {
+    let Some(text) = super::queue::take_next(app) else {
         return;
     };
     dispatch_submission(app, text);
@@ -114,13 +119,24 @@ fn dispatch_submission(app: &mut App, text: String) {
     dispatch_prompt_turn(app, text);
 }
 
-fn dispatch_prompt_turn(app: &mut App, text: String) {
+pub(super) fn dispatch_prompt_turn(app: &mut App, text: String) {
     // New turn started by user input: force-stop stale tool calls from older turns
     // so their spinners don't continue during this turn.
     let _ = app.finalize_in_progress_tool_calls(model::ToolCallStatus::Failed);
 
     let Some(conn) = app.conn.clone() else { return };
 
+    if let Some(reading) = super::budget::session_exhausted(app) {
+        // Keep the draft so it can be sent from a new session.
+        if app.input.is_empty() {
+            app.input.set_text(&text);
+        }
+        super::budget::push_blocked_notice(app, &reading);
+        return;
+    }
+    super::budget::begin_turn(app);
+    super::frecency::record_mentions(app, &text);
+
     app.messages.push(ChatMessage {
         role: MessageRole::User,
         blocks: vec![MessageBlock::Text(
@@ -144,7 +160,12 @@ fn dispatch_prompt_turn(app: &mut App, text: String) {
         return;
     };
     let tx = app.event_tx.clone();
-    match conn.prompt_text(sid.to_string(), text) {
+    let chunks = super::mention_context::prompt_chunks(
+        app,
+        text,
+        super::mention_context::PromptSupport::of(Some(&conn)),
+    );
+    match conn.prompt(sid.to_string(), chunks) {
         Ok(resp) => {
             tracing::debug!("Prompt dispatched: stop_reason={:?}", resp.stop_reason);
         }
@@ -169,15 +190,29 @@ mod tests {
     }
 
     #[test]
-    fn enqueue_submission_while_running_queues_and_requests_auto_cancel() {
+    fn enqueue_submission_while_running_queues_in_order_without_cancelling() {
         let (mut app, mut rx) = app_with_connection();
         app.status = AppStatus::Running;
 
-        enqueue_submission(&mut app, "queued prompt".into());
+        enqueue_submission(&mut app, "first prompt".into());
+        enqueue_submission(&mut app, "second prompt".into());
 
-        assert_eq!(app.queued_submission.as_deref(), Some("queued prompt"));
-        assert_eq!(app.pending_cancel_origin, Some(CancelOrigin::AutoQueue));
+        let queued: Vec<&str> =
+            app.submission_queue.items.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(queued, vec!["first prompt", "second prompt"]);
+        assert_eq!(app.pending_cancel_origin, None);
         assert!(matches!(app.status, AppStatus::Running));
+        assert!(rx.try_recv().is_err(), "after-turn items must not cancel the turn");
+    }
+
+    #[test]
+    fn interrupting_queue_item_requests_auto_cancel() {
+        let (mut app, mut rx) = app_with_connection();
+        app.status = AppStatus::Running;
+
+        super::super::queue::push(&mut app, "stop and do this".into(), QueueDelivery::Interrupt);
+
+        assert_eq!(app.pending_cancel_origin, Some(CancelOrigin::AutoQueue));
         let envelope = rx.try_recv().expect("cancel command should be sent");
         assert!(matches!(
             envelope.command,
@@ -207,11 +242,11 @@ mod tests {
     fn drain_queued_submission_dispatches_prompt_when_ready() {
         let (mut app, mut rx) = app_with_connection();
         app.status = AppStatus::Ready;
-        app.queued_submission = Some("run queued".into());
+        super::super::queue::push(&mut app, "run queued".into(), QueueDelivery::AfterTurn);
 
         drain_queued_submission(&mut app);
 
-        assert!(app.queued_submission.is_none());
+        assert!(app.submission_queue.is_empty());
         assert!(matches!(app.status, AppStatus::Thinking));
         assert_eq!(app.messages.len(), 2);
         let envelope = rx.try_recv().expect("prompt command should be sent");
@@ -229,7 +264,7 @@ mod tests {
 
         submit_input(&mut app);
 
-        assert!(app.queued_submission.is_none());
+        assert!(app.submission_queue.is_empty());
         assert_eq!(app.pending_cancel_origin, Some(CancelOrigin::Manual));
         let envelope = rx.try_recv().expect("cancel command should be sent");
         assert!(matches!(
@@ -237,4 +272,23 @@ mod tests {
             BridgeCommand::CancelTurn { session_id } if session_id == "session-1"
         ));
     }
+
+    #[test]
+    fn submitting_an_edit_saves_it_even_when_it_looks_like_a_shell_command() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let (mut app, _rx) = app_with_connection();
+        app.status = AppStatus::Running;
+        super::super::queue::push(&mut app, "run the tests".into(), QueueDelivery::AfterTurn);
+        assert!(super::super::queue::toggle_focus(&mut app));
+        let edit = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE);
+        assert!(super::super::queue::handle_key(&mut app, edit));
+
+        app.input.set_text("!cargo test");
+        submit_input(&mut app);
+
+        let queued: Vec<&str> =
+            app.submission_queue.items.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(queued, vec!["!cargo test"]);
+        assert!(app.terminals.borrow().is_empty(), "no shell command was started");
+    }
 }
@@ -59,7 +59,7 @@ fn handle_always_allowed_shortcuts(app: &mut App, key: KeyEvent) -> bool {
     false
 }
 
-fn copy_selection_to_clipboard(app: &App) -> bool {
+fn copy_selection_to_clipboard(app: &mut App) -> bool {
     let Some(selection) = app.selection else {
         return false;
     };
@@ -67,9 +67,7 @@ fn copy_selection_to_clipboard(app: &App) -> bool {
     if selected_text.is_empty() {
         return false;
     }
-    if let Ok(mut clipboard) = arboard::Clipboard::new() {
-        let _ = clipboard.set_text(selected_text);
-    }
+    super::clipboard::copy_with_toast(app, &selected_text);
     true
 }
 
@@ -122,6 +120,12 @@ pub(super) fn dispatch_key_by_focus(app: &mut App, key: KeyEvent) {
         return;
     }
 
+    // Tab shortcuts work while the current tab is connecting or resuming so the
+    // user can always move back to a usable session.
+    if handle_tab_shortcuts(app, key) {
+        return;
+    }
+
     if matches!(app.status, AppStatus::Connecting | AppStatus::Resuming | AppStatus::Error) {
         handle_blocked_input_shortcuts(app, key);
         return;
@@ -133,7 +137,12 @@ pub(super) fn dispatch_key_by_focus(app: &mut App, key: KeyEvent) {
         return;
     }
 
-    match app.focus_owner() {
+    if super::overlay::handle_key(app, key) {
+        return;
+    }
+
+    let owner = app.focus_owner();
+    match owner {
         FocusOwner::Mention => handle_autocomplete_key(app, key),
         FocusOwner::Help => handle_help_key(app, key),
         FocusOwner::Permission => {
@@ -141,7 +150,63 @@ pub(super) fn dispatch_key_by_focus(app: &mut App, key: KeyEvent) {
                 handle_normal_key(app, key);
             }
         }
-        FocusOwner::Input | FocusOwner::TodoList => handle_normal_key(app, key),
+        FocusOwner::Queue => {
+            if !super::queue::handle_key(app, key) {
+                handle_normal_key(app, key);
+            }
+        }
+        FocusOwner::Attachments => {
+            if !super::attachments::handle_key(app, key) {
+                handle_normal_key(app, key);
+            }
+        }
+        FocusOwner::Search => {
+            if !super::search::handle_key(app, key) {
+                handle_normal_key(app, key);
+            }
+        }
+        FocusOwner::ChatNav => {
+            if !super::chat_nav::handle_key(app, key) {
+                handle_normal_key(app, key);
+            }
+        }
+        FocusOwner::Input | FocusOwner::TodoList => {
+            if super::subagents::handle_view_key(app, key)
+                || (owner == FocusOwner::Input && super::vim::handle_key(app, key))
+            {
+                return;
+            }
+            handle_normal_key(app, key);
+        }
+    }
+}
+
+fn handle_tab_shortcuts(app: &mut App, key: KeyEvent) -> bool {
+    if key.modifiers != KeyModifiers::ALT {
+        return false;
+    }
+    match key.code {
+        KeyCode::Char('t' | 'T') => {
+            super::tabs::open_tab(app);
+            true
+        }
+        KeyCode::Char('w' | 'W') if app.tabs.list.len() > 1 => {
+            super::tabs::close_active(app);
+            true
+        }
+        KeyCode::Char(c @ '1'..='9') => {
+            let index = usize::from(c as u8 - b'1');
+            if index >= app.tabs.list.len() {
+                return false;
+            }
+            super::tabs::switch_to(app, index);
+            true
+        }
+        KeyCode::Left | KeyCode::Right if app.tabs.list.len() > 1 => {
+            super::tabs::cycle(app, key.code == KeyCode::Right);
+            true
+        }
+        _ => false,
     }
 }
 
@@ -214,6 +279,10 @@ fn handle_global_shortcuts(app: &mut App, key: KeyEvent) -> bool {
             toggle_all_tool_calls(app);
             true
         }
+        (KeyCode::Char('f'), m) if m == KeyModifiers::CONTROL => {
+            super::search::open(app);
+            true
+        }
         (KeyCode::Char('l'), m) if m == KeyModifiers::CONTROL => {
             app.force_redraw = true;
             true
@@ -222,6 +291,28 @@ fn handle_global_shortcuts(app: &mut App, key: KeyEvent) -> bool {
             toggle_header(app);
             true
         }
+        (KeyCode::Char('g'), m) if m == KeyModifiers::CONTROL => {
+            super::external::compose_input(app);
+            true
+        }
+        (KeyCode::Char('a' | 'A'), m) if m == KeyModifiers::ALT => {
+            super::subagents::cycle_view(app);
+            true
+        }
+        (KeyCode::Char('q' | 'Q'), m) if m == KeyModifiers::ALT => super::queue::toggle_focus(app),
+        (KeyCode::Char('e' | 'E'), m) if m == KeyModifiers::ALT => super::branch::step_edit(app),
+        (KeyCode::Char('p' | 'P'), m) if m == KeyModifiers::ALT => {
+            super::attachments::toggle_focus(app)
+        }
+        (KeyCode::Char('d' | 'D'), m) if m == KeyModifiers::ALT => super::draft::discard(app),
+        (KeyCode::Char('j' | 'J'), m) if m == KeyModifiers::ALT => super::chat_nav::open(app),
+        (KeyCode::Char('o' | 'O'), m) if m == KeyModifiers::ALT => {
+            super::chat_nav::open_latest_location(app);
+            true
+        }
+        (KeyCode::Char('i' | 'I'), m) if m == KeyModifiers::ALT => {
+            super::shell::attach_last_output(app)
+        }
         (KeyCode::Up, m) if m == KeyModifiers::CONTROL => {
             app.viewport.scroll_up(1);
             true
@@ -241,7 +332,7 @@ pub(super) fn is_printable_text_modifiers(modifiers: KeyModifiers) -> bool {
     !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) || ctrl_alt
 }
 
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::collapsible_match)]
 pub(super) fn handle_normal_key(app: &mut App, key: KeyEvent) {
     sync_help_focus(app);
     let input_version_before = app.input.version;
@@ -291,12 +382,18 @@ pub(super) fn handle_normal_key(app: &mut App, key: KeyEvent) {
     }
 
     match (key.code, key.modifiers) {
-        // Esc: cancel current turn if thinking/running
+        // Esc: cancel an edit, kill a running `!command`, or cancel the turn
         (KeyCode::Esc, _) => {
             if app.focus_owner() == FocusOwner::TodoList {
                 app.release_focus_target(FocusTarget::TodoList);
                 return;
             }
+            if super::queue::cancel_edit(app) || super::branch::cancel_edit(app) {
+                return;
+            }
+            if super::shell::kill_running(app) {
+                return;
+            }
             if matches!(app.status, AppStatus::Thinking | AppStatus::Running)
                 && let Err(message) = super::input_submit::request_cancel(app, CancelOrigin::Manual)
             {
@@ -656,17 +753,25 @@ fn handle_slash_key(app: &mut App, key: KeyEvent) {
     }
 }
 
-/// Toggle the session-level collapsed preference and apply to all tool calls.
+/// Toggle the session-level collapsed preference and apply to all tool calls
+/// and compaction summaries.
 pub(super) fn toggle_all_tool_calls(app: &mut App) {
     app.tools_collapsed = !app.tools_collapsed;
     for msg in &mut app.messages {
         for block in &mut msg.blocks {
-            if let MessageBlock::ToolCall(tc) = block {
-                let tc = tc.as_mut();
-                if tc.collapsed != app.tools_collapsed {
-                    tc.collapsed = app.tools_collapsed;
-                    tc.mark_tool_call_layout_dirty();
+            match block {
+                MessageBlock::ToolCall(tc) => {
+                    let tc = tc.as_mut();
+                    if tc.collapsed != app.tools_collapsed {
+                        tc.collapsed = app.tools_collapsed;
+                        tc.mark_tool_call_layout_dirty();
+                    }
+                }
+                MessageBlock::Compaction(block) if block.collapsed != app.tools_collapsed => {
+                    block.collapsed = app.tools_collapsed;
+                    block.cache.invalidate();
                 }
+                _ => {}
             }
         }
     }
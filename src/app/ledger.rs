@@ -0,0 +1,484 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Local spend ledger.
+//!
+//! Every live `UsageUpdate` is appended as one JSON line keyed by session, model,
+//! project and UTC day. Reports are aggregated from the file on demand, so totals
+//! survive resumes and restarts. History replayed on resume is never recorded.
+
+use super::App;
+use crate::agent::model;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LEDGER_DIR_NAME: &str = "claude-code-rust";
+const LEDGER_FILE: &str = "usage-ledger.jsonl";
+const SECS_PER_DAY: u64 = 86_400;
+const REPORT_WEEKS: u64 = 4;
+/// Days shown in the daily section unless the caller asks for more.
+pub const DEFAULT_REPORT_DAYS: u64 = 7;
+const LABEL_WIDTH: usize = 28;
+
+/// One recorded turn; `cost_usd` is `None` when the SDK reported no price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub ts: u64,
+    /// UTC day, `YYYY-MM-DD`.
+    pub day: String,
+    pub session_id: String,
+    pub model: String,
+    pub project: String,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    #[serde(default)]
+    pub cache_write_tokens: u64,
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+}
+
+/// Default ledger location under the platform data dir.
+#[must_use]
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join(LEDGER_DIR_NAME).join(LEDGER_FILE))
+}
+
+fn unix_now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Days since the Unix epoch to a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = u32::try_from(doy - (153 * mp + 2) / 5 + 1).unwrap_or(1);
+    let month = u32::try_from(if mp < 10 { mp + 3 } else { mp - 9 }).unwrap_or(1);
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn day_label(epoch_day: u64) -> String {
+    let (year, month, day) = civil_from_days(i64::try_from(epoch_day).unwrap_or(0));
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Append one entry. Failures are logged, never surfaced: the ledger must not
+/// interfere with the session.
+pub fn append(path: &Path, entry: &LedgerEntry) {
+    let result = (|| -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    })();
+    if let Err(err) = result {
+        tracing::warn!("usage ledger append failed ({}): {err}", path.display());
+    }
+}
+
+/// Read all entries, skipping malformed lines (e.g. a torn final write).
+#[must_use]
+pub fn read(path: &Path) -> Vec<LedgerEntry> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Record a live usage update for the active session.
+pub(super) fn record(app: &App, usage: &model::UsageUpdate) {
+    let Some(path) = app.usage_ledger_path.as_deref() else {
+        return;
+    };
+    let Some(session_id) = app.session_id.as_ref() else {
+        return;
+    };
+    let entry = LedgerEntry::new(
+        unix_now_secs(),
+        session_id.to_string(),
+        app.model_name.clone(),
+        app.cwd_raw.clone(),
+        usage,
+    );
+    if entry.is_empty() {
+        return;
+    }
+    append(path, &entry);
+}
+
+impl LedgerEntry {
+    #[must_use]
+    pub fn new(
+        ts: u64,
+        session_id: String,
+        model: String,
+        project: String,
+        usage: &model::UsageUpdate,
+    ) -> Self {
+        Self {
+            ts,
+            day: day_label(ts / SECS_PER_DAY),
+            session_id,
+            model,
+            project,
+            input_tokens: usage.input_tokens.unwrap_or(0),
+            output_tokens: usage.output_tokens.unwrap_or(0),
+            cache_read_tokens: usage.cache_read_tokens.unwrap_or(0),
+            cache_write_tokens: usage.cache_write_tokens.unwrap_or(0),
+            cost_usd: usage.turn_cost_usd,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.input_tokens == 0
+            && self.output_tokens == 0
+            && self.cache_read_tokens == 0
+            && self.cache_write_tokens == 0
+            && self.cost_usd.is_none_or(|cost| cost <= 0.0)
+    }
+}
+
+/// Summed tokens and cost for one report row.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cost_usd: f64,
+    pub turns: u64,
+    /// Turns counted above whose cost is unknown and left out of `cost_usd`.
+    pub unpriced_turns: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, entry: &LedgerEntry) {
+        self.input_tokens = self.input_tokens.saturating_add(entry.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(entry.output_tokens);
+        self.cache_read_tokens = self.cache_read_tokens.saturating_add(entry.cache_read_tokens);
+        self.cache_write_tokens = self.cache_write_tokens.saturating_add(entry.cache_write_tokens);
+        match entry.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced_turns += 1,
+        }
+        self.turns += 1;
+    }
+}
+
+/// Aggregated ledger: daily and weekly windows ending today, plus all-time
+/// per-project and per-model totals.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageReport {
+    /// `(day, totals)`, oldest first, including empty days.
+    pub daily: Vec<(String, UsageTotals)>,
+    /// `(monday of week, totals)`, oldest first.
+    pub weekly: Vec<(String, UsageTotals)>,
+    pub projects: Vec<(String, UsageTotals)>,
+    pub models: Vec<(String, UsageTotals)>,
+    pub total: UsageTotals,
+}
+
+/// One rendered report line; the UI styles headings, the CLI prints them as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportLine {
+    Heading(String),
+    Row(String),
+    Blank,
+}
+
+impl UsageReport {
+    #[must_use]
+    pub fn build(entries: &[LedgerEntry], now_secs: u64, days: u64) -> Self {
+        let today = now_secs / SECS_PER_DAY;
+        let days = days.max(1);
+        // 1970-01-01 was a Thursday; shift so Monday is weekday 0.
+        let this_week = today.saturating_sub((today + 3) % 7);
+
+        let mut daily: BTreeMap<u64, UsageTotals> = (today + 1 - days.min(today + 1)..=today)
+            .map(|d| (d, UsageTotals::default()))
+            .collect();
+        let first_week = this_week.saturating_sub((REPORT_WEEKS - 1) * 7);
+        let mut weekly: BTreeMap<u64, UsageTotals> = (0..REPORT_WEEKS)
+            .map(|w| first_week + w * 7)
+            .filter(|w| *w <= this_week)
+            .map(|w| (w, UsageTotals::default()))
+            .collect();
+        let mut projects: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        let mut models: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        let mut total = UsageTotals::default();
+
+        for entry in entries {
+            let day = entry.ts / SECS_PER_DAY;
+            if let Some(slot) = daily.get_mut(&day) {
+                slot.add(entry);
+            }
+            let week = day.saturating_sub((day + 3) % 7);
+            if let Some(slot) = weekly.get_mut(&week) {
+                slot.add(entry);
+            }
+            projects.entry(shorten_home(&entry.project)).or_default().add(entry);
+            models.entry(entry.model.clone()).or_default().add(entry);
+            total.add(entry);
+        }
+
+        let by_cost_desc = |rows: BTreeMap<String, UsageTotals>| {
+            let mut rows: Vec<_> = rows.into_iter().collect();
+            rows.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+            rows
+        };
+        Self {
+            daily: daily.into_iter().map(|(d, t)| (day_label(d), t)).collect(),
+            weekly: weekly.into_iter().map(|(w, t)| (day_label(w), t)).collect(),
+            projects: by_cost_desc(projects),
+            models: by_cost_desc(models),
+            total,
+        }
+    }
+
+    /// Load the ledger at `path` and aggregate it as of now.
+    #[must_use]
+    pub fn load(path: &Path, days: u64) -> Self {
+        Self::build(&read(path), unix_now_secs(), days)
+    }
+
+    #[must_use]
+    pub fn lines(&self) -> Vec<ReportLine> {
+        let mut out = Vec::new();
+        if self.total.turns == 0 {
+            out.push(ReportLine::Row("No usage recorded yet.".to_owned()));
+            return out;
+        }
+        let sections: [(String, &[(String, UsageTotals)]); 4] = [
+            (format!("Daily (last {} days, UTC)", self.daily.len()), &self.daily),
+            ("Weekly (week starting Monday)".to_owned(), &self.weekly),
+            ("Projects (all time)".to_owned(), &self.projects),
+            ("Models (all time)".to_owned(), &self.models),
+        ];
+        for (index, (title, rows)) in sections.iter().enumerate() {
+            if index > 0 {
+                out.push(ReportLine::Blank);
+            }
+            out.push(ReportLine::Heading(title.clone()));
+            out.push(ReportLine::Row(header_row()));
+            for (label, totals) in *rows {
+                out.push(ReportLine::Row(totals_row(label, totals)));
+            }
+        }
+        out.push(ReportLine::Blank);
+        out.push(ReportLine::Row(totals_row("Total", &self.total)));
+        if self.total.unpriced_turns > 0 {
+            let turns = self.total.unpriced_turns;
+            let noun = if turns == 1 { "turn" } else { "turns" };
+            out.push(ReportLine::Row(format!("* excludes {turns} {noun} with no reported cost")));
+        }
+        out
+    }
+}
+
+fn header_row() -> String {
+    format!(
+        "{:<LABEL_WIDTH$} {:>8} {:>8} {:>9} {:>9} {:>10}",
+        "", "Input", "Output", "Cache rd", "Cache wr", "Cost"
+    )
+}
+
+fn totals_row(label: &str, totals: &UsageTotals) -> String {
+    format!(
+        "{:<LABEL_WIDTH$} {:>8} {:>8} {:>9} {:>9} {:>10}",
+        truncate_label(label),
+        format_tokens(totals.input_tokens),
+        format_tokens(totals.output_tokens),
+        format_tokens(totals.cache_read_tokens),
+        format_tokens(totals.cache_write_tokens),
+        format_cost(totals),
+    )
+}
+
+/// Unpriced turns are flagged rather than counted as free.
+fn format_cost(totals: &UsageTotals) -> String {
+    if totals.unpriced_turns == 0 {
+        format!("${:.2}", totals.cost_usd)
+    } else if totals.unpriced_turns == totals.turns {
+        "-*".to_owned()
+    } else {
+        format!("${:.2}*", totals.cost_usd)
+    }
+}
+
+/// Keep the tail of long labels (project paths differ at the end).
+fn truncate_label(label: &str) -> String {
+    let count = label.chars().count();
+    if count <= LABEL_WIDTH {
+        return label.to_owned();
+    }
+    let tail: String = label.chars().skip(count - (LABEL_WIDTH - 1)).collect();
+    format!("\u{2026}{tail}")
+}
+
+fn format_tokens(value: u64) -> String {
+    let scaled = |divisor: u64, suffix: char| {
+        let tenths = (u128::from(value) * 10 + u128::from(divisor / 2)) / u128::from(divisor);
+        let (whole, frac) = (tenths / 10, tenths % 10);
+        if frac == 0 { format!("{whole}{suffix}") } else { format!("{whole}.{frac}{suffix}") }
+    };
+    if value >= 1_000_000 {
+        scaled(1_000_000, 'M')
+    } else if value >= 1_000 {
+        scaled(1_000, 'k')
+    } else {
+        value.to_string()
+    }
+}
+
+fn shorten_home(path: &str) -> String {
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy();
+        if let Some(rest) = path.strip_prefix(home.as_ref()) {
+            return format!("~{rest}");
+        }
+    }
+    path.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ts: u64, model: &str, project: &str, cost: f64) -> LedgerEntry {
+        priced_entry(ts, model, project, Some(cost))
+    }
+
+    fn priced_entry(ts: u64, model: &str, project: &str, cost: Option<f64>) -> LedgerEntry {
+        let usage = model::UsageUpdate {
+            input_tokens: Some(1_000),
+            output_tokens: Some(200),
+            cache_read_tokens: Some(5_000),
+            cache_write_tokens: Some(0),
+            total_cost_usd: None,
+            turn_cost_usd: cost,
+            context_window: None,
+            max_output_tokens: None,
+        };
+        LedgerEntry::new(ts, "s1".into(), model.into(), project.into(), &usage)
+    }
+
+    #[test]
+    fn civil_dates_match_known_days() {
+        assert_eq!(day_label(0), "1970-01-01");
+        assert_eq!(day_label(19_723), "2024-01-01");
+        assert_eq!(day_label(20_744), "2026-10-18");
+    }
+
+    #[test]
+    fn append_and_read_round_trip_skipping_torn_lines() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("nested").join(LEDGER_FILE);
+        append(&path, &entry(10, "opus", "/p", 0.5));
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(b"{\"ts\": 1, \"day\"\n"))
+            .expect("torn write");
+        append(&path, &entry(20, "opus", "/p", 0.25));
+
+        let entries = read(&path);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[1].cost_usd.is_some_and(|cost| (cost - 0.25).abs() < f64::EPSILON));
+    }
+
+    #[test]
+    fn report_groups_by_day_week_project_and_model() {
+        let today = 20_744; // Sunday 2026-10-18
+        let now = today * SECS_PER_DAY + 3_600;
+        let entries = vec![
+            entry(now, "sonnet", "/work/a", 0.10),
+            entry(now - SECS_PER_DAY, "opus", "/work/a", 1.00),
+            entry(now - 8 * SECS_PER_DAY, "sonnet", "/work/b", 0.50),
+            entry(now - 60 * SECS_PER_DAY, "sonnet", "/work/b", 2.00),
+        ];
+
+        let report = UsageReport::build(&entries, now, 7);
+        assert_eq!(report.daily.len(), 7);
+        assert_eq!(
+            report.daily.last().map(|(d, t)| (d.as_str(), t.turns)),
+            Some(("2026-10-18", 1))
+        );
+        assert_eq!(report.weekly.len(), 4);
+        assert_eq!(
+            report.weekly.last().map(|(w, t)| (w.as_str(), t.turns)),
+            Some(("2026-10-12", 2))
+        );
+        assert_eq!(report.weekly[2].1.turns, 1);
+        assert_eq!(report.projects[0].0, "/work/b");
+        assert_eq!(report.models[0].0, "sonnet");
+        assert_eq!(report.models[0].1.cache_read_tokens, 15_000);
+        assert_eq!(report.total.turns, 4);
+        assert!(report.lines().iter().any(
+            |l| matches!(l, ReportLine::Row(r) if r.starts_with("Total") && r.ends_with("$3.60"))
+        ));
+    }
+
+    #[test]
+    fn unpriced_turns_are_counted_but_not_costed() {
+        let now = 20_744 * SECS_PER_DAY;
+        let entries = vec![
+            entry(now, "opus", "/work/a", 0.40),
+            priced_entry(now, "opus", "/work/a", None),
+            priced_entry(now, "custom", "/work/a", None),
+        ];
+
+        let report = UsageReport::build(&entries, now, 1);
+        assert_eq!(report.total.turns, 3);
+        assert_eq!(report.total.unpriced_turns, 2);
+        let lines = report.lines();
+        let row = |label: &str| {
+            lines.iter().find_map(|l| match l {
+                ReportLine::Row(r) if r.starts_with(label) => Some(r.clone()),
+                _ => None,
+            })
+        };
+        assert!(row("Total").is_some_and(|r| r.ends_with("$0.40*")));
+        assert!(row("custom").is_some_and(|r| r.ends_with("-*")));
+        assert!(row("* excludes 2 turns").is_some());
+    }
+
+    #[test]
+    fn week_of_the_first_epoch_days_does_not_underflow() {
+        let entries = vec![entry(0, "opus", "/p", 0.1), entry(2 * SECS_PER_DAY, "opus", "/p", 0.1)];
+        let report = UsageReport::build(&entries, 2 * SECS_PER_DAY, 3);
+        assert_eq!(report.total.turns, 2);
+        assert_eq!(report.weekly.last().map(|(_, t)| t.turns), Some(2));
+    }
+}
@@ -15,8 +15,6 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use super::{App, FocusTarget, dialog::DialogState};
-use ignore::WalkBuilder;
-use std::path::Path;
 use std::time::SystemTime;
 
 /// Maximum candidates shown in the dropdown.
@@ -48,75 +46,205 @@ pub struct FileCandidate {
     pub modified: SystemTime,
     /// Whether this candidate is a directory (true) or a file (false).
     pub is_dir: bool,
+    /// Char indices of `rel_path` matched by the current query, for highlighting.
+    pub matched: Vec<usize>,
 }
 
-/// Scan all files and directories under `cwd` using the `ignore` crate (respects .gitignore).
-/// Returns candidates sorted by depth ascending, then modified descending.
-pub fn scan_files(cwd: &str) -> Vec<FileCandidate> {
-    let cwd_path = Path::new(cwd);
-    let mut candidates = Vec::new();
-
-    let walker = WalkBuilder::new(cwd_path)
-        .hidden(false) // include dotfiles like .github/, .gitignore
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .build();
-
-    for entry in walker.flatten() {
-        let is_file = entry.file_type().is_some_and(|ft| ft.is_file());
-        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
-
-        if !is_file && !is_dir {
-            continue;
-        }
+const SCORE_MATCH: i32 = 16;
+/// Match at the start of the path, a segment or a word.
+const BONUS_BOUNDARY: i32 = 12;
+const BONUS_CONSECUTIVE: i32 = 8;
+/// Match inside the last path segment (the file or directory name).
+const BONUS_NAME: i32 = 6;
+const BONUS_CASE: i32 = 1;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTEND: i32 = 1;
+const NO_MATCH: i32 = i32::MIN / 2;
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
 
-        let path = entry.path();
-        let Ok(rel) = path.strip_prefix(cwd_path) else {
-            continue;
-        };
+/// Fuzzy scorer for one query, reused across candidates so ranking a whole
+/// file list allocates its tables once per keystroke rather than per path.
+///
+/// Dynamic programming over (query char, text position) in the spirit of fzf:
+/// matches score more at word starts, inside the file name and in runs, and
+/// lose a little for every skipped character in between.
+pub struct FuzzyMatcher {
+    query: Vec<char>,
+    folded_query: Vec<char>,
+    text: Vec<char>,
+    folded: Vec<char>,
+    bonus: Vec<i32>,
+    /// `best[i * m + j]`: query[..=i] matched with query[i] at text[j].
+    best: Vec<i32>,
+    /// `carry[i * m + j]`: best[i][k] for k <= j, minus the gap extension up to j.
+    carry: Vec<i32>,
+}
 
-        // Convert to forward-slash relative path
-        let rel_str = rel.to_string_lossy().replace('\\', "/");
-        if rel_str.is_empty() {
-            continue; // skip the cwd root itself
+impl FuzzyMatcher {
+    #[must_use]
+    pub fn new(query: &str) -> Self {
+        let query: Vec<char> = query.chars().collect();
+        let folded_query = query.iter().copied().map(fold).collect();
+        Self {
+            query,
+            folded_query,
+            text: Vec::new(),
+            folded: Vec::new(),
+            bonus: Vec::new(),
+            best: Vec::new(),
+            carry: Vec::new(),
         }
+    }
 
-        // Compute depth before moving rel_str
-        let depth = rel_str.matches('/').count();
+    /// Cheap rejection before any table is filled.
+    fn is_subsequence(&self, text: &str) -> bool {
+        let mut rest = text.chars().map(fold);
+        self.folded_query.iter().all(|&q| rest.any(|t| t == q))
+    }
 
-        // Directories get a trailing `/` for visual distinction
-        let rel_path = if is_dir { format!("{rel_str}/") } else { rel_str };
-        let modified =
-            entry.metadata().ok().and_then(|m| m.modified().ok()).unwrap_or(SystemTime::UNIX_EPOCH);
+    /// Score of matching the query as a subsequence of `text`, with the char
+    /// indices of the best alignment. `None` when it is not a subsequence.
+    pub fn score(&mut self, text: &str) -> Option<(i32, Vec<usize>)> {
+        let n = self.query.len();
+        if n == 0 {
+            return Some((0, Vec::new()));
+        }
+        if !self.is_subsequence(text) {
+            return None;
+        }
+        self.text.clear();
+        self.text.extend(text.chars());
+        self.folded.clear();
+        self.folded.extend(self.text.iter().copied().map(fold));
+        let m = self.text.len();
+        let (query, text, folded) = (&self.query, &self.text, &self.folded);
+
+        let name_start = {
+            let trimmed = folded.len() - usize::from(folded.last() == Some(&'/'));
+            folded[..trimmed].iter().rposition(|&c| c == '/').map_or(0, |i| i + 1)
+        };
+        self.bonus.clear();
+        self.bonus.extend((0..m).map(|j| {
+            let prev = j.checked_sub(1).map(|p| text[p]);
+            let boundary = prev.is_none_or(|p| matches!(p, '/' | '_' | '-' | '.' | ' '))
+                || prev.is_some_and(|p| p.is_lowercase() && text[j].is_uppercase());
+            SCORE_MATCH
+                + if boundary { BONUS_BOUNDARY } else { 0 }
+                + if j >= name_start { BONUS_NAME } else { 0 }
+        }));
+        let bonus = &self.bonus;
+
+        self.best.clear();
+        self.best.resize(n * m, NO_MATCH);
+        self.carry.clear();
+        self.carry.resize(n * m, NO_MATCH);
+        let (best, carry) = (&mut self.best, &mut self.carry);
+        for i in 0..n {
+            for j in i..m {
+                if folded[j] == self.folded_query[i] {
+                    let here = bonus[j] + if text[j] == query[i] { BONUS_CASE } else { 0 };
+                    best[i * m + j] = if i == 0 {
+                        here
+                    } else {
+                        let above = (i - 1) * m;
+                        let run =
+                            if j >= 1 { best[above + j - 1] + BONUS_CONSECUTIVE } else { NO_MATCH };
+                        let gap = if j >= 2 {
+                            carry[above + j - 2] - PENALTY_GAP_START
+                        } else {
+                            NO_MATCH
+                        };
+                        let previous = run.max(gap);
+                        if previous > NO_MATCH / 2 { previous + here } else { NO_MATCH }
+                    };
+                }
+                let extended =
+                    if j >= 1 { carry[i * m + j - 1] - PENALTY_GAP_EXTEND } else { NO_MATCH };
+                carry[i * m + j] = best[i * m + j].max(extended);
+            }
+        }
 
-        candidates.push(FileCandidate { rel_path, depth, modified, is_dir });
+        let (mut j, score) = best[(n - 1) * m..]
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+        if score <= NO_MATCH / 2 {
+            return None;
+        }
+        let mut positions = vec![0; n];
+        for i in (0..n).rev() {
+            positions[i] = j;
+            if i == 0 {
+                break;
+            }
+            let above = (i - 1) * m;
+            let previous =
+                best[i * m + j] - bonus[j] - if text[j] == query[i] { BONUS_CASE } else { 0 };
+            j = if j >= 1 && best[above + j - 1] + BONUS_CONSECUTIVE == previous {
+                j - 1
+            } else {
+                (0..j.saturating_sub(1)).rev().find(|&k| {
+                    let gap = i32::try_from(j - 2 - k).unwrap_or(i32::MAX);
+                    best[above + k] - PENALTY_GAP_START - gap * PENALTY_GAP_EXTEND == previous
+                })?
+            };
+        }
+        // Shorter paths win ties.
+        let length_penalty = i32::try_from(m / 8).unwrap_or(i32::MAX);
+        Some((score - length_penalty, positions))
     }
-
-    // Sort: directories before files at same depth, then depth ascending,
-    // then modified descending within each group
-    candidates.sort_by(|a, b| {
-        a.depth
-            .cmp(&b.depth)
-            .then_with(|| b.is_dir.cmp(&a.is_dir)) // dirs first (true > false)
-            .then_with(|| b.modified.cmp(&a.modified))
-    });
-    candidates
 }
 
-/// Filter cached candidates by a query string (case-insensitive substring match).
-pub fn filter_candidates(cache: &[FileCandidate], query: &str) -> Vec<FileCandidate> {
+/// Rank cached candidates for `query`: fuzzy subsequence score plus the
+/// project's frecency boost. An empty query lists frequently used files
+/// first, then the scan order.
+pub fn filter_candidates(
+    cache: &[FileCandidate],
+    query: &str,
+    frecency: &super::frecency::Frecency,
+    project: &str,
+) -> Vec<FileCandidate> {
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let boost = |c: &FileCandidate| frecency.boost(project, &c.rel_path, now);
+
+    let mut ranked: Vec<(i32, usize, Vec<usize>)> = if query.is_empty() {
+        cache
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let boost = boost(c);
+                (boost > 0).then_some((boost, i, Vec::new()))
+            })
+            .collect()
+    } else {
+        let mut matcher = FuzzyMatcher::new(query);
+        cache
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                matcher.score(&c.rel_path).map(|(score, matched)| (score + boost(c), i, matched))
+            })
+            .collect()
+    };
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    ranked.truncate(MAX_CANDIDATES);
+
+    let mut out: Vec<FileCandidate> = ranked
+        .into_iter()
+        .map(|(_, i, matched)| FileCandidate { matched, ..cache[i].clone() })
+        .collect();
     if query.is_empty() {
-        return cache.iter().take(MAX_CANDIDATES).cloned().collect();
+        let shown: std::collections::HashSet<String> =
+            out.iter().map(|c| c.rel_path.clone()).collect();
+        let rest = cache.iter().filter(|c| !shown.contains(&c.rel_path));
+        let room = MAX_CANDIDATES - out.len();
+        out.extend(rest.take(room).cloned());
     }
-
-    let query_lower = query.to_lowercase();
-    cache
-        .iter()
-        .filter(|c| c.rel_path.to_lowercase().contains(&query_lower))
-        .take(MAX_CANDIDATES)
-        .cloned()
-        .collect()
+    out
 }
 
 /// Detect an `@` mention at the current cursor position.
@@ -164,11 +292,11 @@ pub fn activate(app: &mut App) {
     let Some((trigger_row, trigger_col, query)) = detection else {
         return;
     };
-    // Rescan files each time `@` is activated so new/deleted files are reflected
-    app.file_cache = Some(scan_files(&app.cwd_raw));
+    super::file_index::refresh(app);
 
-    let candidates =
-        app.file_cache.as_ref().map(|cache| filter_candidates(cache, &query)).unwrap_or_default();
+    let candidates = app.file_index.cache.as_ref().map_or_else(Vec::new, |cache| {
+        filter_candidates(cache, &query, &app.mention_frecency, &app.cwd_raw)
+    });
 
     app.mention = Some(MentionState {
         trigger_row,
@@ -190,8 +318,9 @@ pub fn update_query(app: &mut App) {
         deactivate(app);
         return;
     };
-    let candidates =
-        app.file_cache.as_ref().map(|cache| filter_candidates(cache, &query)).unwrap_or_default();
+    let candidates = app.file_index.cache.as_ref().map_or_else(Vec::new, |cache| {
+        filter_candidates(cache, &query, &app.mention_frecency, &app.cwd_raw)
+    });
 
     if let Some(ref mut mention) = app.mention {
         mention.trigger_row = trigger_row;
@@ -318,6 +447,7 @@ pub fn find_mention_spans(text: &str) -> Vec<(usize, usize, String)> {
 mod tests {
     use super::*;
     use crate::app::App;
+    use crate::app::frecency::Frecency;
 
     /// Create a temp directory with test files and return an App pointing to it.
     fn app_with_temp_files(files: &[&str]) -> (App, tempfile::TempDir) {
@@ -406,4 +536,67 @@ mod tests {
         assert_eq!(mention.query, "");
         assert!(!mention.candidates.is_empty());
     }
+
+    fn candidates(paths: &[&str]) -> Vec<FileCandidate> {
+        paths
+            .iter()
+            .map(|path| FileCandidate {
+                rel_path: (*path).to_owned(),
+                depth: path.matches('/').count(),
+                modified: SystemTime::UNIX_EPOCH,
+                is_dir: path.ends_with('/'),
+                matched: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn ranked(cache: &[FileCandidate], query: &str, frecency: &Frecency) -> Vec<String> {
+        filter_candidates(cache, query, frecency, "/repo").into_iter().map(|c| c.rel_path).collect()
+    }
+
+    #[test]
+    fn fuzzy_match_prefers_runs_and_word_starts() {
+        let fuzzy_match = |text: &str, query: &str| FuzzyMatcher::new(query).score(text);
+        let (_, positions) = fuzzy_match("src/app/state.rs", "apst").expect("subsequence");
+        assert_eq!(positions, vec![4, 5, 8, 9]);
+        assert!(fuzzy_match("src/app/state.rs", "stx").is_none());
+        assert!(fuzzy_match("src/App.rs", "app").is_some());
+    }
+
+    #[test]
+    fn reused_matcher_scores_like_a_fresh_one() {
+        let paths = ["src/app/state.rs", "a.rs", "docs/apst/README.md", "src/ui/app_state.rs"];
+        let mut reused = FuzzyMatcher::new("apst");
+        for path in paths {
+            assert_eq!(reused.score(path), FuzzyMatcher::new("apst").score(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn filter_ranks_file_name_matches_above_scattered_ones() {
+        let cache = candidates(&[
+            "docs/estate/notes.md",
+            "crates/ui/src/stats/mod_test.rs",
+            "src/app/state.rs",
+            "src/app/",
+        ]);
+        let ranked = ranked(&cache, "state", &Frecency::default());
+        assert_eq!(ranked[0], "src/app/state.rs");
+        assert!(!ranked.contains(&"src/app/".to_owned()));
+    }
+
+    #[test]
+    fn frecency_boost_breaks_ties_and_leads_empty_queries() {
+        let cache = candidates(&["src/a/mod.rs", "src/b/mod.rs", "README.md"]);
+        let mut frecency = Frecency::default();
+        assert_eq!(ranked(&cache, "mod", &frecency)[0], "src/a/mod.rs");
+
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        frecency.record("/repo", ["src/b/mod.rs".to_owned()], now);
+        assert_eq!(ranked(&cache, "mod", &frecency)[0], "src/b/mod.rs");
+        assert_eq!(
+            ranked(&cache, "", &frecency),
+            vec!["src/b/mod.rs", "src/a/mod.rs", "README.md"]
+        );
+    }
 }
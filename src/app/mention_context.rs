@@ -0,0 +1,495 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Embedded context for line-range and symbol mentions.
+//!
+//! `@src/app/state.rs:120-180` and `@App::render` resolve to a span of one
+//! file when the prompt is sent. If the bridge advertises
+//! `prompt_embedded_context`, every span travels as a `resource` prompt chunk
+//! next to the text, so the agent gets those lines without reading the whole
+//! file. Plain `@path` mentions are still left for the agent to read unless
+//! the attachments tray switches them to attached.
+
+use super::App;
+use super::attachments::AttachMode;
+use super::mention::{self, FileCandidate};
+use crate::agent::client::AgentConnection;
+use crate::agent::types::PromptChunk;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files larger than this are never read for a span.
+const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// Lines embedded for one mention; longer ranges are cut.
+const MAX_SPAN_LINES: usize = 2_000;
+/// Bytes embedded for one mention; longer ranges are cut.
+const MAX_SPAN_BYTES: usize = 64 * 1024;
+/// Bytes embedded for one prompt; later spans are left out.
+const MAX_TOTAL_BYTES: usize = 256 * 1024;
+/// Images whose base64 encoding is larger are left for the agent to read
+/// (the API limit of 5 MB applies to the encoded data).
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Trailing characters that end a sentence rather than a reference.
+pub(super) const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MentionRef {
+    /// `@path:N` or `@path:N-M`, 1-based and inclusive.
+    Lines { path: String, start: usize, end: usize },
+    /// `@Name`, `@Type::method` or `@Type.method`, split into segments.
+    Symbol(Vec<String>),
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Parse the text after `@`. Trailing sentence punctuation is ignored.
+#[must_use]
+pub fn parse_ref(token: &str) -> Option<MentionRef> {
+    let token = token.trim_end_matches(TRAILING_PUNCTUATION);
+    if let Some((path, range)) = token.rsplit_once(':')
+        && !path.is_empty()
+        && !path.ends_with(':')
+    {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let start: usize = start.parse().ok()?;
+        let end: usize = end.parse().ok()?;
+        return (start >= 1 && end >= start).then(|| MentionRef::Lines {
+            path: path.to_owned(),
+            start,
+            end,
+        });
+    }
+    let segments: Vec<String> =
+        token.split("::").flat_map(|part| part.split('.')).map(str::to_owned).collect();
+    segments.iter().all(|segment| is_identifier(segment)).then_some(MentionRef::Symbol(segments))
+}
+
+/// One resolved span of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The mention as typed, without the `@`.
+    pub mention: String,
+    /// Path as shown to the agent: relative to the project when inside it.
+    pub display_path: String,
+    pub abs_path: PathBuf,
+    /// 1-based inclusive line range actually embedded.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+    /// Whether the requested range was cut at [`MAX_SPAN_LINES`] or
+    /// [`MAX_SPAN_BYTES`].
+    pub truncated: bool,
+}
+
+impl Span {
+    fn to_chunk(&self) -> PromptChunk {
+        let uri = format!(
+            "{}#L{}-{}",
+            crate::ui::hyperlink::file_uri(&self.abs_path),
+            self.start_line,
+            self.end_line
+        );
+        PromptChunk {
+            kind: "resource".to_owned(),
+            value: serde_json::json!({
+                "uri": uri,
+                "mention": format!("@{}", self.mention),
+                "path": self.display_path,
+                "start_line": self.start_line,
+                "end_line": self.end_line,
+                "truncated": self.truncated,
+                "text": self.text,
+            }),
+        }
+    }
+}
+
+fn read_span(cwd: &Path, path: &str, start: usize, end: usize, mention: &str) -> Option<Span> {
+    let abs_path = if Path::new(path).is_absolute() { PathBuf::from(path) } else { cwd.join(path) };
+    let metadata = std::fs::metadata(&abs_path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let content = std::fs::read_to_string(&abs_path).ok()?;
+    let mut text = String::new();
+    let mut last = start - 1;
+    let mut truncated = false;
+    for (idx, line) in content.lines().enumerate().take(end).skip(start - 1) {
+        if idx + 1 - start >= MAX_SPAN_LINES || text.len() + line.len() + 1 > MAX_SPAN_BYTES {
+            truncated = true;
+            break;
+        }
+        text.push_str(line);
+        text.push('\n');
+        last = idx + 1;
+    }
+    if last < start {
+        return None;
+    }
+    let display_path = abs_path
+        .strip_prefix(cwd)
+        .map_or_else(|_| path.to_owned(), |rel| rel.to_string_lossy().replace('\\', "/"));
+    Some(Span {
+        mention: mention.to_owned(),
+        display_path,
+        abs_path,
+        start_line: start,
+        end_line: last,
+        text,
+        truncated,
+    })
+}
+
+/// The file index snapshot, if one is ready. The live index scans on its
+/// worker thread, so this never waits for a scan.
+fn project_files(app: &mut App) -> Option<Arc<Vec<FileCandidate>>> {
+    if app.file_index.cache.is_none() {
+        super::file_index::refresh(app);
+    }
+    app.file_index.cache.clone()
+}
+
+/// Which prompt chunk kinds the connected agent accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PromptSupport {
+    pub embedded_context: bool,
+    pub images: bool,
+}
+
+impl PromptSupport {
+    #[must_use]
+    pub fn of(conn: Option<&AgentConnection>) -> Self {
+        conn.map_or_else(Self::default, |conn| Self {
+            embedded_context: conn.supports_embedded_context(),
+            images: conn.supports_images(),
+        })
+    }
+}
+
+/// What one mention points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    File {
+        abs_path: PathBuf,
+        bytes: u64,
+    },
+    Directory {
+        abs_path: PathBuf,
+    },
+    Image {
+        abs_path: PathBuf,
+        bytes: u64,
+        mime_type: &'static str,
+    },
+    /// Line-range and symbol mentions, one span per definition found.
+    Spans(Vec<Span>),
+    /// A range or qualified symbol that matched nothing.
+    Missing,
+}
+
+impl Target {
+    /// How the mention is sent unless the attachments tray says otherwise:
+    /// spans and images are attached, files and directories stay references.
+    #[must_use]
+    pub fn default_mode(&self, support: PromptSupport) -> AttachMode {
+        match self {
+            Self::Spans(_) | Self::Missing if support.embedded_context => AttachMode::Attached,
+            Self::Image { .. } if support.images => AttachMode::Attached,
+            _ => AttachMode::Inline,
+        }
+    }
+}
+
+fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Resolve the text after `@`. `None` when it is no reference at all (a
+/// bare word naming no symbol, a path that does not exist).
+pub fn resolve_mention(app: &mut App, token: &str) -> Option<Target> {
+    let cwd = PathBuf::from(&app.cwd_raw);
+    let mention = token.trim_end_matches(TRAILING_PUNCTUATION);
+    for path in [token, mention] {
+        let abs_path = cwd.join(path);
+        let Ok(metadata) = std::fs::metadata(&abs_path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            return Some(Target::Directory { abs_path });
+        }
+        let bytes = metadata.len();
+        return Some(match image_mime_type(&abs_path) {
+            Some(mime_type) => Target::Image { abs_path, bytes, mime_type },
+            None => Target::File { abs_path, bytes },
+        });
+    }
+    let spans: Vec<Span> = match parse_ref(token)? {
+        MentionRef::Lines { path, start, end } => {
+            read_span(&cwd, &path, start, end, mention).into_iter().collect()
+        }
+        MentionRef::Symbol(segments) => {
+            let files = project_files(app).unwrap_or_default();
+            let version = app.file_index.version();
+            let spans: Vec<Span> = app
+                .file_index
+                .tags
+                .lookup(&cwd, &files, version, &segments)
+                .into_iter()
+                .filter_map(|found| {
+                    read_span(
+                        &cwd,
+                        &found.rel_path,
+                        found.tag.start_line,
+                        found.tag.end_line,
+                        mention,
+                    )
+                })
+                .collect();
+            // A bare word may just be a handle; only qualified names clearly
+            // meant a symbol.
+            if spans.is_empty() && segments.len() == 1 {
+                return None;
+            }
+            spans
+        }
+    };
+    Some(if spans.is_empty() { Target::Missing } else { Target::Spans(spans) })
+}
+
+/// Length of `bytes` raw bytes once base64 encoded.
+fn encoded_len(bytes: u64) -> u64 {
+    bytes.div_ceil(3).saturating_mul(4)
+}
+
+fn image_chunk(abs_path: &Path, mime_type: &str) -> Option<PromptChunk> {
+    let metadata = std::fs::metadata(abs_path).ok()?;
+    if encoded_len(metadata.len()) > MAX_IMAGE_BYTES {
+        return None;
+    }
+    let data = std::fs::read(abs_path).ok()?;
+    Some(PromptChunk {
+        kind: "image".to_owned(),
+        value: serde_json::json!({
+            "uri": crate::ui::hyperlink::file_uri(abs_path),
+            "mime_type": mime_type,
+            "data": BASE64.encode(data),
+        }),
+    })
+}
+
+/// Chunks for the mentions in `text` that are sent attached, plus the
+/// mentions that should have been attached but could not be.
+fn mention_chunks(
+    app: &mut App,
+    text: &str,
+    support: PromptSupport,
+) -> (Vec<PromptChunk>, Vec<String>) {
+    let cwd = PathBuf::from(&app.cwd_raw);
+    let mut spans: Vec<Span> = Vec::new();
+    let mut images = Vec::new();
+    let mut unattached = Vec::new();
+    let mut total = 0;
+    for (_, _, token) in mention::find_mention_spans(text) {
+        let Some(target) = resolve_mention(app, &token) else {
+            continue;
+        };
+        let mention = token.trim_end_matches(TRAILING_PUNCTUATION);
+        let mode = app
+            .attachments
+            .take_mention_mode(mention)
+            .unwrap_or_else(|| target.default_mode(support));
+        if mode == AttachMode::Inline {
+            continue;
+        }
+        let found = match target {
+            Target::File { abs_path, .. } if support.embedded_context => {
+                let path = abs_path.to_string_lossy();
+                read_span(&cwd, &path, 1, usize::MAX, mention).into_iter().collect()
+            }
+            Target::Spans(found) if support.embedded_context => found,
+            Target::Image { abs_path, mime_type, .. } if support.images => {
+                match image_chunk(&abs_path, mime_type) {
+                    Some(chunk) => images.push(chunk),
+                    None => unattached.push(format!("@{mention}")),
+                }
+                continue;
+            }
+            _ => Vec::new(),
+        };
+        if found.is_empty() {
+            unattached.push(format!("@{mention}"));
+        }
+        for span in found {
+            let duplicate = spans.iter().any(|known| {
+                known.abs_path == span.abs_path
+                    && known.start_line == span.start_line
+                    && known.end_line == span.end_line
+            });
+            if duplicate {
+                continue;
+            }
+            if total + span.text.len() > MAX_TOTAL_BYTES {
+                unattached.push(format!("@{mention}"));
+                break;
+            }
+            total += span.text.len();
+            spans.push(span);
+        }
+    }
+    unattached.dedup();
+    let mut chunks: Vec<PromptChunk> = spans.iter().map(Span::to_chunk).collect();
+    chunks.extend(images);
+    (chunks, unattached)
+}
+
+/// Prompt chunks for `text`: the text itself, then the context attached to
+/// it. Paste blocks switched to attached in the tray leave the text for a
+/// `[Pasted Text N]` reference and follow as their own chunk.
+pub(super) fn prompt_chunks(
+    app: &mut App,
+    mut text: String,
+    support: PromptSupport,
+) -> Vec<PromptChunk> {
+    let mut context = Vec::new();
+    if support.embedded_context {
+        for (n, content) in app.attachments.take_attached_pastes(&text).into_iter().enumerate() {
+            let label = format!("[Pasted Text {}]", n + 1);
+            if let Some(start) = text.find(&content) {
+                text.replace_range(start..start + content.len(), &label);
+            }
+            context.push(PromptChunk {
+                kind: "resource".to_owned(),
+                value: serde_json::json!({
+                    "uri": format!("paste:{}", n + 1),
+                    "mention": label,
+                    "text": content,
+                }),
+            });
+        }
+    }
+    let (mentions, unattached) = mention_chunks(app, &text, support);
+    if !unattached.is_empty() {
+        app.show_toast(format!("Not attached: {}", unattached.join(", ")), true);
+    }
+    let mut chunks =
+        vec![PromptChunk { kind: "text".to_owned(), value: serde_json::Value::String(text) }];
+    chunks.extend(context);
+    chunks.extend(mentions);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ref_reads_ranges_and_symbols() {
+        let lines =
+            |path: &str, start, end| MentionRef::Lines { path: path.to_owned(), start, end };
+        assert_eq!(
+            parse_ref("src/app/state.rs:120-180"),
+            Some(lines("src/app/state.rs", 120, 180))
+        );
+        assert_eq!(parse_ref("main.rs:7,"), Some(lines("main.rs", 7, 7)));
+        assert_eq!(parse_ref("main.rs:0-3"), None);
+        assert_eq!(parse_ref("main.rs:9-3"), None);
+        assert_eq!(
+            parse_ref("App::render."),
+            Some(MentionRef::Symbol(vec!["App".to_owned(), "render".to_owned()]))
+        );
+        assert_eq!(
+            parse_ref("Server.Start"),
+            Some(MentionRef::Symbol(vec!["Server".to_owned(), "Start".to_owned()]))
+        );
+        assert_eq!(parse_ref("src/lib.rs"), None);
+        assert_eq!(parse_ref("some-handle"), None);
+    }
+
+    #[test]
+    fn prompt_chunks_embed_ranges_and_symbols_only_when_supported() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        let body = (1..=10).map(|n| format!("line {n}")).collect::<Vec<_>>().join("\n");
+        std::fs::write(dir.path().join("src/notes.txt"), body).expect("write");
+        std::fs::write(
+            dir.path().join("src/app.rs"),
+            "struct App;\n\nimpl App {\n    fn render(&self) {\n        draw();\n    }\n}\n",
+        )
+        .expect("write");
+        let mut app = App::test_default();
+        app.cwd_raw = dir.path().to_string_lossy().into_owned();
+        let text = "see @src/notes.txt:3-4 and @App::render, not @src/app.rs or @Missing::thing";
+
+        assert_eq!(prompt_chunks(&mut app, text.to_owned(), PromptSupport::default()).len(), 1);
+
+        let support = PromptSupport { embedded_context: true, images: false };
+        let chunks = prompt_chunks(&mut app, text.to_owned(), support);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].value, serde_json::Value::String(text.to_owned()));
+        assert_eq!(chunks[1].kind, "resource");
+        assert_eq!(chunks[1].value["text"], "line 3\nline 4\n");
+        assert_eq!(chunks[1].value["path"], "src/notes.txt");
+        assert!(chunks[1].value["uri"].as_str().is_some_and(|uri| uri.ends_with("notes.txt#L3-4")));
+        assert_eq!(chunks[2].value["mention"], "@App::render");
+        assert_eq!(chunks[2].value["text"], "    fn render(&self) {\n        draw();\n    }\n");
+        assert_eq!(chunks[2].value["start_line"], 4);
+        assert!(app.toast.as_ref().is_some_and(|toast| toast.text.contains("@Missing::thing")));
+    }
+
+    #[test]
+    fn image_limit_applies_to_the_encoded_size() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let small = dir.path().join("small.png");
+        let large = dir.path().join("large.png");
+        std::fs::write(&small, vec![0_u8; 1024]).expect("write");
+        // Under the limit raw, over it once base64 adds a third.
+        std::fs::write(&large, vec![0_u8; 4 * 1024 * 1024]).expect("write");
+
+        assert!(image_chunk(&small, "image/png").is_some());
+        assert!(image_chunk(&large, "image/png").is_none());
+        assert_eq!(encoded_len(3), 4);
+        assert_eq!(encoded_len(4), 8);
+    }
+
+    #[test]
+    fn long_ranges_are_cut_and_flagged() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let body = (1..=MAX_SPAN_LINES + 10).map(|n| n.to_string()).collect::<Vec<_>>().join("\n");
+        std::fs::write(dir.path().join("big.txt"), body).expect("write");
+        let span =
+            read_span(dir.path(), "big.txt", 5, MAX_SPAN_LINES + 9, "big.txt:5-").expect("span");
+        assert!(span.truncated);
+        assert_eq!(span.end_line, MAX_SPAN_LINES + 4);
+        assert!(
+            read_span(dir.path(), "big.txt", MAX_SPAN_LINES + 20, MAX_SPAN_LINES + 30, "x")
+                .is_none()
+        );
+    }
+}
@@ -14,23 +14,46 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub(crate) mod attachments;
+pub(crate) mod branch;
+pub(crate) mod budget;
 mod cache_policy;
+pub(crate) mod chat_nav;
+pub(crate) mod clipboard;
 mod connect;
+pub(crate) mod custom_commands;
 mod dialog;
+pub(crate) mod draft;
 mod events;
+pub(crate) mod external;
+pub(crate) mod file_index;
 mod focus;
+pub(crate) mod frecency;
 pub(crate) mod input;
 mod input_submit;
 mod keys;
+pub mod ledger;
 pub(crate) mod mention;
+pub(crate) mod mention_context;
+pub(crate) mod notify;
+mod osc;
+pub(crate) mod overlay;
 pub(crate) mod paste_burst;
 mod permissions;
+pub(crate) mod queue;
+pub(crate) mod search;
 mod selection;
+pub(crate) mod shell;
 pub(crate) mod slash;
 mod state;
+pub(crate) mod subagents;
+pub(crate) mod tabs;
+pub(crate) mod tags;
 mod terminal;
+pub(crate) mod title;
 mod todos;
 mod update_check;
+pub(crate) mod vim;
 
 // Re-export all public types so `crate::app::App`, `crate::app::BlockCache`, etc. still work.
 pub use cache_policy::{
@@ -43,19 +66,17 @@ pub use focus::{FocusManager, FocusOwner, FocusTarget};
 pub use input::InputState;
 pub(crate) use selection::normalize_selection;
 pub use state::{
-    App, AppStatus, BlockCache, CancelOrigin, ChatMessage, ChatViewport, HelpView,
+    App, AppStatus, BlockCache, CancelOrigin, ChatMessage, ChatViewport, CompactionBlock, HelpView,
     IncrementalMarkdown, InlinePermission, LoginHint, MessageBlock, MessageRole, MessageUsage,
     ModeInfo, ModeState, PasteSessionState, RecentSessionInfo, SelectionKind, SelectionPoint,
-    SelectionState, SessionUsageState, TerminalSnapshotMode, TodoItem, TodoStatus, ToolCallInfo,
-    ToolCallScope, WelcomeBlock, is_execute_tool_name,
+    SelectionState, SessionUsageState, TerminalSnapshotMode, Toast, TodoItem, TodoStatus,
+    ToolCallInfo, ToolCallScope, WelcomeBlock, is_execute_tool_name,
 };
+pub use subagents::{SubagentChild, SubagentTree, SubagentView};
 pub use update_check::start_update_check;
 
 use crate::agent::model;
-use crossterm::event::{
-    EventStream, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
-    PushKeyboardEnhancementFlags,
-};
+use crossterm::event::EventStream;
 use futures::{FutureExt as _, StreamExt};
 use std::time::{Duration, Instant};
 
@@ -69,18 +90,9 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
     let mut os_shutdown = Box::pin(wait_for_shutdown_signal());
 
     // Enable bracketed paste and mouse capture (ignore error on unsupported terminals)
-    let _ = crossterm::execute!(
-        std::io::stdout(),
-        crossterm::event::EnableBracketedPaste,
-        crossterm::event::EnableMouseCapture,
-        crossterm::event::EnableFocusChange,
-        // Enable enhanced keyboard protocol for reliable modifier detection (e.g. Shift+Enter)
-        PushKeyboardEnhancementFlags(
-            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
-                | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
-        )
-    );
+    external::enable_input_modes();
+
+    crate::ui::graphics::init();
 
     let mut events = EventStream::new();
     let tick_duration = Duration::from_millis(16);
@@ -149,6 +161,17 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
             finalize_deferred_submit(app);
         }
         app.drain_key_count = 0;
+        attachments::refresh(app);
+        draft::autosave(app, Instant::now());
+        frecency::autosave(app, Instant::now());
+
+        // Hand the terminal to an external program. The event stream goes
+        // first: its reader thread would otherwise swallow the child's input.
+        if let Some(job) = app.suspended.take() {
+            drop(events);
+            external::run_suspended(app, job);
+            events = EventStream::new();
+        }
 
         if app.should_quit {
             break;
@@ -174,12 +197,18 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
         if terminal::update_terminal_outputs(app) {
             app.needs_redraw = true;
         }
+        if app.expire_toast(Instant::now()) {
+            app.needs_redraw = true;
+        }
         if app.force_redraw {
             terminal.clear()?;
+            app.inline_images.invalidate();
+            app.hyperlinks.invalidate();
             app.force_redraw = false;
             app.needs_redraw = true;
         }
         if app.needs_redraw {
+            title::update(app);
             if let Some(ref mut perf) = app.perf {
                 perf.next_frame();
             }
@@ -190,7 +219,18 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
             {
                 let timer = app.perf.as_ref().map(|p| p.start("frame_total"));
                 let draw_timer = app.perf.as_ref().map(|p| p.start("frame::terminal_draw"));
-                terminal.draw(|f| crate::ui::render(f, app))?;
+                let completed = terminal.draw(|f| crate::ui::render(f, app))?;
+                // Cell-based image protocols leave stale pixels behind when images
+                // move, so those need a clean repaint before drawing them again.
+                if crate::ui::graphics::present(app, &mut std::io::stdout())? {
+                    terminal.clear()?;
+                    app.hyperlinks.invalidate();
+                    let completed = terminal.draw(|f| crate::ui::render(f, app))?;
+                    crate::ui::graphics::present(app, &mut std::io::stdout())?;
+                    crate::ui::hyperlink::present(app, completed.buffer, &mut std::io::stdout())?;
+                } else {
+                    crate::ui::hyperlink::present(app, completed.buffer, &mut std::io::stdout())?;
+                }
                 drop(draw_timer);
                 drop(timer);
             }
@@ -201,6 +241,9 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
 
     // --- Graceful shutdown ---
 
+    draft::close(app);
+    frecency::close(app);
+
     // Dismiss all pending inline permissions (reject via last option)
     for tool_id in std::mem::take(&mut app.pending_permission_ids) {
         if let Some((mi, bi)) = app.tool_call_index.get(&tool_id).copied()
@@ -229,13 +272,9 @@ pub async fn run_tui(app: &mut App) -> anyhow::Result<()> {
     }
 
     // Restore terminal
-    let _ = crossterm::execute!(
-        std::io::stdout(),
-        crossterm::event::DisableBracketedPaste,
-        crossterm::event::DisableMouseCapture,
-        crossterm::event::DisableFocusChange,
-        PopKeyboardEnhancementFlags
-    );
+    title::restore(app);
+    let _ = crate::ui::graphics::clear_all(&mut std::io::stdout());
+    external::disable_input_modes();
     ratatui::restore();
 
     Ok(())
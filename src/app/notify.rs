@@ -0,0 +1,258 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Desktop notifications while the terminal is unfocused.
+//!
+//! Focus comes from crossterm focus events (`EnableFocusChange` is on for the
+//! whole session). Only turn completion, turn errors and new permission
+//! prompts notify, and only when the user is looking elsewhere.
+
+use super::App;
+use crate::Cli;
+use std::process::Stdio;
+
+const METHOD_ENV: &str = "CLAUDE_RS_NOTIFY";
+const EVENTS_ENV: &str = "CLAUDE_RS_NOTIFY_ON";
+const COMMAND_ENV: &str = "CLAUDE_RS_NOTIFY_COMMAND";
+
+/// Longest notification body sent to the terminal or the command.
+const MAX_BODY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotifyMethod {
+    /// `OSC 9` (iTerm2, `WezTerm`, kitty, Ghostty, Windows Terminal).
+    #[default]
+    Osc9,
+    /// `OSC 777;notify` (urxvt, foot, VTE-based terminals).
+    Osc777,
+    /// Plain BEL; most terminals flag the window or tab.
+    Bell,
+    Off,
+}
+
+impl NotifyMethod {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "osc9" | "9" => Some(Self::Osc9),
+            "osc777" | "777" => Some(Self::Osc777),
+            "bell" | "bel" => Some(Self::Bell),
+            "off" | "none" | "0" | "false" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    TurnComplete,
+    TurnError,
+    PermissionRequest,
+}
+
+/// Which events notify. All are on by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotifyEvents {
+    pub turn_complete: bool,
+    pub turn_error: bool,
+    pub permission: bool,
+}
+
+impl Default for NotifyEvents {
+    fn default() -> Self {
+        Self { turn_complete: true, turn_error: true, permission: true }
+    }
+}
+
+impl NotifyEvents {
+    const NONE: Self = Self { turn_complete: false, turn_error: false, permission: false };
+
+    /// Parse a comma-separated list; unknown names are ignored.
+    fn parse(value: &str) -> Self {
+        let mut events = Self::NONE;
+        for name in value.split(',').map(|n| n.trim().to_ascii_lowercase()) {
+            match name.as_str() {
+                "all" => events = Self::default(),
+                "complete" | "done" | "turn-complete" => events.turn_complete = true,
+                "error" | "turn-error" => events.turn_error = true,
+                "permission" | "permissions" => events.permission = true,
+                "" | "none" => {}
+                other => tracing::warn!("unknown notification event: {other}"),
+            }
+        }
+        events
+    }
+
+    #[must_use]
+    pub fn enabled(self, event: NotifyEvent) -> bool {
+        match event {
+            NotifyEvent::TurnComplete => self.turn_complete,
+            NotifyEvent::TurnError => self.turn_error,
+            NotifyEvent::PermissionRequest => self.permission,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotifyConfig {
+    pub method: NotifyMethod,
+    pub events: NotifyEvents,
+    /// Program and leading arguments; title and body are appended.
+    pub command: Option<Vec<String>>,
+}
+
+impl NotifyConfig {
+    /// CLI flags win; each setting falls back to its `CLAUDE_RS_NOTIFY*` env var.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self::resolve(cli, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(cli: &Cli, env: impl Fn(&str) -> Option<String>) -> Self {
+        let setting = |flag: &Option<String>, name: &str| flag.clone().or_else(|| env(name));
+        let method = setting(&cli.notify, METHOD_ENV).map_or(NotifyMethod::default(), |v| {
+            NotifyMethod::parse(&v).unwrap_or_else(|| {
+                tracing::warn!("unknown notification method: {v}");
+                NotifyMethod::default()
+            })
+        });
+        let events = setting(&cli.notify_on, EVENTS_ENV)
+            .map_or_else(NotifyEvents::default, |v| NotifyEvents::parse(&v));
+        let command = setting(&cli.notify_command, COMMAND_ENV).and_then(|v| {
+            let argv = super::external::split_command(&v);
+            if argv.is_none() && !v.trim().is_empty() {
+                tracing::warn!("cannot parse notification command: {v}");
+            }
+            argv
+        });
+        Self { method, events, command }
+    }
+}
+
+/// Collapse control characters (which would end the escape sequence early)
+/// and bound the length.
+pub(super) fn sanitize(text: &str) -> String {
+    let mut out: String =
+        text.chars().map(|c| if c.is_control() { ' ' } else { c }).take(MAX_BODY_CHARS).collect();
+    if text.chars().count() > MAX_BODY_CHARS {
+        out.push('\u{2026}');
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escape sequence for `method`, or `None` when it writes nothing.
+fn escape_sequence(method: NotifyMethod, title: &str, body: &str) -> Option<String> {
+    let title = sanitize(title);
+    let body = sanitize(body);
+    match method {
+        NotifyMethod::Osc9 => Some(format!("\x1b]9;{title}: {body}\x07")),
+        // `;` separates the title from the body in OSC 777.
+        NotifyMethod::Osc777 => {
+            Some(format!("\x1b]777;notify;{};{body}\x07", title.replace(';', ",")))
+        }
+        NotifyMethod::Bell => Some("\x07".to_owned()),
+        NotifyMethod::Off => None,
+    }
+}
+
+fn notification_title(app: &App) -> String {
+    let project = std::path::Path::new(&app.cwd_raw)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if project.is_empty() { "claude-rs".to_owned() } else { format!("claude-rs: {project}") }
+}
+
+/// Notify about `event` if the terminal is unfocused and the event is enabled.
+pub(super) fn notify(app: &App, event: NotifyEvent, body: &str) {
+    if app.terminal_focused || !app.notify.events.enabled(event) {
+        return;
+    }
+    let title = notification_title(app);
+    if let Some(sequence) = escape_sequence(app.notify.method, &title, body)
+        && let Err(e) = super::osc::write(&sequence)
+    {
+        tracing::warn!("failed to write notification: {e}");
+    }
+    if let Some((program, args)) = app.notify.command.as_deref().and_then(<[_]>::split_first) {
+        // Not awaited: tokio reaps the child in the background.
+        let spawned = tokio::process::Command::new(program)
+            .args(args)
+            .arg(&title)
+            .arg(sanitize(body))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(e) = spawned {
+            tracing::warn!("failed to run notification command {program}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser as _;
+
+    fn resolve(args: &[&str], env: &[(&str, &str)]) -> NotifyConfig {
+        let cli = Cli::parse_from(std::iter::once("claude-rs").chain(args.iter().copied()));
+        NotifyConfig::resolve(&cli, |name| {
+            env.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_owned())
+        })
+    }
+
+    #[test]
+    fn config_reads_flags_before_env() {
+        assert_eq!(resolve(&[], &[]), NotifyConfig::default());
+
+        let config = resolve(
+            &["--notify", "bell"],
+            &[
+                (METHOD_ENV, "osc777"),
+                (EVENTS_ENV, "permission, error"),
+                (COMMAND_ENV, "notify-send -a \"Claude RS\""),
+            ],
+        );
+        assert_eq!(config.method, NotifyMethod::Bell);
+        assert_eq!(
+            config.events,
+            NotifyEvents { turn_complete: false, turn_error: true, permission: true }
+        );
+        assert_eq!(
+            config.command.as_deref(),
+            Some(&["notify-send".to_owned(), "-a".to_owned(), "Claude RS".to_owned()][..])
+        );
+
+        let config = resolve(&["--notify-on", "none"], &[(METHOD_ENV, "nonsense")]);
+        assert_eq!(config.method, NotifyMethod::Osc9);
+        assert!(!config.events.enabled(NotifyEvent::TurnComplete));
+    }
+
+    #[test]
+    fn escape_sequences_strip_control_characters() {
+        assert_eq!(
+            escape_sequence(NotifyMethod::Osc9, "claude-rs: app", "Turn failed:\n\x1b[31mboom\x07")
+                .as_deref(),
+            Some("\x1b]9;claude-rs: app: Turn failed: [31mboom\x07")
+        );
+        assert_eq!(
+            escape_sequence(NotifyMethod::Osc777, "a;b", "Permission: Bash").as_deref(),
+            Some("\x1b]777;notify;a,b;Permission: Bash\x07")
+        );
+        assert_eq!(escape_sequence(NotifyMethod::Bell, "t", "b").as_deref(), Some("\x07"));
+        assert_eq!(escape_sequence(NotifyMethod::Off, "t", "b"), None);
+    }
+}
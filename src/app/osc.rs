@@ -0,0 +1,53 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Raw OSC sequences written outside of ratatui's frame (notifications,
+//! clipboard), wrapped for tmux when needed.
+
+use std::io::{self, Write as _};
+
+/// Wrap an escape sequence in tmux's DCS passthrough so it reaches the outer
+/// terminal (needs `allow-passthrough on` in tmux 3.3+).
+#[must_use]
+pub fn tmux_passthrough(sequence: &str) -> String {
+    format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+#[must_use]
+pub fn in_tmux() -> bool {
+    std::env::var_os("TMUX").is_some_and(|v| !v.is_empty())
+}
+
+/// Write `sequence` to stdout, through tmux passthrough when running in tmux.
+pub fn write(sequence: &str) -> io::Result<()> {
+    let mut out = io::stdout();
+    if in_tmux() && sequence.starts_with('\x1b') {
+        out.write_all(tmux_passthrough(sequence).as_bytes())?;
+    } else {
+        out.write_all(sequence.as_bytes())?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tmux_passthrough_doubles_escapes() {
+        assert_eq!(tmux_passthrough("\x1b]9;hi\x07"), "\x1bPtmux;\x1b\x1b]9;hi\x07\x1b\\");
+    }
+}
@@ -0,0 +1,96 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::App;
+use super::ledger::UsageReport;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Rows moved by PageUp/PageDown.
+const PAGE_ROWS: usize = 10;
+
+/// Read-only report drawn over the chat body (opened by slash commands and the
+/// attachments tray).
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayKind {
+    Usage(Box<UsageReport>),
+    /// Reads the live `session_usage`, so it stays current while open.
+    Context,
+    /// Content of one attachment.
+    Preview {
+        title: String,
+        lines: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlay {
+    pub kind: OverlayKind,
+    /// First visible row; clamped by the renderer.
+    pub scroll: usize,
+}
+
+pub(super) fn open(app: &mut App, kind: OverlayKind) {
+    app.overlay = Some(Overlay { kind, scroll: 0 });
+    app.needs_redraw = true;
+}
+
+pub(super) fn close(app: &mut App) {
+    if app.overlay.take().is_some() {
+        app.needs_redraw = true;
+    }
+}
+
+/// Scroll and dismiss keys while an overlay is open. Other keys fall through so
+/// typing into the input keeps working. Returns `true` when consumed.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    let Some(overlay) = app.overlay.as_mut() else {
+        return false;
+    };
+    if key.modifiers != KeyModifiers::NONE {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc => {
+            close(app);
+            return true;
+        }
+        KeyCode::Up => overlay.scroll = overlay.scroll.saturating_sub(1),
+        KeyCode::Down => overlay.scroll = overlay.scroll.saturating_add(1),
+        KeyCode::PageUp => overlay.scroll = overlay.scroll.saturating_sub(PAGE_ROWS),
+        KeyCode::PageDown => overlay.scroll = overlay.scroll.saturating_add(PAGE_ROWS),
+        KeyCode::Home => overlay.scroll = 0,
+        _ => return false,
+    }
+    app.needs_redraw = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn navigation_keys_scroll_and_esc_closes() {
+        let mut app = App::test_default();
+        open(&mut app, OverlayKind::Usage(Box::default()));
+
+        assert!(handle_key(&mut app, KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE)));
+        assert_eq!(app.overlay.as_ref().map(|o| o.scroll), Some(PAGE_ROWS));
+        assert!(!handle_key(&mut app, KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(handle_key(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(app.overlay.is_none());
+    }
+}
@@ -418,6 +418,7 @@ fn respond_permission(app: &mut App, override_index: Option<usize>) {
     }
     if invalidated {
         app.mark_message_layout_dirty(mi);
+        super::subagents::sync_child(app, &tool_id);
     }
 
     // Focus the next permission in the queue (now at index 0), if any.
@@ -450,6 +451,7 @@ fn respond_permission_cancel(app: &mut App) {
         ));
         tc.mark_tool_call_layout_dirty();
         app.mark_message_layout_dirty(mi);
+        super::subagents::sync_child(app, &tool_id);
     }
 
     set_permission_focused(app, 0, true);
@@ -494,6 +496,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         }
     }
 
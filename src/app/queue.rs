@@ -0,0 +1,348 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ordered queue of submissions typed while a turn is still running.
+
+use super::{App, AppStatus, CancelOrigin, FocusOwner, FocusTarget};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// When a queued submission is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueueDelivery {
+    /// Wait for the running turn to finish.
+    #[default]
+    AfterTurn,
+    /// Cancel the running turn and send this next.
+    Interrupt,
+}
+
+impl QueueDelivery {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::AfterTurn => "after turn",
+            Self::Interrupt => "interrupt",
+        }
+    }
+
+    #[must_use]
+    const fn toggled(self) -> Self {
+        match self {
+            Self::AfterTurn => Self::Interrupt,
+            Self::Interrupt => Self::AfterTurn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedSubmission {
+    pub text: String,
+    pub delivery: QueueDelivery,
+}
+
+/// Queued item loaded into the input for editing, and the draft it displaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueEdit {
+    pub index: usize,
+    pub stashed_draft: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionQueue {
+    pub items: Vec<QueuedSubmission>,
+    /// Highlighted row while the panel has focus.
+    pub selected: usize,
+    pub editing: Option<QueueEdit>,
+}
+
+impl SubmissionQueue {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn is_editing(&self, index: usize) -> bool {
+        self.editing.as_ref().is_some_and(|edit| edit.index == index)
+    }
+
+    /// Next item to send: the first interrupting item, else the oldest one.
+    /// The item being edited is never picked.
+    fn next_index(&self) -> Option<usize> {
+        let sendable = |(i, _): &(usize, &QueuedSubmission)| !self.is_editing(*i);
+        let mut candidates = self.items.iter().enumerate().filter(sendable);
+        let first = candidates.next()?;
+        if first.1.delivery == QueueDelivery::Interrupt {
+            return Some(first.0);
+        }
+        Some(
+            candidates
+                .find(|(_, item)| item.delivery == QueueDelivery::Interrupt)
+                .map_or(first.0, |(i, _)| i),
+        )
+    }
+
+    fn remove(&mut self, index: usize) -> QueuedSubmission {
+        let item = self.items.remove(index);
+        if let Some(edit) = self.editing.as_mut()
+            && edit.index > index
+        {
+            edit.index -= 1;
+        }
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        item
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.items.swap(a, b);
+        if let Some(edit) = self.editing.as_mut() {
+            if edit.index == a {
+                edit.index = b;
+            } else if edit.index == b {
+                edit.index = a;
+            }
+        }
+    }
+}
+
+fn is_turn_busy(app: &App) -> bool {
+    matches!(app.status, AppStatus::Thinking | AppStatus::Running)
+}
+
+pub(super) fn push(app: &mut App, text: String, delivery: QueueDelivery) {
+    app.submission_queue.items.push(QueuedSubmission { text, delivery });
+    if delivery == QueueDelivery::Interrupt {
+        interrupt_running_turn(app);
+    }
+    app.needs_redraw = true;
+}
+
+/// Remove and return the next submission to dispatch, if any.
+pub(super) fn take_next(app: &mut App) -> Option<String> {
+    let index = app.submission_queue.next_index()?;
+    let item = app.submission_queue.remove(index);
+    if app.submission_queue.is_empty() {
+        app.release_focus_target(FocusTarget::Queue);
+    }
+    app.needs_redraw = true;
+    Some(item.text)
+}
+
+/// Drop every queued item, putting back the draft displaced by an edit. The
+/// input then holds the item being edited, which goes away with the queue.
+pub(super) fn clear(app: &mut App) {
+    if let Some(edit) = app.submission_queue.editing.take() {
+        app.input.set_text(&edit.stashed_draft);
+        app.needs_redraw = true;
+    }
+    app.submission_queue = SubmissionQueue::default();
+    app.release_focus_target(FocusTarget::Queue);
+}
+
+fn interrupt_running_turn(app: &mut App) {
+    if !is_turn_busy(app) {
+        return;
+    }
+    if let Err(message) = super::input_submit::request_cancel(app, CancelOrigin::AutoQueue) {
+        tracing::error!("Failed to interrupt turn for queued submission: {message}");
+    }
+}
+
+/// Toggle keyboard focus on the queue panel (Alt+Q).
+pub(super) fn toggle_focus(app: &mut App) -> bool {
+    if app.submission_queue.is_empty() {
+        return false;
+    }
+    if app.focus_owner() == FocusOwner::Queue {
+        app.release_focus_target(FocusTarget::Queue);
+    } else {
+        app.claim_focus_target(FocusTarget::Queue);
+    }
+    true
+}
+
+/// Keys while the queue panel has focus. Returns `true` when consumed; other
+/// keys fall through to the input.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    let len = app.submission_queue.len();
+    if len == 0 {
+        return false;
+    }
+    let selected = app.submission_queue.selected.min(len - 1);
+    let queue = &mut app.submission_queue;
+    match (key.code, key.modifiers) {
+        (KeyCode::Esc, _) => app.release_focus_target(FocusTarget::Queue),
+        (KeyCode::Up, KeyModifiers::NONE) => queue.selected = selected.saturating_sub(1),
+        (KeyCode::Down, KeyModifiers::NONE) => queue.selected = (selected + 1).min(len - 1),
+        (KeyCode::Up, KeyModifiers::SHIFT) if selected > 0 => {
+            queue.swap(selected, selected - 1);
+            queue.selected = selected - 1;
+        }
+        (KeyCode::Down, KeyModifiers::SHIFT) if selected + 1 < len => {
+            queue.swap(selected, selected + 1);
+            queue.selected = selected + 1;
+        }
+        (KeyCode::Up | KeyCode::Down, KeyModifiers::SHIFT) => {}
+        (KeyCode::Delete | KeyCode::Backspace | KeyCode::Char('d'), KeyModifiers::NONE) => {
+            if queue.is_editing(selected) {
+                return true;
+            }
+            queue.remove(selected);
+            if queue.is_empty() {
+                app.release_focus_target(FocusTarget::Queue);
+            }
+        }
+        (KeyCode::Char('i'), KeyModifiers::NONE) => {
+            let item = &mut queue.items[selected];
+            item.delivery = item.delivery.toggled();
+            if item.delivery == QueueDelivery::Interrupt {
+                interrupt_running_turn(app);
+            }
+        }
+        (KeyCode::Enter | KeyCode::Char('e'), KeyModifiers::NONE) => begin_edit(app, selected),
+        _ => return false,
+    }
+    app.needs_redraw = true;
+    true
+}
+
+/// Load a queued item into the input. The current draft is stashed and put back
+/// when the edit is saved or cancelled.
+fn begin_edit(app: &mut App, index: usize) {
+    if app.submission_queue.editing.is_some() {
+        return;
+    }
+    let Some(item) = app.submission_queue.items.get(index) else {
+        return;
+    };
+    let text = item.text.clone();
+    let stashed_draft = app.input.text();
+    app.input.set_text(&text);
+    app.submission_queue.editing = Some(QueueEdit { index, stashed_draft });
+    app.release_focus_target(FocusTarget::Queue);
+}
+
+/// Save the submitted text into the item being edited. An empty edit deletes
+/// the item. Returns `false` when no edit is in progress.
+pub(super) fn finish_edit(app: &mut App, text: String) -> bool {
+    let Some(edit) = app.submission_queue.editing.take() else {
+        return false;
+    };
+    if text.trim().is_empty() {
+        app.submission_queue.remove(edit.index);
+    } else if let Some(item) = app.submission_queue.items.get_mut(edit.index) {
+        item.text = text;
+    }
+    app.input.set_text(&edit.stashed_draft);
+    app.needs_redraw = true;
+    super::input_submit::drain_queued_submission(app);
+    true
+}
+
+/// Abandon an in-progress edit, keeping the queued text unchanged.
+pub(super) fn cancel_edit(app: &mut App) -> bool {
+    let Some(edit) = app.submission_queue.editing.take() else {
+        return false;
+    };
+    app.input.set_text(&edit.stashed_draft);
+    app.needs_redraw = true;
+    super::input_submit::drain_queued_submission(app);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn texts(app: &App) -> Vec<&str> {
+        app.submission_queue.items.iter().map(|i| i.text.as_str()).collect()
+    }
+
+    #[test]
+    fn interrupting_item_is_sent_before_older_items() {
+        let mut app = App::test_default();
+        push(&mut app, "first".into(), QueueDelivery::AfterTurn);
+        push(&mut app, "urgent".into(), QueueDelivery::Interrupt);
+        push(&mut app, "last".into(), QueueDelivery::AfterTurn);
+
+        assert_eq!(take_next(&mut app).as_deref(), Some("urgent"));
+        assert_eq!(take_next(&mut app).as_deref(), Some("first"));
+        assert_eq!(texts(&app), vec!["last"]);
+    }
+
+    #[test]
+    fn panel_keys_reorder_toggle_and_delete() {
+        let mut app = App::test_default();
+        push(&mut app, "a".into(), QueueDelivery::AfterTurn);
+        push(&mut app, "b".into(), QueueDelivery::AfterTurn);
+        push(&mut app, "c".into(), QueueDelivery::AfterTurn);
+        assert!(toggle_focus(&mut app));
+        assert_eq!(app.focus_owner(), FocusOwner::Queue);
+
+        assert!(handle_key(&mut app, key(KeyCode::Down, KeyModifiers::NONE)));
+        assert!(handle_key(&mut app, key(KeyCode::Up, KeyModifiers::SHIFT)));
+        assert_eq!(texts(&app), vec!["b", "a", "c"]);
+        assert_eq!(app.submission_queue.selected, 0);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('i'), KeyModifiers::NONE)));
+        assert_eq!(app.submission_queue.items[0].delivery, QueueDelivery::Interrupt);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('d'), KeyModifiers::NONE)));
+        assert_eq!(texts(&app), vec!["a", "c"]);
+        assert!(!handle_key(&mut app, key(KeyCode::Char('x'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn edit_round_trips_through_input_and_restores_draft() {
+        let mut app = App::test_default();
+        app.status = AppStatus::Running;
+        push(&mut app, "fix the tests".into(), QueueDelivery::AfterTurn);
+        app.input.set_text("half-typed draft");
+        toggle_focus(&mut app);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('e'), KeyModifiers::NONE)));
+        assert_eq!(app.input.text(), "fix the tests");
+        assert_eq!(app.focus_owner(), FocusOwner::Input);
+        assert_eq!(take_next(&mut app), None, "item under edit is not sent");
+
+        assert!(finish_edit(&mut app, "fix the flaky tests".into()));
+        assert_eq!(texts(&app), vec!["fix the flaky tests"]);
+        assert_eq!(app.input.text(), "half-typed draft");
+        assert!(!cancel_edit(&mut app));
+    }
+
+    #[test]
+    fn clear_during_an_edit_restores_the_draft() {
+        let mut app = App::test_default();
+        app.status = AppStatus::Running;
+        push(&mut app, "fix the tests".into(), QueueDelivery::AfterTurn);
+        app.input.set_text("half-typed draft");
+        toggle_focus(&mut app);
+        assert!(handle_key(&mut app, key(KeyCode::Char('e'), KeyModifiers::NONE)));
+
+        clear(&mut app);
+        assert!(app.submission_queue.is_empty());
+        assert_eq!(app.input.text(), "half-typed draft");
+    }
+}
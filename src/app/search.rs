@@ -0,0 +1,467 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversation search (Ctrl+F).
+//!
+//! Matching messages are found here from their source text, tool titles and
+//! tool output. Hit rows depend on wrapping, so the chat renderer fills in
+//! `hits` and performs the scroll for `pending_jump`.
+
+use super::{App, ChatMessage, FocusTarget, MessageBlock, ToolCallInfo};
+use crate::agent::model;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// One occurrence of the query in the rendered conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchHit {
+    pub message: usize,
+    /// Wrapped row of the match within the message.
+    pub row: usize,
+    /// Collapsed block whose content matches; the hit sits on its header and
+    /// selecting it expands the block.
+    pub collapsed_block: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchState {
+    pub query: String,
+    /// Typing the query; otherwise n/N browse the hits.
+    pub editing: bool,
+    /// Messages whose source text contains the query, ascending.
+    pub matching_messages: Vec<usize>,
+    /// `(message, block)` of collapsed blocks holding a match, ascending.
+    pub collapsed_matches: Vec<(usize, usize)>,
+    /// Hits in conversation order, filled in by the chat renderer.
+    pub hits: Vec<SearchHit>,
+    /// `hits` must be rebuilt before use.
+    pub hits_stale: bool,
+    /// [`content_version`] the matches were scanned at.
+    pub scanned_version: Option<u64>,
+    /// Width the hits were laid out for.
+    pub hits_width: u16,
+    /// Selected hit; `None` selects the newest once hits are known.
+    pub current: Option<usize>,
+    /// Scroll the selected hit into view on the next frame.
+    pub pending_jump: bool,
+}
+
+impl SearchState {
+    #[must_use]
+    pub fn is_match(&self, message: usize) -> bool {
+        self.matching_messages.binary_search(&message).is_ok()
+    }
+
+    /// Range of `hits` belonging to `message`.
+    #[must_use]
+    pub fn hits_for(&self, message: usize) -> std::ops::Range<usize> {
+        let start = self.hits.partition_point(|hit| hit.message < message);
+        let end = self.hits.partition_point(|hit| hit.message <= message);
+        start..end
+    }
+}
+
+/// Byte offsets of non-overlapping matches of `needle` in `haystack`, ignoring
+/// ASCII case. Offsets always fall on char boundaries.
+#[must_use]
+pub fn find_matches(haystack: &str, needle: &str) -> Vec<usize> {
+    let (hay, pat) = (haystack.as_bytes(), needle.as_bytes());
+    let mut found = Vec::new();
+    if pat.is_empty() || pat.len() > hay.len() {
+        return found;
+    }
+    let mut i = 0;
+    while i + pat.len() <= hay.len() {
+        if hay[i..i + pat.len()].eq_ignore_ascii_case(pat) {
+            found.push(i);
+            i += pat.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    !find_matches(haystack, needle).is_empty()
+}
+
+fn tool_call_matches(tc: &ToolCallInfo, needle: &str) -> bool {
+    if contains(&tc.title, needle)
+        || tc.terminal_command.as_deref().is_some_and(|c| contains(c, needle))
+        || tc.terminal_output.as_deref().is_some_and(|o| contains(o, needle))
+    {
+        return true;
+    }
+    tc.content.iter().any(|content| match content {
+        model::ToolCallContent::Content(model::Content {
+            content: model::ContentBlock::Text(text),
+        }) => contains(&text.text, needle),
+        model::ToolCallContent::Diff(diff) => {
+            contains(&diff.path.to_string_lossy(), needle) || contains(&diff.new_text, needle)
+        }
+        _ => false,
+    })
+}
+
+/// Whether `msg` contains `needle`, pushing the indices of collapsed blocks
+/// holding a match onto `collapsed`.
+fn scan_message(msg: &ChatMessage, needle: &str, collapsed: &mut Vec<usize>) -> bool {
+    let mut matched = false;
+    for (bi, block) in msg.blocks.iter().enumerate() {
+        match block {
+            MessageBlock::Text(text, ..) => matched |= contains(text, needle),
+            MessageBlock::ToolCall(tc) => {
+                if tc.hidden || !tool_call_matches(tc, needle) {
+                    continue;
+                }
+                matched = true;
+                if tc.collapsed {
+                    collapsed.push(bi);
+                }
+            }
+            MessageBlock::Compaction(block) => {
+                if !block.summary.as_deref().is_some_and(|s| contains(s, needle)) {
+                    continue;
+                }
+                matched = true;
+                if block.collapsed {
+                    collapsed.push(bi);
+                }
+            }
+            MessageBlock::Welcome(_) => {}
+        }
+    }
+    matched
+}
+
+/// Cheap fingerprint of everything the scan looks at: text lengths, tool-call
+/// epochs and collapse state. Changes whenever a message grows or is edited.
+pub(crate) fn content_version(messages: &[ChatMessage]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    messages.len().hash(&mut hasher);
+    for msg in messages {
+        msg.blocks.len().hash(&mut hasher);
+        for block in &msg.blocks {
+            match block {
+                MessageBlock::Text(text, ..) => text.len().hash(&mut hasher),
+                MessageBlock::ToolCall(tc) => {
+                    (tc.render_epoch, tc.layout_epoch, tc.collapsed, tc.hidden).hash(&mut hasher);
+                }
+                MessageBlock::Compaction(block) => {
+                    (block.summary.as_ref().map(String::len), block.collapsed).hash(&mut hasher);
+                }
+                MessageBlock::Welcome(_) => {}
+            }
+        }
+    }
+    hasher.finish()
+}
+
+/// Expand the collapsed block behind a selected hit so its matches show.
+pub(crate) fn reveal(app: &mut App, message: usize, block: usize) {
+    let Some(target) = app.messages.get_mut(message).and_then(|msg| msg.blocks.get_mut(block))
+    else {
+        return;
+    };
+    match target {
+        MessageBlock::ToolCall(tc) => {
+            tc.collapsed = false;
+            tc.mark_tool_call_layout_dirty();
+        }
+        MessageBlock::Compaction(block) => {
+            block.collapsed = false;
+            block.cache.invalidate();
+        }
+        MessageBlock::Text(..) | MessageBlock::Welcome(_) => return,
+    }
+    app.mark_message_layout_dirty(message);
+    rescan(app);
+}
+
+/// Open the search bar (Ctrl+F), keeping the previous query for editing.
+pub(super) fn open(app: &mut App) {
+    let search = app.search.get_or_insert_with(SearchState::default);
+    search.editing = true;
+    app.claim_focus_target(FocusTarget::Search);
+    app.needs_redraw = true;
+}
+
+/// Close the search bar and drop the highlights.
+pub(super) fn close(app: &mut App) {
+    if app.search.take().is_some() {
+        app.release_focus_target(FocusTarget::Search);
+        app.needs_redraw = true;
+    }
+}
+
+/// Re-run the query over every message and mark the hits stale, keeping the
+/// selection. The renderer calls this when the content version changed.
+pub(crate) fn rescan(app: &mut App) {
+    let Some(query) = app.search.as_ref().map(|s| s.query.clone()) else {
+        return;
+    };
+    let mut matching = Vec::new();
+    let mut collapsed_matches = Vec::new();
+    if !query.is_empty() {
+        let mut collapsed = Vec::new();
+        for (i, msg) in app.messages.iter().enumerate() {
+            collapsed.clear();
+            if scan_message(msg, &query, &mut collapsed) {
+                matching.push(i);
+                collapsed_matches.extend(collapsed.iter().map(|&bi| (i, bi)));
+            }
+        }
+    }
+    let version = content_version(&app.messages);
+    if let Some(search) = app.search.as_mut() {
+        search.matching_messages = matching;
+        search.collapsed_matches = collapsed_matches;
+        search.scanned_version = Some(version);
+        search.hits_stale = true;
+    }
+    app.needs_redraw = true;
+}
+
+/// Rescan after the query changed and jump to the newest hit.
+fn refresh(app: &mut App) {
+    rescan(app);
+    if let Some(search) = app.search.as_mut() {
+        search.hits.clear();
+        search.current = None;
+        search.pending_jump = !search.query.is_empty();
+    }
+}
+
+/// Select the next (`forward`) or previous hit, wrapping around.
+fn step(app: &mut App, forward: bool) {
+    let Some(search) = app.search.as_mut() else {
+        return;
+    };
+    let len = search.hits.len();
+    if len == 0 {
+        return;
+    }
+    let current = search.current.unwrap_or(len - 1).min(len - 1);
+    search.current = Some(if forward { (current + 1) % len } else { (current + len - 1) % len });
+    search.pending_jump = true;
+    app.needs_redraw = true;
+}
+
+/// Keys while the search bar has focus. Returns `true` when consumed; other
+/// keys close browsing and fall through to the input.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    let Some(search) = app.search.as_mut() else {
+        return false;
+    };
+    let plain = key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT;
+    if search.editing {
+        match key.code {
+            KeyCode::Esc => close(app),
+            KeyCode::Enter => {
+                search.editing = false;
+                if search.query.is_empty() {
+                    close(app);
+                }
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                refresh(app);
+            }
+            KeyCode::Char(c) if plain => {
+                search.query.push(c);
+                refresh(app);
+            }
+            _ => return true,
+        }
+        app.needs_redraw = true;
+        return true;
+    }
+    match (key.code, plain) {
+        (KeyCode::Esc, _) => close(app),
+        (KeyCode::Char('n'), true) | (KeyCode::Down, _) => step(app, true),
+        (KeyCode::Char('N'), true) | (KeyCode::Up, _) => step(app, false),
+        (KeyCode::Char('/'), true) => open(app),
+        _ => {
+            // Keep the highlights but hand the keyboard back to the input.
+            app.release_focus_target(FocusTarget::Search);
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{BlockCache, IncrementalMarkdown, MessageRole};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn tool_call(id: &str, title: &str) -> ToolCallInfo {
+        ToolCallInfo {
+            id: id.to_owned(),
+            title: title.to_owned(),
+            sdk_tool_name: "Bash".to_owned(),
+            raw_input: None,
+            status: model::ToolCallStatus::Completed,
+            content: Vec::new(),
+            collapsed: false,
+            hidden: false,
+            terminal_id: None,
+            terminal_command: None,
+            terminal_output: None,
+            terminal_output_len: 0,
+            terminal_bytes_seen: 0,
+            terminal_snapshot_mode: crate::app::TerminalSnapshotMode::AppendOnly,
+            render_epoch: 0,
+            layout_epoch: 0,
+            last_measured_width: 0,
+            last_measured_height: 0,
+            last_measured_layout_epoch: 0,
+            last_measured_layout_generation: 0,
+            cache: BlockCache::default(),
+            pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
+        }
+    }
+
+    fn type_query(app: &mut App, query: &str) {
+        for c in query.chars() {
+            handle_key(app, key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn find_matches_ignores_ascii_case_and_keeps_char_boundaries() {
+        assert_eq!(find_matches("Foo foo FOO", "foo"), vec![0, 4, 8]);
+        assert_eq!(find_matches("aaaa", "aa"), vec![0, 2]);
+        assert_eq!(find_matches("\u{00E9}t\u{00E9} ET\u{00C9}", "t\u{00E9}"), vec![2]);
+        assert!(find_matches("abc", "").is_empty());
+    }
+
+    #[test]
+    fn typing_query_finds_messages_without_expanding_tool_calls() {
+        let mut app = App::test_default();
+        let text = "the build is green";
+        app.messages.push(ChatMessage {
+            role: MessageRole::User,
+            blocks: vec![MessageBlock::Text(
+                text.to_owned(),
+                BlockCache::default(),
+                IncrementalMarkdown::from_complete(text),
+            )],
+            usage: None,
+        });
+        let mut tool = tool_call("tc-1", "Run cargo test");
+        tool.collapsed = true;
+        tool.terminal_output = Some("error: BUILD failed".to_owned());
+        let mut other = tool_call("tc-2", "Read file");
+        other.collapsed = true;
+        app.messages.push(ChatMessage {
+            role: MessageRole::Assistant,
+            blocks: vec![
+                MessageBlock::ToolCall(Box::new(tool)),
+                MessageBlock::ToolCall(Box::new(other)),
+            ],
+            usage: None,
+        });
+
+        open(&mut app);
+        assert_eq!(app.focus_owner(), crate::app::FocusOwner::Search);
+        type_query(&mut app, "build");
+
+        let search = app.search.as_ref().expect("search open");
+        assert_eq!(search.matching_messages, vec![0, 1]);
+        assert_eq!(search.collapsed_matches, vec![(1, 0)]);
+        assert!(search.pending_jump && search.hits_stale);
+        let MessageBlock::ToolCall(tc) = &app.messages[1].blocks[0] else { panic!() };
+        assert!(tc.collapsed, "matches are only expanded once their hit is selected");
+
+        reveal(&mut app, 1, 0);
+        let MessageBlock::ToolCall(tc) = &app.messages[1].blocks[0] else { panic!() };
+        assert!(!tc.collapsed, "selected hit's tool call expands");
+        let MessageBlock::ToolCall(tc) = &app.messages[1].blocks[1] else { panic!() };
+        assert!(tc.collapsed, "other tool calls stay collapsed");
+        assert!(app.search.as_ref().is_some_and(|s| s.collapsed_matches.is_empty()));
+
+        handle_key(&mut app, key(KeyCode::Enter));
+        assert!(!app.search.as_ref().is_some_and(|s| s.editing));
+        assert!(handle_key(&mut app, key(KeyCode::Esc)));
+        assert!(app.search.is_none());
+        assert_eq!(app.focus_owner(), crate::app::FocusOwner::Input);
+    }
+
+    #[test]
+    fn n_and_shift_n_wrap_around_hits() {
+        let mut app = App::test_default();
+        app.search = Some(SearchState {
+            query: "x".into(),
+            hits: vec![
+                SearchHit { message: 1, row: 0, collapsed_block: None },
+                SearchHit { message: 1, row: 4, collapsed_block: None },
+                SearchHit { message: 3, row: 2, collapsed_block: None },
+            ],
+            ..SearchState::default()
+        });
+        app.claim_focus_target(FocusTarget::Search);
+
+        assert!(handle_key(&mut app, key(KeyCode::Char('n'))));
+        assert_eq!(app.search.as_ref().and_then(|s| s.current), Some(0));
+        assert!(handle_key(&mut app, KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT)));
+        assert_eq!(app.search.as_ref().and_then(|s| s.current), Some(2));
+        assert_eq!(app.search.as_ref().map(|s| s.hits_for(1)), Some(0..2));
+
+        assert!(!handle_key(&mut app, key(KeyCode::Char('x'))), "other keys fall through");
+        assert_eq!(app.focus_owner(), crate::app::FocusOwner::Input);
+        assert!(app.search.is_some(), "highlights stay until Esc");
+    }
+
+    #[test]
+    fn content_version_tracks_streamed_text_and_collapse_toggles() {
+        let mut app = App::test_default();
+        let text = "partial";
+        app.messages.push(ChatMessage {
+            role: MessageRole::Assistant,
+            blocks: vec![
+                MessageBlock::Text(
+                    text.to_owned(),
+                    BlockCache::default(),
+                    IncrementalMarkdown::from_complete(text),
+                ),
+                MessageBlock::ToolCall(Box::new(tool_call("tc-1", "Run"))),
+            ],
+            usage: None,
+        });
+        let before = content_version(&app.messages);
+        assert_eq!(content_version(&app.messages), before);
+
+        if let MessageBlock::Text(text, ..) = &mut app.messages[0].blocks[0] {
+            text.push_str(" answer");
+        }
+        let streamed = content_version(&app.messages);
+        assert_ne!(streamed, before);
+
+        if let MessageBlock::ToolCall(tc) = &mut app.messages[0].blocks[1] {
+            tc.collapsed = true;
+        }
+        assert_ne!(content_version(&app.messages), streamed);
+    }
+}
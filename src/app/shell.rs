@@ -0,0 +1,413 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! `!command` shell escape: runs a command in the session cwd without a model
+//! turn. Output streams through the shared terminal map into an Execute-style
+//! block, Esc kills a command still running, and Alt+I copies the last result
+//! into the prompt.
+
+use super::{
+    App, BlockCache, ChatMessage, MessageBlock, MessageRole, TerminalSnapshotMode, ToolCallInfo,
+};
+use crate::agent::events::{ClientEvent, EventTarget, TerminalMap, TerminalProcess};
+use crate::agent::model;
+use ansi_to_tui::IntoText as _;
+use std::fmt::Write as _;
+use std::process::Stdio;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt as _};
+
+/// Prefix of tool call and terminal ids owned by the shell escape.
+const SHELL_ID_PREFIX: &str = "local-shell-";
+
+/// How often a command whose output has ended is checked for its exit.
+const EXIT_POLL: Duration = Duration::from_millis(20);
+
+static NEXT_SHELL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Command text after a leading `!`, if the submission is a shell escape.
+#[must_use]
+pub fn parse(text: &str) -> Option<&str> {
+    let command = text.trim_start().strip_prefix('!')?.trim();
+    (!command.is_empty()).then_some(command)
+}
+
+/// Whether `msg` holds a shell escape block rather than a system notice.
+#[must_use]
+pub fn is_shell_message(msg: &ChatMessage) -> bool {
+    matches!(msg.role, MessageRole::System)
+        && matches!(msg.blocks.first(), Some(MessageBlock::ToolCall(tc)) if tc.id.starts_with(SHELL_ID_PREFIX))
+}
+
+fn shell_tool_call(id: &str, command: &str) -> ToolCallInfo {
+    ToolCallInfo {
+        id: id.to_owned(),
+        title: command.to_owned(),
+        sdk_tool_name: "Bash".to_owned(),
+        raw_input: None,
+        status: model::ToolCallStatus::InProgress,
+        content: Vec::new(),
+        collapsed: false,
+        hidden: false,
+        terminal_id: Some(id.to_owned()),
+        terminal_command: Some(command.to_owned()),
+        terminal_output: None,
+        terminal_output_len: 0,
+        terminal_bytes_seen: 0,
+        terminal_snapshot_mode: TerminalSnapshotMode::AppendOnly,
+        render_epoch: 0,
+        layout_epoch: 0,
+        last_measured_width: 0,
+        last_measured_height: 0,
+        last_measured_layout_epoch: 0,
+        last_measured_layout_generation: 0,
+        cache: BlockCache::default(),
+        pending_permission: None,
+        parent_task_id: None,
+        subagent: super::SubagentTree::default(),
+        locations: Vec::new(),
+    }
+}
+
+fn shell_process(command: &str, cwd: &str) -> tokio::process::Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_owned());
+        let mut cmd = tokio::process::Command::new(shell);
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Own process group, so Esc reaches every process of a pipeline or list.
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// Kill `child` and, on unix, the rest of its process group: pipelines keep
+/// the output pipes open until every member is gone.
+fn kill_tree(child: &mut tokio::process::Child) -> bool {
+    #[cfg(unix)]
+    if let Some(pgid) = child.id().and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        // SAFETY: killpg takes no pointers; `pgid` is the group this child leads.
+        return unsafe { libc::killpg(pgid, libc::SIGKILL) } == 0;
+    }
+    child.start_kill().is_ok()
+}
+
+async fn pump(reader: Option<impl AsyncRead + Unpin>, buffer: Arc<Mutex<Vec<u8>>>) {
+    let Some(mut reader) = reader else {
+        return;
+    };
+    let mut chunk = [0u8; 8192];
+    while let Ok(n) = reader.read(&mut chunk).await {
+        if n == 0 {
+            break;
+        }
+        if let Ok(mut buf) = buffer.lock() {
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+/// Exit code of shell command `id`. The child stays in the terminal map so
+/// Esc can kill it, so its exit is polled rather than awaited. `None` for a
+/// signal or when the terminal is gone.
+async fn wait_for_exit(terminals: &TerminalMap, id: &str) -> Option<i32> {
+    loop {
+        let status = {
+            let mut map = terminals.borrow_mut();
+            map.get_mut(id).and_then(|terminal| terminal.child.as_mut())?.try_wait()
+        };
+        match status {
+            Ok(Some(status)) => return status.code(),
+            Ok(None) => tokio::time::sleep(EXIT_POLL).await,
+            Err(e) => {
+                tracing::warn!("shell command wait failed: {e}");
+                return None;
+            }
+        }
+    }
+}
+
+/// Run `command` in the session cwd and show it as a streaming block.
+pub(super) fn run(app: &mut App, command: &str) {
+    let id = format!("{SHELL_ID_PREFIX}{}", NEXT_SHELL_ID.fetch_add(1, Ordering::Relaxed));
+    let mut child = match shell_process(command, &app.cwd_raw).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            super::slash::push_system_message(app, format!("Failed to run `{command}`: {e}"));
+            return;
+        }
+    };
+
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    app.terminals.borrow_mut().insert(
+        id.clone(),
+        TerminalProcess {
+            child: Some(child),
+            output_buffer: Arc::clone(&buffer),
+            command: command.to_owned(),
+        },
+    );
+
+    let msg_idx = app.messages.len();
+    app.messages.push(ChatMessage {
+        role: MessageRole::System,
+        blocks: vec![MessageBlock::ToolCall(Box::new(shell_tool_call(&id, command)))],
+        usage: None,
+    });
+    app.index_tool_call(id.clone(), msg_idx, 0);
+    app.terminal_tool_calls.push((id.clone(), msg_idx, 0));
+    app.enforce_history_retention();
+    app.viewport.engage_auto_scroll();
+
+    // Route completion back to this tab even if another one is active by then.
+    let target =
+        (!app.tabs.list.is_empty()).then(|| EventTarget::Tab(super::tabs::active_tab_id(app)));
+    let tx = app.event_tx.clone();
+    let terminals = Rc::clone(&app.terminals);
+    tokio::task::spawn_local(async move {
+        tokio::join!(pump(stdout, Arc::clone(&buffer)), pump(stderr, buffer));
+        let exit_code = wait_for_exit(&terminals, &id).await;
+        let event = ClientEvent::ShellCommandFinished { tool_call_id: id, exit_code };
+        let event = match target {
+            Some(target) => ClientEvent::Routed { target, event: Box::new(event) },
+            None => event,
+        };
+        let _ = tx.send(event);
+    });
+}
+
+/// Esc: kill this tab's running shell commands. Returns `true` when one was
+/// running.
+pub(super) fn kill_running(app: &App) -> bool {
+    let mut terminals = app.terminals.borrow_mut();
+    let mut killed = false;
+    for (id, _, _) in &app.terminal_tool_calls {
+        if !id.starts_with(SHELL_ID_PREFIX) {
+            continue;
+        }
+        if let Some(child) = terminals.get_mut(id).and_then(|terminal| terminal.child.as_mut()) {
+            killed |= kill_tree(child);
+        }
+    }
+    killed
+}
+
+/// Mark the shell block finished once its process exited.
+pub(super) fn finish(app: &mut App, tool_call_id: &str, exit_code: Option<i32>) {
+    // Pick up output written since the last frame before freezing the block.
+    super::terminal::update_terminal_outputs(app);
+    app.terminals.borrow_mut().remove(tool_call_id);
+    let Some((mi, bi)) = app.lookup_tool_call(tool_call_id) else {
+        return;
+    };
+    let Some(MessageBlock::ToolCall(tc)) =
+        app.messages.get_mut(mi).and_then(|m| m.blocks.get_mut(bi))
+    else {
+        return;
+    };
+    let tc = tc.as_mut();
+    tc.status = model::ToolCallStatus::Completed;
+    if exit_code != Some(0) {
+        let code = exit_code.map_or_else(|| "signal".to_owned(), |c| c.to_string());
+        tc.title = format!("{} (exit {code})", tc.terminal_command.as_deref().unwrap_or_default());
+    }
+    tc.mark_tool_call_layout_dirty();
+    app.terminal_tool_calls.retain(|(id, _, _)| id != tool_call_id);
+    app.last_shell_output = Some(tool_call_id.to_owned());
+    app.mark_message_layout_dirty(mi);
+}
+
+fn strip_ansi(output: &str) -> String {
+    let Ok(text) = output.as_bytes().into_text() else {
+        return output.to_owned();
+    };
+    text.lines
+        .iter()
+        .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prompt-ready text for a finished shell block.
+fn attachment_text(tc: &ToolCallInfo) -> String {
+    let command = tc.terminal_command.as_deref().unwrap_or_default();
+    let output = strip_ansi(tc.terminal_output.as_deref().unwrap_or_default());
+    let output = output.trim_end();
+    let mut text = format!("```console\n$ {command}\n");
+    if !output.is_empty() {
+        text.push_str(output);
+        text.push('\n');
+    }
+    text.push_str("```");
+    if tc.title != command {
+        let _ = write!(text, "\n{}", tc.title.trim_start_matches(command).trim());
+    }
+    text
+}
+
+/// Alt+I: insert the last shell output at the cursor, collapsed to a paste
+/// placeholder when large.
+pub(super) fn attach_last_output(app: &mut App) -> bool {
+    let Some(id) = app.last_shell_output.clone() else {
+        return false;
+    };
+    let text = app.lookup_tool_call(&id).and_then(|(mi, bi)| {
+        match app.messages.get(mi).and_then(|m| m.blocks.get(bi)) {
+            Some(MessageBlock::ToolCall(tc)) => Some(attachment_text(tc)),
+            _ => None,
+        }
+    });
+    let Some(text) = text else {
+        app.last_shell_output = None;
+        return false;
+    };
+
+    let input = &mut app.input;
+    if input.lines.get(input.cursor_row).is_some_and(|line| !line.is_empty()) {
+        input.insert_newline();
+    }
+    if super::input::count_text_chars(&text) > super::input::PASTE_PLACEHOLDER_CHAR_THRESHOLD {
+        input.insert_paste_block(&text);
+    } else {
+        input.insert_str(&text);
+    }
+    input.insert_newline();
+    input.sync_textarea_engine();
+    app.last_shell_output = None;
+    app.needs_redraw = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_requires_a_command_after_bang() {
+        assert_eq!(parse("!git status"), Some("git status"));
+        assert_eq!(parse("  ! cargo test  "), Some("cargo test"));
+        assert_eq!(parse("!"), None);
+        assert_eq!(parse("hello !ls"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_streams_output_and_reports_exit() {
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut app = App::test_default();
+                app.cwd_raw = std::env::temp_dir().to_string_lossy().into_owned();
+                run(&mut app, "echo out; echo err >&2; exit 3");
+
+                let event = app.event_rx.recv().await.expect("finish event");
+                crate::app::events::handle_client_event(&mut app, event);
+
+                let Some(MessageBlock::ToolCall(tc)) =
+                    app.messages.last().and_then(|m| m.blocks.first())
+                else {
+                    panic!("expected shell block");
+                };
+                assert_eq!(tc.title, "echo out; echo err >&2; exit 3 (exit 3)");
+                let output = tc.terminal_output.as_deref().unwrap_or_default();
+                assert!(output.contains("out") && output.contains("err"), "{output:?}");
+                assert!(app.terminals.borrow().is_empty());
+            })
+            .await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn kill_running_stops_the_command() {
+        kill_and_expect_title("exec sleep 30").await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn kill_running_stops_every_process_of_a_pipeline() {
+        kill_and_expect_title("sleep 30 | cat").await;
+    }
+
+    #[cfg(unix)]
+    async fn kill_and_expect_title(command: &str) {
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let mut app = App::test_default();
+                app.cwd_raw = std::env::temp_dir().to_string_lossy().into_owned();
+                assert!(!kill_running(&app));
+                run(&mut app, command);
+                // Let the shell start the pipeline before killing it.
+                tokio::time::sleep(Duration::from_millis(200)).await;
+
+                assert!(kill_running(&app));
+                let event = tokio::time::timeout(Duration::from_secs(10), app.event_rx.recv())
+                    .await
+                    .expect("killed in time")
+                    .expect("finish event");
+                crate::app::events::handle_client_event(&mut app, event);
+
+                let Some(MessageBlock::ToolCall(tc)) =
+                    app.messages.last().and_then(|m| m.blocks.first())
+                else {
+                    panic!("expected shell block");
+                };
+                assert_eq!(tc.title, format!("{command} (exit signal)"));
+                assert!(app.terminal_tool_calls.is_empty());
+            })
+            .await;
+    }
+
+    #[test]
+    fn finished_output_is_attached_to_the_prompt() {
+        let mut app = App::test_default();
+        let mut tc = shell_tool_call("local-shell-7", "git status");
+        tc.terminal_output = Some("\u{1b}[32mclean\u{1b}[0m\n".to_owned());
+        app.messages.push(ChatMessage {
+            role: MessageRole::System,
+            blocks: vec![MessageBlock::ToolCall(Box::new(tc))],
+            usage: None,
+        });
+        let mi = app.messages.len() - 1;
+        app.index_tool_call("local-shell-7".to_owned(), mi, 0);
+        assert!(is_shell_message(&app.messages[mi]));
+
+        finish(&mut app, "local-shell-7", Some(1));
+        let Some(MessageBlock::ToolCall(tc)) = app.messages[mi].blocks.first() else {
+            panic!("expected tool call");
+        };
+        assert_eq!(tc.status, model::ToolCallStatus::Completed);
+        assert_eq!(tc.title, "git status (exit 1)");
+
+        app.input.set_text("see:");
+        assert!(attach_last_output(&mut app));
+        assert_eq!(app.input.text(), "see:\n```console\n$ git status\nclean\n```\n(exit 1)\n");
+        assert!(!attach_last_output(&mut app), "output is attached once");
+    }
+}
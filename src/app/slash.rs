@@ -173,7 +173,7 @@ fn detect_slash_at_cursor(
     })
 }
 
-fn push_system_message(app: &mut App, text: impl Into<String>) {
+pub(super) fn push_system_message(app: &mut App, text: impl Into<String>) {
     let text = text.into();
     app.messages.push(ChatMessage {
         role: MessageRole::System,
@@ -285,7 +285,7 @@ fn is_hidden_offer_command(command_name: &str) -> bool {
 }
 
 fn is_builtin_variable_input_command(command_name: &str) -> bool {
-    matches!(command_name, "/mode" | "/model" | "/resume")
+    matches!(command_name, "/branch" | "/mode" | "/model" | "/resume")
 }
 
 fn is_variable_input_command(app: &App, command_name: &str) -> bool {
@@ -302,12 +302,25 @@ fn supported_command_candidates(app: &App) -> Vec<SlashCandidate> {
     use std::collections::BTreeMap;
 
     let mut by_name: BTreeMap<String, String> = BTreeMap::new();
+    by_name.insert("/branch".into(), "List session branches or switch to one".into());
+    by_name.insert("/context".into(), "Show the context window breakdown".into());
     by_name.insert("/cancel".into(), "Cancel active turn".into());
-    by_name.insert("/compact".into(), "Clear conversation history".into());
+    by_name.insert("/close-tab".into(), "Close the current tab".into());
+    by_name.insert(
+        "/compact".into(),
+        "Summarize and clear history (optional focus instructions)".into(),
+    );
     by_name.insert("/mode".into(), "Set session mode".into());
     by_name.insert("/model".into(), "Set session model".into());
     by_name.insert("/new-session".into(), "Start a fresh session".into());
+    by_name.insert("/new-tab".into(), "Open a session in a new tab".into());
     by_name.insert("/resume".into(), "Resume a session by ID".into());
+    by_name.insert("/usage".into(), "Show token and cost totals across sessions".into());
+    by_name.insert("/vim".into(), "Toggle vi-style editing of the input".into());
+
+    for cmd in &app.custom_commands {
+        by_name.entry(cmd.name.clone()).or_insert_with(|| cmd.summary());
+    }
 
     for cmd in &app.available_commands {
         let name = normalize_slash_name(&cmd.name);
@@ -528,6 +541,17 @@ fn argument_candidates(app: &App, command_name: &str, arg_index: usize) -> Vec<S
                 }
             })
             .collect(),
+        "/branch" => app
+            .branches
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, branch)| SlashCandidate {
+                insert_value: (i + 1).to_string(),
+                primary: format!("{}. {}", i + 1, branch.label),
+                secondary: Some(branch.session_id.clone()),
+            })
+            .collect(),
         "/mode" => app
             .mode
             .as_ref()
@@ -587,11 +611,26 @@ fn build_slash_state(app: &App) -> Option<SlashState> {
 }
 
 pub fn is_supported_command(app: &App, command_name: &str) -> bool {
-    matches!(command_name, "/cancel" | "/compact" | "/mode" | "/model" | "/new-session" | "/resume")
+    matches!(
+        command_name,
+        "/cancel"
+            | "/close-tab"
+            | "/compact"
+            | "/mode"
+            | "/model"
+            | "/new-session"
+            | "/new-tab"
+            | "/resume"
+            | "/usage"
+            | "/context"
+            | "/branch"
+            | "/vim"
+    ) || super::custom_commands::find(&app.custom_commands, command_name).is_some()
         || advertised_commands(app).iter().any(|c| c == command_name)
 }
 
 pub fn activate(app: &mut App) {
+    super::custom_commands::refresh(app);
     let Some(state) = build_slash_state(app) else {
         return;
     };
@@ -775,11 +814,6 @@ pub fn try_handle_submit(app: &mut App, text: &str) -> bool {
             true
         }
         "/compact" => {
-            if !parsed.args.is_empty() {
-                push_system_message(app, "Usage: /compact");
-                return true;
-            }
-
             if require_active_session(
                 app,
                 "Cannot compact: not connected yet.",
@@ -790,8 +824,9 @@ pub fn try_handle_submit(app: &mut App, text: &str) -> bool {
                 return true;
             }
 
-            // Forward `/compact` through the bridge via the normal prompt path, then clear
-            // local history once the turn completes.
+            // Forward `/compact [instructions]` through the bridge via the normal prompt path;
+            // the SDK treats trailing text as focus instructions for the summary. Local history
+            // is cleared once the turn completes, keeping the compaction summary block.
             app.pending_compact_clear = true;
             app.is_compacting = true;
             false
@@ -881,8 +916,10 @@ pub fn try_handle_submit(app: &mut App, text: &str) -> bool {
             let cwd = app.cwd_raw.clone();
             let model_override = Some(app.model_name.clone());
             let yolo = false;
+            let replace = app.session_id.as_ref().map(ToString::to_string);
+            let request_id = Some(super::tabs::tab_request_id(super::tabs::active_tab_id(app)));
             tokio::task::spawn_local(async move {
-                if let Err(e) = conn.new_session(cwd, yolo, model_override) {
+                if let Err(e) = conn.new_session(cwd, yolo, model_override, replace, request_id) {
                     let _ = tx.send(ClientEvent::SlashCommandError(format!(
                         "Failed to run /new-session: {e}"
                     )));
@@ -911,8 +948,10 @@ pub fn try_handle_submit(app: &mut App, text: &str) -> bool {
             app.resuming_session_id = Some(session_id.to_owned());
             let tx = app.event_tx.clone();
             let session_id = session_id.to_owned();
+            let replace = app.session_id.as_ref().map(ToString::to_string);
+            let request_id = Some(super::tabs::tab_request_id(super::tabs::active_tab_id(app)));
             tokio::task::spawn_local(async move {
-                if let Err(e) = conn.load_session(session_id) {
+                if let Err(e) = conn.load_session(session_id, replace, request_id) {
                     let _ = tx.send(ClientEvent::SlashCommandError(format!(
                         "Failed to run /resume: {e}"
                     )));
@@ -920,7 +959,80 @@ pub fn try_handle_submit(app: &mut App, text: &str) -> bool {
             });
             true
         }
+        "/new-tab" => {
+            if !parsed.args.is_empty() {
+                push_system_message(app, "Usage: /new-tab");
+                return true;
+            }
+            super::tabs::open_tab(app);
+            true
+        }
+        "/close-tab" => {
+            if !parsed.args.is_empty() {
+                push_system_message(app, "Usage: /close-tab");
+                return true;
+            }
+            super::tabs::close_active(app);
+            true
+        }
+        "/usage" => {
+            if !parsed.args.is_empty() {
+                push_system_message(app, "Usage: /usage");
+                return true;
+            }
+            let Some(path) = app.usage_ledger_path.clone() else {
+                push_system_message(app, "Usage ledger is unavailable: no data directory.");
+                return true;
+            };
+            let report =
+                super::ledger::UsageReport::load(&path, super::ledger::DEFAULT_REPORT_DAYS);
+            super::overlay::open(app, super::overlay::OverlayKind::Usage(Box::new(report)));
+            true
+        }
+        "/vim" => {
+            if !parsed.args.is_empty() {
+                push_system_message(app, "Usage: /vim");
+                return true;
+            }
+            let message = if super::vim::toggle(app) {
+                "Vi mode on: Esc for normal mode, i to insert."
+            } else {
+                "Vi mode off."
+            };
+            push_system_message(app, message);
+            true
+        }
+        "/context" => {
+            if !parsed.args.is_empty() {
+                push_system_message(app, "Usage: /context");
+                return true;
+            }
+            super::overlay::open(app, super::overlay::OverlayKind::Context);
+            true
+        }
+        "/branch" => {
+            match parsed.args.as_slice() {
+                [] => push_system_message(app, super::branch::list_text(app)),
+                [number] => match number.parse::<usize>() {
+                    Ok(n) if n > 0 => {
+                        if let Err(message) = super::branch::switch_to(app, n - 1) {
+                            push_system_message(app, message);
+                        }
+                    }
+                    _ => push_system_message(app, "Usage: /branch [number]"),
+                },
+                _ => push_system_message(app, "Usage: /branch [number]"),
+            }
+            true
+        }
         _ => {
+            super::custom_commands::refresh(app);
+            if let Some(cmd) = super::custom_commands::find(&app.custom_commands, parsed.name) {
+                let args = text.trim_start()[parsed.name.len()..].to_owned();
+                let prompt = cmd.expand(&args);
+                super::input_submit::dispatch_prompt_turn(app, prompt);
+                return true;
+            }
             if is_supported_command(app, parsed.name) {
                 // Adapter-advertised slash command: let normal prompt path send it.
                 false
@@ -984,6 +1096,26 @@ mod tests {
         assert!(!names.iter().any(|n| n == "/logout"));
     }
 
+    #[test]
+    fn custom_commands_are_offered_and_shadow_advertised_ones() {
+        use crate::app::custom_commands::{CommandScope, CustomCommand};
+
+        let mut app = App::test_default();
+        app.available_commands = vec![model::AvailableCommand::new("/review", "Bridge review")];
+        app.custom_commands = vec![CustomCommand::parse(
+            "review",
+            "---\ndescription: Local review\n---\nReview $ARGUMENTS",
+            CommandScope::Project,
+        )];
+
+        let review = supported_command_candidates(&app)
+            .into_iter()
+            .find(|c| c.primary == "/review")
+            .expect("custom candidate");
+        assert_eq!(review.secondary.as_deref(), Some("Local review (project)"));
+        assert!(is_supported_command(&app, "/review"));
+    }
+
     #[test]
     fn detect_slash_argument_context_after_first_space() {
         let lines = vec!["/mode pla".to_owned()];
@@ -1201,29 +1333,16 @@ mod tests {
     }
 
     #[test]
-    fn compact_with_args_returns_usage_message() {
+    fn compact_with_instructions_is_forwarded_to_bridge() {
         let mut app = App::test_default();
-        app.messages.push(ChatMessage {
-            role: MessageRole::User,
-            blocks: vec![MessageBlock::Text(
-                "keep".into(),
-                BlockCache::default(),
-                IncrementalMarkdown::from_complete("keep"),
-            )],
-            usage: None,
-        });
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        app.conn = Some(std::rc::Rc::new(crate::agent::client::AgentConnection::new(tx)));
+        app.session_id = Some(model::SessionId::new("session-1"));
 
-        let consumed = try_handle_submit(&mut app, "/compact now");
-        assert!(consumed);
-        assert!(app.messages.len() >= 2);
-        let Some(last) = app.messages.last() else {
-            panic!("expected system usage message");
-        };
-        assert!(matches!(last.role, MessageRole::System));
-        let Some(MessageBlock::Text(text, _, _)) = last.blocks.first() else {
-            panic!("expected text block");
-        };
-        assert_eq!(text, "Usage: /compact");
+        let consumed = try_handle_submit(&mut app, "/compact keep the auth refactor details");
+        assert!(!consumed);
+        assert!(app.pending_compact_clear);
+        assert!(app.is_compacting);
     }
 
     #[test]
@@ -27,6 +27,7 @@ use super::focus::{FocusContext, FocusManager, FocusOwner, FocusTarget};
 use super::input::InputState;
 use super::mention;
 use super::slash;
+use super::subagents::SubagentTree;
 
 #[derive(Debug)]
 pub struct ModeInfo {
@@ -105,6 +106,10 @@ pub struct SessionUsageState {
     pub max_output_tokens: Option<u64>,
     pub last_compaction_trigger: Option<model::CompactionTrigger>,
     pub last_compaction_pre_tokens: Option<u64>,
+    /// Context size after each turn, most recent last (capped at `CONTEXT_HISTORY_LIMIT`).
+    pub context_history: Vec<u64>,
+    /// Turns recorded into `context_history`, including ones dropped by the cap.
+    pub context_turns: u64,
 }
 
 impl SessionUsageState {
@@ -121,11 +126,70 @@ impl SessionUsageState {
         let cache_write = self.latest_cache_write_tokens.unwrap_or(0);
         Some(input.saturating_add(output).saturating_add(cache_read).saturating_add(cache_write))
     }
+
+    pub fn record_context_turn(&mut self) {
+        let Some(used) = self.context_used_tokens() else {
+            return;
+        };
+        if self.context_history.len() >= CONTEXT_HISTORY_LIMIT {
+            self.context_history.remove(0);
+        }
+        self.context_history.push(used);
+        self.context_turns += 1;
+    }
+
+    /// Output tokens held back from the window for the next response.
+    #[must_use]
+    pub fn reserved_output_tokens(&self) -> u64 {
+        self.max_output_tokens.unwrap_or(0).min(AUTO_COMPACT_MAX_OUTPUT_RESERVE)
+    }
+
+    /// Approximate context size at which the SDK auto-compacts.
+    #[must_use]
+    pub fn auto_compact_threshold(&self) -> Option<u64> {
+        let window = self.context_window?;
+        Some(
+            window
+                .saturating_sub(self.reserved_output_tokens())
+                .saturating_sub(AUTO_COMPACT_BUFFER_TOKENS),
+        )
+    }
+
+    /// Turns that still fit before auto-compaction, at the average growth of the
+    /// recent turns. Shrinking turns (compactions) are ignored; `None` without growth.
+    #[must_use]
+    pub fn turns_until_auto_compact(&self) -> Option<u64> {
+        let threshold = self.auto_compact_threshold()?;
+        let used = self.context_used_tokens()?;
+        if used >= threshold {
+            return Some(0);
+        }
+        let growth: Vec<u64> = self
+            .context_history
+            .windows(2)
+            .rev()
+            .take(CONTEXT_GROWTH_SAMPLE)
+            .filter_map(|pair| pair[1].checked_sub(pair[0]))
+            .filter(|delta| *delta > 0)
+            .collect();
+        if growth.is_empty() {
+            return None;
+        }
+        let average = growth.iter().sum::<u64>().div_ceil(growth.len() as u64);
+        Some((threshold - used) / average)
+    }
 }
 
 pub const DEFAULT_RENDER_CACHE_BUDGET_BYTES: usize = 24 * 1024 * 1024;
 pub const DEFAULT_HISTORY_RETENTION_MAX_BYTES: usize = 64 * 1024 * 1024;
 pub const SUBAGENT_THINKING_DEBOUNCE: Duration = Duration::from_millis(1_500);
+pub const CONTEXT_HISTORY_LIMIT: usize = 48;
+/// Recent turns averaged when projecting context growth.
+const CONTEXT_GROWTH_SAMPLE: usize = 8;
+/// Free headroom the SDK keeps below the effective window before auto-compacting.
+const AUTO_COMPACT_BUFFER_TOKENS: u64 = 13_000;
+/// Cap on the output reservation subtracted from the window.
+const AUTO_COMPACT_MAX_OUTPUT_RESERVE: u64 = 20_000;
 
 const HISTORY_HIDDEN_MARKER_PREFIX: &str = "Older messages hidden to keep memory bounded";
 const HISTORY_ESTIMATE_MESSAGE_OVERHEAD_BYTES: usize = 64;
@@ -242,9 +306,18 @@ pub struct App {
     /// Set when a cancel notification succeeds; consumed on `TurnComplete`
     /// to render a red interruption hint in chat.
     pub cancelled_turn_pending_hint: bool,
-    /// Queued submit text while a turn is still active.
-    /// Latest submission wins and replaces older queued text.
-    pub queued_submission: Option<String>,
+    /// Submissions typed while a turn is still active, in send order.
+    pub submission_queue: super::queue::SubmissionQueue,
+    /// Earlier-prompt edit in progress and the session branches it produced.
+    pub branches: super::branch::BranchState,
+    /// Open conversation search (Ctrl+F).
+    pub search: Option<super::search::SearchState>,
+    /// Keyboard cursor over chat blocks (Alt+J).
+    pub chat_nav: Option<super::chat_nav::ChatNavState>,
+    /// Finished `!command` block whose output Alt+I can attach to the prompt.
+    pub last_shell_output: Option<String>,
+    /// Vi-style modal editing of the input, when enabled (`--vi`, `/vim`).
+    pub vim: Option<super::vim::VimState>,
     /// Origin of the in-flight cancellation request, if any.
     pub pending_cancel_origin: Option<CancelOrigin>,
     pub event_tx: mpsc::UnboundedSender<ClientEvent>,
@@ -285,6 +358,14 @@ pub struct App {
     pub focus: FocusManager,
     /// Commands advertised by the agent via `AvailableCommandsUpdate`.
     pub available_commands: Vec<model::AvailableCommand>,
+    /// Prompt templates from `.claude-rs/commands`, expanded locally.
+    pub custom_commands: Vec<super::custom_commands::CustomCommand>,
+    /// Last focus state reported by the terminal. Assumed focused until a
+    /// `FocusLost` arrives, since not every terminal reports focus.
+    pub terminal_focused: bool,
+    pub notify: super::notify::NotifyConfig,
+    /// `None` when terminal title updates are disabled.
+    pub terminal_title: Option<super::title::TerminalTitle>,
     /// Recently persisted session IDs discovered at startup.
     pub recent_sessions: Vec<RecentSessionInfo>,
     /// Last known frame area (for mouse selection mapping).
@@ -329,8 +410,14 @@ pub struct App {
     pub next_paste_session_id: u64,
     /// Start cursor of the current rapid-key burst.
     pub paste_burst_start: Option<SelectionPoint>,
-    /// Cached file list from cwd (scanned on first `@` trigger).
-    pub file_cache: Option<Vec<mention::FileCandidate>>,
+    /// Background file index with the file list from cwd and the symbols in it.
+    pub file_index: super::file_index::FileIndex,
+    /// How often and how recently files were mentioned or edited, per project.
+    pub mention_frecency: super::frecency::Frecency,
+    /// Attachments tray for the current draft.
+    pub attachments: super::attachments::AttachmentTray,
+    /// Autosaved input drafts, per project and session.
+    pub drafts: super::draft::DraftStore,
     /// Cached todo compact line (invalidated on `set_todos()`).
     pub cached_todo_compact: Option<ratatui::text::Line<'static>>,
     /// Current git branch (refreshed on focus gain + turn complete).
@@ -341,14 +428,36 @@ pub struct App {
     pub cached_footer_line: Option<ratatui::text::Line<'static>>,
     /// Optional startup update-check hint rendered at the footer's right edge.
     pub update_check_hint: Option<String>,
+    /// Short-lived footer message (e.g. clipboard result); cleared once expired.
+    pub toast: Option<Toast>,
+    pub clipboard_mode: super::clipboard::ClipboardMode,
+    /// `--open-command` template for tool call locations; `None` uses `$EDITOR`.
+    pub open_command: Option<Vec<String>>,
+    /// External program waiting for the event loop to hand it the terminal.
+    pub suspended: Option<super::external::Suspended>,
     /// Session-wide usage and cost telemetry from the bridge.
     pub session_usage: SessionUsageState,
+    /// Configured cost/token limits (from CLI flags or env).
+    pub budget_limits: super::budget::BudgetLimits,
+    /// Per-session budget tracking for the running turn.
+    pub budget: super::budget::BudgetState,
+    /// Append-only spend ledger; `None` disables recording (tests, no data dir).
+    pub usage_ledger_path: Option<std::path::PathBuf>,
+    /// Report overlay drawn over the chat body (`/usage`).
+    pub overlay: Option<super::overlay::Overlay>,
     /// True while the SDK reports active compaction.
     pub is_compacting: bool,
 
     /// Indexed terminal tool calls: `(terminal_id, msg_idx, block_idx)`.
     /// Avoids O(n*m) scan of all messages/blocks every frame.
     pub terminal_tool_calls: Vec<(String, usize, usize)>,
+    /// Inline image placements for the current frame and what is painted on screen.
+    pub inline_images: crate::ui::graphics::InlineImageState,
+    pub hyperlinks: crate::ui::hyperlink::HyperlinkState,
+    /// Open session tabs. The active tab's state lives in the fields above.
+    pub tabs: super::tabs::TabStrip,
+    /// Open per-subagent focus view, replacing the chat body.
+    pub subagent_view: Option<super::subagents::SubagentView>,
     /// Dirty flag: skip `terminal.draw()` when nothing changed since last frame.
     pub needs_redraw: bool,
     /// Performance logger. Present only when built with `--features perf`.
@@ -367,7 +476,36 @@ pub struct App {
     pub last_frame_at: Option<Instant>,
 }
 
+/// How long a footer toast stays visible.
+const TOAST_DURATION: Duration = Duration::from_millis(2500);
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub is_error: bool,
+    pub expires_at: Instant,
+}
+
 impl App {
+    /// Show `text` in the footer for a couple of seconds.
+    pub fn show_toast(&mut self, text: impl Into<String>, is_error: bool) {
+        self.toast = Some(Toast {
+            text: text.into(),
+            is_error,
+            expires_at: Instant::now() + TOAST_DURATION,
+        });
+        self.needs_redraw = true;
+    }
+
+    /// Drop an expired toast. Returns whether the footer needs a repaint.
+    pub fn expire_toast(&mut self, now: Instant) -> bool {
+        if self.toast.as_ref().is_some_and(|toast| now >= toast.expires_at) {
+            self.toast = None;
+            return true;
+        }
+        false
+    }
+
     /// Mark one presented frame at `now`, updating smoothed FPS.
     pub fn mark_frame_presented(&mut self, now: Instant) {
         let Some(prev) = self.last_frame_at.replace(now) else {
@@ -659,6 +797,9 @@ impl App {
                 MessageBlock::ToolCall(tc) => {
                     total = total.saturating_add(Self::estimate_tool_call_bytes(tc));
                 }
+                MessageBlock::Compaction(block) => {
+                    total = total.saturating_add(block.summary.as_ref().map_or(0, String::len));
+                }
                 MessageBlock::Welcome(welcome) => {
                     total = total
                         .saturating_add(HISTORY_ESTIMATE_WELCOME_OVERHEAD_BYTES)
@@ -899,6 +1040,7 @@ impl App {
                 let (cache, protect_block) = match block {
                     MessageBlock::Text(_, cache, _) => (cache, false),
                     MessageBlock::Welcome(welcome) => (&welcome.cache, false),
+                    MessageBlock::Compaction(block) => (&block.cache, false),
                     MessageBlock::ToolCall(tc) => (
                         &tc.cache,
                         matches!(
@@ -966,6 +1108,7 @@ impl App {
         match block {
             MessageBlock::Text(_, cache, _) => cache.evict_cached_render(),
             MessageBlock::Welcome(welcome) => welcome.cache.evict_cached_render(),
+            MessageBlock::Compaction(block) => block.cache.evict_cached_render(),
             MessageBlock::ToolCall(tc) => tc.cache.evict_cached_render(),
         }
     }
@@ -996,7 +1139,12 @@ impl App {
             help_view: HelpView::Keys,
             pending_permission_ids: Vec::new(),
             cancelled_turn_pending_hint: false,
-            queued_submission: None,
+            submission_queue: super::queue::SubmissionQueue::default(),
+            branches: super::branch::BranchState::default(),
+            search: None,
+            chat_nav: None,
+            last_shell_output: None,
+            vim: None,
             pending_cancel_origin: None,
             event_tx: tx,
             event_rx: rx,
@@ -1016,6 +1164,10 @@ impl App {
             todo_selected: 0,
             focus: FocusManager::default(),
             available_commands: Vec::new(),
+            custom_commands: Vec::new(),
+            terminal_focused: true,
+            notify: super::notify::NotifyConfig::default(),
+            terminal_title: None,
             recent_sessions: Vec::new(),
             cached_frame_area: ratatui::layout::Rect::default(),
             selection: None,
@@ -1034,15 +1186,31 @@ impl App {
             active_paste_session: None,
             next_paste_session_id: 1,
             paste_burst_start: None,
-            file_cache: None,
+            file_index: super::file_index::FileIndex::default(),
+            mention_frecency: super::frecency::Frecency::default(),
+            attachments: super::attachments::AttachmentTray::default(),
+            drafts: super::draft::DraftStore::default(),
             cached_todo_compact: None,
             git_branch: None,
             cached_header_line: None,
             cached_footer_line: None,
             update_check_hint: None,
+            toast: None,
+            // Never fall back to OSC 52 here; tests must not write to the terminal.
+            clipboard_mode: super::clipboard::ClipboardMode::Native,
+            open_command: None,
+            suspended: None,
             session_usage: SessionUsageState::default(),
+            budget_limits: super::budget::BudgetLimits::default(),
+            budget: super::budget::BudgetState::default(),
+            usage_ledger_path: None,
+            overlay: None,
             is_compacting: false,
             terminal_tool_calls: Vec::new(),
+            inline_images: crate::ui::graphics::InlineImageState::default(),
+            hyperlinks: crate::ui::hyperlink::HyperlinkState::default(),
+            tabs: super::tabs::TabStrip::default(),
+            subagent_view: None,
             needs_redraw: true,
             perf: None,
             render_cache_budget: RenderCacheBudget::default(),
@@ -1112,6 +1280,10 @@ impl App {
             !self.pending_permission_ids.is_empty(),
             self.is_help_active(),
         )
+        .with_queue(!self.submission_queue.is_empty())
+        .with_attachments(!self.attachments.is_empty())
+        .with_search(self.search.is_some())
+        .with_chat_nav(self.chat_nav.is_some())
     }
 }
 
@@ -1311,6 +1483,12 @@ impl ChatViewport {
     pub fn engage_auto_scroll(&mut self) {
         self.auto_scroll = true;
     }
+
+    /// Scroll so message `idx` starts at the top of the chat. Disables auto-scroll.
+    pub fn scroll_to_message(&mut self, idx: usize) {
+        self.scroll_target = self.cumulative_height_before(idx);
+        self.auto_scroll = false;
+    }
 }
 
 impl Default for ChatViewport {
@@ -1342,6 +1520,8 @@ pub enum ToolCallScope {
 pub enum CancelOrigin {
     Manual,
     AutoQueue,
+    /// A cost or token budget was exceeded; the budget notice replaces the interrupted hint.
+    Budget,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1681,6 +1861,7 @@ pub enum MessageBlock {
     Text(String, BlockCache, IncrementalMarkdown),
     ToolCall(Box<ToolCallInfo>),
     Welcome(WelcomeBlock),
+    Compaction(CompactionBlock),
 }
 
 #[derive(Debug)]
@@ -1698,6 +1879,29 @@ pub struct WelcomeBlock {
     pub cache: BlockCache,
 }
 
+/// Marker left where the conversation was compacted. Holds the summary the
+/// agent continues from once the bridge reports it.
+pub struct CompactionBlock {
+    pub trigger: model::CompactionTrigger,
+    pub pre_tokens: u64,
+    pub summary: Option<String>,
+    pub collapsed: bool,
+    pub cache: BlockCache,
+}
+
+impl CompactionBlock {
+    #[must_use]
+    pub fn new(boundary: model::CompactionBoundary, collapsed: bool) -> Self {
+        Self {
+            trigger: boundary.trigger,
+            pre_tokens: boundary.pre_tokens,
+            summary: None,
+            collapsed,
+            cache: BlockCache::default(),
+        }
+    }
+}
+
 pub struct ToolCallInfo {
     pub id: String,
     pub title: String,
@@ -1739,6 +1943,12 @@ pub struct ToolCallInfo {
     pub cache: BlockCache,
     /// Inline permission prompt - rendered inside this tool call block.
     pub pending_permission: Option<InlinePermission>,
+    /// Task tool call that spawned this one, for subagent children.
+    pub parent_task_id: Option<String>,
+    /// Child tool calls of a Task, mirrored for its subagent tree.
+    pub subagent: SubagentTree,
+    /// Files the tool reads or edits, as reported by the agent.
+    pub locations: Vec<model::ToolCallLocation>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1758,6 +1968,13 @@ impl ToolCallInfo {
         is_ask_question_tool_name(&self.sdk_tool_name)
     }
 
+    /// Subagent children render inside their Task node, except while a permission
+    /// prompt is pending, so the user can still answer it in the chat flow.
+    #[must_use]
+    pub fn is_rendered_inline(&self) -> bool {
+        !self.hidden || self.pending_permission.is_some()
+    }
+
     /// Mark render cache for this tool call as stale.
     pub fn mark_tool_call_render_dirty(&mut self) {
         crate::perf::mark("tc_invalidations_requested");
@@ -2166,6 +2383,9 @@ mod tests {
                 last_measured_layout_generation: 0,
                 cache: BlockCache::default(),
                 pending_permission: None,
+                parent_task_id: None,
+                subagent: SubagentTree::default(),
+                locations: Vec::new(),
             }))],
             usage: None,
         }
@@ -2207,6 +2427,9 @@ mod tests {
                     selected_index: 0,
                     focused: false,
                 }),
+                parent_task_id: None,
+                subagent: SubagentTree::default(),
+                locations: Vec::new(),
             }))],
             usage: None,
         }
@@ -2847,6 +3070,28 @@ mod tests {
         assert_eq!(usage.context_used_tokens(), None);
     }
 
+    #[test]
+    fn session_usage_projects_turns_until_auto_compact() {
+        let mut usage = SessionUsageState {
+            context_window: Some(200_000),
+            max_output_tokens: Some(64_000),
+            ..SessionUsageState::default()
+        };
+        for used in [40_000_u64, 20_000, 30_000, 50_000] {
+            usage.latest_input_tokens = Some(used);
+            usage.latest_output_tokens = Some(0);
+            usage.record_context_turn();
+        }
+
+        assert_eq!(usage.auto_compact_threshold(), Some(167_000));
+        // Growth of 10k then 20k (the 40k -> 20k compaction drop is ignored).
+        assert_eq!(usage.turns_until_auto_compact(), Some(7));
+        assert_eq!(usage.context_turns, 4);
+
+        usage.latest_input_tokens = Some(180_000);
+        assert_eq!(usage.turns_until_auto_compact(), Some(0));
+    }
+
     #[test]
     fn focus_owner_defaults_to_input() {
         let app = make_test_app();
@@ -2956,4 +3201,18 @@ mod tests {
         app.release_focus_target(FocusTarget::Mention);
         assert_eq!(app.focus_owner(), FocusOwner::Permission);
     }
+
+    // Toast
+
+    #[test]
+    fn toast_expires_after_its_duration() {
+        let mut app = App::test_default();
+        app.show_toast("Copied via OSC 52", false);
+        let shown_at = Instant::now();
+        assert!(!app.expire_toast(shown_at));
+        assert!(app.toast.is_some());
+        assert!(app.expire_toast(shown_at + TOAST_DURATION));
+        assert!(app.toast.is_none());
+        assert!(!app.expire_toast(shown_at + TOAST_DURATION));
+    }
 }
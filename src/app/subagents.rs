@@ -0,0 +1,312 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{App, MessageBlock, ToolCallInfo, ToolCallScope};
+use crate::agent::model;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::Instant;
+
+/// Rows moved by PageUp/PageDown in the focus view.
+const VIEW_PAGE_ROWS: usize = 10;
+
+/// One child tool call as shown in its parent Task's tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentChild {
+    pub id: String,
+    pub title: String,
+    pub sdk_tool_name: String,
+    pub status: model::ToolCallStatus,
+    pub awaiting_permission: bool,
+}
+
+/// Children of a Task tool call, in arrival order.
+#[derive(Debug, Clone, Default)]
+pub struct SubagentTree {
+    pub children: Vec<SubagentChild>,
+    /// Last time any child was added or changed status; drives the idle readout.
+    pub last_activity: Option<Instant>,
+}
+
+impl SubagentTree {
+    #[must_use]
+    pub fn running_count(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.status,
+                    model::ToolCallStatus::InProgress | model::ToolCallStatus::Pending
+                )
+            })
+            .count()
+    }
+
+    #[must_use]
+    pub fn failed_count(&self) -> usize {
+        self.children.iter().filter(|c| c.status == model::ToolCallStatus::Failed).count()
+    }
+}
+
+/// Per-subagent focus view: replaces the chat body with one Task's transcript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentView {
+    pub task_id: String,
+    /// Rows scrolled up from the bottom; 0 follows new output.
+    pub scroll_from_bottom: usize,
+}
+
+/// Parent Task id advertised by the bridge in `meta.claudeCode.parentToolUseId`.
+pub(super) fn parent_task_from_meta(meta: Option<&serde_json::Value>) -> Option<&str> {
+    meta.and_then(|m| m.get("claudeCode"))
+        .and_then(|v| v.get("parentToolUseId"))
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty())
+}
+
+/// Resolve the Task a subagent tool call belongs to. Without bridge metadata the
+/// parent is only unambiguous when exactly one Task is running.
+pub(super) fn resolve_parent_task(
+    app: &App,
+    meta: Option<&serde_json::Value>,
+    scope: ToolCallScope,
+) -> Option<String> {
+    if let Some(parent) = parent_task_from_meta(meta)
+        && app.tool_call_scope(parent) == Some(ToolCallScope::Task)
+    {
+        return Some(parent.to_owned());
+    }
+    if scope != ToolCallScope::Subagent || app.active_task_ids.len() != 1 {
+        return None;
+    }
+    app.active_task_ids.iter().next().cloned()
+}
+
+/// Mirror the current title/status of a child tool call into its parent's tree.
+pub(super) fn sync_child(app: &mut App, child_id: &str) {
+    let Some((mi, bi)) = app.lookup_tool_call(child_id) else {
+        return;
+    };
+    let Some(MessageBlock::ToolCall(child)) = app.messages.get(mi).and_then(|m| m.blocks.get(bi))
+    else {
+        return;
+    };
+    let Some(parent_id) = child.parent_task_id.clone() else {
+        return;
+    };
+    let entry = SubagentChild {
+        id: child.id.clone(),
+        title: child.title.clone(),
+        sdk_tool_name: child.sdk_tool_name.clone(),
+        status: child.status,
+        awaiting_permission: child.pending_permission.is_some(),
+    };
+
+    let Some((pmi, pbi)) = app.lookup_tool_call(&parent_id) else {
+        return;
+    };
+    let Some(MessageBlock::ToolCall(parent)) =
+        app.messages.get_mut(pmi).and_then(|m| m.blocks.get_mut(pbi))
+    else {
+        return;
+    };
+    let parent = parent.as_mut();
+    let tree = &mut parent.subagent;
+    match tree.children.iter_mut().find(|c| c.id == entry.id) {
+        Some(existing) if *existing == entry => return,
+        Some(existing) => *existing = entry,
+        None => tree.children.push(entry),
+    }
+    tree.last_activity = Some(Instant::now());
+    parent.mark_tool_call_layout_dirty();
+    app.mark_message_layout_dirty(pmi);
+}
+
+/// Task tool call ids in chat order.
+#[must_use]
+pub fn task_ids(app: &App) -> Vec<String> {
+    app.messages
+        .iter()
+        .flat_map(|m| m.blocks.iter())
+        .filter_map(|block| match block {
+            MessageBlock::ToolCall(tc) if tc.sdk_tool_name == "Task" => Some(tc.id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[must_use]
+pub fn find_task<'a>(app: &'a App, task_id: &str) -> Option<&'a ToolCallInfo> {
+    let (mi, bi) = app.lookup_tool_call(task_id)?;
+    match app.messages.get(mi)?.blocks.get(bi)? {
+        MessageBlock::ToolCall(tc) => Some(tc),
+        _ => None,
+    }
+}
+
+/// Open the focus view on the most recent running Task, or step to the previous
+/// Task when the view is already open.
+pub fn cycle_view(app: &mut App) {
+    let ids = task_ids(app);
+    if ids.is_empty() {
+        app.subagent_view = None;
+        return;
+    }
+    let next =
+        match app.subagent_view.as_ref().and_then(|v| ids.iter().position(|id| *id == v.task_id)) {
+            Some(0) => ids.len() - 1,
+            Some(pos) => pos - 1,
+            None => {
+                ids.iter().rposition(|id| app.active_task_ids.contains(id)).unwrap_or(ids.len() - 1)
+            }
+        };
+    app.subagent_view = Some(SubagentView { task_id: ids[next].clone(), scroll_from_bottom: 0 });
+    app.needs_redraw = true;
+}
+
+pub fn close_view(app: &mut App) {
+    if app.subagent_view.take().is_some() {
+        app.needs_redraw = true;
+    }
+}
+
+/// Scroll and dismiss keys for the open focus view. Returns `true` when consumed.
+pub(super) fn handle_view_key(app: &mut App, key: KeyEvent) -> bool {
+    let Some(view) = app.subagent_view.as_mut() else {
+        return false;
+    };
+    if key.modifiers != KeyModifiers::NONE {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc => {
+            close_view(app);
+            return true;
+        }
+        KeyCode::Up => view.scroll_from_bottom = view.scroll_from_bottom.saturating_add(1),
+        KeyCode::Down => view.scroll_from_bottom = view.scroll_from_bottom.saturating_sub(1),
+        KeyCode::PageUp => {
+            view.scroll_from_bottom = view.scroll_from_bottom.saturating_add(VIEW_PAGE_ROWS);
+        }
+        KeyCode::PageDown => {
+            view.scroll_from_bottom = view.scroll_from_bottom.saturating_sub(VIEW_PAGE_ROWS);
+        }
+        _ => return false,
+    }
+    app.needs_redraw = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ChatMessage, MessageRole};
+
+    fn tool_call(id: &str, name: &str, parent: Option<&str>) -> ToolCallInfo {
+        ToolCallInfo {
+            id: id.to_owned(),
+            title: format!("{name} call"),
+            sdk_tool_name: name.to_owned(),
+            raw_input: None,
+            status: model::ToolCallStatus::InProgress,
+            content: Vec::new(),
+            collapsed: false,
+            hidden: parent.is_some(),
+            terminal_id: None,
+            terminal_command: None,
+            terminal_output: None,
+            terminal_output_len: 0,
+            terminal_bytes_seen: 0,
+            terminal_snapshot_mode: crate::app::TerminalSnapshotMode::AppendOnly,
+            render_epoch: 0,
+            layout_epoch: 0,
+            last_measured_width: 0,
+            last_measured_height: 0,
+            last_measured_layout_epoch: 0,
+            last_measured_layout_generation: 0,
+            cache: crate::app::BlockCache::default(),
+            pending_permission: None,
+            parent_task_id: parent.map(str::to_owned),
+            subagent: SubagentTree::default(),
+            locations: Vec::new(),
+        }
+    }
+
+    fn app_with_tasks() -> App {
+        let mut app = App::test_default();
+        let blocks = vec![
+            MessageBlock::ToolCall(Box::new(tool_call("task-a", "Task", None))),
+            MessageBlock::ToolCall(Box::new(tool_call("task-b", "Task", None))),
+            MessageBlock::ToolCall(Box::new(tool_call("child-1", "Read", Some("task-a")))),
+        ];
+        app.messages.push(ChatMessage { role: MessageRole::Assistant, blocks, usage: None });
+        for (bi, id) in ["task-a", "task-b", "child-1"].iter().enumerate() {
+            app.index_tool_call((*id).to_owned(), 0, bi);
+        }
+        app.register_tool_call_scope("task-a".to_owned(), ToolCallScope::Task);
+        app.register_tool_call_scope("task-b".to_owned(), ToolCallScope::Task);
+        app
+    }
+
+    #[test]
+    fn parent_from_meta_wins_over_single_active_task() {
+        let mut app = app_with_tasks();
+        app.insert_active_task("task-b".to_owned());
+        let meta =
+            serde_json::json!({"claudeCode": {"toolName": "Read", "parentToolUseId": "task-a"}});
+
+        assert_eq!(
+            resolve_parent_task(&app, Some(&meta), ToolCallScope::Subagent).as_deref(),
+            Some("task-a")
+        );
+        assert_eq!(
+            resolve_parent_task(&app, None, ToolCallScope::Subagent).as_deref(),
+            Some("task-b")
+        );
+        app.insert_active_task("task-a".to_owned());
+        assert_eq!(resolve_parent_task(&app, None, ToolCallScope::Subagent), None);
+    }
+
+    #[test]
+    fn sync_child_upserts_into_parent_tree() {
+        let mut app = app_with_tasks();
+        sync_child(&mut app, "child-1");
+        if let Some(MessageBlock::ToolCall(child)) = app.messages[0].blocks.get_mut(2) {
+            child.status = model::ToolCallStatus::Failed;
+        }
+        sync_child(&mut app, "child-1");
+
+        let task = find_task(&app, "task-a").expect("task");
+        assert_eq!(task.subagent.children.len(), 1);
+        assert_eq!(task.subagent.failed_count(), 1);
+        assert_eq!(task.subagent.running_count(), 0);
+        assert!(task.subagent.last_activity.is_some());
+    }
+
+    #[test]
+    fn cycle_view_starts_at_running_task_and_steps_back() {
+        let mut app = app_with_tasks();
+        app.insert_active_task("task-a".to_owned());
+
+        cycle_view(&mut app);
+        assert_eq!(app.subagent_view.as_ref().map(|v| v.task_id.as_str()), Some("task-a"));
+        cycle_view(&mut app);
+        assert_eq!(app.subagent_view.as_ref().map(|v| v.task_id.as_str()), Some("task-b"));
+
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(handle_view_key(&mut app, esc));
+        assert!(app.subagent_view.is_none());
+    }
+}
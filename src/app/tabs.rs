@@ -0,0 +1,613 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Concurrent sessions shown as tabs.
+//!
+//! The active tab's session state lives directly in the `App` fields so the rest
+//! of the app keeps working on `app.messages`, `app.input`, etc. Inactive tabs park
+//! the same fields in a [`SessionState`] and are swapped back in on demand, both
+//! when the user switches tabs and when a bridge event arrives for a background
+//! session.
+
+use super::attachments::AttachmentTray;
+use super::branch::BranchState;
+use super::budget::BudgetState;
+use super::chat_nav::ChatNavState;
+use super::queue::SubmissionQueue;
+use super::search::SearchState;
+use super::state::{CancelOrigin, HistoryRetentionStats, ToolCallScope};
+use super::{
+    App, AppStatus, ChatMessage, ChatViewport, FocusManager, InputState, LoginHint, MessageBlock,
+    MessageRole, ModeState, SessionUsageState, SubagentView, TodoItem,
+};
+use crate::agent::events::{ClientEvent, EventTarget};
+use crate::agent::model;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::Instant;
+
+const TAB_REQUEST_PREFIX: &str = "tab:";
+const TAB_LABEL_MAX_CHARS: usize = 24;
+const NEW_TAB_LABEL: &str = "New session";
+
+/// One open session tab.
+pub struct SessionTab {
+    pub id: u64,
+    /// True when the session produced output while the tab was in the background.
+    pub unread: bool,
+    /// Parked session state; `None` while this tab is the active one.
+    parked: Option<Box<SessionState>>,
+}
+
+impl SessionTab {
+    #[must_use]
+    pub fn active(id: u64) -> Self {
+        Self { id, unread: false, parked: None }
+    }
+}
+
+/// All open tabs, with the one currently shown.
+pub struct TabStrip {
+    pub list: Vec<SessionTab>,
+    /// Index into `list` of the tab currently shown.
+    pub active: usize,
+    /// Monotonic counter for tab identifiers.
+    pub next_id: u64,
+}
+
+impl Default for TabStrip {
+    fn default() -> Self {
+        Self { list: vec![SessionTab::active(0)], active: 0, next_id: 1 }
+    }
+}
+
+/// Session-scoped `App` fields that move with a tab.
+#[allow(clippy::struct_excessive_bools)]
+struct SessionState {
+    messages: Vec<ChatMessage>,
+    viewport: ChatViewport,
+    input: InputState,
+    status: AppStatus,
+    resuming_session_id: Option<String>,
+    session_id: Option<model::SessionId>,
+    model_name: String,
+    mode: Option<ModeState>,
+    files_accessed: usize,
+    login_hint: Option<LoginHint>,
+    pending_compact_clear: bool,
+    pending_permission_ids: Vec<String>,
+    cancelled_turn_pending_hint: bool,
+    submission_queue: SubmissionQueue,
+    branches: BranchState,
+    search: Option<SearchState>,
+    chat_nav: Option<ChatNavState>,
+    last_shell_output: Option<String>,
+    pending_cancel_origin: Option<CancelOrigin>,
+    active_task_ids: HashSet<String>,
+    tool_call_scopes: HashMap<String, ToolCallScope>,
+    active_subagent_tool_ids: HashSet<String>,
+    subagent_idle_since: Option<Instant>,
+    tool_call_index: HashMap<String, (usize, usize)>,
+    todos: Vec<TodoItem>,
+    show_todo_panel: bool,
+    todo_scroll: usize,
+    todo_selected: usize,
+    focus: FocusManager,
+    available_commands: Vec<model::AvailableCommand>,
+    session_usage: SessionUsageState,
+    budget: BudgetState,
+    is_compacting: bool,
+    terminal_tool_calls: Vec<(String, usize, usize)>,
+    history_retention_stats: HistoryRetentionStats,
+    subagent_view: Option<SubagentView>,
+    attachments: AttachmentTray,
+}
+
+impl SessionState {
+    /// State for a tab whose session is still being created by the bridge.
+    fn connecting(app: &App) -> Self {
+        Self {
+            messages: vec![ChatMessage::welcome_with_recent(
+                &app.model_name,
+                &app.cwd,
+                &app.recent_sessions,
+            )],
+            viewport: ChatViewport::new(),
+            input: InputState::new(),
+            status: AppStatus::Connecting,
+            resuming_session_id: None,
+            session_id: None,
+            model_name: app.model_name.clone(),
+            mode: None,
+            files_accessed: 0,
+            login_hint: None,
+            pending_compact_clear: false,
+            pending_permission_ids: Vec::new(),
+            cancelled_turn_pending_hint: false,
+            submission_queue: SubmissionQueue::default(),
+            branches: BranchState::default(),
+            search: None,
+            chat_nav: None,
+            last_shell_output: None,
+            pending_cancel_origin: None,
+            active_task_ids: HashSet::new(),
+            tool_call_scopes: HashMap::new(),
+            active_subagent_tool_ids: HashSet::new(),
+            subagent_idle_since: None,
+            tool_call_index: HashMap::new(),
+            todos: Vec::new(),
+            show_todo_panel: false,
+            todo_scroll: 0,
+            todo_selected: 0,
+            focus: FocusManager::default(),
+            available_commands: Vec::new(),
+            session_usage: SessionUsageState::default(),
+            budget: BudgetState::default(),
+            is_compacting: false,
+            terminal_tool_calls: Vec::new(),
+            history_retention_stats: HistoryRetentionStats::default(),
+            subagent_view: None,
+            attachments: AttachmentTray::default(),
+        }
+    }
+
+    /// Exchange this parked state with the one currently held by `app`.
+    fn swap_with(&mut self, app: &mut App) {
+        use std::mem::swap;
+        swap(&mut self.messages, &mut app.messages);
+        swap(&mut self.viewport, &mut app.viewport);
+        swap(&mut self.input, &mut app.input);
+        swap(&mut self.status, &mut app.status);
+        swap(&mut self.resuming_session_id, &mut app.resuming_session_id);
+        swap(&mut self.session_id, &mut app.session_id);
+        swap(&mut self.model_name, &mut app.model_name);
+        swap(&mut self.mode, &mut app.mode);
+        swap(&mut self.files_accessed, &mut app.files_accessed);
+        swap(&mut self.login_hint, &mut app.login_hint);
+        swap(&mut self.pending_compact_clear, &mut app.pending_compact_clear);
+        swap(&mut self.pending_permission_ids, &mut app.pending_permission_ids);
+        swap(&mut self.cancelled_turn_pending_hint, &mut app.cancelled_turn_pending_hint);
+        swap(&mut self.submission_queue, &mut app.submission_queue);
+        swap(&mut self.branches, &mut app.branches);
+        swap(&mut self.search, &mut app.search);
+        swap(&mut self.chat_nav, &mut app.chat_nav);
+        swap(&mut self.last_shell_output, &mut app.last_shell_output);
+        swap(&mut self.pending_cancel_origin, &mut app.pending_cancel_origin);
+        swap(&mut self.active_task_ids, &mut app.active_task_ids);
+        swap(&mut self.tool_call_scopes, &mut app.tool_call_scopes);
+        swap(&mut self.active_subagent_tool_ids, &mut app.active_subagent_tool_ids);
+        swap(&mut self.subagent_idle_since, &mut app.subagent_idle_since);
+        swap(&mut self.tool_call_index, &mut app.tool_call_index);
+        swap(&mut self.todos, &mut app.todos);
+        swap(&mut self.show_todo_panel, &mut app.show_todo_panel);
+        swap(&mut self.todo_scroll, &mut app.todo_scroll);
+        swap(&mut self.todo_selected, &mut app.todo_selected);
+        swap(&mut self.focus, &mut app.focus);
+        swap(&mut self.available_commands, &mut app.available_commands);
+        swap(&mut self.session_usage, &mut app.session_usage);
+        swap(&mut self.budget, &mut app.budget);
+        swap(&mut self.is_compacting, &mut app.is_compacting);
+        swap(&mut self.terminal_tool_calls, &mut app.terminal_tool_calls);
+        swap(&mut self.history_retention_stats, &mut app.history_retention_stats);
+        swap(&mut self.subagent_view, &mut app.subagent_view);
+        swap(&mut self.attachments, &mut app.attachments);
+    }
+}
+
+/// Request id used to route connect events of a tab-owned bridge command.
+#[must_use]
+pub fn tab_request_id(tab_id: u64) -> String {
+    format!("{TAB_REQUEST_PREFIX}{tab_id}")
+}
+
+#[must_use]
+pub fn parse_tab_request_id(request_id: &str) -> Option<u64> {
+    request_id.strip_prefix(TAB_REQUEST_PREFIX)?.parse().ok()
+}
+
+/// Id of the tab currently shown.
+#[must_use]
+pub fn active_tab_id(app: &App) -> u64 {
+    app.tabs.list.get(app.tabs.active).map_or(0, |tab| tab.id)
+}
+
+/// Session ids of the parked tabs.
+#[must_use]
+pub fn session_ids(app: &App) -> Vec<String> {
+    app.tabs
+        .list
+        .iter()
+        .filter_map(|tab| tab.parked.as_ref()?.session_id.as_ref().map(ToString::to_string))
+        .collect()
+}
+
+/// Open a new tab backed by a fresh bridge session and switch to it.
+pub fn open_tab(app: &mut App) {
+    let Some(conn) = app.conn.as_ref().map(Rc::clone) else {
+        push_system_message(app, "Cannot open a new tab: not connected yet.");
+        return;
+    };
+
+    let tab_id = app.tabs.next_id;
+    app.tabs.next_id += 1;
+
+    super::draft::flush(app);
+    let mut state = SessionState::connecting(app);
+    state.swap_with(app);
+    if let Some(tab) = app.tabs.list.get_mut(app.tabs.active) {
+        tab.parked = Some(Box::new(state));
+    }
+    app.tabs.list.push(SessionTab::active(tab_id));
+    app.tabs.active = app.tabs.list.len() - 1;
+    after_switch(app);
+
+    let tx = app.event_tx.clone();
+    let cwd = app.cwd_raw.clone();
+    let model = Some(app.model_name.clone());
+    tokio::task::spawn_local(async move {
+        if let Err(e) = conn.create_session(cwd, false, model, tab_request_id(tab_id)) {
+            let _ = tx.send(ClientEvent::ConnectionFailed(format!("Failed to open tab: {e}")));
+        }
+    });
+}
+
+/// Bring the tab at `index` to the front.
+pub fn switch_to(app: &mut App, index: usize) {
+    if index == app.tabs.active || index >= app.tabs.list.len() {
+        return;
+    }
+    let Some(mut state) = app.tabs.list[index].parked.take() else {
+        return;
+    };
+    super::draft::flush(app);
+    state.swap_with(app);
+    app.tabs.list[app.tabs.active].parked = Some(state);
+    app.tabs.active = index;
+    app.tabs.list[index].unread = false;
+    after_switch(app);
+}
+
+/// Cycle to the next (`forward`) or previous tab, wrapping around.
+pub fn cycle(app: &mut App, forward: bool) {
+    let count = app.tabs.list.len();
+    if count < 2 {
+        return;
+    }
+    let next =
+        if forward { (app.tabs.active + 1) % count } else { (app.tabs.active + count - 1) % count };
+    switch_to(app, next);
+}
+
+/// Close the active tab, ending its bridge session, and show its neighbour.
+pub fn close_active(app: &mut App) {
+    if app.tabs.list.len() < 2 {
+        push_system_message(app, "Cannot close the last tab.");
+        return;
+    }
+
+    if let (Some(conn), Some(sid)) = (app.conn.as_ref(), app.session_id.as_ref()) {
+        let conn = Rc::clone(conn);
+        let sid = sid.to_string();
+        tokio::task::spawn_local(async move {
+            if let Err(e) = conn.close_session(sid) {
+                tracing::error!("Failed to close session: {e}");
+            }
+        });
+    }
+
+    let closing = app.tabs.active;
+    let next = if closing + 1 < app.tabs.list.len() { closing + 1 } else { closing - 1 };
+    switch_to(app, next);
+    // The closed tab's state is parked now; dropping it also drops any pending
+    // permission responders, which the bridge resolves as denied on close.
+    app.tabs.list.remove(closing);
+    if app.tabs.active > closing {
+        app.tabs.active -= 1;
+    }
+    app.force_redraw = true;
+}
+
+/// Dispatch an event that belongs to a specific session or tab.
+pub fn handle_routed_event(app: &mut App, target: &EventTarget, event: ClientEvent) {
+    let Some(index) = find_tab(app, target) else {
+        if matches!(target, EventTarget::Tab(_)) {
+            // The tab was closed before its session finished connecting.
+            close_orphaned_session(app, &event);
+            drop_unroutable_event(event);
+        } else if app.tabs.list.len() <= 1 {
+            // Single-tab sessions keep the pre-tab behavior of routing everything
+            // to the only view, e.g. events emitted before `Connected` arrives.
+            super::handle_client_event(app, event);
+        } else {
+            drop_unroutable_event(event);
+        }
+        return;
+    };
+
+    if index == app.tabs.active {
+        super::handle_client_event(app, event);
+        return;
+    }
+
+    let Some(mut state) = app.tabs.list[index].parked.take() else {
+        return;
+    };
+    state.swap_with(app);
+    super::handle_client_event(app, event);
+    state.swap_with(app);
+    app.tabs.list[index].parked = Some(state);
+    app.tabs.list[index].unread = true;
+    // Background handlers may touch shared caches that were built for the active tab.
+    invalidate_view_caches(app);
+}
+
+fn find_tab(app: &App, target: &EventTarget) -> Option<usize> {
+    match target {
+        EventTarget::Tab(id) => app.tabs.list.iter().position(|tab| tab.id == *id),
+        EventTarget::Session(sid) => app.tabs.list.iter().enumerate().find_map(|(index, tab)| {
+            let session_id = if index == app.tabs.active {
+                app.session_id.as_ref()
+            } else {
+                tab.parked.as_ref()?.session_id.as_ref()
+            };
+            (session_id.is_some_and(|id| id.to_string() == *sid)).then_some(index)
+        }),
+    }
+}
+
+fn close_orphaned_session(app: &App, event: &ClientEvent) {
+    let (ClientEvent::Connected { session_id, .. }
+    | ClientEvent::SessionReplaced { session_id, .. }) = event
+    else {
+        return;
+    };
+    let Some(conn) = app.conn.as_ref().map(Rc::clone) else {
+        return;
+    };
+    let sid = session_id.to_string();
+    tokio::task::spawn_local(async move {
+        if let Err(e) = conn.close_session(sid) {
+            tracing::error!("Failed to close orphaned session: {e}");
+        }
+    });
+}
+
+fn drop_unroutable_event(event: ClientEvent) {
+    if let ClientEvent::PermissionRequest { request, response_tx } = event {
+        tracing::warn!(
+            "Permission request for session without a tab: {}; auto-rejecting",
+            request.session_id
+        );
+        if let Some(last_opt) = request.options.last() {
+            let _ = response_tx.send(model::RequestPermissionResponse::new(
+                model::RequestPermissionOutcome::Selected(model::SelectedPermissionOutcome::new(
+                    last_opt.option_id.clone(),
+                )),
+            ));
+        }
+    } else {
+        tracing::debug!("Dropping event for session without a tab");
+    }
+}
+
+/// Whether the tab at `index` has a permission prompt waiting for the user.
+#[must_use]
+pub fn needs_permission(app: &App, index: usize) -> bool {
+    if index == app.tabs.active {
+        return !app.pending_permission_ids.is_empty();
+    }
+    app.tabs
+        .list
+        .get(index)
+        .and_then(|tab| tab.parked.as_ref())
+        .is_some_and(|state| !state.pending_permission_ids.is_empty())
+}
+
+/// Session title for the tab at `index` when known, otherwise its first user
+/// prompt, whitespace-collapsed. `None` for a fresh session.
+#[must_use]
+pub fn session_title(app: &App, index: usize) -> Option<String> {
+    let (messages, session_id) = if index == app.tabs.active {
+        (&app.messages, app.session_id.as_ref())
+    } else {
+        let state = app.tabs.list.get(index).and_then(|tab| tab.parked.as_ref())?;
+        (&state.messages, state.session_id.as_ref())
+    };
+
+    let title = session_id
+        .and_then(|sid| {
+            let sid = sid.to_string();
+            app.recent_sessions.iter().find(|s| s.session_id == sid)?.title.clone()
+        })
+        .or_else(|| {
+            messages.iter().find(|m| matches!(m.role, MessageRole::User)).and_then(|m| {
+                m.blocks.iter().find_map(|block| match block {
+                    MessageBlock::Text(text, ..) => Some(text.clone()),
+                    _ => None,
+                })
+            })
+        })?;
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Short label for the tab at `index`: the session title when known, otherwise
+/// the first user prompt.
+#[must_use]
+pub fn tab_label(app: &App, index: usize) -> String {
+    let Some(title) = session_title(app, index) else {
+        return NEW_TAB_LABEL.to_owned();
+    };
+    if title.chars().count() <= TAB_LABEL_MAX_CHARS {
+        return title;
+    }
+    let mut short: String = title.chars().take(TAB_LABEL_MAX_CHARS - 1).collect();
+    short.push('\u{2026}');
+    short
+}
+
+fn after_switch(app: &mut App) {
+    app.pending_submit = false;
+    app.drain_key_count = 0;
+    app.paste_burst.reset();
+    app.pending_paste_text.clear();
+    app.pending_paste_session = None;
+    app.active_paste_session = None;
+    app.paste_burst_start = None;
+    app.mention = None;
+    app.slash = None;
+    app.selection = None;
+    app.scrollbar_drag = None;
+    app.rendered_chat_lines.clear();
+    app.rendered_chat_area = ratatui::layout::Rect::default();
+    app.rendered_input_lines.clear();
+    app.rendered_input_area = ratatui::layout::Rect::default();
+    invalidate_view_caches(app);
+    app.force_redraw = true;
+}
+
+fn invalidate_view_caches(app: &mut App) {
+    app.cached_todo_compact = None;
+    app.cached_header_line = None;
+    app.cached_footer_line = None;
+    app.inline_images.invalidate();
+    app.needs_redraw = true;
+}
+
+fn push_system_message(app: &mut App, text: &str) {
+    app.messages.push(ChatMessage {
+        role: MessageRole::System,
+        blocks: vec![MessageBlock::Text(
+            text.to_owned(),
+            super::BlockCache::default(),
+            super::IncrementalMarkdown::from_complete(text),
+        )],
+        usage: None,
+    });
+    app.enforce_history_retention();
+    app.viewport.engage_auto_scroll();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{BlockCache, IncrementalMarkdown};
+
+    fn user_message(text: &str) -> ChatMessage {
+        ChatMessage {
+            role: MessageRole::User,
+            blocks: vec![MessageBlock::Text(
+                text.to_owned(),
+                BlockCache::default(),
+                IncrementalMarkdown::from_complete(text),
+            )],
+            usage: None,
+        }
+    }
+
+    /// Park the active tab behind a second tab, mirroring `open_tab` without a bridge.
+    fn open_offline_tab(app: &mut App) {
+        let mut state = SessionState::connecting(app);
+        state.swap_with(app);
+        app.tabs.list[app.tabs.active].parked = Some(Box::new(state));
+        app.tabs.list.push(SessionTab::active(app.tabs.next_id));
+        app.tabs.next_id += 1;
+        app.tabs.active = app.tabs.list.len() - 1;
+    }
+
+    #[test]
+    fn tab_request_id_roundtrip() {
+        assert_eq!(parse_tab_request_id(&tab_request_id(7)), Some(7));
+        assert_eq!(parse_tab_request_id("req-1"), None);
+        assert_eq!(parse_tab_request_id("tab:x"), None);
+    }
+
+    #[test]
+    fn switching_tabs_swaps_session_state() {
+        let mut app = App::test_default();
+        app.session_id = Some(model::SessionId::new("first"));
+        app.messages.push(user_message("fix the parser"));
+        app.input.set_text("draft one");
+        app.input.insert_paste_block(&"x".repeat(1200));
+        crate::app::attachments::refresh(&mut app);
+
+        open_offline_tab(&mut app);
+        assert_eq!(app.status, AppStatus::Connecting);
+        assert!(app.session_id.is_none());
+        assert!(app.input.is_empty());
+        assert!(app.attachments.is_empty());
+        app.session_id = Some(model::SessionId::new("second"));
+        app.input.set_text("draft two");
+
+        switch_to(&mut app, 0);
+        assert_eq!(app.tabs.active, 0);
+        assert_eq!(app.session_id.as_ref().map(ToString::to_string).as_deref(), Some("first"));
+        assert!(app.input.text().starts_with("draft one"));
+        assert_eq!(app.attachments.entries().len(), 1);
+        assert_eq!(tab_label(&app, 0), "fix the parser");
+        assert_eq!(tab_label(&app, 1), NEW_TAB_LABEL);
+
+        cycle(&mut app, true);
+        assert_eq!(app.input.text(), "draft two");
+    }
+
+    #[test]
+    fn background_event_marks_tab_unread() {
+        let mut app = App::test_default();
+        app.session_id = Some(model::SessionId::new("first"));
+        open_offline_tab(&mut app);
+        app.session_id = Some(model::SessionId::new("second"));
+        app.status = AppStatus::Ready;
+
+        handle_routed_event(
+            &mut app,
+            &EventTarget::Session("first".to_owned()),
+            ClientEvent::SlashCommandError("background failure".to_owned()),
+        );
+
+        assert!(app.tabs.list[0].unread);
+        assert!(!app.tabs.list[1].unread);
+        assert_eq!(app.session_id.as_ref().map(ToString::to_string).as_deref(), Some("second"));
+        assert!(app.messages.iter().all(|m| !matches!(m.role, MessageRole::System)));
+
+        switch_to(&mut app, 0);
+        assert!(!app.tabs.list[0].unread);
+        assert!(app.messages.iter().any(|m| matches!(m.role, MessageRole::System)));
+    }
+
+    #[test]
+    fn needs_permission_reflects_parked_queue() {
+        let mut app = App::test_default();
+        app.pending_permission_ids.push("tool-1".to_owned());
+        open_offline_tab(&mut app);
+
+        assert!(needs_permission(&app, 0));
+        assert!(!needs_permission(&app, 1));
+    }
+
+    #[test]
+    fn close_active_keeps_last_tab() {
+        let mut app = App::test_default();
+        close_active(&mut app);
+        assert_eq!(app.tabs.list.len(), 1);
+
+        open_offline_tab(&mut app);
+        close_active(&mut app);
+        assert_eq!(app.tabs.list.len(), 1);
+        assert_eq!(app.tabs.active, 0);
+        assert!(app.tabs.list[0].parked.is_none());
+    }
+}
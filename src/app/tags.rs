@@ -0,0 +1,777 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Lightweight symbol index for `@Symbol` mentions.
+//!
+//! Definitions are found with ctags-style line regexes per language. A
+//! definition runs to its matching closing brace, or to the end of its
+//! indented block in Python, and members are qualified by the enclosing
+//! `impl`, `trait`, `mod` or class, so `@App::render` finds the method.
+//!
+//! A worker thread parses the project after the first symbol lookup and
+//! again, for files whose mtime changed, whenever the file index publishes a
+//! new snapshot. Lookups on the UI thread only search the latest finished
+//! snapshot, so a prompt sent before the first parse completes resolves
+//! nothing rather than waiting for it.
+
+use super::mention::FileCandidate;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::time::SystemTime;
+
+/// Source files parsed per update; the rest of a huge tree is ignored.
+const MAX_INDEXED_FILES: usize = 20_000;
+/// Larger files are skipped (generated code, bundles).
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Lines scanned for the end of one definition before giving up.
+const MAX_BODY_LINES: usize = 5_000;
+/// Definitions returned for one symbol.
+const MAX_MATCHES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A symbol without members (function, struct, constant).
+    Item,
+    /// A symbol that also qualifies the definitions inside it.
+    Container,
+    /// Qualifies the definitions inside it but is no symbol itself (`impl`).
+    Impl,
+    /// Only indexed inside a container (class methods).
+    Member,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Blocks {
+    /// Bodies in braces; a definition without one ends at `;`.
+    Braces,
+    /// Like `Braces`, but a definition without a body also ends with its line.
+    BracesOrLine,
+    /// Python: the body is the indented block after the header.
+    Indent,
+}
+
+struct Lang {
+    extensions: &'static [&'static str],
+    blocks: Blocks,
+    /// Rust lifetimes make `'` unusable as a string delimiter.
+    quote_strings: bool,
+    /// Patterns with a `name` group and an optional `container` group.
+    patterns: Vec<(Regex, Kind)>,
+}
+
+const VIS: &str = r"(?:pub(?:\([^)]*\))?\s+)?";
+
+fn pattern(source: &str) -> Option<Regex> {
+    Regex::new(source).map_err(|err| tracing::warn!("invalid tag pattern {source}: {err}")).ok()
+}
+
+fn compiled(patterns: Vec<(Option<Regex>, Kind)>) -> Vec<(Regex, Kind)> {
+    patterns.into_iter().filter_map(|(re, kind)| Some((re?, kind))).collect()
+}
+
+fn languages() -> &'static [Lang] {
+    static LANGS: OnceLock<Vec<Lang>> = OnceLock::new();
+    LANGS.get_or_init(|| {
+        vec![
+            Lang {
+                extensions: &["rs"],
+                blocks: Blocks::Braces,
+                quote_strings: false,
+                patterns: compiled(vec![
+                    (
+                        pattern(&format!(
+                            r#"^\s*{VIS}(?:(?:default|const|async|unsafe|extern\s+"[^"]*")\s+)*fn\s+(?P<name>\w+)"#
+                        )),
+                        Kind::Item,
+                    ),
+                    (
+                        pattern(&format!(r"^\s*{VIS}(?:struct|enum|union|type)\s+(?P<name>\w+)")),
+                        Kind::Item,
+                    ),
+                    (
+                        pattern(&format!(r"^\s*{VIS}(?:unsafe\s+)?(?:trait|mod)\s+(?P<name>\w+)")),
+                        Kind::Container,
+                    ),
+                    (
+                        pattern(&format!(r"^\s*{VIS}(?:const|static)\s+(?:mut\s+)?(?P<name>\w+)\s*:")),
+                        Kind::Item,
+                    ),
+                    (pattern(r"^\s*macro_rules!\s*(?P<name>\w+)"), Kind::Item),
+                    (
+                        pattern(
+                            r"^\s*(?:unsafe\s+)?impl\b(?:\s*<[^{]*?>)?\s+(?:[^{]*?\s+for\s+)?(?:&\s*(?:'\w+\s+)?(?:mut\s+)?)?(?:dyn\s+)?(?:\w+::)*(?P<name>\w+)",
+                        ),
+                        Kind::Impl,
+                    ),
+                ]),
+            },
+            Lang {
+                extensions: &["py", "pyi"],
+                blocks: Blocks::Indent,
+                quote_strings: true,
+                patterns: compiled(vec![
+                    (pattern(r"^\s*(?:async\s+)?def\s+(?P<name>\w+)"), Kind::Item),
+                    (pattern(r"^\s*class\s+(?P<name>\w+)"), Kind::Container),
+                ]),
+            },
+            Lang {
+                extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"],
+                blocks: Blocks::BracesOrLine,
+                quote_strings: true,
+                patterns: compiled(vec![
+                    (
+                        pattern(
+                            r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*(?P<name>[\w$]+)",
+                        ),
+                        Kind::Item,
+                    ),
+                    (
+                        pattern(
+                            r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+(?P<name>[\w$]+)",
+                        ),
+                        Kind::Container,
+                    ),
+                    (
+                        pattern(
+                            r"^\s*(?:export\s+)?(?:declare\s+)?(?:const\s+)?(?:interface|type|enum|namespace)\s+(?P<name>[\w$]+)",
+                        ),
+                        Kind::Item,
+                    ),
+                    (
+                        pattern(
+                            r"^\s*(?:export\s+)?(?:const|let|var)\s+(?P<name>[\w$]+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*(?::[^=]+)?=>|[\w$]+\s*=>)",
+                        ),
+                        Kind::Item,
+                    ),
+                    (
+                        pattern(
+                            r"^\s+(?:(?:public|private|protected|static|async|readonly|override|abstract|get|set)\s+)*\*?(?P<name>[\w$#]+)\s*(?:<[^>]*>)?\([^)]*\)?\s*(?::\s*[^{;]+)?\{?\s*$",
+                        ),
+                        Kind::Member,
+                    ),
+                ]),
+            },
+            Lang {
+                extensions: &["go"],
+                blocks: Blocks::BracesOrLine,
+                quote_strings: true,
+                patterns: compiled(vec![
+                    (
+                        pattern(
+                            r"^func\s+\(\s*\w*\s*\*?\s*(?P<container>\w+)(?:\[[^\]]*\])?\s*\)\s*(?P<name>\w+)",
+                        ),
+                        Kind::Item,
+                    ),
+                    (pattern(r"^func\s+(?P<name>\w+)"), Kind::Item),
+                    (pattern(r"^type\s+(?P<name>\w+)"), Kind::Item),
+                ]),
+            },
+        ]
+    })
+}
+
+fn language_for(path: &str) -> Option<&'static Lang> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    languages().iter().find(|lang| lang.extensions.contains(&ext))
+}
+
+/// Words the member pattern also matches at the start of statements.
+const NOT_MEMBERS: &[&str] =
+    &["if", "for", "while", "switch", "catch", "return", "function", "with", "else"];
+
+/// One definition in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// Enclosing containers and the name itself, outermost first.
+    pub path: Vec<String>,
+    /// 1-based first line, including doc comments and attributes above.
+    pub start_line: usize,
+    /// 1-based last line of the body.
+    pub end_line: usize,
+}
+
+impl Tag {
+    #[must_use]
+    pub fn qualified_name(&self) -> String {
+        self.path.join("::")
+    }
+
+    fn matches(&self, query: &[String]) -> bool {
+        self.path.ends_with(query)
+    }
+}
+
+/// A tag found by [`TagIndex::lookup`], with the file it lives in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagMatch {
+    /// Relative path from the project root (forward slashes).
+    pub rel_path: String,
+    pub tag: Tag,
+}
+
+#[derive(Clone)]
+struct IndexedFile {
+    modified: SystemTime,
+    tags: Arc<Vec<Tag>>,
+}
+
+/// Parsed definitions of one project, by relative path.
+#[derive(Clone, Default)]
+struct Tags {
+    root: PathBuf,
+    files: HashMap<String, IndexedFile>,
+}
+
+impl Tags {
+    /// Re-parse files whose mtime changed and drop files no longer listed.
+    fn update(&mut self, root: &Path, candidates: &[FileCandidate]) {
+        if self.root != root {
+            self.root = root.to_path_buf();
+            self.files.clear();
+        }
+        let mut seen = HashSet::new();
+        for candidate in candidates.iter().filter(|c| !c.is_dir) {
+            if seen.len() >= MAX_INDEXED_FILES {
+                break;
+            }
+            let Some(lang) = language_for(&candidate.rel_path) else {
+                continue;
+            };
+            seen.insert(candidate.rel_path.as_str());
+            let full = root.join(&candidate.rel_path);
+            let Ok(metadata) = std::fs::metadata(&full) else {
+                self.files.remove(&candidate.rel_path);
+                continue;
+            };
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            if self.files.get(&candidate.rel_path).is_some_and(|file| file.modified == modified) {
+                continue;
+            }
+            let tags = if metadata.len() > MAX_FILE_BYTES {
+                Vec::new()
+            } else {
+                std::fs::read_to_string(&full)
+                    .map(|content| extract_tags(lang, &content))
+                    .unwrap_or_default()
+            };
+            self.files
+                .insert(candidate.rel_path.clone(), IndexedFile { modified, tags: Arc::new(tags) });
+        }
+        self.files.retain(|rel_path, _| seen.contains(rel_path.as_str()));
+    }
+
+    fn lookup(&self, query: &[String]) -> Vec<TagMatch> {
+        let mut matches: Vec<TagMatch> = self
+            .files
+            .iter()
+            .flat_map(|(rel_path, file)| {
+                file.tags
+                    .iter()
+                    .filter(|tag| tag.matches(query))
+                    .map(|tag| TagMatch { rel_path: rel_path.clone(), tag: tag.clone() })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            a.tag
+                .path
+                .len()
+                .cmp(&b.tag.path.len())
+                .then_with(|| a.rel_path.cmp(&b.rel_path))
+                .then_with(|| a.tag.start_line.cmp(&b.tag.start_line))
+        });
+        matches.truncate(MAX_MATCHES);
+        matches
+    }
+}
+
+enum Command {
+    Update { root: PathBuf, files: Arc<Vec<FileCandidate>> },
+    Stop,
+}
+
+#[derive(Default)]
+struct Shared {
+    snapshot: Mutex<Arc<Tags>>,
+    version: AtomicU64,
+}
+
+struct Worker {
+    shared: Arc<Shared>,
+    commands: mpsc::Sender<Command>,
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Stop);
+    }
+}
+
+#[derive(Default)]
+pub struct TagIndex {
+    /// Parse on a worker thread. Off (the default, used by tests) re-parses
+    /// changed files synchronously on every lookup.
+    live: bool,
+    worker: Option<Worker>,
+    /// Latest snapshot searched by lookups.
+    tags: Arc<Tags>,
+    /// Snapshot version last copied into `tags`.
+    pulled: u64,
+    /// Root and file index version last sent to the worker.
+    requested: Option<(PathBuf, u64)>,
+}
+
+impl TagIndex {
+    #[must_use]
+    pub fn live() -> Self {
+        Self { live: true, ..Self::default() }
+    }
+
+    /// Bumped whenever the worker finishes a snapshot, so results derived
+    /// from earlier lookups know to start over.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.worker.as_ref().map_or(self.pulled, |w| w.shared.version.load(Ordering::Acquire))
+    }
+
+    /// Definitions of `query` (name segments, e.g. `["App", "render"]`),
+    /// tightest qualification first. `files` is the file index snapshot
+    /// numbered `files_version`; a newer one than the worker last saw starts
+    /// an update, and this lookup answers from what is already parsed.
+    pub fn lookup(
+        &mut self,
+        root: &Path,
+        files: &Arc<Vec<FileCandidate>>,
+        files_version: u64,
+        query: &[String],
+    ) -> Vec<TagMatch> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        if self.live {
+            self.request(root, files, files_version);
+            self.pull();
+        } else {
+            Arc::make_mut(&mut self.tags).update(root, files);
+        }
+        self.tags.lookup(query)
+    }
+
+    fn request(&mut self, root: &Path, files: &Arc<Vec<FileCandidate>>, files_version: u64) {
+        if self.requested.as_ref().is_some_and(|(r, v)| r == root && *v == files_version) {
+            return;
+        }
+        if self.worker.is_none() {
+            let shared = Arc::new(Shared::default());
+            let (commands, receiver) = mpsc::channel();
+            let thread_shared = Arc::clone(&shared);
+            let spawned = std::thread::Builder::new()
+                .name("tag-index".to_owned())
+                .spawn(move || run(&thread_shared, &receiver));
+            if let Err(err) = spawned {
+                tracing::warn!("tag index thread failed to start: {err}");
+                self.live = false;
+                return;
+            }
+            self.worker = Some(Worker { shared, commands });
+        }
+        let update = Command::Update { root: root.to_path_buf(), files: Arc::clone(files) };
+        if self.worker.as_ref().is_some_and(|w| w.commands.send(update).is_ok()) {
+            self.requested = Some((root.to_path_buf(), files_version));
+        }
+    }
+
+    fn pull(&mut self) {
+        let Some(worker) = self.worker.as_ref() else {
+            return;
+        };
+        let version = worker.shared.version.load(Ordering::Acquire);
+        if version == self.pulled {
+            return;
+        }
+        if let Ok(snapshot) = worker.shared.snapshot.lock() {
+            self.tags = Arc::clone(&snapshot);
+        }
+        self.pulled = version;
+    }
+}
+
+/// Worker loop: apply the newest pending update, then publish a snapshot.
+fn run(shared: &Shared, commands: &mpsc::Receiver<Command>) {
+    let mut tags = Tags::default();
+    while let Ok(mut command) = commands.recv() {
+        while let Ok(next) = commands.try_recv() {
+            command = next;
+            if matches!(command, Command::Stop) {
+                break;
+            }
+        }
+        let Command::Update { root, files } = command else {
+            return;
+        };
+        tags.update(&root, &files);
+        if let Ok(mut snapshot) = shared.snapshot.lock() {
+            *snapshot = Arc::new(tags.clone());
+        }
+        shared.version.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Tags of one file in `lang`, in source order.
+fn extract_tags(lang: &Lang, content: &str) -> Vec<Tag> {
+    struct Raw {
+        name: String,
+        container: Option<String>,
+        kind: Kind,
+        line: usize,
+        end: usize,
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut raw: Vec<Raw> = Vec::new();
+    for (line, text) in lines.iter().enumerate() {
+        let Some((caps, kind)) =
+            lang.patterns.iter().find_map(|(re, kind)| re.captures(text).map(|caps| (caps, *kind)))
+        else {
+            continue;
+        };
+        let name = caps["name"].to_owned();
+        if kind == Kind::Member && NOT_MEMBERS.contains(&name.as_str()) {
+            continue;
+        }
+        let end = match lang.blocks {
+            Blocks::Indent => indent_end(&lines, line),
+            Blocks::Braces | Blocks::BracesOrLine => brace_end(lang, &lines, line),
+        };
+        let container = caps.name("container").map(|m| m.as_str().to_owned());
+        raw.push(Raw { name, container, kind, line, end });
+    }
+
+    let mut tags = Vec::new();
+    for item in raw.iter().filter(|item| item.kind != Kind::Impl) {
+        let path = if let Some(container) = &item.container {
+            vec![container.clone(), item.name.clone()]
+        } else {
+            let mut path: Vec<String> = raw
+                .iter()
+                .filter(|outer| {
+                    matches!(outer.kind, Kind::Container | Kind::Impl)
+                        && outer.line < item.line
+                        && item.line <= outer.end
+                })
+                .map(|outer| outer.name.clone())
+                .collect();
+            if item.kind == Kind::Member && path.is_empty() {
+                continue;
+            }
+            path.push(item.name.clone());
+            path
+        };
+        tags.push(Tag {
+            path,
+            start_line: preamble_start(&lines, item.line) + 1,
+            end_line: item.end + 1,
+        });
+    }
+    tags
+}
+
+/// First line of the doc comments, attributes and decorators above `line`.
+fn preamble_start(lines: &[&str], line: usize) -> usize {
+    let mut start = line;
+    while start > 0 {
+        let above = lines[start - 1].trim_start();
+        let is_preamble =
+            ["///", "#[", "@", "/**", "* ", "*/"].iter().any(|prefix| above.starts_with(prefix))
+                || above == "*";
+        if !is_preamble {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+/// Last line of the brace-delimited definition starting at `line`.
+fn brace_end(lang: &Lang, lines: &[&str], line: usize) -> usize {
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut opened = false;
+    let mut in_block_comment = false;
+    let mut in_string: Option<char> = None;
+    let last = lines.len().min(line + MAX_BODY_LINES);
+    for (idx, text) in lines.iter().enumerate().take(last).skip(line) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if in_block_comment {
+                if c == '*' && next == Some('/') {
+                    in_block_comment = false;
+                    i += 1;
+                }
+            } else if let Some(quote) = in_string {
+                if c == '\\' {
+                    i += 1;
+                } else if c == quote {
+                    in_string = None;
+                }
+            } else {
+                match c {
+                    '/' if next == Some('/') => break,
+                    '/' if next == Some('*') => {
+                        in_block_comment = true;
+                        i += 1;
+                    }
+                    '"' | '`' => in_string = Some(c),
+                    '\'' if lang.quote_strings => in_string = Some(c),
+                    // Rust char literals; lone quotes are lifetimes.
+                    '\'' if next == Some('\\') => {
+                        while i + 1 < chars.len() && !(chars[i + 1] == '\'' && chars[i] != '\\') {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+                    '(' | '[' => parens += 1,
+                    ')' | ']' => parens = parens.saturating_sub(1),
+                    '{' => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    '}' => {
+                        depth = depth.saturating_sub(1);
+                        if opened && depth == 0 {
+                            return idx;
+                        }
+                    }
+                    ';' if !opened && parens == 0 => return idx,
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+        // Backtick strings may span lines; the others end with the line.
+        if in_string != Some('`') {
+            in_string = None;
+        }
+        if lang.blocks == Blocks::BracesOrLine && !opened && parens == 0 {
+            let trimmed = text.trim_end();
+            let continues = trimmed.ends_with(|c: char| ",(=|&+-*/?:<[".contains(c))
+                || lines.get(idx + 1).is_some_and(|next| next.trim_start().starts_with('{'));
+            if !continues {
+                return idx;
+            }
+        }
+    }
+    last.saturating_sub(1).max(line)
+}
+
+fn indent_of(text: &str) -> usize {
+    text.len() - text.trim_start().len()
+}
+
+/// Last line of the Python block whose header starts at `line`.
+fn indent_end(lines: &[&str], line: usize) -> usize {
+    let indent = indent_of(lines[line]);
+    let mut end = line;
+    let mut parens = 0usize;
+    let mut header = true;
+    let last = lines.len().min(line + MAX_BODY_LINES);
+    for (idx, text) in lines.iter().enumerate().take(last).skip(line) {
+        if header {
+            for c in text.chars() {
+                match c {
+                    '(' | '[' | '{' => parens += 1,
+                    ')' | ']' | '}' => parens = parens.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            end = idx;
+            header = parens > 0 || !text.trim_end().ends_with(':');
+            continue;
+        }
+        if text.trim().is_empty() {
+            continue;
+        }
+        if indent_of(text) <= indent {
+            break;
+        }
+        end = idx;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags_of(path: &str, content: &str) -> Vec<(String, usize, usize)> {
+        let lang = language_for(path).expect("known language");
+        extract_tags(lang, content)
+            .into_iter()
+            .map(|tag| (tag.qualified_name(), tag.start_line, tag.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn rust_methods_are_qualified_by_their_impl_and_span_their_body() {
+        let source = "\
+pub struct App {
+    field: u8,
+}
+
+impl<'a> Widget for &'a App {
+    /// Draw it.
+    pub fn render(&self) {
+        let brace = '}';
+        let text = \"{\";
+    }
+}
+
+mod inner {
+    const LIMIT: usize = 3;
+    fn helper() -> usize;
+}
+";
+        let tags = tags_of("src/app.rs", source);
+        assert_eq!(
+            tags,
+            vec![
+                ("App".to_owned(), 1, 3),
+                ("App::render".to_owned(), 6, 10),
+                ("inner".to_owned(), 13, 16),
+                ("inner::LIMIT".to_owned(), 14, 14),
+                ("inner::helper".to_owned(), 15, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_blocks_end_at_dedent_and_go_receivers_qualify() {
+        let python = "\
+class Server:
+    @property
+    def start(
+        self,
+    ):
+        run()
+
+        return 1
+
+def main():
+    pass
+";
+        assert_eq!(
+            tags_of("app.py", python),
+            vec![
+                ("Server".to_owned(), 1, 8),
+                ("Server::start".to_owned(), 2, 8),
+                ("main".to_owned(), 10, 11),
+            ]
+        );
+
+        let go = "\
+type Server struct {
+\tport int
+}
+
+func (s *Server) Start() error {
+\treturn nil
+}
+
+type ID string
+";
+        assert_eq!(
+            tags_of("main.go", go),
+            vec![
+                ("Server".to_owned(), 1, 3),
+                ("Server::Start".to_owned(), 5, 7),
+                ("ID".to_owned(), 9, 9),
+            ]
+        );
+    }
+
+    fn candidates(paths: &[&str]) -> Vec<FileCandidate> {
+        paths
+            .iter()
+            .map(|rel| FileCandidate {
+                rel_path: (*rel).to_owned(),
+                depth: 1,
+                modified: SystemTime::UNIX_EPOCH,
+                is_dir: false,
+                matched: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lookup_prefers_tight_matches_and_reparses_changed_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let write = |rel: &str, content: &str| {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+            std::fs::write(path, content).expect("write");
+        };
+        write("src/a.rs", "fn render() {}\nimpl App {\n    fn render(&self) {}\n}\n");
+        write(
+            "web/view.ts",
+            "export class View {\n  render(): void {\n    if (x) {\n    }\n  }\n}\n",
+        );
+        let candidates = Arc::new(candidates(&["src/a.rs", "web/view.ts"]));
+        let query = |q: &[&str]| q.iter().map(|s| (*s).to_owned()).collect::<Vec<_>>();
+
+        let mut index = TagIndex::default();
+        let found = index.lookup(dir.path(), &candidates, 0, &query(&["render"]));
+        let names: Vec<String> = found.iter().map(|m| m.tag.qualified_name()).collect();
+        assert_eq!(names, vec!["render", "App::render", "View::render"]);
+        assert_eq!(found[2].tag.start_line..=found[2].tag.end_line, 2..=5);
+
+        let found = index.lookup(dir.path(), &candidates, 0, &query(&["App", "render"]));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rel_path, "src/a.rs");
+        assert!(index.lookup(dir.path(), &candidates, 0, &query(&["App"])).is_empty());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        write("src/a.rs", "struct App;\n");
+        let found = index.lookup(dir.path(), &candidates, 0, &query(&["App"]));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tag.start_line, 1);
+    }
+
+    #[test]
+    fn live_index_answers_from_the_finished_snapshot() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("lib.rs"), "fn render() {}\n").expect("write");
+        let files = Arc::new(candidates(&["lib.rs"]));
+        let query = vec!["render".to_owned()];
+        let mut index = TagIndex::live();
+
+        // The first lookup starts the worker; whether it already answers
+        // depends on how fast the worker parses.
+        index.lookup(dir.path(), &files, 1, &query);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while index.version() == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let found = index.lookup(dir.path(), &files, 1, &query);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].rel_path, "lib.rs");
+    }
+}
@@ -0,0 +1,187 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Terminal window title (OSC 0): status, project, session title and the
+//! active todo, so a glance at the tab bar or tmux shows which agent needs
+//! attention.
+//!
+//! The original title is saved on the terminal's title stack (XTWINOPS 22/23)
+//! before the first write and popped on exit. Terminals without a title stack
+//! ignore both sequences.
+
+use super::{App, AppStatus, TodoStatus};
+use crate::Cli;
+use std::io::{self, Write as _};
+
+const DISABLE_ENV: &str = "CLAUDE_RS_NO_TITLE";
+
+/// Save and restore the current title (icon and window) on the title stack.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+#[derive(Debug, Default)]
+pub struct TerminalTitle {
+    /// Title last written to the terminal; `None` until the first write or
+    /// after the terminal was handed to another program.
+    written: Option<String>,
+    /// The original title was pushed and must be popped on exit.
+    pushed: bool,
+}
+
+impl TerminalTitle {
+    /// `None` when `--no-title` or `CLAUDE_RS_NO_TITLE` disables title updates.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Option<Self> {
+        let disabled_by_env = std::env::var(DISABLE_ENV)
+            .is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false" | "no" | "off"));
+        (!cli.no_title && !disabled_by_env).then(Self::default)
+    }
+
+    /// Forget the last written title so the next update rewrites it, e.g.
+    /// after an external editor set its own.
+    pub fn invalidate(&mut self) {
+        self.written = None;
+    }
+}
+
+fn status_label(app: &App) -> Option<&'static str> {
+    if !app.pending_permission_ids.is_empty() {
+        return Some("\u{26a0} waiting for permission");
+    }
+    let active = app.tabs.active;
+    if (0..app.tabs.list.len()).any(|i| i != active && super::tabs::needs_permission(app, i)) {
+        return Some("\u{26a0} permission in another tab");
+    }
+    if app.is_compacting {
+        return Some("\u{2026} compacting");
+    }
+    match app.status {
+        AppStatus::Connecting => Some("\u{2026} connecting"),
+        AppStatus::Resuming => Some("\u{2026} resuming"),
+        AppStatus::Thinking => Some("\u{2026} thinking"),
+        AppStatus::Running => Some("\u{2026} running tool"),
+        AppStatus::Error => Some("\u{2716} error"),
+        AppStatus::Ready => None,
+    }
+}
+
+/// Title for the current state: `status · project · session · active todo`,
+/// with empty parts left out.
+#[must_use]
+pub fn compose(app: &App) -> String {
+    let project = std::path::Path::new(&app.cwd_raw)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let session = super::tabs::session_title(app, app.tabs.active);
+    let active_todo = matches!(app.status, AppStatus::Thinking | AppStatus::Running)
+        .then(|| app.todos.iter().find(|t| t.status == TodoStatus::InProgress))
+        .flatten()
+        .map(|t| if t.active_form.is_empty() { &t.content } else { &t.active_form });
+
+    let parts = [
+        status_label(app),
+        Some(project.as_str()),
+        session.as_deref(),
+        active_todo.map(String::as_str),
+    ];
+    let title = parts
+        .into_iter()
+        .flatten()
+        .map(super::notify::sanitize)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" \u{b7} ");
+    if title.is_empty() { "claude-rs".to_owned() } else { title }
+}
+
+/// Write the title if it changed since the last frame.
+pub(super) fn update(app: &mut App) {
+    let Some(state) = app.terminal_title.as_ref() else {
+        return;
+    };
+    let title = compose(app);
+    if state.written.as_deref() == Some(title.as_str()) {
+        return;
+    }
+    let push = !state.pushed;
+    let mut out = io::stdout();
+    let result = (|| {
+        if push {
+            out.write_all(PUSH_TITLE.as_bytes())?;
+        }
+        crossterm::execute!(out, crossterm::terminal::SetTitle(&title))
+    })();
+    if let Err(e) = result {
+        tracing::warn!("failed to set terminal title: {e}");
+    }
+    if let Some(state) = app.terminal_title.as_mut() {
+        state.pushed = true;
+        state.written = Some(title);
+    }
+}
+
+/// Pop the title saved before the first update.
+pub(super) fn restore(app: &mut App) {
+    let Some(state) = app.terminal_title.as_mut() else {
+        return;
+    };
+    if std::mem::take(&mut state.pushed) {
+        let mut out = io::stdout();
+        let _ = out.write_all(POP_TITLE.as_bytes()).and_then(|()| out.flush());
+    }
+    state.written = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ChatMessage, MessageBlock, MessageRole, TodoItem};
+
+    #[test]
+    fn compose_lists_status_project_session_and_active_todo() {
+        let mut app = App::test_default();
+        app.cwd_raw = "/home/dev/claude_rust".to_owned();
+        assert_eq!(compose(&app), "claude_rust");
+
+        app.messages.push(ChatMessage {
+            role: MessageRole::User,
+            blocks: vec![MessageBlock::Text(
+                "Fix the\nlogin bug".to_owned(),
+                crate::app::BlockCache::default(),
+                crate::app::IncrementalMarkdown::default(),
+            )],
+            usage: None,
+        });
+        app.status = AppStatus::Running;
+        app.todos.push(TodoItem {
+            content: "Run tests".to_owned(),
+            status: TodoStatus::InProgress,
+            active_form: "Running tests".to_owned(),
+        });
+        assert_eq!(
+            compose(&app),
+            "\u{2026} running tool \u{b7} claude_rust \u{b7} Fix the login bug \u{b7} Running tests"
+        );
+
+        app.pending_permission_ids.push("tool-1".to_owned());
+        assert!(compose(&app).starts_with("\u{26a0} waiting for permission \u{b7} claude_rust"));
+
+        app.pending_permission_ids.clear();
+        app.status = AppStatus::Error;
+        assert_eq!(compose(&app), "\u{2716} error \u{b7} claude_rust \u{b7} Fix the login bug");
+    }
+}
@@ -0,0 +1,1516 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Optional vi-style modal editing for the prompt input (`--vi`, `/vim`).
+//!
+//! Insert mode hands keys to the regular input handling. Normal and visual
+//! mode keys are collected in `pending` until they parse as a complete
+//! command (`"a2dw`, `ci(`, `5x`), which is then applied to the input text as
+//! one flat char buffer. Yanks also go to the system clipboard, and the `+`
+//! and `*` registers read and write it directly.
+
+use super::App;
+use super::clipboard::ClipboardMode;
+use super::input::InputState;
+use crate::Cli;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+const VI_MODE_ENV: &str = "CLAUDE_RS_VI_MODE";
+const UNNAMED: char = '"';
+const YANK_REGISTER: char = '0';
+const UNDO_LIMIT: usize = 100;
+/// Largest count a command takes; bigger ones are clamped.
+const MAX_COUNT: usize = 10_000;
+/// Most chars one counted paste may insert.
+const MAX_PASTE_CHARS: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimMode {
+    Insert,
+    Normal,
+    Visual,
+    VisualLine,
+}
+
+impl VimMode {
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Insert => "INSERT",
+            Self::Normal => "NORMAL",
+            Self::Visual => "VISUAL",
+            Self::VisualLine => "V-LINE",
+        }
+    }
+
+    #[must_use]
+    pub fn is_visual(self) -> bool {
+        matches!(self, Self::Visual | Self::VisualLine)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Register {
+    text: String,
+    /// Whole lines, pasted on their own line rather than inside the text.
+    linewise: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl Snapshot {
+    fn of(input: &InputState) -> Self {
+        Self { lines: input.lines.clone(), row: input.cursor_row, col: input.cursor_col }
+    }
+}
+
+#[derive(Debug)]
+pub struct VimState {
+    pub mode: VimMode,
+    /// Keys of an incomplete normal/visual command, e.g. `2d` or `"a`.
+    pub pending: String,
+    /// Visual selection anchor, as a char offset into the input text.
+    pub visual_anchor: usize,
+    registers: HashMap<char, Register>,
+    last_find: Option<Find>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// Input before the current insert session, recorded on its first key.
+    insert_start: Option<Snapshot>,
+    /// Mirror yanks to, and read `+`/`*` from, the clipboard (`None` keeps
+    /// registers local).
+    clipboard: Option<ClipboardMode>,
+}
+
+impl Default for VimState {
+    fn default() -> Self {
+        Self {
+            mode: VimMode::Insert,
+            pending: String::new(),
+            visual_anchor: 0,
+            registers: HashMap::new(),
+            last_find: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            insert_start: None,
+            clipboard: Some(ClipboardMode::default()),
+        }
+    }
+}
+
+impl VimState {
+    /// Vi mode when `--vi` or `CLAUDE_RS_VI_MODE` asks for it.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Option<Self> {
+        let env = std::env::var(VI_MODE_ENV).ok();
+        let from_env = env
+            .is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"));
+        (cli.vi || from_env).then(|| Self::with_clipboard(ClipboardMode::from_cli(cli)))
+    }
+
+    fn with_clipboard(mode: ClipboardMode) -> Self {
+        Self { clipboard: Some(mode), ..Self::default() }
+    }
+
+    /// Char offsets `[start, end)` of the visual selection in `input`.
+    #[must_use]
+    pub fn visual_range(&self, input: &InputState) -> Option<(usize, usize)> {
+        if !self.mode.is_visual() {
+            return None;
+        }
+        let buf = Buffer::from_input(input);
+        let (start, end, _) = visual_selection(self, &buf);
+        Some((start, end))
+    }
+
+    fn clipboard_get(&self) -> Option<String> {
+        // OSC 52 reads are rarely permitted, so only the native clipboard is read.
+        self.clipboard?;
+        arboard::Clipboard::new().and_then(|mut c| c.get_text()).ok()
+    }
+
+    fn clipboard_set(&self, text: &str) {
+        if let Some(mode) = self.clipboard
+            && let Err(e) = super::clipboard::copy(text, mode)
+        {
+            tracing::debug!("vi yank not copied to clipboard: {e}");
+        }
+    }
+
+    /// Store deleted or yanked text in `register` and the unnamed register.
+    fn store(&mut self, register: Option<char>, text: String, linewise: bool, yank: bool) {
+        let value = Register { text, linewise };
+        let clipboard_text = || {
+            if value.linewise { format!("{}\n", value.text) } else { value.text.clone() }
+        };
+        match register {
+            Some('_') => return,
+            Some('+' | '*') => self.clipboard_set(&clipboard_text()),
+            Some(c) if c.is_ascii_uppercase() => {
+                let entry = self.registers.entry(c.to_ascii_lowercase()).or_default();
+                if entry.linewise || value.linewise {
+                    entry.text.push('\n');
+                }
+                entry.text.push_str(&value.text);
+                entry.linewise |= value.linewise;
+            }
+            Some(c) if c != UNNAMED => {
+                self.registers.insert(c, value.clone());
+            }
+            _ => {
+                if yank {
+                    self.registers.insert(YANK_REGISTER, value.clone());
+                    self.clipboard_set(&clipboard_text());
+                }
+            }
+        }
+        self.registers.insert(UNNAMED, value);
+    }
+
+    fn fetch(&self, register: Option<char>) -> Option<Register> {
+        let from_clipboard = || {
+            self.clipboard_get().map(|text| match text.strip_suffix('\n') {
+                Some(line) => Register { text: line.to_owned(), linewise: true },
+                None => Register { text, linewise: false },
+            })
+        };
+        match register.unwrap_or(UNNAMED) {
+            '+' | '*' => from_clipboard(),
+            UNNAMED => self.registers.get(&UNNAMED).cloned().or_else(from_clipboard),
+            c => self.registers.get(&c.to_ascii_lowercase()).cloned(),
+        }
+    }
+
+    fn push_undo(&mut self, snapshot: Snapshot) {
+        if self.undo.last() == Some(&snapshot) {
+            return;
+        }
+        self.undo.push(snapshot);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Find {
+    ch: char,
+    forward: bool,
+    till: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward { big: bool },
+    WordBackward { big: bool },
+    WordEnd { big: bool },
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FirstLine,
+    LastLine,
+    Find(Find),
+    RepeatFind { reverse: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MotionKind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectKind {
+    Word { big: bool },
+    Quote(char),
+    Pair(char, char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TextObject {
+    kind: ObjectKind,
+    around: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Motion(Motion),
+    Object(TextObject),
+    /// `dd`, `cc`, `yy`: whole lines.
+    Lines,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InsertAt {
+    Before,
+    After,
+    LineStart,
+    LineEnd,
+    LineBelow,
+    LineAbove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Move(Motion),
+    Operate(Operator, Target),
+    Insert(InsertAt),
+    Replace(char),
+    ToggleCase,
+    Join,
+    Paste { before: bool },
+    Undo,
+    Visual { line: bool },
+    // Visual mode only.
+    VisualOperate(Operator),
+    VisualLines(Operator),
+    SelectObject(TextObject),
+    SwapAnchor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Command {
+    register: Option<char>,
+    count: Option<usize>,
+    action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parse {
+    Incomplete,
+    Invalid,
+    Done(Command),
+}
+
+fn is_register(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '"' | '+' | '*' | '_')
+}
+
+/// Leading count; a count cannot start with `0`, which is a motion.
+fn parse_count(keys: &[char]) -> (Option<usize>, usize) {
+    let len = keys
+        .iter()
+        .enumerate()
+        .take_while(|(i, c)| c.is_ascii_digit() && (*i > 0 || **c != '0'))
+        .count();
+    if len == 0 {
+        return (None, 0);
+    }
+    let digits: String = keys[..len].iter().collect();
+    // Only overflow fails to parse, and that is clamped like any huge count.
+    (Some(digits.parse().map_or(MAX_COUNT, |count: usize| count.min(MAX_COUNT))), len)
+}
+
+fn multiply(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(1).saturating_mul(b.unwrap_or(1)).min(MAX_COUNT)),
+    }
+}
+
+/// Motion at the start of `keys`: `Ok(None)` when more keys are needed.
+fn parse_motion(keys: &[char]) -> Result<Option<Motion>, ()> {
+    let Some(&c) = keys.first() else {
+        return Ok(None);
+    };
+    let motion = match c {
+        'h' => Motion::Left,
+        'l' | ' ' => Motion::Right,
+        'k' => Motion::Up,
+        'j' => Motion::Down,
+        'w' | 'W' => Motion::WordForward { big: c == 'W' },
+        'b' | 'B' => Motion::WordBackward { big: c == 'B' },
+        'e' | 'E' => Motion::WordEnd { big: c == 'E' },
+        '0' => Motion::LineStart,
+        '^' => Motion::FirstNonBlank,
+        '$' => Motion::LineEnd,
+        'G' => Motion::LastLine,
+        ';' | ',' => Motion::RepeatFind { reverse: c == ',' },
+        'g' => match keys.get(1) {
+            None => return Ok(None),
+            Some('g') => Motion::FirstLine,
+            Some(_) => return Err(()),
+        },
+        'f' | 'F' | 't' | 'T' => match keys.get(1) {
+            None => return Ok(None),
+            Some(&ch) => Motion::Find(Find {
+                ch,
+                forward: c.is_ascii_lowercase(),
+                till: c.eq_ignore_ascii_case(&'t'),
+            }),
+        },
+        _ => return Err(()),
+    };
+    Ok(Some(motion))
+}
+
+/// Text object after `i`/`a`: `Ok(None)` when more keys are needed.
+fn parse_object(keys: &[char]) -> Result<Option<TextObject>, ()> {
+    let (Some(&prefix), Some(&c)) = (keys.first(), keys.get(1)) else {
+        return Ok(None);
+    };
+    let kind = match c {
+        'w' | 'W' => ObjectKind::Word { big: c == 'W' },
+        '"' | '\'' | '`' => ObjectKind::Quote(c),
+        '(' | ')' | 'b' => ObjectKind::Pair('(', ')'),
+        '[' | ']' => ObjectKind::Pair('[', ']'),
+        '{' | '}' | 'B' => ObjectKind::Pair('{', '}'),
+        '<' | '>' => ObjectKind::Pair('<', '>'),
+        _ => return Err(()),
+    };
+    Ok(Some(TextObject { kind, around: prefix == 'a' }))
+}
+
+fn operator_for(c: char) -> Option<Operator> {
+    match c {
+        'd' => Some(Operator::Delete),
+        'c' => Some(Operator::Change),
+        'y' => Some(Operator::Yank),
+        _ => None,
+    }
+}
+
+/// Operator target after `d`/`c`/`y`, with its own optional count.
+fn parse_operator_target(
+    op_key: char,
+    keys: &[char],
+) -> Result<Option<(Option<usize>, Target)>, ()> {
+    let (count, skip) = parse_count(keys);
+    let rest = &keys[skip..];
+    let target = match rest.first() {
+        None => return Ok(None),
+        Some(&c) if c == op_key => Target::Lines,
+        Some('i' | 'a') => match parse_object(rest)? {
+            Some(object) => Target::Object(object),
+            None => return Ok(None),
+        },
+        Some(_) => match parse_motion(rest)? {
+            Some(motion) => Target::Motion(motion),
+            None => return Ok(None),
+        },
+    };
+    Ok(Some((count, target)))
+}
+
+fn parse_simple(c: char, visual: bool) -> Option<Action> {
+    let action = match (c, visual) {
+        ('x', false) => Action::Operate(Operator::Delete, Target::Motion(Motion::Right)),
+        ('X', false) => Action::Operate(Operator::Delete, Target::Motion(Motion::Left)),
+        ('s', false) => Action::Operate(Operator::Change, Target::Motion(Motion::Right)),
+        ('S', false) => Action::Operate(Operator::Change, Target::Lines),
+        ('D', false) => Action::Operate(Operator::Delete, Target::Motion(Motion::LineEnd)),
+        ('C', false) => Action::Operate(Operator::Change, Target::Motion(Motion::LineEnd)),
+        ('Y', false) => Action::Operate(Operator::Yank, Target::Lines),
+        ('i', false) => Action::Insert(InsertAt::Before),
+        ('a', false) => Action::Insert(InsertAt::After),
+        ('I', false) => Action::Insert(InsertAt::LineStart),
+        ('A', false) => Action::Insert(InsertAt::LineEnd),
+        ('o', false) => Action::Insert(InsertAt::LineBelow),
+        ('O', false) => Action::Insert(InsertAt::LineAbove),
+        ('u', false) => Action::Undo,
+        ('d' | 'x', true) => Action::VisualOperate(Operator::Delete),
+        ('c' | 's', true) => Action::VisualOperate(Operator::Change),
+        ('y', true) => Action::VisualOperate(Operator::Yank),
+        ('D' | 'X', true) => Action::VisualLines(Operator::Delete),
+        ('C' | 'S', true) => Action::VisualLines(Operator::Change),
+        ('Y', true) => Action::VisualLines(Operator::Yank),
+        ('o', true) => Action::SwapAnchor,
+        ('~', _) => Action::ToggleCase,
+        ('J', _) => Action::Join,
+        ('p', _) => Action::Paste { before: false },
+        ('P', _) => Action::Paste { before: true },
+        ('v', _) => Action::Visual { line: false },
+        ('V', _) => Action::Visual { line: true },
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Parse a complete normal (or visual) mode command from typed keys.
+fn parse(keys: &[char], visual: bool) -> Parse {
+    let mut rest = keys;
+    let mut register = None;
+    if rest.first() == Some(&'"') {
+        match rest.get(1) {
+            None => return Parse::Incomplete,
+            Some(&c) if is_register(c) => register = Some(c),
+            Some(_) => return Parse::Invalid,
+        }
+        rest = &rest[2..];
+    }
+    let (count, skip) = parse_count(rest);
+    rest = &rest[skip..];
+    let Some(&c) = rest.first() else {
+        return Parse::Incomplete;
+    };
+    let tail = &rest[1..];
+    let done = |count, action| Parse::Done(Command { register, count, action });
+
+    if let Some(op) = operator_for(c).filter(|_| !visual) {
+        return match parse_operator_target(c, tail) {
+            Ok(Some((inner, target))) => done(multiply(count, inner), Action::Operate(op, target)),
+            Ok(None) => Parse::Incomplete,
+            Err(()) => Parse::Invalid,
+        };
+    }
+    if c == 'r' {
+        return tail.first().map_or(Parse::Incomplete, |&ch| done(count, Action::Replace(ch)));
+    }
+    if visual && matches!(c, 'i' | 'a') {
+        return match parse_object(rest) {
+            Ok(Some(object)) => done(count, Action::SelectObject(object)),
+            Ok(None) => Parse::Incomplete,
+            Err(()) => Parse::Invalid,
+        };
+    }
+    if let Some(action) = parse_simple(c, visual) {
+        return done(count, action);
+    }
+    match parse_motion(rest) {
+        Ok(Some(motion)) => done(count, Action::Move(motion)),
+        Ok(None) => Parse::Incomplete,
+        Err(()) => Parse::Invalid,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Text buffer
+// ---------------------------------------------------------------------------
+
+/// The input as one char vector with `\n` line breaks and a flat cursor.
+#[derive(Debug, Clone)]
+struct Buffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punct,
+}
+
+fn class(c: char, big: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    } else if big || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+impl Buffer {
+    fn from_input(input: &InputState) -> Self {
+        let mut chars = Vec::new();
+        let mut cursor = 0;
+        for (row, line) in input.lines.iter().enumerate() {
+            if row > 0 {
+                chars.push('\n');
+            }
+            if row == input.cursor_row {
+                cursor = chars.len() + input.cursor_col.min(line.chars().count());
+            }
+            chars.extend(line.chars());
+        }
+        Self { chars, cursor }
+    }
+
+    /// Write text and cursor back, returning whether the text changed.
+    fn write_to(&self, input: &mut InputState) -> bool {
+        let text: String = self.chars.iter().collect();
+        let lines: Vec<String> = text.split('\n').map(str::to_owned).collect();
+        let row = self.line_index(self.cursor);
+        let col = self.cursor - self.line_start(self.cursor);
+        let changed = lines != input.lines;
+        if !changed && (input.cursor_row, input.cursor_col) == (row, col) {
+            return false;
+        }
+        input.lines = lines;
+        input.cursor_row = row;
+        input.cursor_col = col;
+        input.version += 1;
+        input.sync_textarea_engine();
+        changed
+    }
+
+    fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        let pos = pos.min(self.len());
+        self.chars[..pos].iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self, pos: usize) -> usize {
+        let pos = pos.min(self.len());
+        self.chars[pos..].iter().position(|&c| c == '\n').map_or(self.len(), |i| pos + i)
+    }
+
+    fn line_index(&self, pos: usize) -> usize {
+        self.chars[..pos.min(self.len())].iter().filter(|&&c| c == '\n').count()
+    }
+
+    fn line_count(&self) -> usize {
+        self.line_index(self.len()) + 1
+    }
+
+    /// Start offset of line `index`, clamped to the last line.
+    fn line_offset(&self, index: usize) -> usize {
+        let mut pos = 0;
+        for _ in 0..index.min(self.line_count() - 1) {
+            pos = self.line_end(pos) + 1;
+        }
+        pos
+    }
+
+    /// Last char of the line holding `pos` (the line start when empty).
+    fn last_char(&self, pos: usize) -> usize {
+        let (start, end) = (self.line_start(pos), self.line_end(pos));
+        if end > start { end - 1 } else { start }
+    }
+
+    fn first_non_blank(&self, pos: usize) -> usize {
+        let (start, end) = (self.line_start(pos), self.line_end(pos));
+        (start..end).find(|&i| !self.chars[i].is_whitespace()).unwrap_or(start)
+    }
+
+    /// Normal mode never rests on a line break.
+    fn clamp_cursor(&mut self) {
+        self.cursor = self.cursor.min(self.last_char(self.cursor));
+    }
+
+    fn class_at(&self, pos: usize, big: bool) -> CharClass {
+        class(self.chars[pos], big)
+    }
+
+    fn word_forward(&self, pos: usize, big: bool) -> usize {
+        let n = self.len();
+        let mut p = pos;
+        if p >= n {
+            return n;
+        }
+        let start_class = self.class_at(p, big);
+        if start_class != CharClass::Blank {
+            while p < n && self.class_at(p, big) == start_class {
+                p += 1;
+            }
+        }
+        while p < n && self.class_at(p, big) == CharClass::Blank {
+            // An empty line is a word of its own.
+            if self.chars[p] == '\n' && self.chars.get(p + 1) == Some(&'\n') {
+                return p + 1;
+            }
+            p += 1;
+        }
+        p
+    }
+
+    fn word_backward(&self, pos: usize, big: bool) -> usize {
+        let mut p = pos.min(self.len());
+        while p > 0 && self.class_at(p - 1, big) == CharClass::Blank {
+            p -= 1;
+        }
+        if p == 0 {
+            return 0;
+        }
+        let word_class = self.class_at(p - 1, big);
+        while p > 0 && self.class_at(p - 1, big) == word_class {
+            p -= 1;
+        }
+        p
+    }
+
+    fn word_end(&self, pos: usize, big: bool) -> usize {
+        let n = self.len();
+        if n == 0 {
+            return 0;
+        }
+        let mut p = pos + 1;
+        while p < n && self.class_at(p, big) == CharClass::Blank {
+            p += 1;
+        }
+        if p >= n {
+            return n - 1;
+        }
+        let word_class = self.class_at(p, big);
+        while p + 1 < n && self.class_at(p + 1, big) == word_class {
+            p += 1;
+        }
+        p
+    }
+
+    fn find_in_line(&self, pos: usize, find: Find, count: usize) -> Option<usize> {
+        let (start, end) = (self.line_start(pos), self.line_end(pos));
+        let mut p = pos;
+        for _ in 0..count {
+            p = if find.forward {
+                (p + 1..end).find(|&j| self.chars[j] == find.ch)?
+            } else {
+                (start..p).rev().find(|&j| self.chars[j] == find.ch)?
+            };
+        }
+        Some(match (find.forward, find.till) {
+            (true, true) => p - 1,
+            (false, true) => p + 1,
+            _ => p,
+        })
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().collect()
+    }
+
+    fn replace(&mut self, start: usize, end: usize, text: &str) {
+        self.chars.splice(start..end, text.chars());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Motions and text objects
+// ---------------------------------------------------------------------------
+
+/// Target of `motion` from the cursor. Operators may move onto the line
+/// break after the last char.
+fn motion_target(
+    vim: &mut VimState,
+    buf: &Buffer,
+    motion: Motion,
+    count: Option<usize>,
+    for_operator: bool,
+) -> Option<(usize, MotionKind)> {
+    let n = count.unwrap_or(1).max(1);
+    let pos = buf.cursor;
+    let line = buf.line_index(pos);
+    let col = pos - buf.line_start(pos);
+    let vertical = |index: usize| {
+        let start = buf.line_offset(index);
+        (start + col).min(buf.last_char(start))
+    };
+    let target = match motion {
+        Motion::Left => (pos - n.min(col), MotionKind::Exclusive),
+        Motion::Right => {
+            let end = if for_operator { buf.line_end(pos) } else { buf.last_char(pos) };
+            (pos.saturating_add(n).min(end), MotionKind::Exclusive)
+        }
+        Motion::Up => (vertical(line.saturating_sub(n)), MotionKind::Linewise),
+        Motion::Down => (vertical(line.saturating_add(n)), MotionKind::Linewise),
+        Motion::WordForward { big } => {
+            let mut p = pos;
+            for _ in 0..n {
+                let next = buf.word_forward(p, big);
+                if next == p {
+                    break;
+                }
+                p = next;
+            }
+            (p, MotionKind::Exclusive)
+        }
+        Motion::WordBackward { big } => {
+            let mut p = pos;
+            for _ in 0..n {
+                let next = buf.word_backward(p, big);
+                if next == p {
+                    break;
+                }
+                p = next;
+            }
+            (p, MotionKind::Exclusive)
+        }
+        Motion::WordEnd { big } => {
+            let mut p = pos;
+            for _ in 0..n {
+                let next = buf.word_end(p, big);
+                if next == p {
+                    break;
+                }
+                p = next;
+            }
+            (p, MotionKind::Inclusive)
+        }
+        Motion::LineStart => (buf.line_start(pos), MotionKind::Exclusive),
+        Motion::FirstNonBlank => (buf.first_non_blank(pos), MotionKind::Exclusive),
+        Motion::LineEnd => {
+            let start = buf.line_offset(line.saturating_add(n - 1));
+            let end = buf.line_end(start);
+            if end > start {
+                (end - 1, MotionKind::Inclusive)
+            } else {
+                (start, MotionKind::Exclusive)
+            }
+        }
+        Motion::FirstLine | Motion::LastLine => {
+            let index = match (motion, count) {
+                (_, Some(c)) => c.saturating_sub(1),
+                (Motion::FirstLine, None) => 0,
+                _ => buf.line_count() - 1,
+            };
+            (buf.first_non_blank(buf.line_offset(index)), MotionKind::Linewise)
+        }
+        Motion::Find(find) => {
+            vim.last_find = Some(find);
+            find_target(buf, find, n)?
+        }
+        Motion::RepeatFind { reverse } => {
+            let mut find = vim.last_find?;
+            find.forward ^= reverse;
+            find_target(buf, find, n)?
+        }
+    };
+    Some(target)
+}
+
+fn find_target(buf: &Buffer, find: Find, count: usize) -> Option<(usize, MotionKind)> {
+    let target = buf.find_in_line(buf.cursor, find, count)?;
+    Some((target, if find.forward { MotionKind::Inclusive } else { MotionKind::Exclusive }))
+}
+
+/// `[start, end)` of a text object around the cursor.
+fn object_range(buf: &Buffer, object: TextObject) -> Option<(usize, usize)> {
+    match object.kind {
+        ObjectKind::Word { big } => word_object(buf, big, object.around),
+        ObjectKind::Quote(quote) => quote_object(buf, quote, object.around),
+        ObjectKind::Pair(open, close) => pair_object(buf, open, close, object.around),
+    }
+}
+
+fn word_object(buf: &Buffer, big: bool, around: bool) -> Option<(usize, usize)> {
+    if buf.len() == 0 {
+        return None;
+    }
+    let pos = buf.cursor.min(buf.len() - 1);
+    let (line_start, line_end) = (buf.line_start(pos), buf.line_end(pos));
+    let run_class = buf.class_at(pos, big);
+    let mut start = pos;
+    while start > line_start && buf.class_at(start - 1, big) == run_class {
+        start -= 1;
+    }
+    let mut end = pos;
+    while end < line_end && buf.class_at(end, big) == run_class {
+        end += 1;
+    }
+    if around && run_class != CharClass::Blank {
+        let mut trailing = end;
+        while trailing < line_end && buf.class_at(trailing, big) == CharClass::Blank {
+            trailing += 1;
+        }
+        if trailing > end {
+            end = trailing;
+        } else {
+            while start > line_start && buf.class_at(start - 1, big) == CharClass::Blank {
+                start -= 1;
+            }
+        }
+    }
+    Some((start, end))
+}
+
+fn quote_object(buf: &Buffer, quote: char, around: bool) -> Option<(usize, usize)> {
+    let (line_start, line_end) = (buf.line_start(buf.cursor), buf.line_end(buf.cursor));
+    let quotes: Vec<usize> = (line_start..line_end).filter(|&i| buf.chars[i] == quote).collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| close >= buf.cursor)?;
+    Some(if around { (open, close + 1) } else { (open + 1, close) })
+}
+
+fn pair_object(buf: &Buffer, open: char, close: char, around: bool) -> Option<(usize, usize)> {
+    let pos = buf.cursor.min(buf.len().checked_sub(1)?);
+    let mut depth = 0usize;
+    let mut start = None;
+    for i in (0..=pos).rev() {
+        let c = buf.chars[i];
+        if c == close && i != pos {
+            depth += 1;
+        } else if c == open {
+            if depth == 0 {
+                start = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let start = start?;
+    depth = 0;
+    let end = (start + 1..buf.len()).find(|&i| {
+        let c = buf.chars[i];
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return true;
+            }
+            depth -= 1;
+        }
+        false
+    })?;
+    Some(if around { (start, end + 1) } else { (start + 1, end) })
+}
+
+/// Whole lines covering `[start, end)`: `(first_line_start, last_line_end)`.
+fn line_span(buf: &Buffer, start: usize, end: usize) -> (usize, usize) {
+    (buf.line_start(start), buf.line_end(end.max(start + 1) - 1).max(buf.line_end(start)))
+}
+
+fn visual_selection(vim: &VimState, buf: &Buffer) -> (usize, usize, bool) {
+    let anchor = vim.visual_anchor.min(buf.len());
+    let (lo, hi) = (anchor.min(buf.cursor), anchor.max(buf.cursor));
+    if vim.mode == VimMode::VisualLine {
+        let (start, end) = line_span(buf, lo, hi + 1);
+        (start, end, true)
+    } else {
+        (lo, (hi + 1).min(buf.len()), false)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Execution
+// ---------------------------------------------------------------------------
+
+fn enter_insert(vim: &mut VimState, snapshot: Snapshot) {
+    vim.mode = VimMode::Insert;
+    vim.insert_start = Some(snapshot);
+}
+
+/// Delete, change or yank `[start, end)`; linewise ranges are whole lines
+/// without their final line break.
+fn apply_operator(
+    vim: &mut VimState,
+    buf: &mut Buffer,
+    op: Operator,
+    register: Option<char>,
+    (start, end): (usize, usize),
+    linewise: bool,
+    before: Snapshot,
+) {
+    vim.store(register, buf.text(start, end), linewise, op == Operator::Yank);
+    match (op, linewise) {
+        (Operator::Yank, _) => {
+            if !linewise || start < buf.line_start(buf.cursor) {
+                buf.cursor = start;
+            }
+        }
+        (Operator::Delete, false) => {
+            buf.replace(start, end, "");
+            buf.cursor = start;
+        }
+        (Operator::Delete, true) => {
+            let (from, to) =
+                if end < buf.len() { (start, end + 1) } else { (start.saturating_sub(1), end) };
+            buf.replace(from, to, "");
+            buf.cursor = buf.first_non_blank(from.min(buf.len()));
+        }
+        (Operator::Change, _) => {
+            buf.replace(start, end, "");
+            buf.cursor = start;
+            enter_insert(vim, before);
+            return;
+        }
+    }
+    if op != Operator::Yank {
+        vim.push_undo(before);
+    }
+}
+
+fn operate(
+    vim: &mut VimState,
+    buf: &mut Buffer,
+    cmd: Command,
+    op: Operator,
+    target: Target,
+    before: Snapshot,
+) {
+    let range = match target {
+        Target::Lines => {
+            let n = cmd.count.unwrap_or(1).max(1);
+            let first = buf.line_index(buf.cursor);
+            let last_start = buf.line_offset(first.saturating_add(n - 1));
+            Some((buf.line_start(buf.cursor), buf.line_end(last_start), true))
+        }
+        Target::Object(object) => object_range(buf, object).map(|(s, e)| (s, e, false)),
+        Target::Motion(motion) => operator_motion_range(vim, buf, op, motion, cmd.count),
+    };
+    let Some((start, end, linewise)) = range else {
+        return;
+    };
+    apply_operator(vim, buf, op, cmd.register, (start, end), linewise, before);
+}
+
+fn operator_motion_range(
+    vim: &mut VimState,
+    buf: &Buffer,
+    op: Operator,
+    motion: Motion,
+    count: Option<usize>,
+) -> Option<(usize, usize, bool)> {
+    let pos = buf.cursor;
+    // `cw` on a word changes to its end, like `ce`.
+    let motion = match motion {
+        Motion::WordForward { big }
+            if op == Operator::Change
+                && pos < buf.len()
+                && buf.class_at(pos, big) != CharClass::Blank =>
+        {
+            Motion::WordEnd { big }
+        }
+        other => other,
+    };
+    let (mut target, kind) = motion_target(vim, buf, motion, count, true)?;
+    // `dw` on the last word of a line stops at the line break.
+    if matches!(motion, Motion::WordForward { .. }) && buf.line_index(target) > buf.line_index(pos)
+    {
+        target = target.min(buf.line_end(buf.line_start(target).saturating_sub(1)));
+    }
+    let (lo, hi) = (pos.min(target), pos.max(target));
+    Some(match kind {
+        MotionKind::Exclusive => (lo, hi, false),
+        MotionKind::Inclusive => (lo, (hi + 1).min(buf.len()), false),
+        MotionKind::Linewise => {
+            let (start, end) = line_span(buf, lo, hi + 1);
+            (start, end, true)
+        }
+    })
+}
+
+fn insert(vim: &mut VimState, buf: &mut Buffer, at: InsertAt, before: Snapshot) {
+    match at {
+        InsertAt::Before => {}
+        InsertAt::After => buf.cursor = (buf.cursor + 1).min(buf.line_end(buf.cursor)),
+        InsertAt::LineStart => buf.cursor = buf.first_non_blank(buf.cursor),
+        InsertAt::LineEnd => buf.cursor = buf.line_end(buf.cursor),
+        InsertAt::LineBelow => {
+            let end = buf.line_end(buf.cursor);
+            buf.replace(end, end, "\n");
+            buf.cursor = end + 1;
+        }
+        InsertAt::LineAbove => {
+            let start = buf.line_start(buf.cursor);
+            buf.replace(start, start, "\n");
+            buf.cursor = start;
+        }
+    }
+    enter_insert(vim, before);
+}
+
+fn paste(vim: &mut VimState, buf: &mut Buffer, cmd: Command, before_cursor: bool) -> bool {
+    let Some(register) = vim.fetch(cmd.register) else {
+        return false;
+    };
+    // Never below one copy, even for a register over the paste limit.
+    let n = cmd.count.unwrap_or(1).min(MAX_PASTE_CHARS / register.text.len().max(1)).max(1);
+    if register.linewise {
+        let block = vec![register.text.as_str(); n].join("\n");
+        if before_cursor {
+            let start = buf.line_start(buf.cursor);
+            buf.replace(start, start, &format!("{block}\n"));
+            buf.cursor = buf.first_non_blank(start);
+        } else {
+            let end = buf.line_end(buf.cursor);
+            buf.replace(end, end, &format!("\n{block}"));
+            buf.cursor = buf.first_non_blank(end + 1);
+        }
+    } else {
+        let text = register.text.repeat(n);
+        let at = if before_cursor || buf.line_end(buf.cursor) == buf.cursor {
+            buf.cursor
+        } else {
+            buf.cursor + 1
+        };
+        buf.replace(at, at, &text);
+        buf.cursor = (at + text.chars().count()).saturating_sub(1).max(at);
+    }
+    true
+}
+
+fn replace_chars(buf: &mut Buffer, start: usize, end: usize, ch: char) {
+    for i in start..end {
+        if buf.chars[i] != '\n' {
+            buf.chars[i] = ch;
+        }
+    }
+}
+
+fn toggle_case(buf: &mut Buffer, start: usize, end: usize) {
+    for i in start..end {
+        let c = buf.chars[i];
+        let toggled: Vec<char> =
+            if c.is_lowercase() { c.to_uppercase().collect() } else { c.to_lowercase().collect() };
+        if let [single] = toggled.as_slice() {
+            buf.chars[i] = *single;
+        }
+    }
+}
+
+/// Join `lines` lines starting at the cursor line with single spaces.
+fn join_lines(buf: &mut Buffer, lines: usize) {
+    for _ in 1..lines.max(2) {
+        let end = buf.line_end(buf.cursor);
+        if end >= buf.len() {
+            break;
+        }
+        let mut next = end + 1;
+        while next < buf.len() && buf.chars[next] != '\n' && buf.chars[next].is_whitespace() {
+            next += 1;
+        }
+        let needs_space = end > buf.line_start(end)
+            && !buf.chars[end - 1].is_whitespace()
+            && next < buf.len()
+            && buf.chars[next] != '\n';
+        buf.replace(end, next, if needs_space { " " } else { "" });
+        buf.cursor = end;
+    }
+}
+
+/// Step through undo history (or redo it), restoring the popped snapshot.
+fn undo(vim: &mut VimState, input: &mut InputState, redo: bool) -> bool {
+    let (from, to) =
+        if redo { (&mut vim.redo, &mut vim.undo) } else { (&mut vim.undo, &mut vim.redo) };
+    let Some(snapshot) = from.pop() else {
+        return false;
+    };
+    to.push(Snapshot::of(input));
+    input.lines.clone_from(&snapshot.lines);
+    input.cursor_row = snapshot.row;
+    input.cursor_col = snapshot.col;
+    input.version += 1;
+    input.sync_textarea_engine();
+    true
+}
+
+fn execute_visual(vim: &mut VimState, buf: &mut Buffer, cmd: Command, before: Snapshot) {
+    let (start, end, linewise) = visual_selection(vim, buf);
+    let line_range = || line_span(buf, start, end);
+    match cmd.action {
+        Action::VisualOperate(op) => {
+            vim.mode = VimMode::Normal;
+            apply_operator(vim, buf, op, cmd.register, (start, end), linewise, before);
+        }
+        Action::VisualLines(op) => {
+            vim.mode = VimMode::Normal;
+            let range = line_range();
+            apply_operator(vim, buf, op, cmd.register, range, true, before);
+        }
+        Action::SwapAnchor => std::mem::swap(&mut vim.visual_anchor, &mut buf.cursor),
+        Action::SelectObject(object) => {
+            if let Some((s, e)) = object_range(buf, object).filter(|(s, e)| e > s) {
+                vim.visual_anchor = s;
+                buf.cursor = e - 1;
+            }
+        }
+        Action::Replace(ch) => {
+            replace_chars(buf, start, end, ch);
+            vim.mode = VimMode::Normal;
+            buf.cursor = start;
+            vim.push_undo(before);
+        }
+        Action::ToggleCase => {
+            toggle_case(buf, start, end);
+            vim.mode = VimMode::Normal;
+            buf.cursor = start;
+            vim.push_undo(before);
+        }
+        Action::Join => {
+            let lines =
+                buf.line_index(end.saturating_sub(1).max(start)) - buf.line_index(start) + 1;
+            buf.cursor = start;
+            join_lines(buf, lines);
+            vim.mode = VimMode::Normal;
+            vim.push_undo(before);
+        }
+        Action::Paste { .. } => {
+            let replaced = buf.text(start, end);
+            let Some(register) = vim.fetch(cmd.register) else {
+                return;
+            };
+            buf.replace(start, end, &register.text);
+            buf.cursor = start;
+            vim.store(None, replaced, linewise, false);
+            vim.mode = VimMode::Normal;
+            vim.push_undo(before);
+        }
+        Action::Visual { line } => {
+            let target = if line { VimMode::VisualLine } else { VimMode::Visual };
+            vim.mode = if vim.mode == target { VimMode::Normal } else { target };
+        }
+        Action::Move(motion) => {
+            if let Some((target, _)) = motion_target(vim, buf, motion, cmd.count, false) {
+                buf.cursor = target;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn execute(vim: &mut VimState, input: &mut InputState, cmd: Command) {
+    let before = Snapshot::of(input);
+    let mut buf = Buffer::from_input(input);
+    if vim.mode.is_visual() {
+        execute_visual(vim, &mut buf, cmd, before);
+    } else {
+        execute_normal(vim, &mut buf, input, cmd, before);
+    }
+    if vim.mode != VimMode::Insert {
+        buf.clamp_cursor();
+    }
+    buf.write_to(input);
+}
+
+fn execute_normal(
+    vim: &mut VimState,
+    buf: &mut Buffer,
+    input: &mut InputState,
+    cmd: Command,
+    before: Snapshot,
+) {
+    let n = cmd.count.unwrap_or(1).max(1);
+    match cmd.action {
+        Action::Move(motion) => {
+            if let Some((target, _)) = motion_target(vim, buf, motion, cmd.count, false) {
+                buf.cursor = target;
+            }
+        }
+        Action::Operate(op, target) => operate(vim, buf, cmd, op, target, before),
+        Action::Insert(at) => insert(vim, buf, at, before),
+        Action::Replace(ch) => {
+            let end = buf.cursor.saturating_add(n);
+            if end <= buf.line_end(buf.cursor) {
+                replace_chars(buf, buf.cursor, end, ch);
+                buf.cursor = end - 1;
+                vim.push_undo(before);
+            }
+        }
+        Action::ToggleCase => {
+            let end = buf.cursor.saturating_add(n).min(buf.line_end(buf.cursor));
+            toggle_case(buf, buf.cursor, end);
+            buf.cursor = end;
+            vim.push_undo(before);
+        }
+        Action::Join => {
+            join_lines(buf, n);
+            vim.push_undo(before);
+        }
+        Action::Paste { before: before_cursor } if paste(vim, buf, cmd, before_cursor) => {
+            vim.push_undo(before);
+        }
+        Action::Undo => {
+            for _ in 0..n {
+                if !undo(vim, input, false) {
+                    break;
+                }
+            }
+            *buf = Buffer::from_input(input);
+        }
+        Action::Visual { line } => {
+            vim.mode = if line { VimMode::VisualLine } else { VimMode::Visual };
+            vim.visual_anchor = buf.cursor;
+        }
+        _ => {}
+    }
+}
+
+/// Back to normal mode, recording the insert session for undo.
+fn leave_insert(vim: &mut VimState, input: &mut InputState) {
+    if let Some(start) = vim.insert_start.take()
+        && start.lines != input.lines
+    {
+        vim.push_undo(start);
+    }
+    vim.mode = VimMode::Normal;
+    let mut buf = Buffer::from_input(input);
+    if buf.cursor > buf.line_start(buf.cursor) {
+        buf.cursor -= 1;
+    }
+    buf.clamp_cursor();
+    buf.write_to(input);
+}
+
+fn key_char(key: KeyEvent) -> Option<char> {
+    match key.code {
+        KeyCode::Char(c) => Some(c),
+        KeyCode::Left | KeyCode::Backspace => Some('h'),
+        KeyCode::Right => Some('l'),
+        KeyCode::Up => Some('k'),
+        KeyCode::Down => Some('j'),
+        KeyCode::Home => Some('0'),
+        KeyCode::End => Some('$'),
+        KeyCode::Delete => Some('x'),
+        _ => None,
+    }
+}
+
+/// Keys while vi mode is on and the input has focus. Returns `true` when
+/// consumed; insert mode and unbound keys fall through to the input.
+pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    let Some(vim) = app.vim.as_mut() else {
+        return false;
+    };
+    let input = &mut app.input;
+    let plain = key.modifiers == KeyModifiers::NONE || key.modifiers == KeyModifiers::SHIFT;
+
+    if vim.mode == VimMode::Insert {
+        if key.code == KeyCode::Esc && plain {
+            leave_insert(vim, input);
+            app.needs_redraw = true;
+            return true;
+        }
+        if vim.insert_start.is_none() {
+            vim.insert_start = Some(Snapshot::of(input));
+        }
+        return false;
+    }
+
+    match key.code {
+        KeyCode::Esc if !vim.pending.is_empty() || vim.mode.is_visual() => {
+            vim.pending.clear();
+            vim.mode = VimMode::Normal;
+        }
+        KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+            vim.pending.clear();
+            undo(vim, input, true);
+        }
+        // Submit from normal mode with the whole prompt, back in insert mode.
+        KeyCode::Enter if plain => {
+            vim.pending.clear();
+            vim.mode = VimMode::Insert;
+            input.cursor_row = input.lines.len().saturating_sub(1);
+            input.move_end();
+            input.sync_textarea_engine();
+            return false;
+        }
+        _ if !plain => return false,
+        _ => {
+            let Some(c) = key_char(key) else {
+                return false;
+            };
+            vim.pending.push(c);
+            let keys: Vec<char> = vim.pending.chars().collect();
+            match parse(&keys, vim.mode.is_visual()) {
+                Parse::Incomplete => {}
+                Parse::Invalid => vim.pending.clear(),
+                Parse::Done(cmd) => {
+                    vim.pending.clear();
+                    execute(vim, input, cmd);
+                }
+            }
+        }
+    }
+    app.needs_redraw = true;
+    true
+}
+
+/// Turn vi mode on or off (`/vim`), returning the new state for the notice.
+pub(super) fn toggle(app: &mut App) -> bool {
+    app.vim =
+        if app.vim.is_some() { None } else { Some(VimState::with_clipboard(app.clipboard_mode)) };
+    app.needs_redraw = true;
+    app.vim.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vim_app(text: &str) -> App {
+        let mut app = App::test_default();
+        app.vim = Some(VimState { clipboard: None, ..VimState::default() });
+        app.input.set_text(text);
+        press(&mut app, KeyCode::Esc);
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        handle_key(app, KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn keys(app: &mut App, typed: &str) {
+        for c in typed.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn cursor(app: &App) -> (usize, usize) {
+        (app.input.cursor_row, app.input.cursor_col)
+    }
+
+    fn mode(app: &App) -> VimMode {
+        app.vim.as_ref().map(|v| v.mode).expect("vi mode")
+    }
+
+    #[test]
+    fn parse_handles_registers_counts_and_text_objects() {
+        let parsed = |s: &str, visual| parse(&s.chars().collect::<Vec<_>>(), visual);
+        assert_eq!(
+            parsed("\"a2d3w", false),
+            Parse::Done(Command {
+                register: Some('a'),
+                count: Some(6),
+                action: Action::Operate(
+                    Operator::Delete,
+                    Target::Motion(Motion::WordForward { big: false })
+                ),
+            })
+        );
+        assert_eq!(parsed("ci", false), Parse::Incomplete);
+        assert!(matches!(
+            parsed("ci(", false),
+            Parse::Done(Command {
+                action: Action::Operate(Operator::Change, Target::Object(_)),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parsed("10j", false),
+            Parse::Done(Command { count: Some(10), action: Action::Move(Motion::Down), .. })
+        ));
+        assert!(matches!(parsed("0", false), Parse::Done(Command { count: None, .. })));
+        assert_eq!(parsed("dq", false), Parse::Invalid);
+        assert!(matches!(
+            parsed("d", true),
+            Parse::Done(Command { action: Action::VisualOperate(Operator::Delete), .. })
+        ));
+    }
+
+    #[test]
+    fn esc_enters_normal_mode_and_motions_move_the_cursor() {
+        let mut app = vim_app("one two three\nfour");
+        assert_eq!(mode(&app), VimMode::Normal);
+        assert_eq!(cursor(&app), (1, 3));
+        keys(&mut app, "gg");
+        assert_eq!(cursor(&app), (0, 0));
+        keys(&mut app, "2w");
+        assert_eq!(cursor(&app), (0, 8));
+        keys(&mut app, "b");
+        assert_eq!(cursor(&app), (0, 4));
+        keys(&mut app, "$");
+        assert_eq!(cursor(&app), (0, 12));
+        keys(&mut app, "0fe");
+        assert_eq!(cursor(&app), (0, 2));
+        keys(&mut app, ";");
+        assert_eq!(cursor(&app), (0, 11));
+        keys(&mut app, "j");
+        assert_eq!(cursor(&app), (1, 3));
+    }
+
+    #[test]
+    fn huge_counts_are_clamped() {
+        let mut app = vim_app("one two\nthree");
+        keys(&mut app, "gg0999999999999w");
+        assert_eq!(cursor(&app), (1, 4));
+        keys(&mut app, "gg99999999999999999999j");
+        assert_eq!(cursor(&app), (1, 0));
+        keys(&mut app, "gg9999999999d9999999999j");
+        assert_eq!(app.input.text(), "");
+
+        let mut app = vim_app("ab");
+        keys(&mut app, "0yl999999999999p");
+        assert_eq!(app.input.text().chars().count(), 2 + MAX_COUNT);
+    }
+
+    #[test]
+    fn operators_with_counts_and_text_objects_edit_text() {
+        let mut app = vim_app("alpha beta gamma delta");
+        keys(&mut app, "0d2w");
+        assert_eq!(app.input.text(), "gamma delta");
+        keys(&mut app, "x");
+        assert_eq!(app.input.text(), "amma delta");
+        keys(&mut app, "wD");
+        assert_eq!(app.input.text(), "amma ");
+
+        let mut app = vim_app("call(foo, \"bar baz\")");
+        keys(&mut app, "0fbci\"");
+        assert_eq!(app.input.text(), "call(foo, \"\")");
+        assert_eq!(mode(&app), VimMode::Insert);
+        press(&mut app, KeyCode::Esc);
+        keys(&mut app, "da(");
+        assert_eq!(app.input.text(), "call");
+
+        let mut app = vim_app("one two");
+        keys(&mut app, "0cwuno");
+        assert_eq!(app.input.text(), " two");
+        assert!(!press(&mut app, KeyCode::Char('u')), "insert keys fall through to the input");
+        assert_eq!(mode(&app), VimMode::Insert);
+    }
+
+    #[test]
+    fn linewise_yank_paste_and_registers() {
+        let mut app = vim_app("first\nsecond\nthird");
+        keys(&mut app, "gg\"ayyjp");
+        assert_eq!(app.input.text(), "first\nsecond\nfirst\nthird");
+        assert_eq!(cursor(&app), (2, 0));
+        keys(&mut app, "dd");
+        assert_eq!(app.input.text(), "first\nsecond\nthird");
+        keys(&mut app, "G\"aP");
+        assert_eq!(app.input.text(), "first\nsecond\nfirst\nthird");
+        keys(&mut app, "u");
+        assert_eq!(app.input.text(), "first\nsecond\nthird");
+        handle_key(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(app.input.text(), "first\nsecond\nfirst\nthird");
+    }
+
+    #[test]
+    fn visual_mode_selects_and_operates() {
+        let mut app = vim_app("hello brave world");
+        keys(&mut app, "0wviw");
+        assert_eq!(mode(&app), VimMode::Visual);
+        let vim = app.vim.as_ref().expect("vi mode");
+        assert_eq!(vim.visual_range(&app.input), Some((6, 11)));
+        keys(&mut app, "y");
+        assert_eq!(mode(&app), VimMode::Normal);
+        keys(&mut app, "$p");
+        assert_eq!(app.input.text(), "hello brave worldbrave");
+
+        let mut app = vim_app("a\nb\nc");
+        keys(&mut app, "ggVjd");
+        assert_eq!(app.input.text(), "c");
+        keys(&mut app, "u");
+        assert_eq!(app.input.text(), "a\nb\nc");
+    }
+
+    #[test]
+    fn enter_in_normal_mode_falls_through_with_cursor_at_end() {
+        let mut app = vim_app("ship it");
+        keys(&mut app, "0");
+        assert!(!press(&mut app, KeyCode::Enter));
+        assert_eq!(cursor(&app), (0, 7));
+        assert_eq!(mode(&app), VimMode::Insert);
+    }
+}
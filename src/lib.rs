@@ -26,6 +26,9 @@ use clap::Parser;
 #[command(name = "claude-rs", about = "Native Rust terminal for Claude Code")]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Override the model (sonnet, opus, haiku)
     #[arg(long, short)]
     pub model: Option<String>,
@@ -50,6 +53,69 @@ pub struct Cli {
     #[arg(long)]
     pub bridge_script: Option<std::path::PathBuf>,
 
+    /// Cancel the turn once the session has cost this many USD
+    /// (env: `CLAUDE_RS_MAX_SESSION_COST`).
+    #[arg(long, value_name = "USD")]
+    pub max_session_cost: Option<f64>,
+
+    /// Cancel a turn once it has cost this many USD (env: `CLAUDE_RS_MAX_TURN_COST`).
+    #[arg(long, value_name = "USD")]
+    pub max_turn_cost: Option<f64>,
+
+    /// Cancel the turn once the session has used this many input + output tokens
+    /// (env: `CLAUDE_RS_MAX_SESSION_TOKENS`).
+    #[arg(long, value_name = "TOKENS")]
+    pub max_session_tokens: Option<u64>,
+
+    /// Cancel a turn once it has used this many input + output tokens
+    /// (env: `CLAUDE_RS_MAX_TURN_TOKENS`).
+    #[arg(long, value_name = "TOKENS")]
+    pub max_turn_tokens: Option<u64>,
+
+    /// Start the prompt input in vi mode (env: `CLAUDE_RS_VI_MODE`).
+    #[arg(long)]
+    pub vi: bool,
+
+    /// How to notify while the terminal is unfocused: `osc9`, `osc777`, `bell`
+    /// or `off` (env: `CLAUDE_RS_NOTIFY`, default `osc9`).
+    #[arg(long, value_name = "METHOD")]
+    pub notify: Option<String>,
+
+    /// Comma-separated events that notify: `complete`, `error`, `permission`,
+    /// `all` or `none` (env: `CLAUDE_RS_NOTIFY_ON`, default `all`).
+    #[arg(long, value_name = "EVENTS")]
+    pub notify_on: Option<String>,
+
+    /// Also run this command per notification with the title and body appended
+    /// as arguments, e.g. `notify-send -a claude-rs` (env: `CLAUDE_RS_NOTIFY_COMMAND`).
+    #[arg(long, value_name = "COMMAND")]
+    pub notify_command: Option<String>,
+
+    /// Clipboard for copies: `auto` (system clipboard, OSC 52 when unavailable),
+    /// `native` or `osc52` (env: `CLAUDE_RS_CLIPBOARD`).
+    #[arg(long, value_name = "MODE")]
+    pub clipboard: Option<String>,
+
+    /// Link target for file paths in the chat, with `{path}` and `{line}`
+    /// placeholders, e.g. `vscode://file{path}:{line}` (env: `CLAUDE_RS_FILE_LINK`,
+    /// default `file://{path}`).
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_link: Option<String>,
+
+    /// Command opening a tool call location, with `{path}` and `{line}`
+    /// placeholders, e.g. `nvim +{line} {path}` or `code -g {path}:{line}`
+    /// (env: `CLAUDE_RS_OPEN_COMMAND`, default `$VISUAL`/`$EDITOR`).
+    #[arg(long, value_name = "TEMPLATE")]
+    pub open_command: Option<String>,
+
+    /// Do not emit OSC 8 hyperlinks (env: `CLAUDE_RS_NO_HYPERLINKS`).
+    #[arg(long)]
+    pub no_hyperlinks: bool,
+
+    /// Leave the terminal window title alone (env: `CLAUDE_RS_NO_TITLE`).
+    #[arg(long)]
+    pub no_title: bool,
+
     /// Write tracing diagnostics to a file (disabled unless explicitly set).
     #[arg(long, value_name = "PATH")]
     pub log_file: Option<std::path::PathBuf>,
@@ -71,3 +137,13 @@ pub struct Cli {
     #[arg(long)]
     pub perf_append: bool,
 }
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Command {
+    /// Print daily, weekly, per-project and per-model totals from the local usage ledger.
+    Usage {
+        /// Number of days in the daily breakdown.
+        #[arg(long, default_value_t = app::ledger::DEFAULT_REPORT_DAYS)]
+        days: u64,
+    },
+}
@@ -15,8 +15,8 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use clap::Parser;
-use claude_code_rust::Cli;
 use claude_code_rust::error::AppError;
+use claude_code_rust::{Cli, Command};
 use std::fs::OpenOptions;
 use std::time::Instant;
 
@@ -36,6 +36,10 @@ fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
     init_tracing(&cli)?;
 
+    if let Some(Command::Usage { days }) = cli.command {
+        return print_usage_report(days);
+    }
+
     #[cfg(not(feature = "perf"))]
     if cli.perf_log.is_some() {
         return Err(anyhow::anyhow!("`--perf-log` requires a binary built with `--features perf`"));
@@ -74,6 +78,22 @@ fn run() -> anyhow::Result<()> {
     }))
 }
 
+fn print_usage_report(days: u64) -> anyhow::Result<()> {
+    use claude_code_rust::app::ledger::{ReportLine, UsageReport, default_path};
+
+    let path =
+        default_path().ok_or_else(|| anyhow::anyhow!("no data directory for the usage ledger"))?;
+    println!("Ledger: {}", path.display());
+    println!();
+    for line in UsageReport::load(&path, days).lines() {
+        match line {
+            ReportLine::Heading(text) | ReportLine::Row(text) => println!("{text}"),
+            ReportLine::Blank => println!(),
+        }
+    }
+    Ok(())
+}
+
 fn extract_app_error(err: &anyhow::Error) -> Option<AppError> {
     err.chain().find_map(|cause| cause.downcast_ref::<AppError>().cloned())
 }
@@ -0,0 +1,154 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::attachments::{AttachMode, Attachment, AttachmentKind};
+use crate::app::{App, FocusOwner};
+use crate::ui::graphics::format_size;
+use crate::ui::message::format_token_count;
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+const SEPARATOR: &str = "  ";
+
+/// One strip line, plus a key hint while focused; 0 with nothing attached.
+pub fn compute_height(app: &App) -> u16 {
+    if app.attachments.is_empty() {
+        0
+    } else if app.focus_owner() == FocusOwner::Attachments {
+        2
+    } else {
+        1
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let focused = app.focus_owner() == FocusOwner::Attachments;
+    let mut lines = vec![strip_line(
+        app.attachments.entries(),
+        focused.then_some(app.attachments.selected),
+        usize::from(area.width),
+    )];
+    if focused && area.height > 1 {
+        lines.push(Line::from(Span::styled(
+            "  \u{2190}\u{2192} select \u{00B7} p preview \u{00B7} c inline/attached \u{00B7} d remove \u{00B7} Esc done",
+            Style::default().fg(theme::DIM),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn chip_text(entry: &Attachment) -> String {
+    let mut parts = vec![entry.label.clone()];
+    match entry.kind {
+        AttachmentKind::Directory => parts.push("dir".to_owned()),
+        AttachmentKind::Missing => parts.push("not found".to_owned()),
+        _ => {}
+    }
+    if let Some(bytes) = entry.bytes {
+        parts.push(format_size(usize::try_from(bytes).unwrap_or(usize::MAX)));
+    }
+    if let Some(tokens) = entry.tokens {
+        parts.push(format!("~{} tok", format_token_count(tokens)));
+    }
+    if entry.kind != AttachmentKind::Directory {
+        parts.push(format!("[{}]", entry.mode.label()));
+    }
+    parts.join(" ")
+}
+
+/// Header and chips on one line. The selected chip is highlighted and kept
+/// visible by dropping chips from the left.
+fn strip_line(entries: &[Attachment], selected: Option<usize>, width: usize) -> Line<'static> {
+    let tokens: u64 = entries.iter().filter_map(|entry| entry.tokens).sum();
+    let header = format!("  Attachments ({}) ~{} tok", entries.len(), format_token_count(tokens));
+    let chips: Vec<String> = entries.iter().map(chip_text).collect();
+
+    let mut offset = 0;
+    if let Some(selected) = selected {
+        let fits = |offset: usize| {
+            let ellipsis = if offset > 0 { 2 } else { 0 };
+            let used: usize = chips[offset..=selected]
+                .iter()
+                .map(|chip| SEPARATOR.len() + chip.chars().count())
+                .sum();
+            header.chars().count() + ellipsis + used <= width
+        };
+        while offset < selected && !fits(offset) {
+            offset += 1;
+        }
+    }
+
+    let mut spans = vec![Span::styled(
+        header,
+        Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD),
+    )];
+    if offset > 0 {
+        spans.push(Span::styled(" \u{2026}", Style::default().fg(theme::DIM)));
+    }
+    for (i, (entry, chip)) in entries.iter().zip(chips).enumerate().skip(offset) {
+        spans.push(Span::raw(SEPARATOR));
+        let mut style = match (entry.kind, entry.mode) {
+            (AttachmentKind::Missing, _) => Style::default().fg(theme::STATUS_ERROR),
+            (_, AttachMode::Attached) => Style::default().fg(Color::White),
+            (_, AttachMode::Inline) => Style::default().fg(theme::DIM),
+        };
+        if selected == Some(i) {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
+        spans.push(Span::styled(chip, style));
+    }
+    if selected.is_none() {
+        spans.push(Span::styled("  Alt+P to manage", Style::default().fg(theme::DIM)));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::attachments;
+    use crate::app::input::InputState;
+
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn strip_keeps_the_selected_chip_in_view() {
+        let mut app = App::test_default();
+        app.input = InputState::new();
+        for n in 0..4 {
+            app.input.insert_paste_block(&format!("{n}").repeat(4000));
+        }
+        attachments::refresh(&mut app);
+        let entries = app.attachments.entries();
+
+        let all = line_text(&strip_line(entries, None, 200));
+        assert!(
+            all.starts_with("  Attachments (4) ~4k tok  Pasted Text 1 3.9 KB ~1k tok [inline]")
+        );
+        assert!(all.ends_with("Alt+P to manage"));
+
+        let narrow = line_text(&strip_line(entries, Some(3), 80));
+        assert!(narrow.contains(" \u{2026}  "));
+        assert!(narrow.ends_with("Pasted Text 4 3.9 KB ~1k tok [inline]"));
+        assert!(!narrow.contains("Pasted Text 1 "));
+    }
+}
@@ -60,36 +60,59 @@ pub fn compute_height(app: &App) -> u16 {
     }
 }
 
+/// Split `text` into runs, styling the chars at `matched` (sorted char indices).
+fn highlight_matches(text: &str, matched: &[usize]) -> Vec<Span<'static>> {
+    let highlight = Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    let mut next = matched.iter().peekable();
+    for (i, ch) in text.chars().enumerate() {
+        let is_match = next.next_if_eq(&&i).is_some();
+        if is_match != run_matched && !run.is_empty() {
+            let style = if run_matched { highlight } else { Style::default() };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = is_match;
+        run.push(ch);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { highlight } else { Style::default() }));
+    }
+    spans
+}
+
 /// Render the autocomplete dropdown as a floating overlay above the input area.
+/// Returns the area the dropdown covers.
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::too_many_lines)]
-pub fn render(frame: &mut Frame, input_area: Rect, app: &App) {
+pub fn render(frame: &mut Frame, input_area: Rect, app: &App) -> Option<Rect> {
     enum Dropdown<'a> {
         Mention(&'a mention::MentionState),
         Slash(&'a slash::SlashState),
     }
     let dropdown = if let Some(m) = &app.mention {
         if m.candidates.is_empty() {
-            return;
+            return None;
         }
         Dropdown::Mention(m)
     } else if let Some(s) = &app.slash {
         if s.candidates.is_empty() {
-            return;
+            return None;
         }
         Dropdown::Slash(s)
     } else {
-        return;
+        return None;
     };
 
     let height = compute_height(app);
     if height == 0 {
-        return;
+        return None;
     }
 
     let text_area = compute_text_area(input_area, app.login_hint.is_some());
     if text_area.width == 0 || text_area.height == 0 {
-        return;
+        return None;
     }
 
     let (trigger_row, trigger_col) = match dropdown {
@@ -149,27 +172,7 @@ pub fn render(frame: &mut Frame, input_area: Rect, app: &App) {
                 } else {
                     spans.push(Span::raw("   "));
                 }
-                let path = &candidate.rel_path;
-                let query = &m.query;
-                if query.is_empty() {
-                    spans.push(Span::raw(path.clone()));
-                } else if let Some(match_start) = path.to_lowercase().find(&query.to_lowercase()) {
-                    let before = &path[..match_start];
-                    let matched = &path[match_start..match_start + query.len()];
-                    let after = &path[match_start + query.len()..];
-                    if !before.is_empty() {
-                        spans.push(Span::raw(before.to_owned()));
-                    }
-                    spans.push(Span::styled(
-                        matched.to_owned(),
-                        Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD),
-                    ));
-                    if !after.is_empty() {
-                        spans.push(Span::raw(after.to_owned()));
-                    }
-                } else {
-                    spans.push(Span::raw(path.clone()));
-                }
+                spans.extend(highlight_matches(&candidate.rel_path, &candidate.matched));
                 lines.push(Line::from(spans));
             }
         }
@@ -251,6 +254,7 @@ pub fn render(frame: &mut Frame, input_area: Rect, app: &App) {
     // Clear the area first so the overlay has a solid background
     frame.render_widget(ratatui::widgets::Clear, dropdown_area);
     frame.render_widget(paragraph, dropdown_area);
+    Some(dropdown_area)
 }
 
 fn compute_text_area(input_area: Rect, has_login_hint: bool) -> Rect {
@@ -380,7 +384,7 @@ fn choose_dropdown_y(anchor_y: u16, height: u16, frame_top: u16, frame_bottom: u
 
 #[cfg(test)]
 mod tests {
-    use super::choose_dropdown_y;
+    use super::{choose_dropdown_y, highlight_matches};
 
     #[test]
     fn dropdown_prefers_below_with_gap_when_space_available() {
@@ -401,4 +405,19 @@ mod tests {
         let y = choose_dropdown_y(anchor, height, 0, 11);
         assert!(!(y <= anchor && anchor < y + height));
     }
+
+    #[test]
+    fn highlight_matches_groups_matched_runs() {
+        let spans = highlight_matches("src/app/state.rs", &[4, 5, 8, 9]);
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| {
+                (span.content.as_ref(), span.style.fg == Some(crate::ui::theme::RUST_ORANGE))
+            })
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("src/", false), ("ap", true), ("p/", false), ("st", true), ("ate.rs", false)]
+        );
+    }
 }
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::app::{App, AppStatus, MessageRole, SelectionKind, SelectionState};
+use crate::ui::graphics;
 use crate::ui::message::{self, SpinnerState};
 use crate::ui::theme;
 use ratatui::Frame;
@@ -46,6 +47,8 @@ struct CulledRenderStats {
     local_scroll: usize,
     first_visible: usize,
     render_start: usize,
+    /// One past the last message index visited while rendering.
+    render_end: usize,
     rendered_msgs: usize,
 }
 
@@ -75,6 +78,63 @@ fn msg_spinner(
     }
 }
 
+fn base_spinner(app: &App) -> SpinnerState {
+    SpinnerState {
+        frame: app.spinner_frame,
+        is_active: matches!(app.status, AppStatus::Thinking | AppStatus::Running),
+        is_last_message: false,
+        is_thinking_mid_turn: false,
+        is_subagent_thinking: false,
+        is_compacting: app.is_compacting,
+    }
+}
+
+/// Tool call whose title was drawn at screen cell `(column, row)` in the
+/// last frame, as `(message, block)`. `None` while the subagent view or an
+/// overlay covers the chat, whose row map is then stale.
+pub fn tool_call_title_at(app: &mut App, column: u16, row: u16) -> Option<(usize, usize)> {
+    if app.subagent_view.is_some() || app.overlay.is_some() {
+        return None;
+    }
+    let area = app.rendered_chat_area;
+    if !area.contains(ratatui::layout::Position::new(column, row)) {
+        return None;
+    }
+    let row = app.viewport.scroll_offset + usize::from(row - area.y);
+    if row >= app.viewport.total_message_height() {
+        return None;
+    }
+    let index = app.viewport.find_first_visible(row);
+    let local_row = row - app.viewport.cumulative_height_before(index);
+    let msg = app.messages.get(index)?;
+    let is_thinking = matches!(app.status, AppStatus::Thinking);
+    let show_subagent_thinking = app.should_show_subagent_thinking(Instant::now());
+    let sp = msg_spinner(
+        base_spinner(app),
+        index,
+        app.messages.len(),
+        is_thinking,
+        show_subagent_thinking,
+        msg,
+    );
+    super::chat_nav::tool_title_at(app, index, &sp, area.width, local_row)
+        .map(|block| (index, block))
+}
+
+/// Conversation rows of the block selected by chat navigation, if any.
+fn nav_block_rows(
+    app: &mut App,
+    base: SpinnerState,
+    is_thinking: bool,
+    show_subagent_thinking: bool,
+    width: u16,
+) -> Option<(usize, usize)> {
+    let index = app.chat_nav.as_ref()?.message;
+    let msg = app.messages.get(index)?;
+    let sp = msg_spinner(base, index, app.messages.len(), is_thinking, show_subagent_thinking, msg);
+    super::chat_nav::locate(app, &sp, width)
+}
+
 /// Ensure every message has an up-to-date height in the viewport at the given width.
 /// The last message is always recomputed while streaming (content changes each frame).
 ///
@@ -220,6 +280,16 @@ fn render_scrolled(
     );
     crate::perf::mark_with("chat::render_scrolled_start", "idx", render_stats.render_start);
 
+    app.inline_images.frame = graphics::locate_placements(
+        app,
+        render_stats.render_start..render_stats.render_end,
+        &all_lines,
+        render_stats.local_scroll,
+        area,
+    );
+    app.hyperlinks.targets =
+        super::hyperlink::collect_targets(app, render_stats.render_start..render_stats.render_end);
+
     let paragraph = {
         let _t = app
             .perf
@@ -395,7 +465,9 @@ fn render_culled_messages(
     let mut rendered_msgs = 0usize;
     let mut local_scroll = scroll.saturating_sub(height_before_start);
     let mut consume_skip_in_messages = true;
+    let mut render_end = render_start;
     for i in render_start..msg_count {
+        render_end = i + 1;
         let sp =
             msg_spinner(base, i, msg_count, is_thinking, show_subagent_thinking, &app.messages[i]);
         let before = out.len();
@@ -417,6 +489,12 @@ fn render_culled_messages(
             local_scroll = rem;
         } else {
             message::render_message(&mut app.messages[i], &sp, width, out);
+            if app.branches.is_editing(i) && out.len() > before {
+                out[before] = message::editing_role_label_line();
+            }
+        }
+        if let Some(search) = app.search.as_ref() {
+            super::search::highlight_message_lines(search, i, &mut out[before..]);
         }
         if out.len() > before {
             rendered_msgs += 1;
@@ -426,7 +504,7 @@ fn render_culled_messages(
         }
     }
 
-    CulledRenderStats { local_scroll, first_visible, render_start, rendered_msgs }
+    CulledRenderStats { local_scroll, first_visible, render_start, render_end, rendered_msgs }
 }
 
 #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
@@ -437,14 +515,7 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
     let show_subagent_thinking = app.should_show_subagent_thinking(Instant::now());
     let width = area.width;
 
-    let base_spinner = SpinnerState {
-        frame: app.spinner_frame,
-        is_active: matches!(app.status, AppStatus::Thinking | AppStatus::Running),
-        is_last_message: false,
-        is_thinking_mid_turn: false,
-        is_subagent_thinking: false,
-        is_compacting: app.is_compacting,
-    };
+    let base_spinner = base_spinner(app);
 
     // Detect width change and invalidate layout caches
     {
@@ -452,6 +523,8 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
         app.viewport.on_frame(width);
     }
 
+    super::search::prepare_hits(app, base_spinner, width);
+
     // Update per-message visual heights
     let height_stats =
         update_visual_heights(app, base_spinner, is_thinking, show_subagent_thinking, width);
@@ -476,6 +549,9 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
     // O(1) via prefix sums instead of O(n) sum every frame
     let content_height: usize = app.viewport.total_message_height();
     let viewport_height = area.height as usize;
+    super::search::apply_jump(app, viewport_height);
+    let nav_rows = nav_block_rows(app, base_spinner, is_thinking, show_subagent_thinking, width);
+    super::chat_nav::apply_jump(app, nav_rows, viewport_height);
     crate::perf::mark_with("chat::content_height", "rows", content_height);
     crate::perf::mark_with("chat::viewport_height", "rows", viewport_height);
     crate::perf::mark_with(
@@ -511,6 +587,8 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
         viewport_height,
     );
 
+    super::chat_nav::render_highlight(frame, area, app, nav_rows);
+
     if let Some(sel) = app.selection
         && sel.kind == SelectionKind::Chat
     {
@@ -581,7 +659,7 @@ fn render_lines_from_paragraph(
 mod tests {
     use super::{
         SCROLLBAR_MIN_THUMB_HEIGHT, ScrollbarGeometry, clamp_scroll_to_content,
-        compute_scrollbar_geometry, update_visual_heights,
+        compute_scrollbar_geometry, tool_call_title_at, update_visual_heights,
     };
     use crate::app::{
         App, AppStatus, BlockCache, ChatMessage, ChatViewport, IncrementalMarkdown, MessageBlock,
@@ -681,6 +759,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tool_call_title_at_maps_clicks_to_the_title_row() {
+        let tool_call = crate::app::ToolCallInfo {
+            id: "tc-1".to_owned(),
+            title: "Read src/lib.rs".to_owned(),
+            sdk_tool_name: "Read".to_owned(),
+            raw_input: None,
+            status: crate::agent::model::ToolCallStatus::Completed,
+            content: Vec::new(),
+            collapsed: true,
+            hidden: false,
+            terminal_id: None,
+            terminal_command: None,
+            terminal_output: None,
+            terminal_output_len: 0,
+            terminal_bytes_seen: 0,
+            terminal_snapshot_mode: crate::app::TerminalSnapshotMode::AppendOnly,
+            render_epoch: 0,
+            layout_epoch: 0,
+            last_measured_width: 0,
+            last_measured_height: 0,
+            last_measured_layout_epoch: 0,
+            last_measured_layout_generation: 0,
+            cache: BlockCache::default(),
+            pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: vec![crate::agent::model::ToolCallLocation::new("src/lib.rs").line(3)],
+        };
+        let mut app = App::test_default();
+        app.status = AppStatus::Ready;
+        app.messages = vec![
+            assistant_text_message("intro"),
+            ChatMessage {
+                role: MessageRole::Assistant,
+                blocks: vec![MessageBlock::ToolCall(Box::new(tool_call))],
+                usage: None,
+            },
+        ];
+        app.viewport.on_frame(40);
+        let spinner = SpinnerState {
+            frame: 0,
+            is_active: false,
+            is_last_message: false,
+            is_thinking_mid_turn: false,
+            is_subagent_thinking: false,
+            is_compacting: false,
+        };
+        update_visual_heights(&mut app, spinner, false, false, 40);
+        app.viewport.rebuild_prefix_sums();
+        app.rendered_chat_area = ratatui::layout::Rect::new(0, 2, 40, 20);
+
+        let hits: Vec<u16> =
+            (0..24).filter(|&row| tool_call_title_at(&mut app, 5, row).is_some()).collect();
+        let title_row = u16::try_from(app.viewport.cumulative_height_before(1)).unwrap() + 3;
+        assert_eq!(hits, vec![title_row]);
+        assert_eq!(tool_call_title_at(&mut app, 5, title_row), Some((1, 0)));
+        assert_eq!(tool_call_title_at(&mut app, 45, title_row), None);
+
+        app.subagent_view = Some(crate::app::subagents::SubagentView {
+            task_id: "task-1".to_owned(),
+            scroll_from_bottom: 0,
+        });
+        assert_eq!(tool_call_title_at(&mut app, 5, title_row), None);
+    }
+
     #[test]
     fn clamp_scroll_to_content_snaps_overscroll_after_shrink() {
         let mut viewport = ChatViewport::new();
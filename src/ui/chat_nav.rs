@@ -0,0 +1,158 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::{App, FocusOwner, MessageBlock};
+use crate::ui::message::{self, SpinnerState};
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+
+fn is_blank(line: &Line<'_>) -> bool {
+    line.spans.iter().all(|span| span.content.trim().is_empty())
+}
+
+fn wrapped_rows(lines: &[Line<'static>], width: u16) -> usize {
+    lines
+        .iter()
+        .map(|line| Paragraph::new(line.clone()).wrap(Wrap { trim: false }).line_count(width))
+        .sum()
+}
+
+/// Rows `(first, count)` of the selected block within the whole conversation.
+/// Renders only the selected message, with the spinner the chat renders it with.
+pub fn locate(app: &mut App, spinner: &SpinnerState, width: u16) -> Option<(usize, usize)> {
+    let (message, block) = app.chat_nav.as_ref().map(|nav| (nav.message, nav.block))?;
+    let msg = app.messages.get_mut(message)?;
+    let mut lines = Vec::new();
+    let mut starts = Vec::new();
+    message::render_message_with_block_starts(msg, spinner, width, &mut lines, &mut starts);
+    let pos = starts.iter().position(|(b, _)| *b == block)?;
+    let start = starts[pos].1;
+    // The trailing line is the blank separator between messages.
+    let mut end = starts.get(pos + 1).map_or(lines.len().saturating_sub(1), |(_, line)| *line);
+    while end > start && is_blank(&lines[end - 1]) {
+        end -= 1;
+    }
+    let top = app.viewport.cumulative_height_before(message) + wrapped_rows(&lines[..start], width);
+    Some((top, wrapped_rows(&lines[start..end], width).max(1)))
+}
+
+/// Tool call block of `message` whose title covers `row`, counted from the
+/// message top.
+pub fn tool_title_at(
+    app: &mut App,
+    message: usize,
+    spinner: &SpinnerState,
+    width: u16,
+    row: usize,
+) -> Option<usize> {
+    let msg = app.messages.get_mut(message)?;
+    let mut lines = Vec::new();
+    let mut starts = Vec::new();
+    message::render_message_with_block_starts(msg, spinner, width, &mut lines, &mut starts);
+    let (mut top, mut counted) = (0, 0);
+    for (block, line) in starts {
+        top += wrapped_rows(&lines[counted..line], width);
+        counted = line;
+        if top > row {
+            break;
+        }
+        let title = lines.get(line).map_or(0, |l| wrapped_rows(std::slice::from_ref(l), width));
+        if row < top + title {
+            let is_tool = matches!(msg.blocks.get(block), Some(MessageBlock::ToolCall(_)));
+            return is_tool.then_some(block);
+        }
+    }
+    None
+}
+
+/// Scroll the selected block into view when the cursor moved, showing its top
+/// when it is taller than the chat.
+pub fn apply_jump(app: &mut App, rows: Option<(usize, usize)>, viewport_height: usize) {
+    let Some(nav) = app.chat_nav.as_mut() else {
+        return;
+    };
+    if !std::mem::take(&mut nav.pending_jump) {
+        return;
+    }
+    let Some((top, count)) = rows else {
+        return;
+    };
+    let vp = &mut app.viewport;
+    if top < vp.scroll_target || count > viewport_height {
+        vp.scroll_target = top;
+    } else if top + count > vp.scroll_target + viewport_height {
+        vp.scroll_target = top + count - viewport_height;
+    }
+    vp.auto_scroll = false;
+}
+
+/// Tint the visible rows of the selected block.
+pub fn render_highlight(frame: &mut Frame, area: Rect, app: &App, rows: Option<(usize, usize)>) {
+    let (Some((top, count)), Some(_)) = (rows, app.chat_nav.as_ref()) else {
+        return;
+    };
+    let offset = app.viewport.scroll_offset;
+    let first = top.max(offset) - offset;
+    let last = (top + count).saturating_sub(offset).min(usize::from(area.height));
+    let style = Style::default().bg(theme::CHAT_NAV_BG);
+    for row in first..last {
+        let Ok(row) = u16::try_from(row) else {
+            break;
+        };
+        frame.buffer_mut().set_style(Rect { y: area.y + row, height: 1, ..area }, style);
+    }
+}
+
+fn bar_line(notice: Option<&str>) -> Line<'static> {
+    let mut spans = vec![Span::styled(
+        " Navigate ",
+        Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD),
+    )];
+    if let Some(notice) = notice {
+        spans.push(Span::styled(format!(" {notice} "), Style::default().fg(Color::White)));
+    }
+    spans.push(Span::styled(
+        " j/k block \u{00B7} J/K message \u{00B7} Enter expand \u{00B7} y/Y copy text/code \
+         \u{00B7} o open \u{00B7} d diff \u{00B7} Esc exit",
+        Style::default().fg(theme::DIM),
+    ));
+    Line::from(spans)
+}
+
+/// Navigation bar drawn over the last chat row, above the search bar if open.
+/// Returns the row it covers.
+pub fn render_bar(frame: &mut Frame, area: Rect, app: &App) -> Option<Rect> {
+    let nav = app.chat_nav.as_ref()?;
+    if app.focus_owner() != FocusOwner::ChatNav {
+        return None;
+    }
+    let reserved = if app.search.is_some() { 2 } else { 1 };
+    if area.height < reserved {
+        return None;
+    }
+    let bar = Rect { y: area.bottom() - reserved, height: 1, ..area };
+    frame.render_widget(Clear, bar);
+    frame.render_widget(
+        Paragraph::new(bar_line(nav.notice.as_deref()))
+            .style(Style::default().bg(theme::USER_MSG_BG)),
+        bar,
+    );
+    Some(bar)
+}
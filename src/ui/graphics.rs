@@ -0,0 +1,754 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Inline images via terminal graphics protocols (kitty, iTerm2, sixel).
+//!
+//! Images never go through the ratatui buffer. The chat renderer reserves blank rows
+//! under a caption line, records where those rows landed on screen, and `present()`
+//! writes the protocol escape sequences at those cells after the frame is flushed.
+
+use crate::agent::model;
+use crate::app::{App, MessageBlock};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use crossterm::cursor::MoveTo;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Override for protocol detection: `kitty`, `iterm2`, `sixel` or `none`.
+const GRAPHICS_ENV: &str = "CLAUDE_RS_GRAPHICS";
+/// Upper bound on rows reserved for a single image.
+const MAX_IMAGE_ROWS: u16 = 24;
+/// Fallback cell size when the terminal does not report pixel dimensions.
+const DEFAULT_CELL_PX: (u16, u16) = (8, 16);
+/// Base64 prefix decoded to sniff the image header before falling back to a full decode.
+const HEADER_PROBE_CHARS: usize = 16 * 1024;
+/// Width of the `"  │  "` prefix that tool-call content lines carry.
+const TOOL_CONTENT_INDENT: u16 = 5;
+/// Kitty requires base64 payloads to be split into chunks of at most 4096 bytes.
+const KITTY_CHUNK_LEN: usize = 4096;
+const SIXEL_CACHE_LIMIT: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphicsProtocol {
+    #[default]
+    None,
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphicsSupport {
+    pub protocol: GraphicsProtocol,
+    pub cell_width_px: u16,
+    pub cell_height_px: u16,
+}
+
+impl Default for GraphicsSupport {
+    fn default() -> Self {
+        Self {
+            protocol: GraphicsProtocol::None,
+            cell_width_px: DEFAULT_CELL_PX.0,
+            cell_height_px: DEFAULT_CELL_PX.1,
+        }
+    }
+}
+
+static SUPPORT: OnceLock<GraphicsSupport> = OnceLock::new();
+
+/// Detect graphics support once for the process. Must run after the terminal is
+/// initialized so the pixel size query reflects the alternate screen.
+pub fn init() -> GraphicsSupport {
+    *SUPPORT.get_or_init(|| {
+        let protocol = detect_protocol(|name| std::env::var(name).ok());
+        let (cell_width_px, cell_height_px) = cell_pixel_size().unwrap_or(DEFAULT_CELL_PX);
+        tracing::debug!(?protocol, cell_width_px, cell_height_px, "graphics support detected");
+        GraphicsSupport { protocol, cell_width_px, cell_height_px }
+    })
+}
+
+/// Graphics support for the current process. Text-only until `init()` ran.
+pub fn support() -> GraphicsSupport {
+    SUPPORT.get().copied().unwrap_or_default()
+}
+
+fn cell_pixel_size() -> Option<(u16, u16)> {
+    let size = crossterm::terminal::window_size().ok()?;
+    if size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+    let cell = (size.width / size.columns, size.height / size.rows);
+    (cell.0 > 0 && cell.1 > 0).then_some(cell)
+}
+
+fn detect_protocol(var: impl Fn(&str) -> Option<String>) -> GraphicsProtocol {
+    if let Some(forced) = var(GRAPHICS_ENV) {
+        match forced.trim().to_ascii_lowercase().as_str() {
+            "kitty" => return GraphicsProtocol::Kitty,
+            "iterm2" | "iterm" => return GraphicsProtocol::Iterm2,
+            "sixel" => return GraphicsProtocol::Sixel,
+            "none" | "off" | "0" | "false" => return GraphicsProtocol::None,
+            _ => {}
+        }
+    }
+    // Multiplexers swallow graphics escapes unless passthrough is configured,
+    // which we cannot detect reliably. Users can force a protocol via the env var.
+    if var("TMUX").is_some() || var("ZELLIJ").is_some() {
+        return GraphicsProtocol::None;
+    }
+    let term = var("TERM").unwrap_or_default().to_ascii_lowercase();
+    let term_program = var("TERM_PROGRAM").unwrap_or_default().to_ascii_lowercase();
+    if var("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || term_program == "ghostty"
+    {
+        GraphicsProtocol::Kitty
+    } else if matches!(term_program.as_str(), "iterm.app" | "wezterm")
+        || var("LC_TERMINAL").is_some_and(|v| v == "iTerm2")
+    {
+        GraphicsProtocol::Iterm2
+    } else if term.starts_with("foot")
+        || term.contains("mlterm")
+        || term.contains("contour")
+        || term.contains("sixel")
+    {
+        GraphicsProtocol::Sixel
+    } else {
+        GraphicsProtocol::None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+/// Columns available to an image inside a tool call rendered at `width`.
+/// One column is kept free for the chat scrollbar.
+pub fn max_image_cols(width: u16) -> u16 {
+    width.saturating_sub(TOOL_CONTENT_INDENT + 1)
+}
+
+/// Pixel dimensions of an image, computed once per image.
+pub fn image_dimensions(image: &model::ImageContent) -> Option<(u32, u32)> {
+    *image.derived.dimensions.get_or_init(|| decode_dimensions(image))
+}
+
+/// Decode the header only when possible, the whole payload otherwise.
+fn decode_dimensions(image: &model::ImageContent) -> Option<(u32, u32)> {
+    let data = image.data.trim();
+    let probe_len = data.len().min(HEADER_PROBE_CHARS) / 4 * 4;
+    if let Some(prefix) = data.get(..probe_len)
+        && let Ok(bytes) = BASE64.decode(prefix)
+        && let Some(dims) = dimensions_from_bytes(&bytes)
+    {
+        return Some(dims);
+    }
+    dimensions_from_bytes(&decode_image_bytes(image)?)
+}
+
+fn dimensions_from_bytes(bytes: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+        .filter(|&(w, h)| w > 0 && h > 0)
+}
+
+fn decode_image_bytes(image: &model::ImageContent) -> Option<Vec<u8>> {
+    BASE64.decode(image.data.trim()).ok()
+}
+
+/// Cell footprint `(cols, rows)` of an image scaled to fit `max_cols` while keeping
+/// its aspect ratio. Small images are never upscaled.
+pub fn image_cell_size(dims: (u32, u32), max_cols: u16, support: GraphicsSupport) -> (u16, u16) {
+    let (width_px, height_px) = (u64::from(dims.0.max(1)), u64::from(dims.1.max(1)));
+    let cell_w = u64::from(support.cell_width_px.max(1));
+    let cell_h = u64::from(support.cell_height_px.max(1));
+    let mut cols = width_px.div_ceil(cell_w).clamp(1, u64::from(max_cols.max(1)));
+    let mut rows = (cols * cell_w * height_px).div_ceil(width_px * cell_h).max(1);
+    if rows > u64::from(MAX_IMAGE_ROWS) {
+        rows = u64::from(MAX_IMAGE_ROWS);
+        cols = (rows * cell_h * width_px / (height_px * cell_w)).max(1);
+    }
+    (u16::try_from(cols).unwrap_or(max_cols), u16::try_from(rows).unwrap_or(MAX_IMAGE_ROWS))
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub(super) fn format_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+fn approx_decoded_len(image: &model::ImageContent) -> usize {
+    image.data.trim().len() / 4 * 3
+}
+
+fn caption_text(image: &model::ImageContent, dims: (u32, u32)) -> String {
+    format!(
+        "\u{25A3} {} {}\u{00D7}{} ({})",
+        image.mime_type,
+        dims.0,
+        dims.1,
+        format_size(approx_decoded_len(image))
+    )
+}
+
+/// One-line description used for collapsed tool-call summaries.
+pub fn image_summary(image: &model::ImageContent) -> String {
+    match image_dimensions(image) {
+        Some((w, h)) => format!("{} {w}\u{00D7}{h}", image.mime_type),
+        None => image.mime_type.clone(),
+    }
+}
+
+/// Lines for an image inside tool-call content: a caption followed by blank rows
+/// that `present()` paints over, or a single textual placeholder when the terminal
+/// cannot display images.
+pub fn image_lines(image: &model::ImageContent, max_cols: u16) -> Vec<Line<'static>> {
+    image_lines_with_support(image, max_cols, support())
+}
+
+fn image_lines_with_support(
+    image: &model::ImageContent,
+    max_cols: u16,
+    support: GraphicsSupport,
+) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(super::theme::DIM);
+    let dims = image_dimensions(image);
+    match dims {
+        Some(dims) if support.protocol != GraphicsProtocol::None && max_cols > 0 => {
+            let (_, rows) = image_cell_size(dims, max_cols, support);
+            let mut lines = Vec::with_capacity(usize::from(rows) + 1);
+            lines.push(Line::from(Span::styled(caption_text(image, dims), dim)));
+            lines.extend((0..rows).map(|_| Line::default()));
+            lines
+        }
+        Some((w, h)) => vec![Line::from(Span::styled(
+            format!(
+                "[image: {} {w}\u{00D7}{h}, {}]",
+                image.mime_type,
+                format_size(approx_decoded_len(image))
+            ),
+            dim.add_modifier(Modifier::ITALIC),
+        ))],
+        None => vec![Line::from(Span::styled(
+            format!(
+                "[image: {}, {} - could not be decoded]",
+                image.mime_type,
+                format_size(approx_decoded_len(image))
+            ),
+            dim.add_modifier(Modifier::ITALIC),
+        ))],
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Placement
+// ---------------------------------------------------------------------------
+
+/// Screen rectangle reserved for one image in the last rendered frame.
+/// The image is addressed by its position in `App.messages`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImagePlacement {
+    pub message: usize,
+    pub block: usize,
+    pub content: usize,
+    pub x: u16,
+    pub y: u16,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Per-frame image placements plus the protocol-side state needed to avoid
+/// re-sending image data on every scroll step.
+#[derive(Debug, Default)]
+pub struct InlineImageState {
+    /// Placements computed by the chat renderer for the frame being drawn.
+    pub frame: Vec<ImagePlacement>,
+    /// Placements currently painted on the terminal.
+    presented: Vec<ImagePlacement>,
+    kitty_transmitted: HashSet<u32>,
+    sixel_cache: HashMap<(u64, u16, u16), String>,
+}
+
+impl InlineImageState {
+    /// Forget what is painted on screen, e.g. after a full terminal clear.
+    pub fn invalidate(&mut self) {
+        self.presented.clear();
+    }
+
+    /// Drop placements that intersect `area`, so an image painted after the
+    /// draw cannot cover a popup or bar drawn over the chat.
+    pub fn hide_under(&mut self, area: Rect) {
+        self.frame.retain(|p| {
+            let image = Rect { x: p.x, y: p.y, width: p.cols, height: p.rows };
+            !image.intersects(area)
+        });
+    }
+}
+
+/// Find where the images of `messages` landed in the rendered `lines`.
+///
+/// Images are matched to their caption lines in render order, then converted to
+/// absolute rows using the same wrapping as the chat paragraph. Only images whose
+/// reserved rows are fully inside `area` are placed.
+pub fn locate_placements(
+    app: &App,
+    messages: Range<usize>,
+    lines: &[Line<'static>],
+    scroll: usize,
+    area: Rect,
+) -> Vec<ImagePlacement> {
+    let support = support();
+    if support.protocol == GraphicsProtocol::None || lines.is_empty() {
+        return Vec::new();
+    }
+    let max_cols = max_image_cols(area.width);
+    let mut candidates = Vec::new();
+    for mi in messages {
+        let Some(msg) = app.messages.get(mi) else { break };
+        for (bi, block) in msg.blocks.iter().enumerate() {
+            let MessageBlock::ToolCall(tc) = block else { continue };
+            if !super::tool_call::shows_inline_images(tc) {
+                continue;
+            }
+            for (ci, content) in tc.content.iter().enumerate() {
+                if let model::ToolCallContent::Content(c) = content
+                    && let model::ContentBlock::Image(image) = &c.content
+                    && let Some(dims) = image_dimensions(image)
+                {
+                    let (cols, rows) = image_cell_size(dims, max_cols, support);
+                    candidates.push((mi, bi, ci, caption_text(image, dims), cols, rows));
+                }
+            }
+        }
+    }
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    let mut placements = Vec::new();
+    let mut next_line = 0usize;
+    let mut row = 0usize;
+    let mut counted_until = 0usize;
+    for (message, block, content, caption, cols, rows) in candidates {
+        let Some(offset) = lines[next_line..].iter().position(|line| {
+            line.spans.iter().any(|span| span.content.as_ref() == caption.as_str())
+        }) else {
+            continue;
+        };
+        let caption_idx = next_line + offset;
+        for line in &lines[counted_until..caption_idx] {
+            row += Paragraph::new(line.clone()).wrap(Wrap { trim: false }).line_count(area.width);
+        }
+        counted_until = caption_idx;
+        next_line = caption_idx + 1;
+
+        // Image rows start right below the caption line.
+        let Some(top) = (row + 1).checked_sub(scroll) else { continue };
+        if top + usize::from(rows) > usize::from(area.height) {
+            continue;
+        }
+        let Ok(top) = u16::try_from(top) else { continue };
+        placements.push(ImagePlacement {
+            message,
+            block,
+            content,
+            x: area.x + TOOL_CONTENT_INDENT,
+            y: area.y + top,
+            cols,
+            rows,
+        });
+    }
+    placements
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// Paint the placements recorded for the last frame.
+///
+/// Returns `true` when a cell-based protocol (iTerm2, sixel) still has stale
+/// images on screen. The caller must then clear, redraw and call `present()` again.
+pub fn present(app: &mut App, out: &mut impl Write) -> io::Result<bool> {
+    let protocol = support().protocol;
+    let frame = std::mem::take(&mut app.inline_images.frame);
+    if protocol == GraphicsProtocol::None || frame == app.inline_images.presented {
+        return Ok(false);
+    }
+
+    match protocol {
+        GraphicsProtocol::Kitty => {
+            // Drop all placements but keep transmitted image data for reuse.
+            write!(out, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+        }
+        GraphicsProtocol::Iterm2 | GraphicsProtocol::Sixel => {
+            if !app.inline_images.presented.is_empty() {
+                app.inline_images.presented.clear();
+                app.inline_images.frame = frame;
+                return Ok(true);
+            }
+        }
+        GraphicsProtocol::None => {}
+    }
+
+    for (idx, placement) in frame.iter().enumerate() {
+        let Some(image) = image_at(app, placement) else { continue };
+        let key = image_key(image);
+        crossterm::queue!(out, MoveTo(placement.x, placement.y))?;
+        match protocol {
+            GraphicsProtocol::Kitty => {
+                let id = kitty_image_id(key);
+                if !app.inline_images.kitty_transmitted.contains(&id) {
+                    let Some(png) = png_base64(image) else { continue };
+                    write_kitty_transmit(out, id, &png)?;
+                    app.inline_images.kitty_transmitted.insert(id);
+                }
+                write!(
+                    out,
+                    "\x1b_Ga=p,i={id},p={},c={},r={},C=1,q=2\x1b\\",
+                    idx + 1,
+                    placement.cols,
+                    placement.rows
+                )?;
+            }
+            GraphicsProtocol::Iterm2 => {
+                write!(
+                    out,
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                    approx_decoded_len(image),
+                    placement.cols,
+                    placement.rows,
+                    image.data.trim()
+                )?;
+            }
+            GraphicsProtocol::Sixel => {
+                let cache_key = (key, placement.cols, placement.rows);
+                if !app.inline_images.sixel_cache.contains_key(&cache_key) {
+                    let Some(encoded) =
+                        sixel_for_image(image, placement.cols, placement.rows, support())
+                    else {
+                        continue;
+                    };
+                    if app.inline_images.sixel_cache.len() >= SIXEL_CACHE_LIMIT {
+                        app.inline_images.sixel_cache.clear();
+                    }
+                    app.inline_images.sixel_cache.insert(cache_key, encoded);
+                }
+                if let Some(encoded) = app.inline_images.sixel_cache.get(&cache_key) {
+                    out.write_all(encoded.as_bytes())?;
+                }
+            }
+            GraphicsProtocol::None => {}
+        }
+    }
+    out.flush()?;
+    app.inline_images.presented = frame;
+    Ok(false)
+}
+
+/// Release all protocol-side image state before the terminal is restored.
+pub fn clear_all(out: &mut impl Write) -> io::Result<()> {
+    if support().protocol == GraphicsProtocol::Kitty {
+        write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn image_at<'a>(app: &'a App, placement: &ImagePlacement) -> Option<&'a model::ImageContent> {
+    let MessageBlock::ToolCall(tc) =
+        app.messages.get(placement.message)?.blocks.get(placement.block)?
+    else {
+        return None;
+    };
+    match tc.content.get(placement.content)? {
+        model::ToolCallContent::Content(c) => match &c.content {
+            model::ContentBlock::Image(image) => Some(image),
+            model::ContentBlock::Text(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Identity of an image: hash of the whole payload and mime type, computed once.
+fn image_key(image: &model::ImageContent) -> u64 {
+    *image.derived.key.get_or_init(|| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        image.data.hash(&mut hasher);
+        image.mime_type.hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn kitty_image_id(key: u64) -> u32 {
+    // Kitty ids are 32-bit and must be non-zero.
+    ((key ^ (key >> 32)) as u32).max(1)
+}
+
+fn png_base64(image: &model::ImageContent) -> Option<String> {
+    if image.mime_type == "image/png" {
+        return Some(image.data.chars().filter(|c| !c.is_ascii_whitespace()).collect());
+    }
+    let decoded = image::load_from_memory(&decode_image_bytes(image)?).ok()?;
+    let mut png = io::Cursor::new(Vec::new());
+    decoded.write_to(&mut png, image::ImageFormat::Png).ok()?;
+    Some(BASE64.encode(png.into_inner()))
+}
+
+fn write_kitty_transmit(out: &mut impl Write, id: u32, payload: &str) -> io::Result<()> {
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_LEN).collect();
+    let last = chunks.len().saturating_sub(1);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i < last);
+        if i == 0 {
+            write!(out, "\x1b_Ga=t,f=100,i={id},q=2,m={more};")?;
+        } else {
+            write!(out, "\x1b_Gm={more};")?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+fn sixel_for_image(
+    image: &model::ImageContent,
+    cols: u16,
+    rows: u16,
+    support: GraphicsSupport,
+) -> Option<String> {
+    let decoded = image::load_from_memory(&decode_image_bytes(image)?).ok()?;
+    let target_w = u32::from(cols) * u32::from(support.cell_width_px);
+    let target_h = u32::from(rows) * u32::from(support.cell_height_px);
+    let scaled = decoded.resize(target_w, target_h, image::imageops::FilterType::Triangle);
+    Some(encode_sixel(&scaled.to_rgba8()))
+}
+
+/// Quantize a channel to one of the six levels of the 6x6x6 color cube.
+fn cube_level(channel: u8) -> u8 {
+    u8::try_from((u16::from(channel) * 5 + 127) / 255).unwrap_or(5)
+}
+
+/// Encode an image as sixel using a fixed 216-color palette.
+/// Transparent pixels are left unpainted so the terminal background shows through.
+fn encode_sixel(img: &image::RgbaImage) -> String {
+    let (width, height) = img.dimensions();
+    let mut out = String::from("\x1bP0;1;0q");
+    let _ = write!(out, "\"1;1;{width};{height}");
+    for idx in 0u16..216 {
+        let (r, g, b) = (idx / 36, (idx / 6) % 6, idx % 6);
+        let _ = write!(out, "#{idx};2;{};{};{}", r * 20, g * 20, b * 20);
+    }
+    let width_usize = width as usize;
+    for band_top in (0..height).step_by(6) {
+        let mut bands: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+        for dy in 0..6u32.min(height - band_top) {
+            for x in 0..width {
+                let px = img.get_pixel(x, band_top + dy);
+                if px[3] < 128 {
+                    continue;
+                }
+                let color = cube_level(px[0]) * 36 + cube_level(px[1]) * 6 + cube_level(px[2]);
+                bands.entry(color).or_insert_with(|| vec![0; width_usize])[x as usize] |= 1 << dy;
+            }
+        }
+        for (i, (color, bits)) in bands.iter().enumerate() {
+            if i > 0 {
+                out.push('$');
+            }
+            let _ = write!(out, "#{color}");
+            push_sixel_runs(&mut out, bits);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_sixel_runs(out: &mut String, bits: &[u8]) {
+    let mut iter = bits.iter().peekable();
+    while let Some(&value) = iter.next() {
+        let mut run = 1usize;
+        while iter.next_if_eq(&&value).is_some() {
+            run += 1;
+        }
+        let ch = char::from(63 + value);
+        if run > 3 {
+            let _ = write!(out, "!{run}{ch}");
+        } else {
+            out.extend(std::iter::repeat_n(ch, run));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn env(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let owned: Vec<(String, String)> =
+            pairs.iter().map(|(k, v)| ((*k).to_owned(), (*v).to_owned())).collect();
+        move |name| owned.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+    }
+
+    fn png_image(width: u32, height: u32) -> model::ImageContent {
+        let img = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 0, 0, 255]));
+        let mut png = io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(img).write_to(&mut png, image::ImageFormat::Png).unwrap();
+        model::ImageContent::new(BASE64.encode(png.into_inner()), "image/png")
+    }
+
+    fn graphics(protocol: GraphicsProtocol) -> GraphicsSupport {
+        GraphicsSupport { protocol, cell_width_px: 10, cell_height_px: 20 }
+    }
+
+    #[test]
+    fn detects_protocol_from_terminal_env() {
+        assert_eq!(detect_protocol(env(&[("KITTY_WINDOW_ID", "1")])), GraphicsProtocol::Kitty);
+        assert_eq!(detect_protocol(env(&[("TERM", "xterm-ghostty")])), GraphicsProtocol::Kitty);
+        assert_eq!(
+            detect_protocol(env(&[("TERM_PROGRAM", "iTerm.app")])),
+            GraphicsProtocol::Iterm2
+        );
+        assert_eq!(detect_protocol(env(&[("TERM", "foot")])), GraphicsProtocol::Sixel);
+        assert_eq!(detect_protocol(env(&[("TERM", "xterm-256color")])), GraphicsProtocol::None);
+    }
+
+    #[test]
+    fn multiplexer_disables_detection_unless_forced() {
+        assert_eq!(
+            detect_protocol(env(&[("KITTY_WINDOW_ID", "1"), ("TMUX", "/tmp/tmux")])),
+            GraphicsProtocol::None
+        );
+        assert_eq!(
+            detect_protocol(env(&[("TMUX", "/tmp/tmux"), (GRAPHICS_ENV, "sixel")])),
+            GraphicsProtocol::Sixel
+        );
+        assert_eq!(
+            detect_protocol(env(&[("KITTY_WINDOW_ID", "1"), (GRAPHICS_ENV, "none")])),
+            GraphicsProtocol::None
+        );
+    }
+
+    #[test]
+    fn reads_dimensions_from_header() {
+        assert_eq!(image_dimensions(&png_image(64, 32)), Some((64, 32)));
+        assert_eq!(image_dimensions(&model::ImageContent::new("not base64!", "image/png")), None);
+    }
+
+    #[test]
+    fn image_key_covers_the_whole_payload() {
+        let edge = "A".repeat(2048);
+        let first = model::ImageContent::new(format!("{edge}BBBB{edge}"), "image/png");
+        let second = model::ImageContent::new(format!("{edge}CCCC{edge}"), "image/png");
+        assert_ne!(image_key(&first), image_key(&second));
+        assert_eq!(image_key(&first), image_key(&first.clone()));
+    }
+
+    #[test]
+    fn dimensions_are_computed_once_and_kept_by_clones() {
+        let image = png_image(64, 32);
+        assert_eq!(image.derived.dimensions.get(), None);
+        assert_eq!(image_dimensions(&image), Some((64, 32)));
+        assert_eq!(image.clone().derived.dimensions.get(), Some(&Some((64, 32))));
+    }
+
+    #[test]
+    fn cell_size_fits_width_and_keeps_aspect() {
+        let support = graphics(GraphicsProtocol::Kitty);
+        // 400x200 px at 10x20 px cells: 40 cols, 10 rows.
+        assert_eq!(image_cell_size((400, 200), 80, support), (40, 10));
+        // Narrow chat: scaled down to 20 cols, 5 rows.
+        assert_eq!(image_cell_size((400, 200), 20, support), (20, 5));
+        // Tall image: rows capped, cols shrink to keep the ratio.
+        assert_eq!(image_cell_size((100, 2000), 80, support), (2, MAX_IMAGE_ROWS));
+    }
+
+    #[test]
+    fn lines_reserve_rows_when_graphics_available() {
+        let image = png_image(400, 200);
+        let lines = image_lines_with_support(&image, 80, graphics(GraphicsProtocol::Kitty));
+        assert_eq!(lines.len(), 11);
+        assert!(lines[0].spans[0].content.starts_with("\u{25A3} image/png 400\u{00D7}200"));
+        assert!(lines[1..].iter().all(|l| l.spans.is_empty()));
+    }
+
+    #[test]
+    fn lines_fall_back_to_placeholder_without_graphics() {
+        let image = png_image(400, 200);
+        let lines = image_lines_with_support(&image, 80, graphics(GraphicsProtocol::None));
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].spans[0].content.starts_with("[image: image/png 400\u{00D7}200"));
+    }
+
+    #[test]
+    fn sixel_encoding_is_framed_and_run_length_encoded() {
+        let img = image::RgbaImage::from_pixel(8, 6, image::Rgba([255, 0, 0, 255]));
+        let encoded = encode_sixel(&img);
+        assert!(encoded.starts_with("\x1bP0;1;0q\"1;1;8;6"));
+        assert!(encoded.ends_with("-\x1b\\"));
+        // Pure red is cube index 5*36 = 180, one full band of 8 columns.
+        assert!(encoded.contains("#180!8~"));
+    }
+
+    #[test]
+    fn kitty_transmit_chunks_payload() {
+        let payload = "A".repeat(KITTY_CHUNK_LEN + 10);
+        let mut out = Vec::new();
+        write_kitty_transmit(&mut out, 7, &payload).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("\x1b_Ga=t,f=100,i=7,q=2,m=1;"));
+        assert!(text.contains("\x1b_Gm=0;AAAAAAAAAA\x1b\\"));
+    }
+
+    #[test]
+    fn placements_under_a_popup_are_hidden() {
+        let at = |message, y| ImagePlacement {
+            message,
+            block: 0,
+            content: 0,
+            x: 2,
+            y,
+            cols: 10,
+            rows: 4,
+        };
+        let mut state = InlineImageState { frame: vec![at(0, 1), at(1, 8)], ..Default::default() };
+        state.hide_under(Rect { x: 5, y: 10, width: 20, height: 3 });
+        assert_eq!(state.frame, vec![at(0, 1)]);
+        state.hide_under(Rect { x: 20, y: 0, width: 5, height: 30 });
+        assert_eq!(state.frame, vec![at(0, 1)]);
+    }
+}
@@ -56,6 +56,12 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
             spans.push(Span::styled(branch.clone(), white));
         }
 
+        if let Some((active, total)) = app.branches.indicator() {
+            spans.push(sep());
+            spans.push(Span::styled("Fork: ", Style::default().fg(theme::DIM)));
+            spans.push(Span::styled(format!("{active}/{total}"), white));
+        }
+
         app.cached_header_line = Some(Line::from(spans));
     }
 
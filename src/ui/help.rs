@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::app::vim::VimMode;
 use crate::app::{App, AppStatus, FocusOwner, HelpView};
 use crate::ui::theme;
 use ratatui::Frame;
@@ -190,6 +191,7 @@ fn build_help_items(app: &App) -> Vec<(String, String)> {
 fn build_key_help_items(app: &App) -> Vec<(String, String)> {
     if app.status == AppStatus::Connecting {
         let mut items = blocked_input_help_items("Unavailable while connecting");
+        push_tab_help_items(app, &mut items);
         if app.update_check_hint.is_some() {
             items.push(("Ctrl+u".to_owned(), "Hide update hint".to_owned()));
         }
@@ -197,6 +199,7 @@ fn build_key_help_items(app: &App) -> Vec<(String, String)> {
     }
     if app.status == AppStatus::Resuming {
         let mut items = blocked_input_help_items("Unavailable while resuming");
+        push_tab_help_items(app, &mut items);
         if app.update_check_hint.is_some() {
             items.push(("Ctrl+u".to_owned(), "Hide update hint".to_owned()));
         }
@@ -204,6 +207,7 @@ fn build_key_help_items(app: &App) -> Vec<(String, String)> {
     }
     if app.status == AppStatus::Error {
         let mut items = blocked_input_help_items("Unavailable after error");
+        push_tab_help_items(app, &mut items);
         if app.update_check_hint.is_some() {
             items.push(("Ctrl+u".to_owned(), "Hide update hint".to_owned()));
         }
@@ -220,10 +224,21 @@ fn build_key_help_items(app: &App) -> Vec<(String, String)> {
         ("Shift+Tab".to_owned(), "Cycle mode".to_owned()),
         ("Ctrl+o".to_owned(), "Toggle tool collapse".to_owned()),
         ("Ctrl+t".to_owned(), "Toggle todos (when available)".to_owned()),
+        ("Ctrl+f".to_owned(), "Search conversation".to_owned()),
         // Chat scrolling
         ("Ctrl+Up/Down".to_owned(), "Scroll chat".to_owned()),
         ("Mouse wheel".to_owned(), "Scroll chat".to_owned()),
     ];
+    push_tab_help_items(app, &mut items);
+    items.push(("Alt+a".to_owned(), "Subagent focus view (next subagent)".to_owned()));
+    items.push(("Alt+e".to_owned(), "Edit an earlier prompt and fork from it".to_owned()));
+    if app.branches.editing.is_some() {
+        items.push(("Esc".to_owned(), "Cancel earlier prompt edit".to_owned()));
+    }
+    if app.subagent_view.is_some() {
+        items.push(("Up/Down/PgUp/PgDn".to_owned(), "Scroll subagent view".to_owned()));
+        items.push(("Esc".to_owned(), "Close subagent view".to_owned()));
+    }
     if app.update_check_hint.is_some() {
         items.push(("Ctrl+u".to_owned(), "Hide update hint".to_owned()));
     }
@@ -235,27 +250,27 @@ fn build_key_help_items(app: &App) -> Vec<(String, String)> {
     if app.show_todo_panel && !app.todos.is_empty() {
         items.push(("Tab".to_owned(), "Toggle todo focus".to_owned()));
     }
-
-    // Input + navigation (active outside todo-list and mention focus)
-    if focus_owner != FocusOwner::TodoList
-        && focus_owner != FocusOwner::Mention
-        && focus_owner != FocusOwner::Help
-    {
-        items.push(("Enter".to_owned(), "Send message".to_owned()));
-        items.push(("Shift+Enter".to_owned(), "Insert newline".to_owned()));
-        items.push(("Up/Down".to_owned(), "Move cursor / scroll chat".to_owned()));
-        items.push(("Left/Right".to_owned(), "Move cursor".to_owned()));
-        items.push(("Ctrl+Left/Right".to_owned(), "Word left/right".to_owned()));
-        items.push(("Home/End".to_owned(), "Line start/end".to_owned()));
-        items.push(("Backspace".to_owned(), "Delete before".to_owned()));
-        items.push(("Delete".to_owned(), "Delete after".to_owned()));
-        items.push(("Ctrl+Backspace/Delete".to_owned(), "Delete word".to_owned()));
-        items.push(("Ctrl+z/y".to_owned(), "Undo/redo".to_owned()));
-        items.push(("Paste".to_owned(), "Insert text".to_owned()));
+    push_queue_help_items(app, focus_owner, &mut items);
+    push_attachments_help_items(app, focus_owner, &mut items);
+    push_search_help_items(app, focus_owner, &mut items);
+    push_chat_nav_help_items(focus_owner, &mut items);
+    if app.last_shell_output.is_some() {
+        items.push(("Alt+i".to_owned(), "Attach shell output to prompt".to_owned()));
+    }
+    if crate::app::draft::visible_restored(app).is_some() {
+        items.push(("Alt+d".to_owned(), "Discard restored draft".to_owned()));
     }
 
+    push_input_help_items(focus_owner, &mut items);
+    push_vim_help_items(app, focus_owner, &mut items);
+
     // Turn control
-    if matches!(app.status, crate::app::AppStatus::Thinking | crate::app::AppStatus::Running) {
+    if focus_owner == FocusOwner::Queue {
+        items.push(("Esc".to_owned(), "Exit queue focus".to_owned()));
+    } else if focus_owner == FocusOwner::Attachments {
+        items.push(("Esc".to_owned(), "Exit attachments focus".to_owned()));
+    } else if matches!(app.status, crate::app::AppStatus::Thinking | crate::app::AppStatus::Running)
+    {
         items.push(("Esc".to_owned(), "Cancel current turn".to_owned()));
     } else if focus_owner == FocusOwner::TodoList {
         items.push(("Esc".to_owned(), "Exit todo focus".to_owned()));
@@ -280,6 +295,121 @@ fn build_key_help_items(app: &App) -> Vec<(String, String)> {
     items
 }
 
+/// Input + navigation (active outside todo-list and mention focus)
+fn push_input_help_items(focus_owner: FocusOwner, items: &mut Vec<(String, String)>) {
+    if matches!(
+        focus_owner,
+        FocusOwner::TodoList
+            | FocusOwner::Mention
+            | FocusOwner::Help
+            | FocusOwner::Queue
+            | FocusOwner::Attachments
+            | FocusOwner::Search
+            | FocusOwner::ChatNav
+    ) {
+        return;
+    }
+    items.push(("Enter".to_owned(), "Send message".to_owned()));
+    items.push(("Shift+Enter".to_owned(), "Insert newline".to_owned()));
+    items.push(("Up/Down".to_owned(), "Move cursor / scroll chat".to_owned()));
+    items.push(("Left/Right".to_owned(), "Move cursor".to_owned()));
+    items.push(("Ctrl+Left/Right".to_owned(), "Word left/right".to_owned()));
+    items.push(("Home/End".to_owned(), "Line start/end".to_owned()));
+    items.push(("Backspace".to_owned(), "Delete before".to_owned()));
+    items.push(("Delete".to_owned(), "Delete after".to_owned()));
+    items.push(("Ctrl+Backspace/Delete".to_owned(), "Delete word".to_owned()));
+    items.push(("Ctrl+z/y".to_owned(), "Undo/redo".to_owned()));
+    items.push(("Ctrl+g".to_owned(), "Edit prompt in $EDITOR".to_owned()));
+    items.push(("!command".to_owned(), "Run in shell (no model turn)".to_owned()));
+    items.push(("Paste".to_owned(), "Insert text".to_owned()));
+}
+
+fn push_vim_help_items(app: &App, focus_owner: FocusOwner, items: &mut Vec<(String, String)>) {
+    let Some(vim) = app.vim.as_ref().filter(|_| focus_owner == FocusOwner::Input) else {
+        return;
+    };
+    if vim.mode == VimMode::Insert {
+        items.push(("Esc".to_owned(), "Vi normal mode".to_owned()));
+        return;
+    }
+    items.push(("i/a/o".to_owned(), "Insert / append / open line".to_owned()));
+    items.push(("v/V".to_owned(), "Visual / visual line".to_owned()));
+    items.push(("hjkl w b e".to_owned(), "Vi motions (with counts)".to_owned()));
+    items.push(("d/c/y{motion}".to_owned(), "Delete / change / yank".to_owned()));
+    items.push(("iw a( i\"".to_owned(), "Text objects".to_owned()));
+    items.push(("p/P \"x".to_owned(), "Put / pick register (+ is clipboard)".to_owned()));
+    items.push(("u/Ctrl+r".to_owned(), "Vi undo/redo".to_owned()));
+}
+
+fn push_queue_help_items(app: &App, focus_owner: FocusOwner, items: &mut Vec<(String, String)>) {
+    if !app.submission_queue.is_empty() {
+        items.push(("Alt+q".to_owned(), "Manage queued messages".to_owned()));
+    }
+    if app.submission_queue.editing.is_some() {
+        items.push(("Esc".to_owned(), "Cancel queued message edit".to_owned()));
+    }
+    if focus_owner == FocusOwner::Queue {
+        items.push(("Up/Down".to_owned(), "Select queued message".to_owned()));
+        items.push(("Shift+Up/Down".to_owned(), "Move queued message".to_owned()));
+        items.push(("e/Enter".to_owned(), "Edit queued message".to_owned()));
+        items.push(("i".to_owned(), "Interrupt now / after turn".to_owned()));
+        items.push(("d/Delete".to_owned(), "Remove queued message".to_owned()));
+    }
+}
+
+fn push_attachments_help_items(
+    app: &App,
+    focus_owner: FocusOwner,
+    items: &mut Vec<(String, String)>,
+) {
+    if !app.attachments.is_empty() {
+        items.push(("Alt+p".to_owned(), "Manage attachments".to_owned()));
+    }
+    if focus_owner == FocusOwner::Attachments {
+        items.push(("Left/Right".to_owned(), "Select attachment".to_owned()));
+        items.push(("p/Enter".to_owned(), "Preview attachment".to_owned()));
+        items.push(("c/Tab".to_owned(), "Send inline / attached".to_owned()));
+        items.push(("d/Delete".to_owned(), "Remove from prompt".to_owned()));
+    }
+}
+
+fn push_search_help_items(app: &App, focus_owner: FocusOwner, items: &mut Vec<(String, String)>) {
+    if focus_owner != FocusOwner::Search {
+        return;
+    }
+    if app.search.as_ref().is_some_and(|s| s.editing) {
+        items.push(("Enter".to_owned(), "Browse matches".to_owned()));
+    } else {
+        items.push(("n/N".to_owned(), "Next/previous match".to_owned()));
+        items.push(("/".to_owned(), "Edit search".to_owned()));
+    }
+    items.push(("Esc".to_owned(), "Close search".to_owned()));
+}
+
+fn push_chat_nav_help_items(focus_owner: FocusOwner, items: &mut Vec<(String, String)>) {
+    items.push(("Alt+j".to_owned(), "Navigate chat blocks".to_owned()));
+    items.push(("Alt+o".to_owned(), "Open latest tool location in editor".to_owned()));
+    if focus_owner != FocusOwner::ChatNav {
+        return;
+    }
+    items.push(("j/k".to_owned(), "Next/previous block".to_owned()));
+    items.push(("J/K".to_owned(), "Next/previous message".to_owned()));
+    items.push(("Enter".to_owned(), "Expand/collapse tool call".to_owned()));
+    items.push(("y/Y".to_owned(), "Copy block text/code".to_owned()));
+    items.push(("o".to_owned(), "Open tool location in editor".to_owned()));
+    items.push(("d".to_owned(), "Open diff in pager".to_owned()));
+    items.push(("Esc".to_owned(), "Exit chat navigation".to_owned()));
+}
+
+fn push_tab_help_items(app: &App, items: &mut Vec<(String, String)>) {
+    items.push(("Alt+t".to_owned(), "Open session tab".to_owned()));
+    if app.tabs.list.len() > 1 {
+        items.push(("Alt+w".to_owned(), "Close session tab".to_owned()));
+        items.push(("Alt+Left/Right".to_owned(), "Previous/next tab".to_owned()));
+        items.push(("Alt+1..9".to_owned(), "Jump to tab".to_owned()));
+    }
+}
+
 fn blocked_input_help_items(input_line: &str) -> Vec<(String, String)> {
     vec![
         ("Left/Right".to_owned(), "Switch help tab".to_owned()),
@@ -316,6 +446,9 @@ fn build_slash_help_items(app: &App) -> Vec<(String, String)> {
         })
         .filter(|(name, _)| !matches!(name.as_str(), "/login" | "/logout"))
         .collect();
+    // Custom commands shadow advertised ones of the same name, so list them first.
+    let custom = app.custom_commands.iter().map(|cmd| (cmd.name.clone(), cmd.summary()));
+    commands.splice(0..0, custom);
 
     commands.sort_by(|a, b| a.0.cmp(&b.0));
     commands.dedup_by(|a, b| a.0 == b.0);
@@ -0,0 +1,454 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! OSC 8 hyperlinks for URLs and tool-call file paths in the chat.
+//!
+//! Links are found in the drawn buffer, after wrapping, instead of being
+//! baked into the cached `Line`s: block caches and height measurement stay
+//! byte-for-byte the same, and a URL wrapped over two rows is still one link.
+//! After each frame the linked cells are printed again inside an OSC 8 pair.
+//! Terminals without OSC 8 support ignore the sequence and show the same text.
+
+use crate::Cli;
+use crate::agent::model;
+use crate::app::{App, MessageBlock, ToolCallInfo};
+use crossterm::cursor::{RestorePosition, SavePosition};
+use ratatui::backend::{Backend as _, CrosstermBackend};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::Path;
+
+const DISABLE_ENV: &str = "CLAUDE_RS_NO_HYPERLINKS";
+const FILE_LINK_ENV: &str = "CLAUDE_RS_FILE_LINK";
+
+/// Default target for file paths. `{path}` is absolute, `{line}` 1-based.
+const DEFAULT_FILE_LINK: &str = "file://{path}";
+
+/// Shorter path texts match too much unrelated output.
+const MIN_PATH_TEXT_CHARS: usize = 3;
+
+/// Text in the chat that links to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkTarget {
+    pub text: String,
+    pub url: String,
+}
+
+/// Linked cells in one drawn frame, in print order.
+#[derive(Debug, Clone, PartialEq)]
+struct LinkRegion {
+    url: String,
+    cells: Vec<(u16, u16, Cell)>,
+}
+
+#[derive(Debug, Default)]
+pub struct HyperlinkState {
+    enabled: bool,
+    /// URI template for file paths, with `{path}` and `{line}` placeholders.
+    file_link: String,
+    /// File path texts visible in the frame being drawn, set by the chat renderer.
+    pub targets: Vec<LinkTarget>,
+    /// Regions currently linked on screen. The backend only rewrites changed
+    /// cells, so a region that did not change keeps its link.
+    presented: Vec<LinkRegion>,
+}
+
+impl HyperlinkState {
+    /// Enabled unless `--no-hyperlinks`, `CLAUDE_RS_NO_HYPERLINKS` or a
+    /// terminal known to print OSC 8 as garbage.
+    #[must_use]
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self::resolve(cli, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(cli: &Cli, env: impl Fn(&str) -> Option<String>) -> Self {
+        let disabled_by_env = env(DISABLE_ENV)
+            .is_some_and(|v| !matches!(v.trim(), "" | "0" | "false" | "no" | "off"));
+        let term = env("TERM").unwrap_or_default();
+        let unsupported_term = matches!(term.as_str(), "linux" | "dumb");
+        let file_link = cli
+            .file_link
+            .clone()
+            .or_else(|| env(FILE_LINK_ENV))
+            .filter(|t| t.contains("{path}"))
+            .unwrap_or_else(|| DEFAULT_FILE_LINK.to_owned());
+        Self {
+            enabled: !cli.no_hyperlinks && !disabled_by_env && !unsupported_term,
+            file_link,
+            targets: Vec::new(),
+            presented: Vec::new(),
+        }
+    }
+
+    /// Forget what is linked on screen, e.g. after a full terminal clear.
+    pub fn invalidate(&mut self) {
+        self.presented.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Targets
+// ---------------------------------------------------------------------------
+
+/// Percent-encode what is not allowed verbatim in a URI path.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/:.-_~+@".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+/// Fill a file link template for `path` (absolute) and `line`.
+fn file_url(template: &str, path: &Path, line: Option<u32>) -> String {
+    let mut path = path.to_string_lossy().replace('\\', "/");
+    if !path.starts_with('/') {
+        // Windows drive paths: `C:/x` -> `/C:/x`.
+        path.insert(0, '/');
+    }
+    let path = encode_path(&path);
+    let url = match line {
+        Some(line) => template.replace("{line}", &line.to_string()),
+        None => template.replace(":{line}", "").replace("{line}", "1"),
+    };
+    url.replace("{path}", &path)
+}
+
+/// Plain `file://` URI for an absolute path.
+#[must_use]
+pub fn file_uri(path: &Path) -> String {
+    file_url(DEFAULT_FILE_LINK, path, None)
+}
+
+/// Path as shown after `shorten_tool_title`: relative to `cwd` when inside it.
+fn display_path(path: &Path, cwd: &str) -> Option<String> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let cwd = cwd.replace('\\', "/");
+    let cwd = cwd.trim_end_matches('/');
+    if cwd.is_empty() {
+        return None;
+    }
+    path.strip_prefix(cwd)?.strip_prefix('/').map(str::to_owned)
+}
+
+fn tool_paths(tc: &ToolCallInfo) -> impl Iterator<Item = (&Path, Option<u32>)> {
+    let locations = tc.locations.iter().map(|l| (l.path.as_path(), l.line));
+    let diffs = tc.content.iter().filter_map(|content| match content {
+        model::ToolCallContent::Diff(diff) => Some((diff.path.as_path(), None)),
+        _ => None,
+    });
+    locations.chain(diffs)
+}
+
+/// File path targets of the tool calls in `messages`.
+#[must_use]
+pub fn collect_targets(app: &App, messages: Range<usize>) -> Vec<LinkTarget> {
+    if !app.hyperlinks.enabled {
+        return Vec::new();
+    }
+    let mut targets: Vec<LinkTarget> = Vec::new();
+    let mut push = |text: String, url: &str| {
+        if text.chars().count() >= MIN_PATH_TEXT_CHARS && !targets.iter().any(|t| t.text == text) {
+            targets.push(LinkTarget { text, url: url.to_owned() });
+        }
+    };
+    for msg in app.messages.get(messages).unwrap_or_default() {
+        for block in &msg.blocks {
+            let MessageBlock::ToolCall(tc) = block else { continue };
+            for (path, line) in tool_paths(tc) {
+                let absolute = if path.is_absolute() {
+                    path.to_path_buf()
+                } else {
+                    Path::new(&app.cwd_raw).join(path)
+                };
+                let url = file_url(&app.hyperlinks.file_link, &absolute, line);
+                if let Some(relative) = display_path(&absolute, &app.cwd_raw) {
+                    push(relative, &url);
+                }
+                push(path.to_string_lossy().replace('\\', "/"), &url);
+            }
+        }
+    }
+    // Longest first, so `src/app/mod.rs` wins over `mod.rs`.
+    targets.sort_by_key(|t| std::cmp::Reverse(t.text.len()));
+    targets
+}
+
+// ---------------------------------------------------------------------------
+// Locating links in the drawn buffer
+// ---------------------------------------------------------------------------
+
+/// One buffer row as text, with the byte offset at which each cell starts.
+struct RowText {
+    y: u16,
+    text: String,
+    cells: Vec<(usize, u16)>,
+}
+
+impl RowText {
+    fn read(buffer: &Buffer, area: Rect, y: u16) -> Self {
+        let mut text = String::new();
+        let mut cells = Vec::with_capacity(usize::from(area.width));
+        for x in area.left()..area.right() {
+            let Some(cell) = buffer.cell((x, y)) else { break };
+            cells.push((text.len(), x));
+            text.push_str(cell.symbol());
+        }
+        Self { y, text, cells }
+    }
+
+    /// Cells whose text starts inside `bytes`.
+    fn cells_in(&self, bytes: Range<usize>) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.cells.iter().filter(move |(start, _)| bytes.contains(start)).map(|&(_, x)| (x, self.y))
+    }
+
+    /// Whether `end` is the end of the row's text (ignoring nothing: wrapped
+    /// text fills the row to the last column).
+    fn ends_at(&self, end: usize) -> bool {
+        end == self.text.len()
+    }
+}
+
+fn is_url_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=%".contains(c)
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || "/._-~".contains(c)
+}
+
+/// Drop trailing punctuation that belongs to the sentence, and a closing
+/// bracket without an opening one inside the URL.
+fn trim_url_end(mut url: &str) -> &str {
+    loop {
+        let Some(last) = url.chars().last() else { return url };
+        let unbalanced = |open: char, close: char| {
+            last == close && url.matches(open).count() < url.matches(close).count()
+        };
+        if ".,;:!?'\"".contains(last) || unbalanced('(', ')') || unbalanced('[', ']') {
+            url = &url[..url.len() - last.len_utf8()];
+        } else {
+            return url;
+        }
+    }
+}
+
+/// URLs starting in `rows[index]`, following wraps onto the next rows.
+fn find_urls(rows: &[RowText], index: usize, out: &mut Vec<LinkRegion>) {
+    let row = &rows[index];
+    let mut search_from = 0;
+    while let Some(found) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| row.text[search_from..].find(scheme).map(|i| search_from + i))
+        .min()
+    {
+        let run_end =
+            row.text[found..].find(|c: char| !is_url_char(c)).map_or(row.text.len(), |i| found + i);
+        let mut url = row.text[found..run_end].to_owned();
+        let mut spans = vec![(index, found..run_end)];
+        // A URL filling the row to its edge continues at the start of the next row.
+        let mut next = index + 1;
+        let mut wrapped = row.ends_at(run_end);
+        while wrapped && let Some(next_row) = rows.get(next) {
+            let len = next_row.text.find(|c: char| !is_url_char(c)).unwrap_or(next_row.text.len());
+            if len == 0 {
+                break;
+            }
+            url.push_str(&next_row.text[..len]);
+            spans.push((next, 0..len));
+            wrapped = next_row.ends_at(len);
+            next += 1;
+        }
+
+        let trimmed = trim_url_end(&url).len();
+        let mut remaining = trimmed;
+        let mut cells = Vec::new();
+        for (row_index, bytes) in spans {
+            let take = remaining.min(bytes.len());
+            remaining -= take;
+            cells.extend(rows[row_index].cells_in(bytes.start..bytes.start + take));
+        }
+        url.truncate(trimmed);
+        if url.len() > "https://".len() {
+            out.push(LinkRegion {
+                url,
+                cells: cells.into_iter().map(|(x, y)| (x, y, Cell::EMPTY)).collect(),
+            });
+        }
+        search_from = run_end;
+    }
+}
+
+fn find_targets(row: &RowText, targets: &[LinkTarget], out: &mut Vec<LinkRegion>) {
+    let mut taken: Vec<Range<usize>> = Vec::new();
+    for target in targets {
+        for (start, _) in row.text.match_indices(target.text.as_str()) {
+            let end = start + target.text.len();
+            let before = row.text[..start].chars().next_back();
+            let after = row.text[end..].chars().next();
+            if before.is_some_and(is_path_char)
+                || after.is_some_and(|c| is_path_char(c) && c != '.')
+                || taken.iter().any(|r| r.start < end && start < r.end)
+            {
+                continue;
+            }
+            taken.push(start..end);
+            let cells = row.cells_in(start..end).map(|(x, y)| (x, y, Cell::EMPTY)).collect();
+            out.push(LinkRegion { url: target.url.clone(), cells });
+        }
+    }
+}
+
+/// Link regions in `area` of the drawn `buffer`.
+fn locate(buffer: &Buffer, area: Rect, targets: &[LinkTarget]) -> Vec<LinkRegion> {
+    let area = area.intersection(buffer.area);
+    if area.is_empty() {
+        return Vec::new();
+    }
+    let rows: Vec<RowText> =
+        (area.top()..area.bottom()).map(|y| RowText::read(buffer, area, y)).collect();
+    let mut regions = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        if row.text.contains("http") {
+            find_urls(&rows, index, &mut regions);
+        }
+        if !targets.is_empty() {
+            find_targets(row, targets, &mut regions);
+        }
+    }
+    for region in &mut regions {
+        for (x, y, cell) in &mut region.cells {
+            if let Some(drawn) = buffer.cell((*x, *y)) {
+                cell.clone_from(drawn);
+            }
+        }
+    }
+    regions.retain(|region| !region.cells.is_empty());
+    regions
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// Link the URLs and file paths of the chat area in the frame just drawn.
+/// Only regions whose cells changed since they were last linked are printed.
+pub fn present(app: &mut App, buffer: &Buffer, out: &mut impl Write) -> io::Result<()> {
+    if !app.hyperlinks.enabled {
+        return Ok(());
+    }
+    let targets = std::mem::take(&mut app.hyperlinks.targets);
+    let regions = locate(buffer, app.rendered_chat_area, &targets);
+    let state = &mut app.hyperlinks;
+    let fresh: Vec<&LinkRegion> =
+        regions.iter().filter(|region| !state.presented.contains(region)).collect();
+    if !fresh.is_empty() {
+        crossterm::queue!(out, SavePosition)?;
+        for region in fresh {
+            write!(out, "\x1b]8;;{}\x1b\\", region.url)?;
+            CrosstermBackend::new(&mut *out)
+                .draw(region.cells.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+            write!(out, "\x1b]8;;\x1b\\")?;
+        }
+        crossterm::queue!(out, RestorePosition)?;
+        out.flush()?;
+    }
+    state.presented = regions;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+
+    fn buffer(rows: &[&str]) -> Buffer {
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, u16::try_from(width).unwrap(), 3));
+        for (y, row) in rows.iter().enumerate() {
+            buf.set_string(0, u16::try_from(y).unwrap(), row, Style::default());
+        }
+        buf
+    }
+
+    #[test]
+    fn file_url_fills_template_and_encodes_path() {
+        let path = Path::new("/home/dev/my project/src/lib.rs");
+        assert_eq!(
+            file_url(DEFAULT_FILE_LINK, path, Some(12)),
+            "file:///home/dev/my%20project/src/lib.rs"
+        );
+        assert_eq!(
+            file_url("vscode://file{path}:{line}", path, Some(12)),
+            "vscode://file/home/dev/my%20project/src/lib.rs:12"
+        );
+        assert_eq!(
+            file_url("vscode://file{path}:{line}", Path::new("C:\\src\\a.rs"), None),
+            "vscode://file/C:/src/a.rs"
+        );
+    }
+
+    #[test]
+    fn locate_follows_wrapped_urls_and_trims_punctuation() {
+        let buf = buffer(&["see https://exam", "ple.com/a_(b)).", "x src/lib.rs:3 y"]);
+        let targets =
+            vec![LinkTarget { text: "src/lib.rs".into(), url: "file:///p/src/lib.rs".into() }];
+        let regions = locate(&buf, buf.area, &targets);
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].url, "https://example.com/a_(b)");
+        let cells: Vec<(u16, u16)> = regions[0].cells.iter().map(|(x, y, _)| (*x, *y)).collect();
+        assert_eq!(cells.first(), Some(&(4, 0)));
+        assert_eq!(cells.last(), Some(&(12, 1)));
+        assert_eq!(cells.len(), 12 + 13);
+
+        assert_eq!(regions[1].url, "file:///p/src/lib.rs");
+        assert_eq!(regions[1].cells.len(), "src/lib.rs".len());
+        assert_eq!(regions[1].cells[0].2.symbol(), "s");
+    }
+
+    #[test]
+    fn present_links_changed_regions_once() {
+        let mut app = App::test_default();
+        app.hyperlinks.enabled = true;
+        let mut buf = buffer(&["go to https://example.com now"]);
+        buf.set_style(Rect::new(6, 0, 19, 1), Style::default().fg(Color::Blue));
+        app.rendered_chat_area = buf.area;
+
+        let mut out = Vec::new();
+        present(&mut app, &buf, &mut out).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.contains("\x1b]8;;https://example.com\x1b\\"), "{written:?}");
+        assert!(written.contains("https://example.com\x1b"), "{written:?}");
+        assert!(written.ends_with("\x1b8"), "cursor restored: {written:?}");
+
+        let mut out = Vec::new();
+        present(&mut app, &buf, &mut out).unwrap();
+        assert!(out.is_empty(), "unchanged regions are not printed again");
+
+        app.hyperlinks.invalidate();
+        let mut out = Vec::new();
+        present(&mut app, &buf, &mut out).unwrap();
+        assert!(!out.is_empty());
+    }
+}
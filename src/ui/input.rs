@@ -38,6 +38,7 @@ const MAX_INPUT_HEIGHT: u16 = 12;
 const HIGHLIGHT_SLASH_PRIORITY: u8 = 6;
 const HIGHLIGHT_MENTION_PRIORITY: u8 = 7;
 const HIGHLIGHT_PASTE_PRIORITY: u8 = 8;
+const HIGHLIGHT_VISUAL_PRIORITY: u8 = 9;
 
 /// Braille spinner frames (same as message.rs) for the connecting animation.
 const SPINNER_FRAMES: &[char] = &[
@@ -187,9 +188,30 @@ fn build_input_textarea(app: &App) -> TextArea<'static> {
     ));
 
     apply_textarea_highlights(&mut textarea, &app.input.lines);
+    if let Some((start, end)) = app.vim.as_ref().and_then(|vim| vim.visual_range(&app.input)) {
+        apply_visual_highlight(&mut textarea, &app.input.lines, start, end);
+    }
     textarea
 }
 
+/// Highlight the vi visual selection, given as flat char offsets where each
+/// line break counts as one char.
+fn apply_visual_highlight(textarea: &mut TextArea<'_>, lines: &[String], start: usize, end: usize) {
+    let style = Style::default().bg(theme::VIM_SELECTION_BG);
+    let mut line_start = 0;
+    for (row, line) in lines.iter().enumerate() {
+        let len = line.chars().count();
+        // Include the line break so selected empty lines stay visible.
+        let line_end = line_start + len + 1;
+        if start < line_end && end > line_start {
+            let from = start.saturating_sub(line_start);
+            let to = (end - line_start).min(len + 1);
+            textarea.custom_highlight(((row, from), (row, to)), style, HIGHLIGHT_VISUAL_PRIORITY);
+        }
+        line_start = line_end;
+    }
+}
+
 fn apply_textarea_highlights(textarea: &mut TextArea<'_>, lines: &[String]) {
     let slash_style = Style::default().fg(theme::SLASH_COMMAND);
     let mention_style = Style::default().fg(Color::Cyan);
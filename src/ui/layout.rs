@@ -25,6 +25,10 @@ pub struct AppLayout {
     /// Area for the todo panel (zero-height when hidden or no todos).
     /// Positioned below the input top separator and above the input field.
     pub todo: Rect,
+    /// Area for the submission queue panel, directly above the input.
+    pub queue: Rect,
+    /// Area for the attachments tray, between the queue and the input.
+    pub attachments: Rect,
     pub input: Rect,
     pub input_bottom_sep: Rect,
     pub help: Rect,
@@ -36,6 +40,8 @@ pub fn compute(
     input_lines: u16,
     show_header: bool,
     todo_height: u16,
+    queue_height: u16,
+    attachments_height: u16,
     help_height: u16,
 ) -> AppLayout {
     let input_height = input_lines.max(1);
@@ -44,7 +50,7 @@ pub fn compute(
     let zero = Rect::new(area.x, area.y, area.width, 0);
 
     if area.height < 8 {
-        // Ultra-compact: no header, no separator, no footer, no todo
+        // Ultra-compact: no header, no separator, no footer, no todo, no queue, no tray
         let [body, input, input_bottom_sep, help] = Layout::vertical([
             Constraint::Min(1),
             Constraint::Length(input_height),
@@ -58,6 +64,8 @@ pub fn compute(
             header_bot_sep: zero,
             body,
             todo: zero,
+            queue: zero,
+            attachments: zero,
             input_sep: Rect::new(area.x, input.y, area.width, 0),
             input,
             input_bottom_sep,
@@ -72,6 +80,8 @@ pub fn compute(
             body,
             input_sep,
             todo,
+            queue,
+            attachments,
             input,
             input_bottom_sep,
             help,
@@ -83,6 +93,8 @@ pub fn compute(
             Constraint::Min(3),
             Constraint::Length(1),
             Constraint::Length(todo_height),
+            Constraint::Length(queue_height),
+            Constraint::Length(attachments_height),
             Constraint::Length(input_height),
             Constraint::Length(1),
             Constraint::Length(help_height),
@@ -96,6 +108,8 @@ pub fn compute(
             body,
             input_sep,
             todo,
+            queue,
+            attachments,
             input,
             input_bottom_sep,
             help,
@@ -107,7 +121,7 @@ pub fn compute(
 #[cfg(test)]
 mod tests {
     // =====
-    // TESTS: 33
+    // TESTS: 35
     // =====
 
     use super::*;
@@ -124,6 +138,8 @@ mod tests {
             + layout.header_bot_sep.height
             + layout.body.height
             + layout.todo.height
+            + layout.queue.height
+            + layout.attachments.height
             + layout.input_sep.height
             + layout.input.height
             + layout.input_bottom_sep.height
@@ -140,6 +156,8 @@ mod tests {
             layout.body,
             layout.input_sep,
             layout.todo,
+            layout.queue,
+            layout.attachments,
             layout.input,
             layout.input_bottom_sep,
             layout.help,
@@ -167,7 +185,7 @@ mod tests {
 
     #[test]
     fn normal_terminal_with_header() {
-        let layout = compute(area(80, 24), 1, true, 0, 0);
+        let layout = compute(area(80, 24), 1, true, 0, 0, 0, 0);
         assert!(layout.footer.is_some());
         assert_eq!(layout.header.height, 1);
         assert_eq!(layout.header_bot_sep.height, 1);
@@ -180,13 +198,13 @@ mod tests {
 
     #[test]
     fn normal_all_areas_sum_to_total() {
-        let layout = compute(area(80, 24), 1, true, 3, 2);
+        let layout = compute(area(80, 24), 1, true, 3, 0, 0, 2);
         assert_eq!(total_height(&layout), 24);
     }
 
     #[test]
     fn normal_no_header() {
-        let layout = compute(area(80, 24), 1, false, 0, 0);
+        let layout = compute(area(80, 24), 1, false, 0, 0, 0, 0);
         assert_eq!(layout.header.height, 0);
         assert_eq!(layout.header_bot_sep.height, 0);
         assert!(layout.footer.is_some());
@@ -196,7 +214,7 @@ mod tests {
 
     #[test]
     fn ultra_compact_no_header_no_footer() {
-        let layout = compute(area(80, 6), 1, true, 0, 0);
+        let layout = compute(area(80, 6), 1, true, 0, 0, 0, 0);
         assert_eq!(layout.header.height, 0);
         assert!(layout.footer.is_none());
         assert_eq!(layout.todo.height, 0);
@@ -204,37 +222,55 @@ mod tests {
 
     #[test]
     fn ultra_compact_areas_sum_to_total() {
-        let layout = compute(area(80, 6), 1, true, 0, 0);
+        let layout = compute(area(80, 6), 1, true, 0, 0, 0, 0);
         assert_eq!(total_height(&layout), 6);
     }
 
     #[test]
     fn todo_panel_gets_requested_height() {
-        let layout = compute(area(80, 24), 1, true, 5, 0);
+        let layout = compute(area(80, 24), 1, true, 5, 0, 0, 0);
         assert_eq!(layout.todo.height, 5);
     }
 
     #[test]
     fn zero_todo_height_produces_zero_area() {
-        let layout = compute(area(80, 24), 1, true, 0, 0);
+        let layout = compute(area(80, 24), 1, true, 0, 0, 0, 0);
         assert_eq!(layout.todo.height, 0);
     }
 
+    #[test]
+    fn queue_panel_sits_between_todo_and_input() {
+        let layout = compute(area(80, 24), 1, true, 2, 3, 0, 0);
+        assert_eq!(layout.queue.height, 3);
+        assert_eq!(layout.queue.y, layout.todo.y + layout.todo.height);
+        assert_eq!(layout.input.y, layout.queue.y + layout.queue.height);
+        assert_no_overlap_and_ordered(&layout);
+    }
+
+    #[test]
+    fn attachments_tray_sits_between_queue_and_input() {
+        let layout = compute(area(80, 24), 1, true, 0, 2, 1, 0);
+        assert_eq!(layout.attachments.height, 1);
+        assert_eq!(layout.attachments.y, layout.queue.y + layout.queue.height);
+        assert_eq!(layout.input.y, layout.attachments.y + layout.attachments.height);
+        assert_no_overlap_and_ordered(&layout);
+    }
+
     #[test]
     fn help_gets_requested_height() {
-        let layout = compute(area(80, 24), 1, true, 0, 4);
+        let layout = compute(area(80, 24), 1, true, 0, 0, 0, 4);
         assert_eq!(layout.help.height, 4);
     }
 
     #[test]
     fn multi_line_input() {
-        let layout = compute(area(80, 24), 5, true, 0, 0);
+        let layout = compute(area(80, 24), 5, true, 0, 0, 0, 0);
         assert_eq!(layout.input.height, 5);
     }
 
     #[test]
     fn input_lines_zero_clamped_to_one() {
-        let layout = compute(area(80, 24), 0, true, 0, 0);
+        let layout = compute(area(80, 24), 0, true, 0, 0, 0, 0);
         assert_eq!(layout.input.height, 1);
     }
 
@@ -242,26 +278,26 @@ mod tests {
 
     #[test]
     fn ultra_compact_threshold_exactly_8() {
-        let layout = compute(area(80, 8), 1, true, 0, 0);
+        let layout = compute(area(80, 8), 1, true, 0, 0, 0, 0);
         assert!(layout.footer.is_some());
     }
 
     #[test]
     fn ultra_compact_threshold_7() {
-        let layout = compute(area(80, 7), 1, true, 0, 0);
+        let layout = compute(area(80, 7), 1, true, 0, 0, 0, 0);
         assert!(layout.footer.is_none());
     }
 
     #[test]
     fn large_terminal() {
-        let layout = compute(area(200, 100), 3, true, 5, 2);
+        let layout = compute(area(200, 100), 3, true, 5, 0, 0, 2);
         assert_eq!(total_height(&layout), 100);
         assert!(layout.body.height >= 3);
     }
 
     #[test]
     fn width_carries_through() {
-        let layout = compute(area(120, 24), 1, true, 0, 0);
+        let layout = compute(area(120, 24), 1, true, 0, 0, 0, 0);
         assert_eq!(layout.header.width, 120);
         assert_eq!(layout.body.width, 120);
         assert_eq!(layout.input.width, 120);
@@ -269,13 +305,13 @@ mod tests {
 
     #[test]
     fn no_overlap_between_areas() {
-        let layout = compute(area(80, 24), 2, true, 3, 1);
+        let layout = compute(area(80, 24), 2, true, 3, 0, 0, 1);
         assert_no_overlap_and_ordered(&layout);
     }
 
     #[test]
     fn everything_maxed_out() {
-        let layout = compute(area(80, 24), 3, true, 5, 3);
+        let layout = compute(area(80, 24), 3, true, 5, 0, 0, 3);
         assert!(layout.body.height >= 3);
         assert_eq!(total_height(&layout), 24);
     }
@@ -286,7 +322,7 @@ mod tests {
     #[test]
     fn offset_area_respects_origin() {
         let r = Rect::new(10, 5, 80, 24);
-        let layout = compute(r, 1, true, 0, 0);
+        let layout = compute(r, 1, true, 0, 0, 0, 0);
         // All areas should have x=10 and width=80
         assert_eq!(layout.header.x, 10);
         assert_eq!(layout.body.x, 10);
@@ -302,7 +338,7 @@ mod tests {
     #[test]
     fn offset_area_compact() {
         let r = Rect::new(5, 10, 60, 6);
-        let layout = compute(r, 1, true, 0, 0);
+        let layout = compute(r, 1, true, 0, 0, 0, 0);
         assert!(layout.footer.is_none());
         assert_eq!(layout.body.x, 5);
         assert_eq!(total_height(&layout), 6);
@@ -313,7 +349,7 @@ mod tests {
     /// Zero-height area - everything gets zero or minimal height.
     #[test]
     fn zero_height_area() {
-        let layout = compute(area(80, 0), 1, true, 0, 0);
+        let layout = compute(area(80, 0), 1, true, 0, 0, 0, 0);
         // Ultra-compact path (0 < 8), areas should still be valid (not panicking)
         assert!(layout.footer.is_none());
     }
@@ -321,7 +357,7 @@ mod tests {
     /// Height = 1 - absolute minimum.
     #[test]
     fn height_one() {
-        let layout = compute(area(80, 1), 1, true, 0, 0);
+        let layout = compute(area(80, 1), 1, true, 0, 0, 0, 0);
         assert!(layout.footer.is_none());
         assert_eq!(total_height(&layout), 1);
     }
@@ -329,14 +365,14 @@ mod tests {
     /// Height = 2.
     #[test]
     fn height_two() {
-        let layout = compute(area(80, 2), 1, true, 0, 0);
+        let layout = compute(area(80, 2), 1, true, 0, 0, 0, 0);
         assert_eq!(total_height(&layout), 2);
     }
 
     /// Width = 1 - very narrow terminal.
     #[test]
     fn width_one() {
-        let layout = compute(Rect::new(0, 0, 1, 24), 1, true, 0, 0);
+        let layout = compute(Rect::new(0, 0, 1, 24), 1, true, 0, 0, 0, 0);
         assert_eq!(layout.body.width, 1);
         assert_eq!(layout.input.width, 1);
         assert_eq!(total_height(&layout), 24);
@@ -345,7 +381,7 @@ mod tests {
     /// Width = 0.
     #[test]
     fn width_zero() {
-        let layout = compute(area(0, 24), 1, true, 0, 0);
+        let layout = compute(area(0, 24), 1, true, 0, 0, 0, 0);
         assert_eq!(layout.body.width, 0);
         assert_eq!(total_height(&layout), 24);
     }
@@ -355,7 +391,7 @@ mod tests {
     /// Input requests more lines than the terminal has rows.
     #[test]
     fn input_larger_than_terminal() {
-        let layout = compute(area(80, 10), 50, true, 0, 0);
+        let layout = compute(area(80, 10), 50, true, 0, 0, 0, 0);
         // Layout should still produce valid areas that sum to total
         assert_eq!(total_height(&layout), 10);
         // Input gets what it asks, but body gets squeezed
@@ -365,7 +401,7 @@ mod tests {
     /// Todo + help + input together exceed available space.
     #[test]
     fn competing_constraints_squeeze_body() {
-        let layout = compute(area(80, 12), 3, true, 4, 3);
+        let layout = compute(area(80, 12), 3, true, 4, 0, 0, 3);
         // header(1) + sep(1) + body(min 3) + todo(4) + sep(1) + input(3) + sep(1) + help(3) + footer(1) = 18
         // Only 12 available - body gets squeezed below min, or ratatui adjusts
         assert_eq!(total_height(&layout), 12);
@@ -376,7 +412,7 @@ mod tests {
     /// Ultra-compact with `help_height` > 0.
     #[test]
     fn compact_with_help() {
-        let layout = compute(area(80, 6), 1, true, 0, 2);
+        let layout = compute(area(80, 6), 1, true, 0, 0, 0, 2);
         assert!(layout.footer.is_none());
         assert_eq!(layout.help.height, 2);
         assert_eq!(total_height(&layout), 6);
@@ -385,7 +421,7 @@ mod tests {
     /// Ultra-compact with multi-line input.
     #[test]
     fn compact_with_multiline_input() {
-        let layout = compute(area(80, 7), 3, true, 0, 0);
+        let layout = compute(area(80, 7), 3, true, 0, 0, 0, 0);
         assert!(layout.footer.is_none());
         assert_eq!(layout.input.height, 3);
         assert_eq!(total_height(&layout), 7);
@@ -396,21 +432,21 @@ mod tests {
     /// In normal mode, areas must be in strict top-to-bottom order.
     #[test]
     fn normal_mode_y_ordering() {
-        let layout = compute(area(80, 30), 2, true, 3, 1);
+        let layout = compute(area(80, 30), 2, true, 3, 0, 0, 1);
         assert_no_overlap_and_ordered(&layout);
     }
 
     /// In compact mode, areas must be in strict top-to-bottom order.
     #[test]
     fn compact_mode_y_ordering() {
-        let layout = compute(area(80, 6), 1, true, 0, 1);
+        let layout = compute(area(80, 6), 1, true, 0, 0, 0, 1);
         assert_no_overlap_and_ordered(&layout);
     }
 
     /// Footer (when present) must be at the very bottom.
     #[test]
     fn footer_at_bottom() {
-        let layout = compute(area(80, 24), 1, true, 0, 0);
+        let layout = compute(area(80, 24), 1, true, 0, 0, 0, 0);
         let footer = layout.footer.unwrap();
         assert_eq!(footer.y + footer.height, 24);
     }
@@ -418,7 +454,7 @@ mod tests {
     /// Body starts immediately after header separator.
     #[test]
     fn body_follows_header_bot_sep() {
-        let layout = compute(area(80, 24), 1, true, 0, 0);
+        let layout = compute(area(80, 24), 1, true, 0, 0, 0, 0);
         assert_eq!(
             layout.body.y,
             layout.header.y + layout.header.height + layout.header_bot_sep.height
@@ -432,7 +468,7 @@ mod tests {
     fn parametric_sizes_invariants() {
         for h in [1, 2, 3, 5, 7, 8, 10, 15, 24, 50, 100] {
             for w in [1, 10, 80, 200] {
-                let layout = compute(Rect::new(0, 0, w, h), 1, true, 0, 0);
+                let layout = compute(Rect::new(0, 0, w, h), 1, true, 0, 0, 0, 0);
                 assert_eq!(total_height(&layout), h, "Height mismatch for {w}x{h}");
                 for a in visible_areas(&layout) {
                     assert_eq!(a.width, w, "Width mismatch in area {a:?} for {w}x{h}");
@@ -447,7 +483,7 @@ mod tests {
         for input in [0, 1, 3, 10] {
             for todo in [0, 2, 5] {
                 for help in [0, 1, 3] {
-                    let layout = compute(area(80, 30), input, true, todo, help);
+                    let layout = compute(area(80, 30), input, true, todo, 0, 0, help);
                     assert_eq!(
                         total_height(&layout),
                         30,
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::agent::model;
 use crate::app::{
-    BlockCache, ChatMessage, IncrementalMarkdown, MessageBlock, MessageRole, WelcomeBlock,
+    BlockCache, ChatMessage, CompactionBlock, IncrementalMarkdown, MessageBlock, MessageRole,
+    WelcomeBlock,
 };
 use crate::ui::tables;
 use crate::ui::theme;
@@ -24,6 +26,9 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Paragraph, Wrap};
 
+/// Characters of the first summary line shown while a compaction block is collapsed.
+const COMPACTION_PREVIEW_CHARS: usize = 96;
+
 const SPINNER_FRAMES: &[char] = &[
     '\u{280B}', '\u{2819}', '\u{2839}', '\u{2838}', '\u{283C}', '\u{2834}', '\u{2826}', '\u{2827}',
     '\u{2807}', '\u{280F}',
@@ -69,7 +74,7 @@ fn format_scaled_count(value: u64, divisor: u64, suffix: char) -> String {
     if frac == 0 { format!("{whole}{suffix}") } else { format!("{whole}.{frac}{suffix}") }
 }
 
-fn format_token_count(value: u64) -> String {
+pub(super) fn format_token_count(value: u64) -> String {
     if value >= 1_000_000 {
         format_scaled_count(value, 1_000_000, 'M')
     } else if value >= 1_000 {
@@ -124,6 +129,35 @@ pub fn render_message(
     width: u16,
     out: &mut Vec<Line<'static>>,
 ) {
+    render_message_tracked(msg, spinner, width, out, None);
+}
+
+/// Like [`render_message`], also recording `(block_index, first_line)` for
+/// every rendered block, with line offsets relative to the message start.
+pub fn render_message_with_block_starts(
+    msg: &mut ChatMessage,
+    spinner: &SpinnerState,
+    width: u16,
+    out: &mut Vec<Line<'static>>,
+    block_starts: &mut Vec<(usize, usize)>,
+) {
+    render_message_tracked(msg, spinner, width, out, Some(block_starts));
+}
+
+#[allow(clippy::too_many_lines)]
+fn render_message_tracked(
+    msg: &mut ChatMessage,
+    spinner: &SpinnerState,
+    width: u16,
+    out: &mut Vec<Line<'static>>,
+    mut block_starts: Option<&mut Vec<(usize, usize)>>,
+) {
+    let base = out.len();
+    let mut mark = |block: usize, line: usize| {
+        if let Some(starts) = block_starts.as_deref_mut() {
+            starts.push((block, line - base));
+        }
+    };
     match msg.role {
         MessageRole::Welcome => {
             out.push(role_label_line(&msg.role));
@@ -141,8 +175,9 @@ pub fn render_message(
             )));
 
             // User message: markdown-rendered with background overlay
-            for block in &mut msg.blocks {
+            for (bi, block) in msg.blocks.iter_mut().enumerate() {
                 if let MessageBlock::Text(text, cache, incr) = block {
+                    mark(bi, out.len());
                     render_text_cached(
                         text,
                         cache,
@@ -172,30 +207,32 @@ pub fn render_message(
             // Render blocks in order with spacing at text<->tool transitions
             let show_subagent_thinking = spinner.is_subagent_thinking;
             let mut prev_was_tool = false;
-            for block in &mut msg.blocks {
+            for (bi, block) in msg.blocks.iter_mut().enumerate() {
                 match block {
                     MessageBlock::Text(text, cache, incr) => {
                         // Add half-spacing when transitioning from tools back to text
                         if prev_was_tool {
                             out.push(Line::default());
                         }
+                        mark(bi, out.len());
                         render_text_cached(text, cache, incr, width, None, false, out);
                         prev_was_tool = false;
                     }
                     MessageBlock::ToolCall(tc) => {
                         let tc = tc.as_mut();
                         // Skip hidden tool calls (subagent children)
-                        if tc.hidden {
+                        if !tc.is_rendered_inline() {
                             continue;
                         }
                         // Add half-spacing when transitioning from text to tools
                         if !prev_was_tool && out.len() > 1 {
                             out.push(Line::default());
                         }
+                        mark(bi, out.len());
                         tool_call::render_tool_call_cached(tc, width, spinner.frame, out);
                         prev_was_tool = true;
                     }
-                    MessageBlock::Welcome(_) => {}
+                    MessageBlock::Welcome(_) | MessageBlock::Compaction(_) => {}
                 }
             }
 
@@ -215,17 +252,24 @@ pub fn render_message(
             }
         }
         MessageRole::System => {
-            out.push(Line::from(Span::styled(
-                "System",
-                Style::default().fg(theme::STATUS_ERROR).add_modifier(Modifier::BOLD),
-            )));
+            out.push(message_label_line(msg));
 
-            for block in &mut msg.blocks {
-                if let MessageBlock::Text(text, cache, incr) = block {
-                    let mut lines = Vec::new();
-                    render_text_cached(text, cache, incr, width, None, false, &mut lines);
-                    tint_lines(&mut lines, theme::STATUS_ERROR);
-                    out.extend(lines);
+            for (bi, block) in msg.blocks.iter_mut().enumerate() {
+                mark(bi, out.len());
+                match block {
+                    MessageBlock::Text(text, cache, incr) => {
+                        let mut lines = Vec::new();
+                        render_text_cached(text, cache, incr, width, None, false, &mut lines);
+                        tint_lines(&mut lines, theme::STATUS_ERROR);
+                        out.extend(lines);
+                    }
+                    MessageBlock::Compaction(compaction) => {
+                        render_compaction_cached(compaction, width, out);
+                    }
+                    MessageBlock::ToolCall(tc) => {
+                        tool_call::render_tool_call_cached(tc.as_mut(), width, spinner.frame, out);
+                    }
+                    MessageBlock::Welcome(_) => {}
                 }
             }
         }
@@ -240,6 +284,7 @@ pub fn render_message(
 ///
 /// Accuracy is preserved because each block height is computed with
 /// `Paragraph::line_count(width)` on the exact rendered `Vec<Line>`.
+#[allow(clippy::too_many_lines)]
 pub fn measure_message_height_cached(
     msg: &mut ChatMessage,
     spinner: &SpinnerState,
@@ -300,7 +345,7 @@ pub fn measure_message_height_cached(
                     }
                     MessageBlock::ToolCall(tc) => {
                         let tc = tc.as_mut();
-                        if tc.hidden {
+                        if !tc.is_rendered_inline() {
                             continue;
                         }
                         if !prev_was_tool && lines_after_label > 0 {
@@ -318,7 +363,7 @@ pub fn measure_message_height_cached(
                         wrapped_lines += lines;
                         prev_was_tool = true;
                     }
-                    MessageBlock::Welcome(_) => {}
+                    MessageBlock::Welcome(_) | MessageBlock::Compaction(_) => {}
                 }
             }
 
@@ -333,12 +378,23 @@ pub fn measure_message_height_cached(
         }
         MessageRole::System => {
             for block in &mut msg.blocks {
-                if let MessageBlock::Text(text, cache, incr) = block {
-                    let (h, lines) =
-                        text_block_height_cached(text, cache, incr, width, None, false);
-                    height += h;
-                    wrapped_lines += lines;
-                }
+                let (h, lines) = match block {
+                    MessageBlock::Text(text, cache, incr) => {
+                        text_block_height_cached(text, cache, incr, width, None, false)
+                    }
+                    MessageBlock::Compaction(compaction) => {
+                        compaction_block_height_cached(compaction, width)
+                    }
+                    MessageBlock::ToolCall(tc) => tool_call::measure_tool_call_height_cached(
+                        tc.as_mut(),
+                        width,
+                        spinner.frame,
+                        layout_generation,
+                    ),
+                    MessageBlock::Welcome(_) => continue,
+                };
+                height += h;
+                wrapped_lines += lines;
             }
         }
     }
@@ -368,7 +424,7 @@ pub fn render_message_from_offset(
     let role_line = if matches!(msg.role, MessageRole::Assistant) {
         assistant_role_label_line(msg, spinner)
     } else {
-        role_label_line(&msg.role)
+        message_label_line(msg)
     };
     emit_line_with_skip(role_line, out, &mut remaining_skip, can_consume_skip);
 
@@ -456,7 +512,7 @@ pub fn render_message_from_offset(
                     }
                     MessageBlock::ToolCall(tc) => {
                         let tc = tc.as_mut();
-                        if tc.hidden {
+                        if !tc.is_rendered_inline() {
                             continue;
                         }
                         if !prev_was_tool && lines_after_label > 0 {
@@ -483,7 +539,7 @@ pub fn render_message_from_offset(
                         lines_after_label += h;
                         prev_was_tool = true;
                     }
-                    MessageBlock::Welcome(_) => {}
+                    MessageBlock::Welcome(_) | MessageBlock::Compaction(_) => {}
                 }
             }
 
@@ -509,17 +565,39 @@ pub fn render_message_from_offset(
         }
         MessageRole::System => {
             for block in &mut msg.blocks {
-                if let MessageBlock::Text(text, cache, incr) = block {
-                    let (h, _) = text_block_height_cached(text, cache, incr, width, None, false);
-                    let mut render = |dst: &mut Vec<Line<'static>>| {
-                        let mut lines = Vec::new();
-                        render_text_cached(text, cache, incr, width, None, false, &mut lines);
-                        tint_lines(&mut lines, theme::STATUS_ERROR);
-                        dst.extend(lines);
-                    };
-                    if !should_skip_whole_block(h, &mut remaining_skip, &mut can_consume_skip) {
-                        render(out);
+                match block {
+                    MessageBlock::Text(text, cache, incr) => {
+                        let (h, _) =
+                            text_block_height_cached(text, cache, incr, width, None, false);
+                        let mut render = |dst: &mut Vec<Line<'static>>| {
+                            let mut lines = Vec::new();
+                            render_text_cached(text, cache, incr, width, None, false, &mut lines);
+                            tint_lines(&mut lines, theme::STATUS_ERROR);
+                            dst.extend(lines);
+                        };
+                        if !should_skip_whole_block(h, &mut remaining_skip, &mut can_consume_skip) {
+                            render(out);
+                        }
+                    }
+                    MessageBlock::Compaction(compaction) => {
+                        let (h, _) = compaction_block_height_cached(compaction, width);
+                        if !should_skip_whole_block(h, &mut remaining_skip, &mut can_consume_skip) {
+                            render_compaction_cached(compaction, width, out);
+                        }
                     }
+                    MessageBlock::ToolCall(tc) => {
+                        let tc = tc.as_mut();
+                        let (h, _) = tool_call::measure_tool_call_height_cached(
+                            tc,
+                            width,
+                            spinner.frame,
+                            layout_generation,
+                        );
+                        if !should_skip_whole_block(h, &mut remaining_skip, &mut can_consume_skip) {
+                            tool_call::render_tool_call_cached(tc, width, spinner.frame, out);
+                        }
+                    }
+                    MessageBlock::Welcome(_) => {}
                 }
             }
         }
@@ -562,6 +640,18 @@ fn should_skip_whole_block(
     false
 }
 
+/// Role label for `msg`; `!command` output reads "Shell" rather than the red
+/// "System" used for notices.
+fn message_label_line(msg: &ChatMessage) -> Line<'static> {
+    if crate::app::shell::is_shell_message(msg) {
+        return Line::from(Span::styled(
+            "Shell",
+            Style::default().fg(theme::DIM).add_modifier(Modifier::BOLD),
+        ));
+    }
+    role_label_line(&msg.role)
+}
+
 fn role_label_line(role: &MessageRole) -> Line<'static> {
     match role {
         MessageRole::Welcome => Line::from(Span::styled(
@@ -583,6 +673,17 @@ fn role_label_line(role: &MessageRole) -> Line<'static> {
     }
 }
 
+/// Role label for the user message currently loaded into the input for editing.
+pub fn editing_role_label_line() -> Line<'static> {
+    Line::from(vec![
+        Span::styled("User", Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD)),
+        Span::styled(
+            "  \u{270E} editing \u{00B7} Enter forks a new branch \u{00B7} Alt+E older \u{00B7} Esc cancel",
+            Style::default().fg(theme::DIM).add_modifier(Modifier::ITALIC),
+        ),
+    ])
+}
+
 fn thinking_line(frame: usize) -> Line<'static> {
     let ch = SPINNER_FRAMES[frame % SPINNER_FRAMES.len()];
     Line::from(Span::styled(format!("{ch} Thinking..."), Style::default().fg(theme::DIM)))
@@ -668,6 +769,96 @@ fn welcome_block_height_cached(block: &mut WelcomeBlock, width: u16) -> (usize,
     (h, lines)
 }
 
+/// Header plus the summary preview (collapsed) or the full summary (expanded).
+fn compaction_lines(block: &CompactionBlock) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(theme::DIM);
+    let marker = if block.collapsed { "\u{25b8}" } else { "\u{25be}" };
+    let trigger = match block.trigger {
+        model::CompactionTrigger::Manual => "manual",
+        model::CompactionTrigger::Auto => "auto",
+    };
+    let detail = if block.pre_tokens > 0 {
+        format!(
+            " \u{00B7} {trigger} \u{00B7} {} tokens before",
+            format_token_count(block.pre_tokens)
+        )
+    } else {
+        format!(" \u{00B7} {trigger}")
+    };
+    let mut header = vec![
+        Span::styled(format!("{marker} "), Style::default().fg(theme::RUST_ORANGE)),
+        Span::styled(
+            "Context compacted",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(detail, dim),
+    ];
+
+    let Some(summary) = block.summary.as_deref() else {
+        return vec![Line::from(header), Line::from(Span::styled("  Waiting for summary...", dim))];
+    };
+    let mut lines = Vec::new();
+    if block.collapsed {
+        header.push(Span::styled("  (Ctrl+O to expand)", dim));
+        lines.push(Line::from(header));
+        let mut rest = summary.lines().filter(|line| !line.trim().is_empty());
+        let first = rest.next().unwrap_or_default().trim();
+        let mut preview: String = first.chars().take(COMPACTION_PREVIEW_CHARS).collect();
+        if first.chars().count() > COMPACTION_PREVIEW_CHARS {
+            preview.push('\u{2026}');
+        }
+        let more = rest.count();
+        let mut spans =
+            vec![Span::styled(format!("  {preview}"), Style::default().fg(Color::Gray))];
+        if more > 0 {
+            spans.push(Span::styled(
+                format!("  (+{more} line{})", if more == 1 { "" } else { "s" }),
+                dim,
+            ));
+        }
+        lines.push(Line::from(spans));
+    } else {
+        header.push(Span::styled("  (Ctrl+O to collapse)", dim));
+        lines.push(Line::from(header));
+        lines.extend(summary.lines().map(|line| {
+            Line::from(Span::styled(format!("  {line}"), Style::default().fg(Color::Gray)))
+        }));
+    }
+    lines
+}
+
+fn render_compaction_cached(block: &mut CompactionBlock, width: u16, out: &mut Vec<Line<'static>>) {
+    if let Some(cached_lines) = block.cache.get() {
+        out.extend_from_slice(cached_lines);
+        return;
+    }
+
+    let fresh = compaction_lines(block);
+    let h = Paragraph::new(Text::from(fresh.clone())).wrap(Wrap { trim: false }).line_count(width);
+    block.cache.store(fresh);
+    block.cache.set_height(h, width);
+    if let Some(stored) = block.cache.get() {
+        out.extend_from_slice(stored);
+    }
+}
+
+fn compaction_block_height_cached(block: &mut CompactionBlock, width: u16) -> (usize, usize) {
+    if let Some(h) = block.cache.height_at(width) {
+        return (h, 0);
+    }
+
+    if let Some(h) = block.cache.measure_and_set_height(width) {
+        return (h, block.cache.get().map_or(0, Vec::len));
+    }
+
+    let fresh = compaction_lines(block);
+    let lines = fresh.len();
+    let h = Paragraph::new(Text::from(fresh.clone())).wrap(Wrap { trim: false }).line_count(width);
+    block.cache.store(fresh);
+    block.cache.set_height(h, width);
+    (h, lines)
+}
+
 fn text_block_height_cached(
     text: &str,
     cache: &mut BlockCache,
@@ -818,6 +1009,34 @@ mod tests {
     use pretty_assertions::assert_eq;
     use ratatui::widgets::{Paragraph, Wrap};
 
+    #[test]
+    fn compaction_block_collapses_summary_to_preview() {
+        let mut block = CompactionBlock::new(
+            model::CompactionBoundary {
+                trigger: model::CompactionTrigger::Auto,
+                pre_tokens: 150_000,
+            },
+            true,
+        );
+        let text = |lines: &[Line<'_>]| -> Vec<String> {
+            lines.iter().map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect()).collect()
+        };
+
+        let pending = text(&compaction_lines(&block));
+        assert!(pending[0].contains("Context compacted \u{00B7} auto \u{00B7} 150k tokens before"));
+        assert_eq!(pending[1], "  Waiting for summary...");
+
+        block.summary = Some("Goal: ship the parser\n\n- step one\n- step two".into());
+        let collapsed = text(&compaction_lines(&block));
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(collapsed[1], "  Goal: ship the parser  (+2 lines)");
+
+        block.collapsed = false;
+        let expanded = text(&compaction_lines(&block));
+        assert_eq!(expanded.len(), 5);
+        assert_eq!(expanded[4], "  - step two");
+    }
+
     // preprocess_markdown
 
     #[test]
@@ -1119,6 +1338,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         }
     }
 
@@ -1332,4 +1554,34 @@ mod tests {
 
         assert!(thinking_idx > bash_idx);
     }
+
+    #[test]
+    fn block_starts_point_at_each_rendered_block() {
+        let spinner = SpinnerState {
+            frame: 0,
+            is_active: false,
+            is_last_message: false,
+            is_thinking_mid_turn: false,
+            is_subagent_thinking: false,
+            is_compacting: false,
+        };
+        let mut msg = make_text_message(MessageRole::Assistant, "Checking the build.");
+        msg.blocks.push(MessageBlock::ToolCall(Box::new(make_tool_call_info(
+            "bash-1",
+            "Bash",
+            crate::agent::model::ToolCallStatus::Completed,
+            "",
+        ))));
+
+        let mut lines = vec![Line::from("previous message")];
+        let mut starts = Vec::new();
+        render_message_with_block_starts(&mut msg, &spinner, 120, &mut lines, &mut starts);
+        let rendered = render_lines_to_strings(&lines[1..]);
+
+        assert_eq!(starts.len(), 2);
+        assert_eq!(starts[0], (0, 1));
+        assert!(rendered[starts[0].1].contains("Checking the build."));
+        assert_eq!(starts[1].0, 1);
+        assert!(rendered[starts[1].1].contains("Bash"));
+    }
 }
@@ -14,24 +14,34 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod attachments;
 mod autocomplete;
-mod chat;
+pub mod chat;
+mod chat_nav;
 mod diff;
+pub mod graphics;
 mod header;
 mod help;
+pub mod hyperlink;
 mod input;
 mod layout;
 mod markdown;
 mod message;
+mod overlay;
+mod queue;
+mod search;
+mod subagent_view;
+mod tab_bar;
 mod tables;
 pub mod theme;
 mod todo;
 mod tool_call;
 
 use crate::app::App;
+use crate::app::vim::VimMode;
 use ratatui::Frame;
 use ratatui::layout::{Alignment, Rect};
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
@@ -47,6 +57,8 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         let _t = app.perf.as_ref().map(|p| p.start("ui::todo_height"));
         todo::compute_height(app)
     };
+    let queue_height = queue::compute_height(app);
+    let attachments_height = attachments::compute_height(app);
     let help_height = {
         let _t = app.perf.as_ref().map(|p| p.start("ui::help_height"));
         help::compute_height(app, frame_area.width)
@@ -57,7 +69,15 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     };
     let areas = {
         let _t = app.perf.as_ref().map(|p| p.start("ui::layout"));
-        layout::compute(frame_area, input_visual_lines, app.show_header, todo_height, help_height)
+        layout::compute(
+            frame_area,
+            input_visual_lines,
+            app.show_header,
+            todo_height,
+            queue_height,
+            attachments_height,
+            help_height,
+        )
     };
 
     // Header bar (toggleable via Ctrl+H)
@@ -68,14 +88,40 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_separator(frame, areas.header_bot_sep);
     }
 
-    // Body: chat (includes welcome text when no messages yet)
-    {
+    // Tab strip (only with more than one session open) takes the top body row.
+    let mut body = areas.body;
+    if tab_bar::is_visible(app) && body.height > 1 {
+        tab_bar::render(frame, Rect { height: 1, ..body }, app);
+        body.y += 1;
+        body.height -= 1;
+    }
+
+    // Body: chat (includes welcome text when no messages yet), or the focused
+    // subagent's transcript while that view is open. A view whose Task is gone
+    // closes itself and requests a redraw that shows the chat.
+    if app.subagent_view.is_some() {
+        let _t = app.perf.as_ref().map(|p| p.start("ui::subagent_view"));
+        subagent_view::render(frame, body, app);
+    } else {
         let _t = app.perf.as_ref().map(|p| p.start("ui::chat"));
-        chat::render(frame, areas.body, app);
+        chat::render(frame, body, app);
+        // Inline images are painted after the draw, so drop any that would
+        // land on a bar or popup drawn over the chat.
+        let covered =
+            [search::render_bar(frame, body, app), chat_nav::render_bar(frame, body, app)];
+        for area in covered.into_iter().flatten() {
+            app.inline_images.hide_under(area);
+        }
+    }
+    if app.overlay.is_some() {
+        let _t = app.perf.as_ref().map(|p| p.start("ui::overlay"));
+        if let Some(popup) = overlay::render(frame, body, app) {
+            app.inline_images.hide_under(popup);
+        }
     }
 
-    // Input separator (above)
-    render_separator(frame, areas.input_sep);
+    // Input separator (above), carrying the vi mode indicator
+    render_input_separator(frame, areas.input_sep, app);
 
     // Todo panel (below input top separator, above input)
     if areas.todo.height > 0 {
@@ -83,6 +129,18 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         todo::render(frame, areas.todo, app);
     }
 
+    // Submission queue (directly above input)
+    if areas.queue.height > 0 {
+        let _t = app.perf.as_ref().map(|p| p.start("ui::queue"));
+        queue::render(frame, areas.queue, app);
+    }
+
+    // Attachments tray (between the queue and the input)
+    if areas.attachments.height > 0 {
+        let _t = app.perf.as_ref().map(|p| p.start("ui::attachments"));
+        attachments::render(frame, areas.attachments, app);
+    }
+
     // Input
     {
         let _t = app.perf.as_ref().map(|p| p.start("ui::input"));
@@ -92,7 +150,9 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     // Autocomplete dropdown (floating overlay above input)
     if autocomplete::is_active(app) {
         let _t = app.perf.as_ref().map(|p| p.start("ui::autocomplete"));
-        autocomplete::render(frame, areas.input, app);
+        if let Some(dropdown) = autocomplete::render(frame, areas.input, app) {
+            app.inline_images.hide_under(dropdown);
+        }
     }
 
     // Input separator (below input)
@@ -226,13 +286,35 @@ fn footer_telemetry_text(app: &App) -> Option<String> {
     Some(text)
 }
 
-/// Returns `(telemetry, update_hint)` -- either or both may be `None`.
+/// Budget nearest its limit once it passes the warning threshold.
+fn footer_budget_warning(app: &App) -> FooterItem {
+    let reading = crate::app::budget::most_pressing(app)?;
+    if reading.fraction() < crate::app::budget::WARN_FRACTION {
+        return None;
+    }
+    let color = if reading.is_exceeded() { theme::STATUS_ERROR } else { theme::RUST_ORANGE };
+    Some((format!("Budget: {}", reading.short_label()), color))
+}
+
+/// Returns `(telemetry, update_hint)` -- either or both may be `None`. A live
+/// toast takes the update hint's place.
 fn footer_right_items(app: &App) -> (FooterItem, FooterItem) {
-    let telemetry = footer_telemetry_text(app).map(|text| {
-        let color = if app.is_compacting { theme::RUST_ORANGE } else { theme::DIM };
-        (text, color)
-    });
-    let update_hint = app.update_check_hint.as_ref().map(|hint| (hint.clone(), theme::RUST_ORANGE));
+    let budget = footer_budget_warning(app);
+    let telemetry = match (budget, footer_telemetry_text(app)) {
+        (Some((warning, color)), Some(text)) => Some((format!("{warning} | {text}"), color)),
+        (Some(warning), None) => Some(warning),
+        (None, text) => text.map(|text| {
+            let color = if app.is_compacting { theme::RUST_ORANGE } else { theme::DIM };
+            (text, color)
+        }),
+    };
+    let update_hint = match &app.toast {
+        Some(toast) => {
+            let color = if toast.is_error { theme::STATUS_ERROR } else { theme::STATUS_SUCCESS };
+            Some((toast.text.clone(), color))
+        }
+        None => app.update_check_hint.as_ref().map(|hint| (hint.clone(), theme::RUST_ORANGE)),
+    };
     (telemetry, update_hint)
 }
 
@@ -340,6 +422,58 @@ fn mode_color(mode_id: &str) -> Color {
     }
 }
 
+/// Input top border; in vi mode it shows the mode and any pending keys.
+fn render_input_separator(frame: &mut Frame, area: Rect, app: &App) {
+    let vim = app.vim.as_ref();
+    let restored = crate::app::draft::visible_restored(app);
+    if area.height == 0 || (vim.is_none() && restored.is_none()) {
+        render_separator(frame, area);
+        return;
+    }
+    let dim = Style::default().fg(theme::DIM);
+    let mut spans = vec![Span::styled(theme::SEPARATOR_CHAR.repeat(2), dim)];
+    if let Some(vim) = vim {
+        let color = match vim.mode {
+            VimMode::Insert => theme::VIM_INSERT,
+            VimMode::Normal => theme::VIM_NORMAL,
+            VimMode::Visual | VimMode::VisualLine => theme::VIM_VISUAL,
+        };
+        spans.push(Span::styled(
+            format!(" {} ", vim.mode.label()),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+        if !vim.pending.is_empty() {
+            spans
+                .push(Span::styled(format!("{} ", vim.pending), Style::default().fg(Color::White)));
+        }
+    }
+    if let Some(restored) = restored {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        spans.push(Span::styled(
+            format!(" Draft restored ({}) ", draft_age(restored.saved_at, now)),
+            Style::default().fg(theme::RUST_ORANGE),
+        ));
+        spans.push(Span::styled("Alt+D discard ", dim));
+    }
+    let used: usize = spans.iter().map(Span::width).sum();
+    let rest = usize::from(area.width).saturating_sub(used);
+    spans.push(Span::styled(theme::SEPARATOR_CHAR.repeat(rest), dim));
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// How long ago a draft was saved, coarsely.
+fn draft_age(saved_at: u64, now: u64) -> String {
+    let secs = now.saturating_sub(saved_at);
+    match secs {
+        s if s < 60 => "just now".to_owned(),
+        s if s < 3_600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3_600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
+
 fn render_separator(frame: &mut Frame, area: Rect) {
     if area.height == 0 {
         return;
@@ -495,4 +629,20 @@ mod tests {
         let text = footer_telemetry_text(&app).expect("footer telemetry");
         assert_eq!(text, "Context: -");
     }
+
+    #[test]
+    fn footer_warns_when_budget_nears_limit() {
+        let mut app = App::test_default();
+        app.budget_limits.session_cost_usd = Some(1.0);
+        app.session_usage.total_cost_usd = Some(0.5);
+        assert_eq!(footer_budget_warning(&app), None);
+
+        app.session_usage.total_cost_usd = Some(0.85);
+        let (text, color) = footer_budget_warning(&app).expect("budget warning");
+        assert_eq!(text, "Budget: session $0.85/$1.00");
+        assert_eq!(color, theme::RUST_ORANGE);
+
+        app.session_usage.total_cost_usd = Some(1.1);
+        assert_eq!(footer_budget_warning(&app).map(|(_, c)| c), Some(theme::STATUS_ERROR));
+    }
 }
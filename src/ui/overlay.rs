@@ -0,0 +1,276 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::agent::model;
+use crate::app::ledger::{ReportLine, UsageReport};
+use crate::app::overlay::OverlayKind;
+use crate::app::{App, SessionUsageState};
+use crate::ui::message::format_token_count;
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
+
+const MAX_WIDTH: u16 = 96;
+const MARGIN: u16 = 2;
+/// Per-turn rows shown in the `/context` history.
+const CONTEXT_HISTORY_ROWS: usize = 12;
+/// Columns taken by the legend and turn labels next to the context bars.
+const CONTEXT_LABEL_COLUMNS: u16 = 16;
+
+/// Draw the open overlay centered over `area` (the chat body) and return
+/// the popup area.
+pub fn render(frame: &mut Frame, area: Rect, app: &mut App) -> Option<Rect> {
+    let overlay = app.overlay.as_ref()?;
+    let width = area.width.saturating_sub(MARGIN * 2).min(MAX_WIDTH);
+    let (title, lines) = match &overlay.kind {
+        OverlayKind::Usage(report) => (" Usage ".to_owned(), usage_lines(report)),
+        OverlayKind::Context => {
+            (" Context ".to_owned(), context_lines(&app.session_usage, width.saturating_sub(2)))
+        }
+        OverlayKind::Preview { title, lines } => (format!(" {title} "), preview_lines(lines)),
+    };
+
+    let content_rows = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+    let height = content_rows.saturating_add(2).min(area.height);
+    if width < 4 || height < 3 {
+        return None;
+    }
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let visible = usize::from(height - 2);
+    let max_scroll = lines.len().saturating_sub(visible);
+    let scroll = overlay.scroll.min(max_scroll);
+    if let Some(open) = app.overlay.as_mut() {
+        open.scroll = scroll;
+    }
+
+    let hint = if max_scroll > 0 { " Up/Down scroll \u{00B7} Esc close " } else { " Esc close " };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::DIM))
+        .title(Span::styled(title, Style::default().fg(theme::RUST_ORANGE)))
+        .title_bottom(
+            Line::from(Span::styled(hint, Style::default().fg(theme::DIM))).right_aligned(),
+        );
+    let body: Vec<Line<'static>> = lines.into_iter().skip(scroll).take(visible).collect();
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(body).block(block), popup);
+    Some(popup)
+}
+
+fn usage_lines(report: &UsageReport) -> Vec<Line<'static>> {
+    report
+        .lines()
+        .into_iter()
+        .map(|line| match line {
+            ReportLine::Heading(text) => Line::from(Span::styled(
+                text,
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            )),
+            ReportLine::Row(text) => Line::from(Span::raw(text)),
+            ReportLine::Blank => Line::default(),
+        })
+        .collect()
+}
+
+fn preview_lines(lines: &[String]) -> Vec<Line<'static>> {
+    lines.iter().map(|line| Line::from(Span::raw(line.replace('\t', "    ")))).collect()
+}
+
+fn heading(text: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        text.to_owned(),
+        Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+    ))
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn cells_for(tokens: u64, window: u64, bar_width: usize) -> usize {
+    if tokens == 0 || window == 0 {
+        return 0;
+    }
+    let cells = (tokens as f64 / window as f64 * bar_width as f64).round() as usize;
+    cells.max(1)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percent_of(tokens: u64, window: u64) -> String {
+    if window == 0 {
+        return String::new();
+    }
+    format!("{:>3.0}%", tokens as f64 / window as f64 * 100.0)
+}
+
+/// Breakdown bar, per-turn history and compaction projection for `/context`.
+fn context_lines(usage: &SessionUsageState, inner_width: u16) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(theme::DIM);
+    let bar_width = usize::from(inner_width.saturating_sub(2)).max(10);
+    let mut lines = Vec::new();
+
+    let Some(window) = usage.context_window.filter(|w| *w > 0) else {
+        lines.push(Line::from(Span::styled(
+            "Context window size is not known yet; it is reported after the first turn.",
+            dim,
+        )));
+        return lines;
+    };
+    let used = usage.context_used_tokens().unwrap_or(0);
+    lines.push(Line::from(vec![
+        Span::styled(
+            "Context window  ",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(
+            "{} / {} used ({})",
+            format_token_count(used),
+            format_token_count(window),
+            percent_of(used, window).trim_start()
+        )),
+    ]));
+
+    let segments: [(&str, u64, Color, char); 5] = [
+        ("Cached read", usage.latest_cache_read_tokens.unwrap_or(0), Color::Cyan, '\u{2588}'),
+        ("Cache write", usage.latest_cache_write_tokens.unwrap_or(0), Color::Magenta, '\u{2588}'),
+        ("Fresh input", usage.latest_input_tokens.unwrap_or(0), theme::RUST_ORANGE, '\u{2588}'),
+        ("Last output", usage.latest_output_tokens.unwrap_or(0), Color::Yellow, '\u{2588}'),
+        ("Reserved output", usage.reserved_output_tokens(), Color::Gray, '\u{2592}'),
+    ];
+    let mut bar = Vec::new();
+    let mut remaining = bar_width;
+    for (_, tokens, color, glyph) in segments {
+        let cells = cells_for(tokens, window, bar_width).min(remaining);
+        remaining -= cells;
+        bar.push(Span::styled(glyph.to_string().repeat(cells), Style::default().fg(color)));
+    }
+    bar.push(Span::styled("\u{2591}".repeat(remaining), dim));
+    lines.push(Line::from(bar));
+
+    let reserved = usage.reserved_output_tokens();
+    let free = window.saturating_sub(used).saturating_sub(reserved);
+    for (label, tokens, color, glyph) in
+        segments.into_iter().chain(std::iter::once(("Free", free, theme::DIM, '\u{2591}')))
+    {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {glyph} "), Style::default().fg(color)),
+            Span::raw(format!("{label:<16}{:>8}", format_token_count(tokens))),
+            Span::styled(format!("  {}", percent_of(tokens, window)), dim),
+        ]));
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Context per turn"));
+    if usage.context_history.is_empty() {
+        lines.push(Line::from(Span::styled("  No completed turns yet", dim)));
+    } else {
+        let history_bar = usize::from(inner_width.saturating_sub(CONTEXT_LABEL_COLUMNS)).max(10);
+        let shown = usage.context_history.len().min(CONTEXT_HISTORY_ROWS);
+        let first_turn = usage.context_turns - shown as u64 + 1;
+        let recent = &usage.context_history[usage.context_history.len() - shown..];
+        for (turn, size) in (first_turn..).zip(recent) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  #{turn:<4}"), dim),
+                Span::raw(format!("{:>7}  ", format_token_count(*size))),
+                Span::styled(
+                    "\u{2588}".repeat(cells_for(*size, window, history_bar)),
+                    Style::default().fg(theme::RUST_ORANGE),
+                ),
+            ]));
+        }
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Compaction"));
+    let last = match (usage.last_compaction_trigger, usage.last_compaction_pre_tokens) {
+        (Some(trigger), pre_tokens) => {
+            let trigger = match trigger {
+                model::CompactionTrigger::Manual => "manual",
+                model::CompactionTrigger::Auto => "auto",
+            };
+            let at = pre_tokens
+                .map(|t| format!(" at {} tokens", format_token_count(t)))
+                .unwrap_or_default();
+            format!("  Last compaction: {trigger}{at}")
+        }
+        (None, _) => "  No compaction this session".to_owned(),
+    };
+    lines.push(Line::from(Span::raw(last)));
+    if let Some(threshold) = usage.auto_compact_threshold() {
+        let projection = match usage.turns_until_auto_compact() {
+            Some(0) => "auto-compaction is due on the next turn".to_owned(),
+            Some(1) => "about 1 turn left".to_owned(),
+            Some(turns) => format!("about {turns} turns left"),
+            None => "not enough growth history to project".to_owned(),
+        };
+        lines.push(Line::from(Span::raw(format!(
+            "  Auto-compacts near {}: {projection}",
+            format_token_count(threshold)
+        ))));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line<'_>]) -> String {
+        lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn context_lines_break_down_window_and_project_compaction() {
+        let mut usage = SessionUsageState {
+            context_window: Some(200_000),
+            max_output_tokens: Some(32_000),
+            latest_output_tokens: Some(0),
+            last_compaction_trigger: Some(model::CompactionTrigger::Auto),
+            last_compaction_pre_tokens: Some(180_000),
+            ..SessionUsageState::default()
+        };
+        for input in [20_000_u64, 40_000] {
+            usage.latest_input_tokens = Some(input);
+            usage.record_context_turn();
+        }
+        usage.latest_cache_read_tokens = Some(30_000);
+        usage.latest_input_tokens = Some(10_000);
+
+        let lines = context_lines(&usage, 60);
+        let rendered = text(&lines);
+        assert!(rendered.contains("40k / 200k used (20%)"), "{rendered}");
+        assert!(rendered.contains("Cached read"));
+        // The 32k output limit only reserves the capped 20k.
+        assert!(rendered.contains("Reserved output      20k"), "{rendered}");
+        assert!(rendered.contains("Free                140k"), "{rendered}");
+        assert!(rendered.contains("#2"));
+        assert!(rendered.contains("Last compaction: auto at 180k tokens"));
+        assert!(rendered.contains("Auto-compacts near 167k: about 6 turns left"), "{rendered}");
+        // The breakdown bar fills the full width.
+        assert_eq!(lines[1].width(), 58);
+    }
+}
@@ -0,0 +1,135 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::queue::{QueueDelivery, SubmissionQueue};
+use crate::app::{App, FocusOwner};
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+/// Maximum queued items shown at once; the list scrolls past this.
+const MAX_VISIBLE: usize = 4;
+
+/// Header line plus up to `MAX_VISIBLE` items; 0 when nothing is queued.
+pub fn compute_height(app: &App) -> u16 {
+    if app.submission_queue.is_empty() {
+        return 0;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        1 + app.submission_queue.len().min(MAX_VISIBLE) as u16
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let focused = app.focus_owner() == FocusOwner::Queue;
+    let lines = queue_lines(&app.submission_queue, focused, usize::from(area.height));
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn header_line(queue: &SubmissionQueue, focused: bool) -> Line<'static> {
+    let dim = Style::default().fg(theme::DIM);
+    let mut spans = vec![Span::styled(
+        format!("  Queued ({})", queue.len()),
+        Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD),
+    )];
+    let hint = if let Some(edit) = &queue.editing {
+        format!("  editing #{} \u{00B7} Enter save \u{00B7} Esc cancel", edit.index + 1)
+    } else if focused {
+        "  \u{2191}\u{2193} select \u{00B7} Shift+\u{2191}\u{2193} move \u{00B7} e edit \u{00B7} i interrupt/after turn \u{00B7} d delete \u{00B7} Esc done"
+            .to_owned()
+    } else {
+        "  Alt+Q to manage".to_owned()
+    };
+    spans.push(Span::styled(hint, dim));
+    Line::from(spans)
+}
+
+fn queue_lines(queue: &SubmissionQueue, focused: bool, height: usize) -> Vec<Line<'static>> {
+    let mut lines = vec![header_line(queue, focused)];
+    let visible = height.saturating_sub(1).min(MAX_VISIBLE);
+    if visible == 0 {
+        return lines;
+    }
+    let selected = queue.selected.min(queue.len().saturating_sub(1));
+    let offset = if focused { (selected + 1).saturating_sub(visible) } else { 0 };
+
+    for (i, item) in queue.items.iter().enumerate().skip(offset).take(visible) {
+        let is_selected = focused && i == selected;
+        let editing = queue.editing.as_ref().is_some_and(|edit| edit.index == i);
+        let marker = if is_selected { "\u{25b8}" } else { " " };
+        let first_line = item.text.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+        let more = if item.text.lines().count() > 1 { " \u{2026}" } else { "" };
+        let mut text_style = Style::default().fg(Color::White);
+        if is_selected {
+            text_style = text_style.add_modifier(Modifier::BOLD);
+        }
+        if editing {
+            text_style = Style::default().fg(theme::DIM).add_modifier(Modifier::ITALIC);
+        }
+        let delivery_style = match item.delivery {
+            QueueDelivery::AfterTurn => Style::default().fg(theme::DIM),
+            QueueDelivery::Interrupt => Style::default().fg(theme::RUST_ORANGE),
+        };
+        let mut spans = vec![
+            Span::styled(format!("  {marker} {}. ", i + 1), Style::default().fg(theme::DIM)),
+            Span::styled(format!("[{}] ", item.delivery.label()), delivery_style),
+            Span::styled(format!("{}{more}", first_line.trim()), text_style),
+        ];
+        if editing {
+            spans.push(Span::styled("  (editing)", Style::default().fg(theme::DIM)));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::queue::QueuedSubmission;
+
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn queue_lines_show_delivery_and_follow_selection() {
+        let queue = SubmissionQueue {
+            items: (1..=6)
+                .map(|n| QueuedSubmission {
+                    text: format!("prompt {n}\nmore"),
+                    delivery: if n == 6 {
+                        QueueDelivery::Interrupt
+                    } else {
+                        QueueDelivery::AfterTurn
+                    },
+                })
+                .collect(),
+            selected: 5,
+            editing: None,
+        };
+
+        let lines = queue_lines(&queue, true, 5);
+        assert_eq!(lines.len(), 5);
+        assert!(line_text(&lines[0]).starts_with("  Queued (6)"));
+        assert_eq!(line_text(&lines[1]), "    3. [after turn] prompt 3 \u{2026}");
+        assert_eq!(line_text(&lines[4]), "  \u{25b8} 6. [interrupt] prompt 6 \u{2026}");
+    }
+}
@@ -0,0 +1,311 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::search::{SearchHit, SearchState, content_version, find_matches};
+use crate::app::{App, FocusOwner};
+use crate::ui::message::{self, SpinnerState};
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Rebuild the hit list when the query, message content or width changed.
+/// Runs before heights are measured so a block expanded for the selected hit
+/// is measured this frame.
+pub fn prepare_hits(app: &mut App, base: SpinnerState, width: u16) {
+    let Some(search) = app.search.as_ref() else {
+        return;
+    };
+    if search.query.is_empty() {
+        return;
+    }
+    if search.scanned_version != Some(content_version(&app.messages)) {
+        crate::app::search::rescan(app);
+    }
+    let Some(search) = app.search.as_ref() else {
+        return;
+    };
+    if !search.hits_stale && search.hits_width == width {
+        return;
+    }
+    let query = search.query.clone();
+    let matching = search.matching_messages.clone();
+    let collapsed = search.collapsed_matches.clone();
+    let mut hits = Vec::new();
+    let mut scratch = Vec::new();
+    let mut block_starts = Vec::new();
+    for index in matching {
+        let Some(msg) = app.messages.get_mut(index) else {
+            continue;
+        };
+        scratch.clear();
+        block_starts.clear();
+        message::render_message_with_block_starts(
+            msg,
+            &base,
+            width,
+            &mut scratch,
+            &mut block_starts,
+        );
+        let mut row = 0;
+        for (line_index, line) in scratch.iter().enumerate() {
+            for &(block, _) in block_starts.iter().filter(|(_, start)| *start == line_index) {
+                if collapsed.binary_search(&(index, block)).is_ok() {
+                    hits.push(SearchHit { message: index, row, collapsed_block: Some(block) });
+                }
+            }
+            let count = find_matches(&line_text(line), &query).len();
+            hits.extend(std::iter::repeat_n(
+                SearchHit { message: index, row, collapsed_block: None },
+                count,
+            ));
+            row += Paragraph::new(line.clone()).wrap(Wrap { trim: false }).line_count(width);
+        }
+    }
+    let Some(search) = app.search.as_mut() else {
+        return;
+    };
+    if let Some(current) = search.current {
+        search.current = Some(current.min(hits.len().saturating_sub(1)));
+    }
+    let selected = search
+        .pending_jump
+        .then(|| hits.get(search.current.unwrap_or(hits.len().saturating_sub(1))))
+        .flatten()
+        .and_then(|hit| hit.collapsed_block.map(|block| (hit.message, block)));
+    search.hits = hits;
+    search.hits_stale = false;
+    search.hits_width = width;
+    // Expand only the block the jump lands on, then lay the hits out again.
+    if let Some((message, block)) = selected {
+        crate::app::search::reveal(app, message, block);
+        prepare_hits(app, base, width);
+    }
+}
+
+/// Scroll the selected hit into view, a third of the way down the chat.
+pub fn apply_jump(app: &mut App, viewport_height: usize) {
+    let Some(search) = app.search.as_mut() else {
+        return;
+    };
+    if !search.pending_jump {
+        return;
+    }
+    search.pending_jump = false;
+    if search.hits.is_empty() {
+        return;
+    }
+    let index = search.current.unwrap_or(search.hits.len() - 1).min(search.hits.len() - 1);
+    search.current = Some(index);
+    let hit = search.hits[index];
+    let row = app.viewport.cumulative_height_before(hit.message) + hit.row;
+    app.viewport.scroll_target = row.saturating_sub(viewport_height / 3);
+    app.viewport.auto_scroll = false;
+}
+
+fn highlight_line(
+    line: &mut Line<'static>,
+    needle: &str,
+    ordinals: &mut impl Iterator<Item = usize>,
+    current: Option<usize>,
+) {
+    let matches = find_matches(&line_text(line), needle);
+    if matches.is_empty() {
+        return;
+    }
+    let ranges: Vec<(usize, usize, bool)> = matches
+        .into_iter()
+        .map(|start| {
+            let ordinal = ordinals.next();
+            let is_current = ordinal.is_some() && ordinal == current;
+            (start, start + needle.len(), is_current)
+        })
+        .collect();
+    let match_style = Style::default().bg(theme::SEARCH_MATCH_BG).fg(Color::White);
+    let current_style =
+        Style::default().bg(theme::SEARCH_CURRENT_BG).fg(Color::Black).add_modifier(Modifier::BOLD);
+
+    let mut spans = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in line.spans.drain(..) {
+        let content = span.content.as_ref();
+        let (start, end) = (offset, offset + content.len());
+        offset = end;
+        let mut cursor = start;
+        for &(match_start, match_end, is_current) in &ranges {
+            if match_end <= cursor || match_start >= end {
+                continue;
+            }
+            let (from, to) = (match_start.max(cursor), match_end.min(end));
+            if from > cursor {
+                spans.push(Span::styled(
+                    content[cursor - start..from - start].to_owned(),
+                    span.style,
+                ));
+            }
+            let style = if is_current { current_style } else { match_style };
+            spans.push(Span::styled(
+                content[from - start..to - start].to_owned(),
+                span.style.patch(style),
+            ));
+            cursor = to;
+        }
+        if cursor == start {
+            spans.push(span);
+        } else if cursor < end {
+            spans.push(Span::styled(content[cursor - start..].to_owned(), span.style));
+        }
+    }
+    line.spans = spans;
+}
+
+/// Highlight matches in the lines rendered for `message`. The lines may be a
+/// suffix of the message (partially scrolled), so the visible occurrences are
+/// matched to the message's last text hits.
+pub fn highlight_message_lines(search: &SearchState, message: usize, lines: &mut [Line<'static>]) {
+    if search.query.is_empty() || !search.is_match(message) {
+        return;
+    }
+    let text_hits: Vec<usize> =
+        search.hits_for(message).filter(|&i| search.hits[i].collapsed_block.is_none()).collect();
+    let visible: usize =
+        lines.iter().map(|line| find_matches(&line_text(line), &search.query).len()).sum();
+    let mut ordinals = text_hits[text_hits.len().saturating_sub(visible)..].iter().copied();
+    for line in lines {
+        highlight_line(line, &search.query, &mut ordinals, search.current);
+    }
+}
+
+fn bar_line(search: &SearchState, focused: bool) -> Line<'static> {
+    let dim = Style::default().fg(theme::DIM);
+    let mut spans = vec![
+        Span::styled(
+            " Search: ",
+            Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(search.query.clone(), Style::default().fg(Color::White)),
+    ];
+    if search.editing && focused {
+        spans.push(Span::styled("\u{258F}", Style::default().fg(theme::RUST_ORANGE)));
+    }
+    let count = if search.query.is_empty() {
+        String::new()
+    } else if search.hits.is_empty() {
+        "  no matches".to_owned()
+    } else {
+        let current = search.current.map_or(search.hits.len(), |c| c + 1);
+        format!("  {current}/{}", search.hits.len())
+    };
+    spans.push(Span::styled(count, Style::default().fg(Color::White)));
+    let hint = if !focused {
+        "  Ctrl+F to search again"
+    } else if search.editing {
+        "  Enter browse \u{00B7} Esc close"
+    } else {
+        "  n/N next/prev \u{00B7} / edit \u{00B7} Esc close"
+    };
+    spans.push(Span::styled(hint, dim));
+    Line::from(spans)
+}
+
+/// Search bar drawn over the last row of the chat area. Returns the row it covers.
+pub fn render_bar(frame: &mut Frame, area: Rect, app: &App) -> Option<Rect> {
+    let search = app.search.as_ref()?;
+    if area.height == 0 {
+        return None;
+    }
+    let bar = Rect { y: area.bottom() - 1, height: 1, ..area };
+    let focused = app.focus_owner() == FocusOwner::Search;
+    frame.render_widget(Clear, bar);
+    frame.render_widget(
+        Paragraph::new(bar_line(search, focused)).style(Style::default().bg(theme::USER_MSG_BG)),
+        bar,
+    );
+    Some(bar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_splits_spans_and_marks_current_hit() {
+        let search = SearchState {
+            query: "cargo".into(),
+            matching_messages: vec![4],
+            hits: vec![
+                SearchHit { message: 4, row: 0, collapsed_block: None },
+                SearchHit { message: 4, row: 0, collapsed_block: None },
+                SearchHit { message: 4, row: 3, collapsed_block: None },
+            ],
+            current: Some(2),
+            ..SearchState::default()
+        };
+        // Only the last line of the message is visible: it holds the third hit.
+        let mut lines = vec![Line::from(vec![Span::raw("run CAR"), Span::raw("GO test")])];
+        highlight_message_lines(&search, 4, &mut lines);
+
+        let spans = &lines[0].spans;
+        assert_eq!(line_text(&lines[0]), "run CARGO test");
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[1].content, "CAR");
+        assert_eq!(spans[2].content, "GO");
+        assert_eq!(spans[1].style.bg, Some(theme::SEARCH_CURRENT_BG));
+        assert_eq!(spans[2].style.bg, Some(theme::SEARCH_CURRENT_BG));
+        assert_eq!(spans[3].style.bg, None);
+    }
+
+    #[test]
+    fn highlight_skips_hits_on_collapsed_blocks() {
+        let search = SearchState {
+            query: "cargo".into(),
+            matching_messages: vec![1],
+            hits: vec![
+                SearchHit { message: 1, row: 0, collapsed_block: None },
+                SearchHit { message: 1, row: 2, collapsed_block: Some(1) },
+                SearchHit { message: 1, row: 5, collapsed_block: None },
+            ],
+            current: Some(2),
+            ..SearchState::default()
+        };
+        let mut lines = vec![Line::from("cargo build")];
+        highlight_message_lines(&search, 1, &mut lines);
+        assert_eq!(lines[0].spans[0].style.bg, Some(theme::SEARCH_CURRENT_BG));
+    }
+
+    #[test]
+    fn bar_reports_position_and_mode_hints() {
+        let mut search = SearchState {
+            query: "fix".into(),
+            hits: vec![SearchHit { message: 0, row: 0, collapsed_block: None }; 3],
+            current: Some(0),
+            ..SearchState::default()
+        };
+        assert_eq!(
+            line_text(&bar_line(&search, true)),
+            " Search: fix  1/3  n/N next/prev \u{00B7} / edit \u{00B7} Esc close"
+        );
+        search.hits.clear();
+        search.editing = true;
+        assert!(line_text(&bar_line(&search, true)).contains("no matches  Enter browse"));
+    }
+}
@@ -0,0 +1,148 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::{App, MessageBlock, SubagentTree, subagents};
+use crate::ui::theme;
+use crate::ui::tool_call;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Paragraph, Wrap};
+use std::time::{Duration, Instant};
+
+/// A subagent with running children and no activity for this long is flagged.
+const STALL_AFTER: Duration = Duration::from_secs(60);
+
+/// Render the focused Task's child transcript in place of the chat.
+pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+    let Some(task_id) = app.subagent_view.as_ref().map(|v| v.task_id.clone()) else {
+        return;
+    };
+    let Some(task) = subagents::find_task(app, &task_id) else {
+        // The Task vanished (new session, history trimmed): fall back to chat.
+        subagents::close_view(app);
+        return;
+    };
+
+    let (icon, icon_color) = tool_call::status_icon(task.status, app.spinner_frame);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{icon} "), Style::default().fg(icon_color)),
+            Span::styled(
+                task.title.clone(),
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        activity_line(&task.subagent, Instant::now()),
+        Line::default(),
+    ];
+    let child_ids: Vec<String> = task.subagent.children.iter().map(|c| c.id.clone()).collect();
+
+    let width = area.width;
+    for id in &child_ids {
+        let Some((mi, bi)) = app.lookup_tool_call(id) else {
+            continue;
+        };
+        if let Some(MessageBlock::ToolCall(tc)) =
+            app.messages.get_mut(mi).and_then(|m| m.blocks.get_mut(bi))
+        {
+            tool_call::render_tool_call_cached(tc, width, app.spinner_frame, &mut lines);
+        }
+    }
+    if child_ids.is_empty() {
+        lines
+            .push(Line::from(Span::styled("  No tool calls yet", Style::default().fg(theme::DIM))));
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines)).wrap(Wrap { trim: false });
+    let total = paragraph.line_count(width);
+    let max_scroll = total.saturating_sub(usize::from(area.height));
+    let scroll_from_bottom = app.subagent_view.as_ref().map_or(0, |v| v.scroll_from_bottom);
+    let clamped = scroll_from_bottom.min(max_scroll);
+    if let Some(view) = app.subagent_view.as_mut() {
+        view.scroll_from_bottom = clamped;
+    }
+    let offset = u16::try_from(max_scroll - clamped).unwrap_or(u16::MAX);
+    frame.render_widget(paragraph.scroll((offset, 0)), area);
+}
+
+/// "3 tool calls · 1 running · idle 45s  (Esc to close)"; idle turns red past
+/// `STALL_AFTER` while children are still running so a stuck subagent stands out.
+fn activity_line(tree: &SubagentTree, now: Instant) -> Line<'static> {
+    let dim = Style::default().fg(theme::DIM);
+    let total = tree.children.len();
+    let running = tree.running_count();
+    let mut spans =
+        vec![Span::styled(format!("{total} tool call{}", if total == 1 { "" } else { "s" }), dim)];
+    if running > 0 {
+        spans.push(Span::styled(
+            format!(" \u{00B7} {running} running"),
+            Style::default().fg(theme::RUST_ORANGE),
+        ));
+    }
+    let failed = tree.failed_count();
+    if failed > 0 {
+        spans.push(Span::styled(
+            format!(" \u{00B7} {failed} failed"),
+            Style::default().fg(theme::STATUS_ERROR),
+        ));
+    }
+    if let Some(last) = tree.last_activity {
+        let idle = now.saturating_duration_since(last);
+        let style = if running > 0 && idle >= STALL_AFTER {
+            Style::default().fg(theme::STATUS_ERROR).add_modifier(Modifier::BOLD)
+        } else {
+            dim
+        };
+        spans.push(Span::styled(format!(" \u{00B7} idle {}s", idle.as_secs()), style));
+    }
+    spans.push(Span::styled("  (Esc to close, Alt+A next subagent)", dim));
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::model;
+    use crate::app::SubagentChild;
+
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn activity_line_flags_stalled_running_subagent() {
+        let start = Instant::now();
+        let tree = SubagentTree {
+            children: vec![SubagentChild {
+                id: "child-1".into(),
+                title: "Read src/main.rs".into(),
+                sdk_tool_name: "Read".into(),
+                status: model::ToolCallStatus::InProgress,
+                awaiting_permission: false,
+            }],
+            last_activity: Some(start),
+        };
+
+        let fresh = activity_line(&tree, start + Duration::from_secs(5));
+        assert!(line_text(&fresh).contains("1 tool call \u{00B7} 1 running \u{00B7} idle 5s"));
+        assert!(fresh.spans.iter().all(|s| s.style.fg != Some(theme::STATUS_ERROR)));
+
+        let stalled = activity_line(&tree, start + STALL_AFTER);
+        assert!(stalled.spans.iter().any(|s| s.style.fg == Some(theme::STATUS_ERROR)));
+    }
+}
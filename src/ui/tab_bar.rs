@@ -0,0 +1,104 @@
+// Claude Code Rust - A native Rust terminal interface for Claude Code
+// Copyright (C) 2025  Simon Peter Rothgang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::app::App;
+use crate::app::tabs;
+use crate::ui::theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+const TAB_BAR_PAD: u16 = 2;
+const PERMISSION_MARKER: &str = "!";
+const UNREAD_MARKER: &str = "\u{25CF}";
+
+/// Whether the tab strip takes a row; a single session keeps the classic layout.
+#[must_use]
+pub fn is_visible(app: &App) -> bool {
+    app.tabs.list.len() > 1
+}
+
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let padded = Rect {
+        x: area.x + TAB_BAR_PAD,
+        y: area.y,
+        width: area.width.saturating_sub(TAB_BAR_PAD * 2),
+        height: area.height,
+    };
+    frame.render_widget(Paragraph::new(tab_bar_line(app)), padded);
+}
+
+fn tab_bar_line(app: &App) -> Line<'static> {
+    let mut spans = Vec::new();
+    for index in 0..app.tabs.list.len() {
+        if index > 0 {
+            spans.push(Span::styled(" \u{2502} ", Style::default().fg(theme::DIM)));
+        }
+        let active = index == app.tabs.active;
+        let label_style = if active {
+            Style::default().fg(theme::RUST_ORANGE).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        spans.push(Span::styled(format!("{} ", index + 1), Style::default().fg(theme::DIM)));
+        spans.push(Span::styled(tabs::tab_label(app, index), label_style));
+        if tabs::needs_permission(app, index) {
+            spans.push(Span::styled(
+                format!(" {PERMISSION_MARKER}"),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ));
+        }
+        if app.tabs.list[index].unread {
+            spans.push(Span::styled(
+                format!(" {UNREAD_MARKER}"),
+                Style::default().fg(theme::RUST_ORANGE),
+            ));
+        }
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tabs::SessionTab;
+
+    fn line_text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn single_tab_hides_strip() {
+        let app = App::test_default();
+        assert!(!is_visible(&app));
+    }
+
+    #[test]
+    fn strip_shows_permission_and_unread_markers() {
+        let mut app = App::test_default();
+        app.pending_permission_ids.push("tool-1".to_owned());
+        let mut second = SessionTab::active(1);
+        second.unread = true;
+        app.tabs.list.push(second);
+
+        assert!(is_visible(&app));
+        let text = line_text(&tab_bar_line(&app));
+        assert!(text.starts_with("1 New session !"), "{text}");
+        assert!(text.ends_with(&format!("2 New session {UNREAD_MARKER}")), "{text}");
+    }
+}
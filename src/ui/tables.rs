@@ -323,6 +323,7 @@ fn parse_inline_chunks(text: &str, base_style: Style) -> Vec<StyledChunk> {
     chunks
 }
 
+#[allow(clippy::manual_is_variant_and)]
 fn wrap_chunks_to_lines(chunks: &[StyledChunk], width: usize) -> Vec<Line<'static>> {
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut line_spans: Vec<Span<'static>> = Vec::new();
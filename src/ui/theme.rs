@@ -36,8 +36,22 @@ pub const ICON_FAILED: &str = "\u{2717}";
 
 // Status colors
 pub const STATUS_ERROR: Color = Color::Red;
+pub const STATUS_SUCCESS: Color = Color::Green;
 pub const SLASH_COMMAND: Color = Color::LightMagenta;
 
+// Search highlights
+pub const SEARCH_MATCH_BG: Color = Color::Rgb(110, 90, 20);
+pub const SEARCH_CURRENT_BG: Color = RUST_ORANGE;
+
+// Chat navigation cursor
+pub const CHAT_NAV_BG: Color = Color::Rgb(38, 46, 64);
+
+// Vi mode indicator and visual selection
+pub const VIM_INSERT: Color = Color::Green;
+pub const VIM_NORMAL: Color = RUST_ORANGE;
+pub const VIM_VISUAL: Color = Color::LightMagenta;
+pub const VIM_SELECTION_BG: Color = Color::Rgb(60, 64, 92);
+
 /// SDK tool icon + label pair. Monochrome Unicode symbols.
 /// Unknown tool names fall back to a generic Tool label.
 pub fn tool_name_label(sdk_tool_name: &str) -> (&'static str, &'static str) {
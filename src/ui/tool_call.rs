@@ -21,6 +21,7 @@ use crate::agent::error_handling::{
 use crate::agent::model::{self as model, PermissionOptionKind};
 use crate::app::{InlinePermission, ToolCallInfo};
 use crate::ui::diff::{is_markdown_file, lang_from_title, render_diff, strip_outer_code_fence};
+use crate::ui::graphics;
 use crate::ui::markdown;
 use crate::ui::theme;
 use ansi_to_tui::IntoText as _;
@@ -35,6 +36,10 @@ const SPINNER_STRS: &[&str] = &[
     "\u{2807}", "\u{280F}",
 ];
 
+/// Most recent subagent children listed under an expanded Task; older ones collapse
+/// into a single "earlier" line.
+const SUBAGENT_TREE_MAX_CHILDREN: usize = 10;
+
 /// Max visible output lines for Execute/Bash tool calls.
 /// Total box height = 1 (title) + 1 (command) + this + 1 (bottom border) = 15.
 /// TODO: make configurable (see ROADMAP.md)
@@ -52,6 +57,28 @@ pub fn status_icon(status: model::ToolCallStatus, spinner_frame: usize) -> (&'st
     }
 }
 
+fn has_image_content(tc: &ToolCallInfo) -> bool {
+    tc.content.iter().any(|c| {
+        matches!(c, model::ToolCallContent::Content(inner)
+            if matches!(inner.content, model::ContentBlock::Image(_)))
+    })
+}
+
+/// True when the rendered body of `tc` reserves rows for inline images.
+pub fn shows_inline_images(tc: &ToolCallInfo) -> bool {
+    let has_diff = tc.content.iter().any(|c| matches!(c, model::ToolCallContent::Diff(_)));
+    let collapsed = tc.collapsed && !has_diff && tc.pending_permission.is_none();
+    tc.is_rendered_inline() && !collapsed && !tc.is_execute_tool() && has_image_content(tc)
+}
+
+/// Inline images are sized to the chat width, so their cached lines are only valid
+/// at the width they were measured for. Other tool calls cache width-independent lines.
+fn invalidate_width_dependent_cache(tc: &mut ToolCallInfo, width: u16) {
+    if tc.cache.height_at(width).is_none() && shows_inline_images(tc) {
+        tc.cache.invalidate();
+    }
+}
+
 /// Render a tool call with caching. Only re-renders when cache is stale.
 ///
 /// For Execute/Bash tool calls, the cache stores **content only** (command, output,
@@ -89,6 +116,7 @@ pub fn render_tool_call_cached(
     }
 
     // Non-Execute tool calls: existing caching strategy
+    invalidate_width_dependent_cache(tc, width);
     let is_in_progress =
         matches!(tc.status, model::ToolCallStatus::InProgress | model::ToolCallStatus::Pending);
 
@@ -120,7 +148,7 @@ pub fn render_tool_call_cached(
     } else {
         crate::perf::mark("tc::cache_miss_body");
         let _t = crate::perf::start("tc::render_body");
-        let body = render_tool_call_body(tc);
+        let body = render_tool_call_body(tc, width);
         tc.cache.store(body);
         if let Some(stored) = tc.cache.get() {
             out.extend_from_slice(stored);
@@ -161,6 +189,7 @@ pub fn measure_tool_call_height_cached(
         return (0, 0);
     }
 
+    invalidate_width_dependent_cache(tc, width);
     let is_in_progress =
         matches!(tc.status, model::ToolCallStatus::InProgress | model::ToolCallStatus::Pending);
 
@@ -198,7 +227,7 @@ pub fn measure_tool_call_height_cached(
         return (total, tc.cache.get().map_or(1, |b| b.len() + 1));
     }
 
-    let body = render_tool_call_body(tc);
+    let body = render_tool_call_body(tc, width);
     let body_h =
         Paragraph::new(Text::from(body.clone())).wrap(Wrap { trim: false }).line_count(width);
     tc.cache.store(body);
@@ -231,9 +260,9 @@ fn render_tool_call_title(tc: &ToolCallInfo, _width: u16, spinner_frame: usize)
 /// Render the body lines (everything after the title) for a non-Execute tool call.
 /// Used for in-progress tool calls where the body is cached separately from the title.
 /// Execute tool calls are handled separately via `render_execute_with_borders`.
-fn render_tool_call_body(tc: &ToolCallInfo) -> Vec<Line<'static>> {
+fn render_tool_call_body(tc: &ToolCallInfo, width: u16) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
-    render_standard_body(tc, &mut lines);
+    render_standard_body(tc, width, &mut lines);
     lines
 }
 
@@ -242,19 +271,21 @@ fn render_tool_call_body(tc: &ToolCallInfo) -> Vec<Line<'static>> {
 fn render_tool_call(tc: &ToolCallInfo, width: u16, spinner_frame: usize) -> Vec<Line<'static>> {
     let title = render_tool_call_title(tc, width, spinner_frame);
     let mut lines = vec![title];
-    render_standard_body(tc, &mut lines);
+    render_standard_body(tc, width, &mut lines);
     lines
 }
 
 /// Render the body (everything after the title line) of a standard (non-Execute) tool call.
-fn render_standard_body(tc: &ToolCallInfo, lines: &mut Vec<Line<'static>>) {
+fn render_standard_body(tc: &ToolCallInfo, width: u16, lines: &mut Vec<Line<'static>>) {
     let pipe_style = Style::default().fg(theme::DIM);
     let has_permission = tc.pending_permission.is_some();
 
     // Diffs (Edit tool) are always shown -- user needs to see changes
     let has_diff = tc.content.iter().any(|c| matches!(c, model::ToolCallContent::Diff(_)));
 
-    if tc.content.is_empty() && !has_permission {
+    let has_subagents = !tc.subagent.children.is_empty();
+
+    if tc.content.is_empty() && !has_permission && !has_subagents {
         return;
     }
 
@@ -265,7 +296,15 @@ fn render_standard_body(tc: &ToolCallInfo, lines: &mut Vec<Line<'static>>) {
 
     if effectively_collapsed {
         // Collapsed: show summary + ctrl+o hint
-        let summary = content_summary(tc);
+        let summary = if has_subagents {
+            let tree = subagent_tree_summary(tc);
+            match content_summary(tc) {
+                content if content.is_empty() => tree,
+                content => format!("{tree} \u{00B7} {content}"),
+            }
+        } else {
+            content_summary(tc)
+        };
         lines.push(Line::from(vec![
             Span::styled("  \u{2514}\u{2500} ", pipe_style),
             Span::styled(summary, Style::default().fg(theme::DIM)),
@@ -273,7 +312,8 @@ fn render_standard_body(tc: &ToolCallInfo, lines: &mut Vec<Line<'static>>) {
         ]));
     } else {
         // Expanded: render full content with | prefix on each line
-        let mut content_lines = render_tool_content(tc);
+        let mut content_lines = render_subagent_tree(tc);
+        content_lines.extend(render_tool_content(tc, width));
 
         // Append inline permission controls if pending
         if let Some(ref perm) = tc.pending_permission {
@@ -294,6 +334,63 @@ fn render_standard_body(tc: &ToolCallInfo, lines: &mut Vec<Line<'static>>) {
     }
 }
 
+/// "N tool calls · k running · f failed" for a Task with subagent children.
+fn subagent_tree_summary(tc: &ToolCallInfo) -> String {
+    let tree = &tc.subagent;
+    let total = tree.children.len();
+    let mut parts = vec![format!("{total} tool call{}", if total == 1 { "" } else { "s" })];
+    let running = tree.running_count();
+    if running > 0 {
+        parts.push(format!("{running} running"));
+    }
+    let failed = tree.failed_count();
+    if failed > 0 {
+        parts.push(format!("{failed} failed"));
+    }
+    parts.join(" \u{00B7} ")
+}
+
+/// Child tool calls of a Task as tree lines. Icons are static (no spinner) so the
+/// body stays cacheable while the subagent works.
+fn render_subagent_tree(tc: &ToolCallInfo) -> Vec<Line<'static>> {
+    let children = &tc.subagent.children;
+    if children.is_empty() {
+        return Vec::new();
+    }
+    let branch_style = Style::default().fg(theme::DIM);
+    let skipped = children.len().saturating_sub(SUBAGENT_TREE_MAX_CHILDREN);
+    let mut lines = Vec::with_capacity(children.len() - skipped + 2);
+    lines.push(Line::from(Span::styled(subagent_tree_summary(tc), branch_style)));
+    if skipped > 0 {
+        lines.push(Line::from(Span::styled(format!("\u{2026} {skipped} earlier"), branch_style)));
+    }
+    let visible = &children[skipped..];
+    for (i, child) in visible.iter().enumerate() {
+        let branch = if i + 1 == visible.len() { "\u{2514}\u{2500} " } else { "\u{251C}\u{2500} " };
+        let (icon, icon_color) = match child.status {
+            model::ToolCallStatus::Pending => ("\u{25CB}", theme::RUST_ORANGE),
+            model::ToolCallStatus::InProgress => ("\u{25CF}", theme::RUST_ORANGE),
+            model::ToolCallStatus::Completed => (theme::ICON_COMPLETED, Color::Green),
+            model::ToolCallStatus::Failed => (theme::ICON_FAILED, theme::STATUS_ERROR),
+        };
+        let (kind_icon, _kind_name) = theme::tool_name_label(&child.sdk_tool_name);
+        let mut spans = vec![
+            Span::styled(branch, branch_style),
+            Span::styled(format!("{icon} "), Style::default().fg(icon_color)),
+            Span::styled(format!("{kind_icon} "), Style::default().fg(Color::White)),
+        ];
+        spans.extend(markdown_inline_spans(&child.title));
+        if child.awaiting_permission {
+            spans.push(Span::styled(
+                "  awaiting permission",
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
 /// Render Execute/Bash content lines WITHOUT any border decoration.
 /// This is width-independent and safe to cache across resizes.
 /// Returns: command line + output lines + permission lines (no border prefixes).
@@ -742,6 +839,9 @@ fn content_summary(tc: &ToolCallInfo) -> String {
                 return name;
             }
             model::ToolCallContent::Content(c) => {
+                if let model::ContentBlock::Image(image) = &c.content {
+                    return graphics::image_summary(image);
+                }
                 if let model::ContentBlock::Text(text) = &c.content {
                     let stripped = strip_outer_code_fence(&text.text);
                     if matches!(tc.status, model::ToolCallStatus::Failed)
@@ -765,7 +865,8 @@ fn content_summary(tc: &ToolCallInfo) -> String {
 }
 
 /// Render the full content of a tool call as lines.
-fn render_tool_content(tc: &ToolCallInfo) -> Vec<Line<'static>> {
+/// `width` only affects inline images, which are sized to the chat width.
+fn render_tool_content(tc: &ToolCallInfo, width: u16) -> Vec<Line<'static>> {
     let is_execute = tc.is_execute_tool();
     let mut lines: Vec<Line<'static>> = Vec::new();
 
@@ -806,7 +907,9 @@ fn render_tool_content(tc: &ToolCallInfo) -> Vec<Line<'static>> {
                 lines.extend(render_diff(diff));
             }
             model::ToolCallContent::Content(c) => {
-                if let model::ContentBlock::Text(text) = &c.content {
+                if let model::ContentBlock::Image(image) = &c.content {
+                    lines.extend(graphics::image_lines(image, graphics::max_image_cols(width)));
+                } else if let model::ContentBlock::Text(text) = &c.content {
                     let stripped = strip_outer_code_fence(&text.text);
                     if matches!(tc.status, model::ToolCallStatus::Failed)
                         && let Some(msg) = extract_tool_use_error_message(&stripped)
@@ -982,6 +1085,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         }
     }
 
@@ -1095,6 +1201,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         };
 
         let rendered = render_execute_with_borders(&tc, &[], 80, 0);
@@ -1214,6 +1323,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         };
         assert_eq!(content_summary(&tc), "done");
     }
@@ -1243,6 +1355,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         };
         assert_eq!(content_summary(&tc), "bad");
     }
@@ -1274,6 +1389,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         };
         assert_eq!(content_summary(&tc), "Exit code 1");
     }
@@ -1305,6 +1423,9 @@ mod tests {
             last_measured_layout_generation: 0,
             cache: BlockCache::default(),
             pending_permission: None,
+            parent_task_id: None,
+            subagent: crate::app::SubagentTree::default(),
+            locations: Vec::new(),
         };
 
         let lines = render_execute_content(&tc);
@@ -1315,4 +1436,54 @@ mod tests {
         assert_eq!(rendered.len(), 2);
         assert_eq!(rendered[1], "Exit code 1");
     }
+
+    #[test]
+    fn task_with_subagents_renders_tree_and_collapsed_counts() {
+        let mut tc = test_tool_call("task-1", "Task", model::ToolCallStatus::InProgress);
+        for i in 0..12 {
+            tc.subagent.children.push(crate::app::SubagentChild {
+                id: format!("child-{i}"),
+                title: format!("step {i}"),
+                sdk_tool_name: "Read".into(),
+                status: if i == 11 {
+                    model::ToolCallStatus::InProgress
+                } else {
+                    model::ToolCallStatus::Completed
+                },
+                awaiting_permission: i == 11,
+            });
+        }
+        let text: Vec<String> = render_tool_call_body(&tc, 80)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert!(text[0].contains("12 tool calls \u{00B7} 1 running"));
+        assert!(text[1].contains("2 earlier"));
+        assert!(!text.iter().any(|l| l.ends_with("step 1")));
+        assert!(text.last().is_some_and(|l| l.contains("step 11") && l.contains("awaiting")));
+
+        tc.collapsed = true;
+        let collapsed = render_tool_call_body(&tc, 80);
+        assert_eq!(collapsed.len(), 1);
+    }
+
+    #[test]
+    fn image_content_renders_placeholder_and_summary() {
+        // 1x1 transparent PNG.
+        let png = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        let mut tc = test_tool_call("read-png", "Read", model::ToolCallStatus::Completed);
+        tc.content = vec![model::ToolCallContent::Content(model::Content::new(
+            model::ContentBlock::Image(model::ImageContent::new(png, "image/png")),
+        ))];
+
+        let lines = render_tool_content(&tc, 80);
+        assert_eq!(lines.len(), 1);
+        let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert!(text.starts_with("[image: image/png 1\u{00D7}1"), "{text}");
+        assert_eq!(content_summary(&tc), "image/png 1\u{00D7}1");
+        assert!(shows_inline_images(&tc));
+
+        tc.collapsed = true;
+        assert!(!shows_inline_images(&tc));
+    }
 }